@@ -1,4 +1,5 @@
 pub use super::lexer::Location;
+use num_bigint::BigInt;
 
 #[derive(Debug, PartialEq)]
 pub struct Program {
@@ -12,66 +13,77 @@ pub struct SingleImport {
   pub alias: Option<String>,
 }
 
-#[derive(Debug, PartialEq)]
+// A node of the syntax tree, with where in the source it starts and the
+// position right after its end:
+#[derive(Debug, PartialEq, Clone)]
 pub struct Located<T> {
   pub location: Location,
+  pub end_location: Location,
   pub node: T,
 }
 
 pub type LocatedStatement = Located<Statement>;
+pub type LocatedExpression = Located<Expression>;
 
 #[derive(Debug, PartialEq)]
 pub enum Statement {
   Break,
   Continue,
   Return {
-    value: Option<Vec<Expression>>,
+    value: Option<Vec<LocatedExpression>>,
   },
   Import {
     import_parts: Vec<SingleImport>,
   },
   Pass,
+  Global {
+    names: Vec<String>,
+  },
+  Nonlocal {
+    names: Vec<String>,
+  },
   Assert {
-    test: Expression,
-    msg: Option<Expression>,
+    test: LocatedExpression,
+    msg: Option<LocatedExpression>,
   },
   Delete {
-    targets: Vec<Expression>,
+    targets: Vec<LocatedExpression>,
   },
   Assign {
-    targets: Vec<Expression>,
-    value: Expression,
+    targets: Vec<LocatedExpression>,
+    value: LocatedExpression,
   },
   AugAssign {
-    target: Expression,
+    target: LocatedExpression,
     op: Operator,
-    value: Expression,
+    value: LocatedExpression,
   },
   Expression {
-    expression: Expression,
+    expression: LocatedExpression,
   },
   If {
-    test: Expression,
+    test: LocatedExpression,
     body: Vec<LocatedStatement>,
     orelse: Option<Vec<LocatedStatement>>,
   },
   While {
-    test: Expression,
+    test: LocatedExpression,
     body: Vec<LocatedStatement>,
     orelse: Option<Vec<LocatedStatement>>,
   },
   With {
-    items: Expression,
+    items: Vec<WithItem>,
     body: Vec<LocatedStatement>,
   },
   For {
-    target: Vec<Expression>,
-    iter: Vec<Expression>,
+    target: Vec<LocatedExpression>,
+    iter: Vec<LocatedExpression>,
     body: Vec<LocatedStatement>,
     orelse: Option<Vec<LocatedStatement>>,
   },
   Raise {
-    expression: Option<Expression>,
+    expression: Option<LocatedExpression>,
+    cause: Option<LocatedExpression>,
   },
   Try {
     body: Vec<LocatedStatement>,
@@ -87,7 +99,7 @@ pub enum Statement {
   },
   FunctionDef {
     name: String,
-    args: Vec<String>,
+    args: Parameters,
     // docstring: String,
     body: Vec<LocatedStatement>,
   },
@@ -96,69 +108,162 @@ pub enum Statement {
 #[derive(Debug, PartialEq, Clone)]
 pub enum Expression {
   BoolOp {
-    a: Box<Expression>,
+    a: Box<LocatedExpression>,
     op: BooleanOperator,
-    b: Box<Expression>,
+    b: Box<LocatedExpression>,
   },
   Binop {
-    a: Box<Expression>,
+    a: Box<LocatedExpression>,
     op: Operator,
-    b: Box<Expression>,
+    b: Box<LocatedExpression>,
   },
   Subscript {
-    a: Box<Expression>,
-    b: Box<Expression>,
+    a: Box<LocatedExpression>,
+    b: Box<LocatedExpression>,
   },
   Unop {
     op: UnaryOperator,
-    a: Box<Expression>,
+    a: Box<LocatedExpression>,
   },
   Compare {
-    a: Box<Expression>,
+    a: Box<LocatedExpression>,
     op: Comparison,
-    b: Box<Expression>,
+    b: Box<LocatedExpression>,
   },
   Attribute {
-    value: Box<Expression>,
+    value: Box<LocatedExpression>,
     name: String,
   },
   Call {
-    function: Box<Expression>,
-    args: Vec<Expression>,
+    function: Box<LocatedExpression>,
+    args: Vec<LocatedExpression>,
+    keywords: Vec<Keyword>,
   },
   Number {
     value: Number,
   },
   List {
-    elements: Vec<Expression>,
+    elements: Vec<LocatedExpression>,
   },
   Tuple {
-    elements: Vec<Expression>,
+    elements: Vec<LocatedExpression>,
   },
   Dict {
-    elements: Vec<(Expression, Expression)>,
+    elements: Vec<(LocatedExpression, LocatedExpression)>,
+  },
+  Set {
+    elements: Vec<LocatedExpression>,
+  },
+  Comprehension {
+    kind: Box<ComprehensionKind>,
+    generators: Vec<Comprehension>,
   },
   Slice {
-    elements: Vec<Expression>,
+    elements: Vec<LocatedExpression>,
   },
   String {
     value: String,
   },
+  Bytes {
+    value: Vec<u8>,
+  },
+  // An f-string, made of string literals and formatted values:
+  JoinedStr {
+    values: Vec<LocatedExpression>,
+  },
+  // A replacement field of an f-string. Its format spec is itself a string
+  // or an f-string, for specs with replacement fields:
+  FormattedValue {
+    value: Box<LocatedExpression>,
+    conversion: Option<ConversionFlag>,
+    spec: Option<Box<LocatedExpression>>,
+  },
   Identifier {
     name: String,
   },
   Lambda {
-    args: Vec<String>,
-    body: Box<Expression>,
+    args: Parameters,
+    body: Box<LocatedExpression>,
+  },
+  Starred {
+    value: Box<LocatedExpression>,
+  },
+  Yield {
+    value: Option<Box<LocatedExpression>>,
+  },
+  YieldFrom {
+    value: Box<LocatedExpression>,
   },
   True,
   False,
   PyNone,
 }
 
+// The conversion of a formatted value: !s, !r or !a:
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConversionFlag {
+  Str,
+  Repr,
+  Ascii,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ComprehensionKind {
+  GeneratorExpression {
+    element: LocatedExpression,
+  },
+  List {
+    element: LocatedExpression,
+  },
+  Set {
+    element: LocatedExpression,
+  },
+  Dict {
+    key: LocatedExpression,
+    value: LocatedExpression,
+  },
+}
+
+// A "for target in iter if condition" clause of a comprehension. Each clause
+// loops inside the loop of the clause before it:
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comprehension {
+  pub target: LocatedExpression,
+  pub iter: LocatedExpression,
+  pub ifs: Vec<LocatedExpression>,
+}
+
+// The parameters of a function definition or lambda. Defaults belong to the
+// last positional parameters, and keyword-only parameters without a default
+// have None in kw_defaults:
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Parameters {
+  pub args: Vec<String>,
+  pub vararg: Option<String>,
+  pub kwonlyargs: Vec<String>,
+  pub kwarg: Option<String>,
+  pub defaults: Vec<LocatedExpression>,
+  pub kw_defaults: Vec<Option<LocatedExpression>>,
+}
+
+// A keyword argument in a call, or **kwargs when the name is None:
+#[derive(Debug, PartialEq, Clone)]
+pub struct Keyword {
+  pub name: Option<String>,
+  pub value: LocatedExpression,
+}
+
+// A context manager of a with statement, and the target its __enter__ result
+// is assigned to:
+#[derive(Debug, PartialEq)]
+pub struct WithItem {
+  pub context_expr: LocatedExpression,
+  pub optional_vars: Option<LocatedExpression>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ExceptHandler {
-  pub typ: Option<Expression>,
+  pub typ: Option<LocatedExpression>,
   pub name: Option<String>,
   pub body: Vec<LocatedStatement>,
 }
@@ -188,7 +293,10 @@ pub enum BooleanOperator {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum UnaryOperator {
+  Pos,
   Neg,
+  Not,
+  Inv,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -207,6 +315,7 @@ pub enum Comparison {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Number {
-  Integer { value: i32 },
+  Integer { value: BigInt },
   Float { value: f64 },
+  Complex { real: f64, imag: f64 },
 }
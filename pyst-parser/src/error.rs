@@ -0,0 +1,220 @@
+/*
+ * The errors which parsing can give. Each one knows where in the source it
+ * happened, so that it can be reported as a SyntaxError pointing there.
+ */
+
+use std::fmt;
+
+use lalrpop_util;
+
+use super::fstring::FStringError;
+use super::function::ArgumentError;
+use super::lexer::{LexicalError, Location};
+use super::token::Tok;
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+  pub kind: ParseErrorKind,
+  pub location: Location,
+  pub end_location: Location,
+}
+
+// The errors which the grammar gives: those of the lexer, and those which its
+// actions find in tokens that its rules accept:
+#[derive(Debug, PartialEq)]
+pub enum GrammarError {
+  Lexical(LexicalError),
+  Argument {
+    error: ArgumentError,
+    location: Location,
+  },
+}
+
+impl From<LexicalError> for GrammarError {
+  fn from(error: LexicalError) -> Self {
+    GrammarError::Lexical(error)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+  // The lexer could not make a token out of the source:
+  Lexical(LexicalError),
+  // The parameters of a definition or the arguments of a call are out of
+  // order or repeated:
+  Argument(ArgumentError),
+  // The source ended before the statement did, so that an interactive
+  // shell should read more lines:
+  Incomplete { expected: Vec<String> },
+  UnrecognizedToken { token: Tok, expected: Vec<String> },
+  ExtraToken { token: Tok },
+  InvalidToken,
+}
+
+impl ParseError {
+  pub fn is_incomplete(&self) -> bool {
+    match self.kind {
+      ParseErrorKind::Incomplete { .. } => true,
+      _ => false,
+    }
+  }
+}
+
+impl From<lalrpop_util::ParseError<Location, Tok, GrammarError>>
+  for ParseError
+{
+  fn from(
+    error: lalrpop_util::ParseError<Location, Tok, GrammarError>,
+  ) -> Self {
+    let (kind, location, end_location) = match error {
+      lalrpop_util::ParseError::InvalidToken { location } => {
+        (ParseErrorKind::InvalidToken, location, location)
+      }
+      lalrpop_util::ParseError::UnrecognizedEof { location, expected } => (
+        ParseErrorKind::Incomplete { expected: expected },
+        location,
+        location,
+      ),
+      lalrpop_util::ParseError::UnrecognizedToken {
+        token: (location, token, end_location),
+        expected,
+      } => (
+        ParseErrorKind::UnrecognizedToken {
+          token: token,
+          expected: expected,
+        },
+        location,
+        end_location,
+      ),
+      lalrpop_util::ParseError::ExtraToken {
+        token: (location, token, end_location),
+      } => (
+        ParseErrorKind::ExtraToken { token: token },
+        location,
+        end_location,
+      ),
+      lalrpop_util::ParseError::User {
+        error: GrammarError::Lexical(error),
+      } => {
+        let location = error.location();
+        (ParseErrorKind::Lexical(error), location, location)
+      }
+      lalrpop_util::ParseError::User {
+        error: GrammarError::Argument { error, location },
+      } => (ParseErrorKind::Argument(error), location, location),
+    };
+    ParseError {
+      kind: kind,
+      location: location,
+      end_location: end_location,
+    }
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.kind {
+      ParseErrorKind::Lexical(ref error) => write!(f, "{}", error),
+      ParseErrorKind::Argument(ref error) => write!(f, "{}", error),
+      ParseErrorKind::Incomplete { .. } => {
+        write!(f, "unexpected EOF while parsing")
+      }
+      ParseErrorKind::UnrecognizedToken { .. }
+      | ParseErrorKind::ExtraToken { .. } => write!(f, "invalid syntax"),
+      ParseErrorKind::InvalidToken => write!(f, "invalid token"),
+    }
+  }
+}
+
+impl fmt::Display for LexicalError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LexicalError::UnterminatedString { .. } => {
+        write!(f, "unterminated string literal")
+      }
+      LexicalError::InvalidEscape { escape, .. } => {
+        write!(f, "invalid escape sequence '{}'", escape)
+      }
+      LexicalError::NonAsciiBytes { .. } => {
+        write!(f, "bytes can only contain ASCII literal characters")
+      }
+      LexicalError::FStringError { error, .. } => {
+        write!(f, "f-string: {}", error)
+      }
+      LexicalError::InvalidNumber { .. } => {
+        write!(f, "invalid number literal")
+      }
+      LexicalError::InvalidDigit { digit, radix, .. } => {
+        let base = match radix {
+          2 => "binary",
+          8 => "octal",
+          _ => "hexadecimal",
+        };
+        write!(f, "invalid digit '{}' in {} literal", digit, base)
+      }
+      LexicalError::LeadingZeros { .. } => write!(
+        f,
+        "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers"
+      ),
+      LexicalError::UnexpectedCharacter { character, .. } => write!(
+        f,
+        "invalid character '{}' (U+{:04X})",
+        character, *character as u32
+      ),
+      LexicalError::CharacterAfterContinuation { .. } => {
+        write!(f, "unexpected character after line continuation character")
+      }
+      LexicalError::InconsistentDedent { .. } => write!(
+        f,
+        "unindent does not match any outer indentation level"
+      ),
+    }
+  }
+}
+
+impl fmt::Display for ArgumentError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ArgumentError::NonDefaultAfterDefault => {
+        write!(f, "non-default argument follows default argument")
+      }
+      ArgumentError::DuplicateParameter(name) => {
+        write!(f, "duplicate argument '{}' in function definition", name)
+      }
+      ArgumentError::PositionalAfterKeyword => {
+        write!(f, "positional argument follows keyword argument")
+      }
+      ArgumentError::PositionalAfterKeywordUnpacking => {
+        write!(f, "positional argument follows keyword argument unpacking")
+      }
+      ArgumentError::UnpackingAfterKeywordUnpacking => write!(
+        f,
+        "iterable argument unpacking follows keyword argument unpacking"
+      ),
+      ArgumentError::RepeatedKeyword(name) => {
+        write!(f, "keyword argument repeated: {}", name)
+      }
+    }
+  }
+}
+
+impl fmt::Display for FStringError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      FStringError::UnclosedLbrace => write!(f, "expecting '}}'"),
+      FStringError::UnopenedRbrace => {
+        write!(f, "single '}}' is not allowed")
+      }
+      FStringError::EmptyExpression => {
+        write!(f, "empty expression not allowed")
+      }
+      FStringError::InvalidConversionFlag => {
+        write!(f, "invalid conversion character: expected 's', 'r', or 'a'")
+      }
+      FStringError::ExpressionNestedTooDeeply => {
+        write!(f, "expressions nested too deeply")
+      }
+      FStringError::InvalidExpression(error) => write!(f, "{}", error),
+    }
+  }
+}
@@ -0,0 +1,266 @@
+/*
+ * Parse the contents of f-strings. The lexer hands over the text between
+ * the quotes, and the expressions of the replacement fields in it are
+ * parsed like any other expression.
+ *
+ * The string literals and formatted values are located at the whole string
+ * group, while the expressions in replacement fields are located in the
+ * source by counting from the start of the token they are in. That puts them
+ * on the right line, but columns are off by the prefix and quotes, and by any
+ * escape sequences before them.
+ */
+
+use std::mem;
+
+use super::ast::{ConversionFlag, Expression, LocatedExpression, Location};
+use super::error::ParseError;
+use super::parser;
+
+#[derive(Debug, PartialEq)]
+pub enum FStringError {
+  UnclosedLbrace,
+  UnopenedRbrace,
+  EmptyExpression,
+  InvalidConversionFlag,
+  ExpressionNestedTooDeeply,
+  InvalidExpression(Box<ParseError>),
+}
+
+// Join the parts of an implicitly concatenated string, some of which may be
+// f-strings, into a single string or f-string:
+pub fn join_string_parts(
+  parts: Vec<(Location, String, bool)>,
+  location: Location,
+  end_location: Location,
+) -> Result<LocatedExpression, FStringError> {
+  let group = StringGroup {
+    location: location,
+    end_location: end_location,
+  };
+  let mut values = vec![];
+  let mut is_fstring = false;
+  for (part_location, value, fstring) in parts {
+    if fstring {
+      is_fstring = true;
+      for value in parse_values(&value, part_location, &group, 0)? {
+        push_value(&mut values, value);
+      }
+    } else {
+      push_value(
+        &mut values,
+        group.locate(Expression::String { value: value }),
+      );
+    }
+  }
+  if !is_fstring {
+    return Ok(values.pop().unwrap_or_else(|| {
+      group.locate(Expression::String {
+        value: String::new(),
+      })
+    }));
+  }
+  Ok(group.locate(Expression::JoinedStr { values: values }))
+}
+
+// Where a group of adjacent strings is, which its literals and formatted
+// values are located at:
+struct StringGroup {
+  location: Location,
+  end_location: Location,
+}
+
+impl StringGroup {
+  fn locate(&self, node: Expression) -> LocatedExpression {
+    LocatedExpression {
+      location: self.location,
+      end_location: self.end_location,
+      node: node,
+    }
+  }
+}
+
+// The location of chars[pos], when chars starts at location:
+fn location_at(chars: &[char], location: Location, pos: usize) -> Location {
+  let newlines = chars[..pos].iter().filter(|c| **c == '\n').count();
+  match chars[..pos].iter().rposition(|c| *c == '\n') {
+    Some(newline) => {
+      Location::new(location.get_row() + newlines, pos - newline)
+    }
+    None => Location::new(location.get_row(), location.get_column() + pos),
+  }
+}
+
+// Adjacent string literals are merged into one:
+fn push_value(values: &mut Vec<LocatedExpression>, value: LocatedExpression) {
+  if let Expression::String { value: ref suffix } = value.node {
+    if let Some(LocatedExpression {
+      node: Expression::String { ref mut value },
+      ..
+    }) = values.last_mut()
+    {
+      value.push_str(suffix);
+      return;
+    }
+  }
+  values.push(value);
+}
+
+// The string literals and formatted values of an f-string, or of the format
+// spec of a formatted value when nested is more than 0. The source starts at
+// location:
+fn parse_values(
+  source: &str,
+  location: Location,
+  group: &StringGroup,
+  nested: usize,
+) -> Result<Vec<LocatedExpression>, FStringError> {
+  let chars: Vec<char> = source.chars().collect();
+  let mut values = vec![];
+  let mut literal = String::new();
+  let mut pos = 0;
+  while pos < chars.len() {
+    match chars[pos] {
+      '{' if chars.get(pos + 1) == Some(&'{') => {
+        literal.push('{');
+        pos += 2;
+      }
+      '}' if chars.get(pos + 1) == Some(&'}') => {
+        literal.push('}');
+        pos += 2;
+      }
+      '{' => {
+        if !literal.is_empty() {
+          values.push(group.locate(Expression::String {
+            value: mem::replace(&mut literal, String::new()),
+          }));
+        }
+        let (value, end) =
+          parse_field(&chars, pos + 1, location, group, nested)?;
+        values.push(value);
+        pos = end;
+      }
+      '}' => return Err(FStringError::UnopenedRbrace),
+      c => {
+        literal.push(c);
+        pos += 1;
+      }
+    }
+  }
+  if !literal.is_empty() {
+    values.push(group.locate(Expression::String { value: literal }));
+  }
+  Ok(values)
+}
+
+// Parse the replacement field which starts at start, right after its '{',
+// into a formatted value. Also return the position after its '}':
+fn parse_field(
+  chars: &[char],
+  start: usize,
+  location: Location,
+  group: &StringGroup,
+  nested: usize,
+) -> Result<(LocatedExpression, usize), FStringError> {
+  if nested > 1 {
+    return Err(FStringError::ExpressionNestedTooDeeply);
+  }
+
+  // The expression ends at a '!', ':' or '}' outside of brackets and
+  // strings. A '!' which is part of '!=' belongs to the expression:
+  let mut pos = start;
+  let mut depth = 0;
+  let mut quote = None;
+  loop {
+    let c = match chars.get(pos) {
+      Some(c) => *c,
+      None => return Err(FStringError::UnclosedLbrace),
+    };
+    match quote {
+      Some(quote_char) => {
+        if c == quote_char {
+          quote = None;
+        }
+      }
+      None => match c {
+        '\'' | '"' => quote = Some(c),
+        '(' | '[' | '{' => depth += 1,
+        ')' | ']' | '}' if depth > 0 => depth -= 1,
+        '!' if depth == 0 && chars.get(pos + 1) != Some(&'=') => break,
+        ':' | '}' if depth == 0 => break,
+        _ => {}
+      },
+    }
+    pos += 1;
+  }
+  let expression: String = chars[start..pos].iter().collect();
+  if expression.trim().is_empty() {
+    return Err(FStringError::EmptyExpression);
+  }
+
+  let mut conversion = None;
+  if chars[pos] == '!' {
+    conversion = Some(match chars.get(pos + 1) {
+      Some('s') => ConversionFlag::Str,
+      Some('r') => ConversionFlag::Repr,
+      Some('a') => ConversionFlag::Ascii,
+      Some(_) => return Err(FStringError::InvalidConversionFlag),
+      None => return Err(FStringError::UnclosedLbrace),
+    });
+    pos += 2;
+    match chars.get(pos) {
+      Some(':') | Some('}') => {}
+      Some(_) => return Err(FStringError::InvalidConversionFlag),
+      None => return Err(FStringError::UnclosedLbrace),
+    }
+  }
+
+  // The spec runs up to the '}' which matches the opening '{', and may
+  // contain replacement fields of its own:
+  let mut spec = None;
+  if chars[pos] == ':' {
+    let spec_start = pos + 1;
+    let mut depth = 0;
+    loop {
+      pos += 1;
+      match chars.get(pos) {
+        Some('{') => depth += 1,
+        Some('}') if depth == 0 => break,
+        Some('}') => depth -= 1,
+        Some(_) => {}
+        None => return Err(FStringError::UnclosedLbrace),
+      }
+    }
+    let spec_source: String = chars[spec_start..pos].iter().collect();
+    let spec_location = location_at(chars, location, spec_start);
+    let mut values =
+      parse_values(&spec_source, spec_location, group, nested + 1)?;
+    spec = match values.len() {
+      0 => None,
+      1 if is_string(&values[0]) => Some(Box::new(values.pop().unwrap())),
+      _ => Some(Box::new(
+        group.locate(Expression::JoinedStr { values: values }),
+      )),
+    };
+  }
+
+  // Parenthesize the expression, so that it may span lines and be a tuple.
+  // The parenthesis takes the place of the '{':
+  let value = parser::parse_expression_at(
+    &format!("({})", expression),
+    location_at(chars, location, start - 1),
+  )
+  .map_err(|e| FStringError::InvalidExpression(Box::new(e)))?;
+  let value = group.locate(Expression::FormattedValue {
+    value: Box::new(value),
+    conversion: conversion,
+    spec: spec,
+  });
+  Ok((value, pos + 1))
+}
+
+fn is_string(value: &LocatedExpression) -> bool {
+  match value.node {
+    Expression::String { .. } => true,
+    _ => false,
+  }
+}
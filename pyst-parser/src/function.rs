@@ -0,0 +1,117 @@
+/*
+ * Rules about the parameters of a function definition and the arguments of
+ * a call which the grammar does not check by itself, such as parameters with
+ * defaults coming last.
+ */
+
+use std::collections::HashSet;
+
+use super::ast::{Expression, Keyword, LocatedExpression, Parameters};
+use super::error::GrammarError;
+use super::lexer::Location;
+
+#[derive(Debug, PartialEq)]
+pub enum ArgumentError {
+  NonDefaultAfterDefault,
+  DuplicateParameter(String),
+  PositionalAfterKeyword,
+  PositionalAfterKeywordUnpacking,
+  UnpackingAfterKeywordUnpacking,
+  RepeatedKeyword(String),
+}
+
+// Split positional parameters into their names and defaults. Once one of
+// them has a default, all the ones after it must have one too:
+pub fn split_parameters(
+  params: Vec<(Location, String, Option<LocatedExpression>)>,
+) -> Result<(Vec<String>, Vec<LocatedExpression>), GrammarError> {
+  let mut names = vec![];
+  let mut defaults = vec![];
+  for (location, name, default) in params {
+    match default {
+      Some(default) => defaults.push(default),
+      None if !defaults.is_empty() => {
+        return Err(GrammarError::Argument {
+          error: ArgumentError::NonDefaultAfterDefault,
+          location: location,
+        })
+      }
+      None => {}
+    }
+    names.push(name);
+  }
+  Ok((names, defaults))
+}
+
+// No two parameters of a function may have the same name:
+pub fn check_parameters(
+  params: Parameters,
+  location: Location,
+) -> Result<Parameters, GrammarError> {
+  {
+    let mut seen = HashSet::new();
+    let names = params
+      .args
+      .iter()
+      .chain(&params.vararg)
+      .chain(&params.kwonlyargs)
+      .chain(&params.kwarg);
+    for name in names {
+      if !seen.insert(name) {
+        return Err(GrammarError::Argument {
+          error: ArgumentError::DuplicateParameter(name.clone()),
+          location: location,
+        });
+      }
+    }
+  }
+  Ok(params)
+}
+
+// Split the arguments of a call into positional and keyword arguments.
+// Positional arguments come before keyword arguments, though *args may come
+// after name=value, and neither may come after **kwargs:
+pub fn split_arguments(
+  arguments: Vec<(Option<Option<String>>, LocatedExpression)>,
+) -> Result<(Vec<LocatedExpression>, Vec<Keyword>), GrammarError> {
+  let mut args = vec![];
+  let mut keywords: Vec<Keyword> = vec![];
+  for (name, value) in arguments {
+    let unpacked_keywords = keywords.iter().any(|k| k.name.is_none());
+    let error = match name {
+      Some(Some(ref name))
+        if keywords.iter().any(|k| k.name.as_ref() == Some(name)) =>
+      {
+        ArgumentError::RepeatedKeyword(name.clone())
+      }
+      Some(name) => {
+        keywords.push(Keyword {
+          name: name,
+          value: value,
+        });
+        continue;
+      }
+      None => {
+        let is_starred = match value.node {
+          Expression::Starred { .. } => true,
+          _ => false,
+        };
+        if unpacked_keywords && is_starred {
+          ArgumentError::UnpackingAfterKeywordUnpacking
+        } else if unpacked_keywords {
+          ArgumentError::PositionalAfterKeywordUnpacking
+        } else if !keywords.is_empty() && !is_starred {
+          ArgumentError::PositionalAfterKeyword
+        } else {
+          args.push(value);
+          continue;
+        }
+      }
+    };
+    return Err(GrammarError::Argument {
+      error: error,
+      location: value.location,
+    });
+  }
+  Ok((args, keywords))
+}
@@ -1,6 +1,9 @@
 pub use super::token::Tok;
+use super::fstring::FStringError;
+use num_bigint::BigInt;
 use std::collections::HashMap;
 use std::str::CharIndices;
+use std::str::FromStr;
 
 pub struct Lexer<'input> {
   chars: CharIndices<'input>,
@@ -13,9 +16,35 @@ pub struct Lexer<'input> {
   location: Location,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum LexicalError {
-  StringError,
+  UnterminatedString { location: Location },
+  InvalidEscape { escape: String, location: Location },
+  NonAsciiBytes { location: Location },
+  FStringError { error: FStringError, location: Location },
+  InvalidNumber { location: Location },
+  InvalidDigit { digit: char, radix: u32, location: Location },
+  LeadingZeros { location: Location },
+  UnexpectedCharacter { character: char, location: Location },
+  CharacterAfterContinuation { location: Location },
+  InconsistentDedent { location: Location },
+}
+
+impl LexicalError {
+  pub fn location(&self) -> Location {
+    match *self {
+      LexicalError::UnterminatedString { location }
+      | LexicalError::InvalidEscape { location, .. }
+      | LexicalError::NonAsciiBytes { location }
+      | LexicalError::FStringError { location, .. }
+      | LexicalError::InvalidNumber { location }
+      | LexicalError::InvalidDigit { location, .. }
+      | LexicalError::LeadingZeros { location }
+      | LexicalError::UnexpectedCharacter { location, .. }
+      | LexicalError::CharacterAfterContinuation { location }
+      | LexicalError::InconsistentDedent { location } => location,
+    }
+  }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Copy)]
@@ -31,6 +60,41 @@ impl Location {
       column: column,
     }
   }
+
+  pub fn get_row(&self) -> usize {
+    self.row
+  }
+
+  pub fn get_column(&self) -> usize {
+    self.column
+  }
+}
+
+// The kind of string literal which a prefix such as rb gives:
+#[derive(Clone, Copy, Default)]
+struct StringPrefix {
+  is_bytes: bool,
+  is_raw: bool,
+  is_fstring: bool,
+}
+
+impl StringPrefix {
+  fn parse(prefix: &str) -> Option<StringPrefix> {
+    let mut kind = StringPrefix::default();
+    for c in prefix.chars() {
+      match c {
+        'b' | 'B' if !kind.is_bytes => kind.is_bytes = true,
+        'r' | 'R' if !kind.is_raw => kind.is_raw = true,
+        'f' | 'F' if !kind.is_fstring => kind.is_fstring = true,
+        'u' | 'U' if prefix.len() == 1 => {}
+        _ => return None,
+      }
+    }
+    if kind.is_bytes && kind.is_fstring {
+      return None;
+    }
+    Some(kind)
+  }
 }
 
 pub type Spanned<Tok> = Result<(Location, Tok, Location), LexicalError>;
@@ -54,6 +118,14 @@ impl<'input> Lexer<'input> {
     lxr
   }
 
+  // A lexer for input which starts at location, rather than at the start of
+  // a file:
+  pub fn new_at(input: &'input str, location: Location) -> Self {
+    let mut lxr = Lexer::new(input);
+    lxr.location = location;
+    lxr
+  }
+
   fn next_char(&mut self) -> Option<char> {
     let c = self.chr0;
     let nxt = self.chars.next();
@@ -75,6 +147,14 @@ impl<'input> Lexer<'input> {
     }
     let end_pos = self.get_pos();
 
+    // A name right before a quote may be a string prefix, such as b for
+    // bytes, r for a raw string or f for an f-string:
+    if let Some('"') | Some('\'') = self.chr0 {
+      if let Some(prefix) = StringPrefix::parse(&name) {
+        return self.lex_string(start_pos, prefix);
+      }
+    }
+
     let mut keywords: HashMap<String, Tok> = HashMap::new();
 
     // Alphabetical keywords:
@@ -122,26 +202,107 @@ impl<'input> Lexer<'input> {
   }
 
   fn lex_number(&mut self) -> Spanned<Tok> {
-    let mut value_text = String::new();
-
     let start_pos = self.get_pos();
-    while self.is_number() {
-      value_text.push(self.next_char().unwrap());
+
+    // Integers in other bases start with 0x, 0o or 0b:
+    if self.chr0 == Some('0') {
+      let radix = match self.chr1 {
+        Some('x') | Some('X') => Some(16),
+        Some('o') | Some('O') => Some(8),
+        Some('b') | Some('B') => Some(2),
+        _ => None,
+      };
+      if let Some(radix) = radix {
+        self.next_char();
+        self.next_char();
+        let digits = self.lex_digits(radix, true)?;
+        if digits.is_empty() {
+          return Err(LexicalError::InvalidNumber { location: self.get_pos() });
+        }
+        let value = BigInt::parse_bytes(digits.as_bytes(), radix).unwrap();
+        let end_pos = self.get_pos();
+        return Ok((start_pos, Tok::Int { value: value }, end_pos));
+      }
     }
 
-    // If float:
+    // A decimal number may start with the fraction, as in .5:
+    let mut value_text = self.lex_digits(10, false)?;
+    let mut is_float = false;
     if let Some('.') = self.chr0 {
       value_text.push(self.next_char().unwrap());
-      while self.is_number() {
+      value_text.push_str(&self.lex_digits(10, false)?);
+      is_float = true;
+    }
+
+    // The exponent needs at least one digit:
+    if let Some('e') | Some('E') = self.chr0 {
+      value_text.push(self.next_char().unwrap());
+      if let Some('+') | Some('-') = self.chr0 {
         value_text.push(self.next_char().unwrap());
       }
+      let exponent = self.lex_digits(10, false)?;
+      if exponent.is_empty() {
+        return Err(LexicalError::InvalidNumber { location: self.get_pos() });
+      }
+      value_text.push_str(&exponent);
+      is_float = true;
+    }
+
+    // If imaginary:
+    if let Some('j') | Some('J') = self.chr0 {
+      self.next_char();
+      let end_pos = self.get_pos();
+      let imag = f64::from_str(&value_text).unwrap();
+      return Ok((
+        start_pos,
+        Tok::Complex {
+          real: 0.0,
+          imag: imag,
+        },
+        end_pos,
+      ));
     }
 
     let end_pos = self.get_pos();
+    if is_float {
+      let value = f64::from_str(&value_text).unwrap();
+      return Ok((start_pos, Tok::Float { value: value }, end_pos));
+    }
 
-    let value = value_text;
+    // Only zero itself may be written with leading zeros, as in 00:
+    if value_text.starts_with('0') && value_text.chars().any(|c| c != '0') {
+      return Err(LexicalError::LeadingZeros { location: start_pos });
+    }
+    let value = BigInt::from_str(&value_text).unwrap();
+    return Ok((start_pos, Tok::Int { value: value }, end_pos));
+  }
 
-    return Ok((start_pos, Tok::Number { value: value }, end_pos));
+  // Lex a run of digits in the given radix, which may be grouped by single
+  // underscores, and return the digits without the underscores. Right after
+  // a base prefix, as in 0x_ff, the run may start with an underscore:
+  fn lex_digits(&mut self, radix: u32, leading_underscore: bool) -> Result<String, LexicalError> {
+    let mut digits = String::new();
+    loop {
+      match self.chr0 {
+        Some('_') => {
+          let follows_digit = leading_underscore || !digits.is_empty();
+          let precedes_digit = self.chr1.map_or(false, |c| c.is_digit(radix.max(10)));
+          if !follows_digit || !precedes_digit {
+            return Err(LexicalError::InvalidNumber { location: self.get_pos() });
+          }
+          self.next_char();
+        }
+        Some(c) if c.is_digit(radix) => digits.push(self.next_char().unwrap()),
+        Some(c) if c.is_digit(10) => {
+          return Err(LexicalError::InvalidDigit {
+            digit: c,
+            radix: radix,
+            location: self.get_pos(),
+          });
+        }
+        _ => return Ok(digits),
+      }
+    }
   }
 
   fn lex_comment(&mut self) {
@@ -164,10 +325,9 @@ impl<'input> Lexer<'input> {
     }
   }
 
-  fn lex_string(&mut self) -> Spanned<Tok> {
+  fn lex_string(&mut self, start_pos: Location, prefix: StringPrefix) -> Spanned<Tok> {
     let quote_char = self.next_char().unwrap();
     let mut string_content = String::new();
-    let start_pos = self.get_pos();
 
     // If the next two characters are also the quote character, then we have a triple-quoted
     // string; consume those two characters and ensure that we require a triple-quote to close
@@ -180,47 +340,26 @@ impl<'input> Lexer<'input> {
     };
 
     loop {
+      let char_pos = self.get_pos();
       match self.next_char() {
-        Some('\\') => {
-          match self.next_char() {
-            Some('\\') => {
-              string_content.push('\\');
-            }
-            Some('\'') => string_content.push('\''),
-            Some('\"') => string_content.push('\"'),
-            Some('\n') => {
-              // Ignore Unix EOL character
-            }
-            Some('\r') => {
-              match self.chr0 {
-                Some('\n') => {
-                  // Ignore Windows EOL characters (2 bytes)
-                  self.next_char();
-                }
-                _ => {
-                  // Ignore Mac EOL character
-                }
-              }
-            }
-            Some('a') => string_content.push('\x07'),
-            Some('b') => string_content.push('\x08'),
-            Some('f') => string_content.push('\x0c'),
-            Some('n') => {
-              string_content.push('\n');
-            }
-            Some('r') => string_content.push('\r'),
-            Some('t') => {
-              string_content.push('\t');
-            }
-            Some('v') => string_content.push('\x0b'),
-            Some(c) => {
-              string_content.push('\\');
-              string_content.push(c);
-            }
-            None => {
-              return Err(LexicalError::StringError);
+        // In a raw string, a backslash stays in the string along with the
+        // character after it, which may still be a quote or a newline:
+        Some('\\') if prefix.is_raw => {
+          string_content.push('\\');
+          match self.chr0 {
+            Some('\n') | Some('\r') => self.lex_string_newline(&mut string_content),
+            Some(c) if prefix.is_bytes && !c.is_ascii() => {
+              return Err(LexicalError::NonAsciiBytes { location: self.get_pos() });
             }
+            Some(_) => string_content.push(self.next_char().unwrap()),
+            None => return Err(LexicalError::UnterminatedString { location: start_pos }),
+          }
+        }
+        Some('\\') => {
+          if self.chr0.is_none() {
+            return Err(LexicalError::UnterminatedString { location: start_pos });
           }
+          self.lex_escape(&mut string_content, prefix.is_bytes, char_pos)?;
         }
         Some(c) => {
           if c == quote_char {
@@ -237,24 +376,147 @@ impl<'input> Lexer<'input> {
             } else {
               break;
             }
+          } else if c == '\n' || c == '\r' {
+            // Only triple-quoted strings may span lines:
+            if !triple_quoted {
+              return Err(LexicalError::UnterminatedString { location: start_pos });
+            }
+            if c == '\r' && self.chr0 == Some('\n') {
+              self.next_char();
+            }
+            string_content.push('\n');
+            self.new_line();
+          } else if prefix.is_bytes && !c.is_ascii() {
+            // Bytes literals can only contain ASCII characters:
+            return Err(LexicalError::NonAsciiBytes { location: char_pos });
           } else {
             string_content.push(c);
           }
         }
         None => {
-          return Err(LexicalError::StringError);
+          return Err(LexicalError::UnterminatedString { location: start_pos });
         }
       }
     }
     let end_pos = self.get_pos();
 
-    return Ok((
-      start_pos,
+    let tok = if prefix.is_bytes {
+      Tok::Bytes {
+        value: string_content.chars().map(|c| c as u8).collect(),
+      }
+    } else if prefix.is_fstring {
+      Tok::FString {
+        value: string_content,
+      }
+    } else {
       Tok::String {
         value: string_content,
-      },
-      end_pos,
-    ));
+      }
+    };
+    return Ok((start_pos, tok, end_pos));
+  }
+
+  // Take the newline at chr0 into the string, counting \r\n as one newline:
+  fn lex_string_newline(&mut self, string_content: &mut String) {
+    if self.next_char() == Some('\r') && self.chr0 == Some('\n') {
+      self.next_char();
+    }
+    string_content.push('\n');
+    self.new_line();
+  }
+
+  // Decode the escape sequence after a backslash at location. Bytes hold
+  // the value of each byte as a char, and have no \u, \U or \N escapes.
+  // Unknown escapes are kept as they are, backslash and all:
+  fn lex_escape(&mut self, string_content: &mut String, is_bytes: bool, location: Location) -> Result<(), LexicalError> {
+    let c = match self.chr0 {
+      Some(c) if is_bytes && !c.is_ascii() => {
+        return Err(LexicalError::NonAsciiBytes { location: self.get_pos() });
+      }
+      Some('\n') | Some('\r') => {
+        // A backslash at the end of a line joins it to the next one:
+        let mut ignored = String::new();
+        self.lex_string_newline(&mut ignored);
+        return Ok(());
+      }
+      Some(_) => self.next_char().unwrap(),
+      None => unreachable!(),
+    };
+    let value = match c {
+      '\\' | '\'' | '"' => c,
+      'a' => '\x07',
+      'b' => '\x08',
+      'f' => '\x0c',
+      'n' => '\n',
+      'r' => '\r',
+      't' => '\t',
+      'v' => '\x0b',
+      '0'..='7' => {
+        // Up to three octal digits:
+        let mut code = c.to_digit(8).unwrap();
+        for _ in 0..2 {
+          match self.chr0.and_then(|d| d.to_digit(8)) {
+            Some(digit) => {
+              self.next_char();
+              code = code * 8 + digit;
+            }
+            None => break,
+          }
+        }
+        if is_bytes {
+          code &= 0xff;
+        }
+        char::from_u32(code).unwrap()
+      }
+      'x' => self.lex_hex_escape(c, 2, location)?,
+      'u' if !is_bytes => self.lex_hex_escape(c, 4, location)?,
+      'U' if !is_bytes => self.lex_hex_escape(c, 8, location)?,
+      'N' if !is_bytes => self.lex_named_escape(location)?,
+      c => {
+        string_content.push('\\');
+        c
+      }
+    };
+    string_content.push(value);
+    Ok(())
+  }
+
+  // An escape such as \x41 of exactly len hex digits:
+  fn lex_hex_escape(&mut self, kind: char, len: usize, location: Location) -> Result<char, LexicalError> {
+    let mut escape = format!("\\{}", kind);
+    for _ in 0..len {
+      match self.chr0 {
+        Some(c) if c.is_digit(16) => escape.push(self.next_char().unwrap()),
+        _ => return Err(LexicalError::InvalidEscape { escape: escape, location: location }),
+      }
+    }
+    match u32::from_str_radix(&escape[2..], 16).ok().and_then(char::from_u32) {
+      Some(c) => Ok(c),
+      None => Err(LexicalError::InvalidEscape { escape: escape, location: location }),
+    }
+  }
+
+  // An escape such as \N{BULLET}, which names a Unicode character:
+  fn lex_named_escape(&mut self, location: Location) -> Result<char, LexicalError> {
+    let mut escape = String::from("\\N");
+    if self.chr0 == Some('{') {
+      escape.push(self.next_char().unwrap());
+      loop {
+        match self.chr0 {
+          Some('}') => {
+            escape.push(self.next_char().unwrap());
+            let name = &escape[3..escape.len() - 1];
+            if let Some(c) = unicode_names2::character(name) {
+              return Ok(c);
+            }
+            break;
+          }
+          Some(c) if c != '\n' && c != '\r' && c != '"' && c != '\'' => escape.push(self.next_char().unwrap()),
+          _ => break,
+        }
+      }
+    }
+    Err(LexicalError::InvalidEscape { escape: escape, location: location })
   }
 
   fn inner_next(&mut self) -> Option<Spanned<Tok>> {
@@ -275,6 +537,11 @@ impl<'input> Lexer<'input> {
               self.next_char();
               col += 1;
             }
+            // A tab goes to the next multiple of eight columns:
+            Some('\t') => {
+              self.next_char();
+              col = (col / 8 + 1) * 8;
+            }
             Some('#') => {
               self.lex_comment();
               self.at_begin_of_line = true;
@@ -327,8 +594,7 @@ impl<'input> Lexer<'input> {
             }
 
             if col != *self.indentation_stack.last().unwrap() {
-              // TODO: handle wrong indentations
-              panic!("Non matching indentation levels!");
+              return Some(Err(LexicalError::InconsistentDedent { location: self.get_pos() }));
             }
 
             return Some(self.pending.remove(0));
@@ -343,11 +609,9 @@ impl<'input> Lexer<'input> {
           self.lex_comment();
           continue;
         }
-        Some('"') => {
-          return Some(self.lex_string());
-        }
-        Some('\'') => {
-          return Some(self.lex_string());
+        Some('"') | Some('\'') => {
+          let start_pos = self.get_pos();
+          return Some(self.lex_string(start_pos, StringPrefix::default()));
         }
         Some('=') => {
           let tok_start = self.get_pos();
@@ -541,7 +805,7 @@ impl<'input> Lexer<'input> {
               let tok_end = self.get_pos();
               return Some(Ok((tok_start, Tok::NotEqual, tok_end)));
             }
-            _ => panic!("Invalid token '!'"),
+            _ => return Some(Err(LexicalError::UnexpectedCharacter { character: '!', location: tok_start })),
           }
         }
         Some('~') => {
@@ -554,7 +818,7 @@ impl<'input> Lexer<'input> {
         }
         Some(')') => {
           let result = self.eat_single_char(Tok::Rpar);
-          self.nesting -= 1;
+          self.nesting = self.nesting.saturating_sub(1);
           return Some(result);
         }
         Some('[') => {
@@ -564,7 +828,7 @@ impl<'input> Lexer<'input> {
         }
         Some(']') => {
           let result = self.eat_single_char(Tok::Rsqb);
-          self.nesting -= 1;
+          self.nesting = self.nesting.saturating_sub(1);
           return Some(result);
         }
         Some('{') => {
@@ -574,7 +838,7 @@ impl<'input> Lexer<'input> {
         }
         Some('}') => {
           let result = self.eat_single_char(Tok::Rbrace);
-          self.nesting -= 1;
+          self.nesting = self.nesting.saturating_sub(1);
           return Some(result);
         }
         Some(':') => {
@@ -648,6 +912,9 @@ impl<'input> Lexer<'input> {
           return Some(Ok((tok_start, Tok::Comma, tok_end)));
         }
         Some('.') => {
+          if let Some('0'..='9') = self.chr1 {
+            return Some(self.lex_number());
+          }
           let tok_start = self.get_pos();
           self.next_char();
           let tok_end = self.get_pos();
@@ -681,16 +948,30 @@ impl<'input> Lexer<'input> {
             continue;
           }
         }
-        Some(' ') => {
+        Some(' ') | Some('\t') | Some('\x0c') => {
           // Skip whitespaces
           self.next_char();
           continue;
         }
+        Some('\\') => {
+          // A backslash at the end of a line joins it to the next one:
+          let location = self.get_pos();
+          self.next_char();
+          match self.chr0 {
+            Some('\n') | Some('\r') => {
+              let mut ignored = String::new();
+              self.lex_string_newline(&mut ignored);
+              continue;
+            }
+            _ => return Some(Err(LexicalError::CharacterAfterContinuation { location: location })),
+          }
+        }
         None => return None,
         _ => {
-          let c = self.next_char();
-          panic!("Not impl {:?}", c)
-        } // Ignore all the rest..
+          let location = self.get_pos();
+          let c = self.next_char().unwrap();
+          return Some(Err(LexicalError::UnexpectedCharacter { character: c, location: location }));
+        }
       }
     }
   }
@@ -702,13 +983,6 @@ impl<'input> Lexer<'input> {
     }
   }
 
-  fn is_number(&self) -> bool {
-    match self.chr0 {
-      Some('0'..='9') => return true,
-      _ => return false,
-    }
-  }
-
   fn new_line(&mut self) {
     self.location.row += 1;
     self.location.column = 1;
@@ -1,15 +1,23 @@
 #[macro_use]
 extern crate log;
 extern crate lalrpop_util;
+extern crate num_bigint;
+extern crate unicode_names2;
 
 use lalrpop_util::lalrpop_mod;
 
+pub mod error;
+
 pub mod lexer;
 
 pub mod token;
 
 pub mod ast;
 
+pub mod fstring;
+
+pub mod function;
+
 pub mod parser;
 
 lalrpop_mod!(python);
@@ -3,8 +3,10 @@ use std::io::Read;
 use std::path::Path;
 
 use super::ast;
+use super::error::{GrammarError, ParseError};
 use super::lexer;
 use super::python;
+use super::token::Tok;
 
 pub fn read_file(filename: &Path) -> Result<String, String> {
   match File::open(&filename) {
@@ -25,36 +27,59 @@ pub fn parse(filename: &Path) -> Result<ast::Program, String> {
   match read_file(filename) {
     Ok(txt) => {
       debug!("Read contents of file: {}", txt);
-      parse_program(&txt)
+      parse_program(&txt).map_err(|e| {
+        format!(
+          "{}:{}:{}: {}",
+          filename.display(),
+          e.location.get_row(),
+          e.location.get_column(),
+          e
+        )
+      })
     }
     Err(msg) => Err(msg),
   }
 }
 
-pub fn parse_program(source: &String) -> Result<ast::Program, String> {
+pub fn parse_program(source: &String) -> Result<ast::Program, ParseError> {
   let lxr = lexer::Lexer::new(&source);
-  match python::ProgramParser::new().parse(lxr) {
-    Err(lalrpop_util::ParseError::UnrecognizedToken {
-      token: _,
-      expected: _,
-    }) => Err(String::from("Unexpected end of input.")),
-    Err(why) => Err(String::from(format!("{:?}", why))),
-    Ok(p) => Ok(p),
-  }
+  python::ProgramParser::new()
+    .parse(tokens(lxr))
+    .map_err(ParseError::from)
 }
 
-pub fn parse_statement(source: &String) -> Result<ast::LocatedStatement, String> {
+pub fn parse_statement(
+  source: &String,
+) -> Result<ast::LocatedStatement, ParseError> {
   let lxr = lexer::Lexer::new(&source);
-  match python::StatementParser::new().parse(lxr) {
-    Err(why) => Err(String::from(format!("{:?}", why))),
-    Ok(p) => Ok(p),
-  }
+  python::StatementParser::new()
+    .parse(tokens(lxr))
+    .map_err(ParseError::from)
 }
 
-pub fn parse_expression(source: &String) -> Result<ast::Expression, String> {
-  let lxr = lexer::Lexer::new(&source);
-  match python::ExpressionParser::new().parse(lxr) {
-    Err(why) => Err(String::from(format!("{:?}", why))),
-    Ok(p) => Ok(p),
-  }
+pub fn parse_expression(
+  source: &String,
+) -> Result<ast::LocatedExpression, ParseError> {
+  parse_expression_at(source, ast::Location::new(1, 1))
+}
+
+// Parse an expression which is part of a larger source, such as one in an
+// f-string, so that its nodes are located in that source:
+pub fn parse_expression_at(
+  source: &String,
+  location: ast::Location,
+) -> Result<ast::LocatedExpression, ParseError> {
+  let lxr = lexer::Lexer::new_at(&source, location);
+  python::ExpressionParser::new()
+    .parse(tokens(lxr))
+    .map_err(ParseError::from)
+}
+
+// The tokens of a lexer, with its errors in the form the grammar gives them:
+fn tokens<'input>(
+  lxr: lexer::Lexer<'input>,
+) -> impl Iterator<
+  Item = Result<(lexer::Location, Tok, lexer::Location), GrammarError>,
+> + 'input {
+  lxr.map(|token| token.map_err(GrammarError::from))
 }
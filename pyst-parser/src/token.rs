@@ -1,9 +1,15 @@
+use num_bigint::BigInt;
+
 // based on token.h from CPython source:
 #[derive(Clone, Debug, PartialEq)]
 pub enum Tok {
   Name { name: String },
-  Number { value: String },
+  Int { value: BigInt },
+  Float { value: f64 },
+  Complex { real: f64, imag: f64 },
   String { value: String },
+  FString { value: String },
+  Bytes { value: Vec<u8> },
   Newline,
   Indent,
   Dedent,
@@ -1,8 +1,10 @@
+extern crate num_bigint;
 extern crate pyst_parser;
 
 #[cfg(test)]
 mod tests {
-  use pyst_parser::lexer::Lexer;
+  use num_bigint::BigInt;
+  use pyst_parser::lexer::{Lexer, LexicalError, Location};
   use pyst_parser::token::Tok;
   use std::iter::FromIterator;
 
@@ -22,7 +24,7 @@ mod tests {
       fn $name() {
         let source = String::from(format!(r"99232  # {}", $eol));
         let tokens = lex_source(&source);
-        assert_eq!(tokens, vec![Tok::Number { value: "99232".to_string() }]);
+        assert_eq!(tokens, vec![Tok::Int { value: BigInt::from(99232) }]);
       }
       )*
     }
@@ -45,9 +47,9 @@ mod tests {
         assert_eq!(
           tokens,
           vec![
-              Tok::Number { value: "123".to_string() },
+              Tok::Int { value: BigInt::from(123) },
               Tok::Newline,
-              Tok::Number { value: "456".to_string() },
+              Tok::Int { value: BigInt::from(456) },
           ]
         )
       }
@@ -72,16 +74,16 @@ mod tests {
           name: String::from("avariable"),
         },
         Tok::Equal,
-        Tok::Number {
-          value: "99".to_string()
+        Tok::Int {
+          value: BigInt::from(99)
         },
         Tok::Plus,
-        Tok::Number {
-          value: "2".to_string()
+        Tok::Int {
+          value: BigInt::from(2)
         },
         Tok::Minus,
-        Tok::Number {
-          value: "0".to_string()
+        Tok::Int {
+          value: BigInt::from(0)
         },
       ]
     );
@@ -107,7 +109,7 @@ mod tests {
                     Tok::Newline,
                     Tok::Indent,
                     Tok::Return,
-                    Tok::Number { value: "99".to_string() },
+                    Tok::Int { value: BigInt::from(99) },
                     Tok::Newline,
                     Tok::Dedent,
                 ]
@@ -150,7 +152,7 @@ mod tests {
             Tok::Newline,
             Tok::Indent,
             Tok::Return,
-            Tok::Number { value: "99".to_string() },
+            Tok::Int { value: BigInt::from(99) },
             Tok::Newline,
             Tok::Dedent,
             Tok::Dedent,
@@ -182,9 +184,9 @@ mod tests {
             },
             Tok::Equal,
             Tok::Lsqb,
-            Tok::Number { value: "1".to_string() },
+            Tok::Int { value: BigInt::from(1) },
             Tok::Comma,
-            Tok::Number { value: "2".to_string() },
+            Tok::Int { value: BigInt::from(2) },
             Tok::Rsqb,
             Tok::Newline,
           ]
@@ -270,4 +272,322 @@ mod tests {
     test_string_continuation_mac_eol: MAC_EOL,
     test_string_continuation_unix_eol: UNIX_EOL,
   }
+
+  #[test]
+  fn test_imaginary_numbers() {
+    let source = String::from("2j 1.5J 3");
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::Complex {
+          real: 0.0,
+          imag: 2.0,
+        },
+        Tok::Complex {
+          real: 0.0,
+          imag: 1.5,
+        },
+        Tok::Int {
+          value: BigInt::from(3),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_fstring_prefix() {
+    let source = String::from(r#"f"{x:>10}" F'a' f"#);
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::FString {
+          value: String::from("{x:>10}"),
+        },
+        Tok::FString {
+          value: String::from("a"),
+        },
+        Tok::Name {
+          name: String::from("f"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_bytes_prefix() {
+    let source = String::from(r#"b"\x00a\n" B'\xff' b"#);
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::Bytes {
+          value: vec![0, b'a', b'\n'],
+        },
+        Tok::Bytes { value: vec![255] },
+        Tok::Name {
+          name: String::from("b"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_numbers() {
+    let source = String::from("0xFf 0o17 0B1010 0x_1 1_000_000 00 1.5 1. .5 1e-9 2E+3 1_0.2_5e1_0 012j 1e3j");
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::Int {
+          value: BigInt::from(255),
+        },
+        Tok::Int {
+          value: BigInt::from(15),
+        },
+        Tok::Int {
+          value: BigInt::from(10),
+        },
+        Tok::Int {
+          value: BigInt::from(1),
+        },
+        Tok::Int {
+          value: BigInt::from(1000000),
+        },
+        Tok::Int {
+          value: BigInt::from(0),
+        },
+        Tok::Float { value: 1.5 },
+        Tok::Float { value: 1.0 },
+        Tok::Float { value: 0.5 },
+        Tok::Float { value: 1e-9 },
+        Tok::Float { value: 2000.0 },
+        Tok::Float { value: 10.25e10 },
+        Tok::Complex {
+          real: 0.0,
+          imag: 12.0,
+        },
+        Tok::Complex {
+          real: 0.0,
+          imag: 1000.0,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_dot_before_name_is_not_a_number() {
+    let source = String::from("x.y");
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::Name {
+          name: String::from("x"),
+        },
+        Tok::Dot,
+        Tok::Name {
+          name: String::from("y"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_invalid_numbers() {
+    let lex_error = |source: &str| {
+      Lexer::new(source)
+        .filter_map(|x| x.err())
+        .next()
+        .unwrap()
+    };
+    assert_eq!(
+      lex_error("x = 0b102"),
+      LexicalError::InvalidDigit {
+        digit: '2',
+        radix: 2,
+        location: Location::new(1, 9),
+      }
+    );
+    assert_eq!(
+      lex_error("0o8"),
+      LexicalError::InvalidDigit {
+        digit: '8',
+        radix: 8,
+        location: Location::new(1, 3),
+      }
+    );
+    assert_eq!(
+      lex_error("0x"),
+      LexicalError::InvalidNumber {
+        location: Location::new(1, 3),
+      }
+    );
+    assert_eq!(
+      lex_error("1__0"),
+      LexicalError::InvalidNumber {
+        location: Location::new(1, 2),
+      }
+    );
+    assert_eq!(
+      lex_error("10_"),
+      LexicalError::InvalidNumber {
+        location: Location::new(1, 3),
+      }
+    );
+    assert_eq!(
+      lex_error("1e+"),
+      LexicalError::InvalidNumber {
+        location: Location::new(1, 4),
+      }
+    );
+    assert_eq!(
+      lex_error("y = 012"),
+      LexicalError::LeadingZeros {
+        location: Location::new(1, 5),
+      }
+    );
+  }
+
+  #[test]
+  fn test_string_escapes() {
+    let source = String::from(r#""\x41é\U0001F600\N{BULLET}\101\0\q" b"\x41\101\777\u00e9""#);
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::String {
+          value: String::from("A\u{e9}\u{1f600}\u{2022}A\0\\q"),
+        },
+        Tok::Bytes {
+          value: b"AA\xff\\u00e9".to_vec(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_string_prefixes() {
+    let source = String::from(r#"r"\d\"" Rb'\x00' u'a' bR"\n" rf"{x}\n" ur"#);
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::String {
+          value: String::from("\\d\\\""),
+        },
+        Tok::Bytes {
+          value: b"\\x00".to_vec(),
+        },
+        Tok::String {
+          value: String::from("a"),
+        },
+        Tok::Bytes {
+          value: b"\\n".to_vec(),
+        },
+        Tok::FString {
+          value: String::from("{x}\\n"),
+        },
+        Tok::Name {
+          name: String::from("ur"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_invalid_strings() {
+    let lex_error = |source: &str| {
+      Lexer::new(source)
+        .filter_map(|x| x.err())
+        .next()
+        .unwrap()
+    };
+    assert_eq!(
+      lex_error(r"x = 'a\x4g'"),
+      LexicalError::InvalidEscape {
+        escape: String::from(r"\x4"),
+        location: Location::new(1, 7),
+      }
+    );
+    assert_eq!(
+      lex_error(r"'\N{NOT A NAME}'"),
+      LexicalError::InvalidEscape {
+        escape: String::from(r"\N{NOT A NAME}"),
+        location: Location::new(1, 2),
+      }
+    );
+    assert_eq!(
+      lex_error("x = 'ab\nc'"),
+      LexicalError::UnterminatedString {
+        location: Location::new(1, 5),
+      }
+    );
+    assert_eq!(
+      lex_error("b'\u{e9}'"),
+      LexicalError::NonAsciiBytes {
+        location: Location::new(1, 3),
+      }
+    );
+  }
+
+  #[test]
+  fn test_line_continuation_and_tabs() {
+    let source = String::from("x = 1 + \\\n\t2\n");
+    let tokens = lex_source(&source);
+    assert_eq!(
+      tokens,
+      vec![
+        Tok::Name {
+          name: String::from("x"),
+        },
+        Tok::Equal,
+        Tok::Int {
+          value: BigInt::from(1),
+        },
+        Tok::Plus,
+        Tok::Int {
+          value: BigInt::from(2),
+        },
+        Tok::Newline,
+      ]
+    );
+  }
+
+  #[test]
+  fn test_invalid_characters_and_indentation() {
+    let lex_error = |source: &str| {
+      Lexer::new(source)
+        .filter_map(|x| x.err())
+        .next()
+        .unwrap()
+    };
+    assert_eq!(
+      lex_error("x = $"),
+      LexicalError::UnexpectedCharacter {
+        character: '$',
+        location: Location::new(1, 5),
+      }
+    );
+    assert_eq!(
+      lex_error("a ! b"),
+      LexicalError::UnexpectedCharacter {
+        character: '!',
+        location: Location::new(1, 3),
+      }
+    );
+    assert_eq!(
+      lex_error("x = 1 \\ 2"),
+      LexicalError::CharacterAfterContinuation {
+        location: Location::new(1, 7),
+      }
+    );
+    assert_eq!(
+      lex_error("if x:\n    a\n  b\n"),
+      LexicalError::InconsistentDedent {
+        location: Location::new(3, 3),
+      }
+    );
+  }
 }
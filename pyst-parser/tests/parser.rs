@@ -1,11 +1,56 @@
+extern crate num_bigint;
 extern crate pyst_parser;
 
 #[cfg(test)]
 mod tests {
+  use num_bigint::BigInt;
   use pyst_parser::ast;
+  use pyst_parser::error::ParseErrorKind;
+  use pyst_parser::function::ArgumentError;
+  use pyst_parser::lexer::LexicalError;
+  use pyst_parser::token::Tok;
   use pyst_parser::parser::parse_program;
   use pyst_parser::parser::parse_statement;
 
+  // A node on the given row, from column start up to column end:
+  fn located<T>(
+    row: usize,
+    start: usize,
+    end: usize,
+    node: T,
+  ) -> ast::Located<T> {
+    ast::Located {
+      location: ast::Location::new(row, start),
+      end_location: ast::Location::new(row, end),
+      node: node,
+    }
+  }
+
+  fn number(row: usize, start: usize, value: i32) -> ast::LocatedExpression {
+    let end = start + value.to_string().len();
+    located(
+      row,
+      start,
+      end,
+      ast::Expression::Number {
+        value: ast::Number::Integer {
+          value: BigInt::from(value),
+        },
+      },
+    )
+  }
+
+  fn name(start: usize, name: &str) -> ast::LocatedExpression {
+    located(
+      1,
+      start,
+      start + name.len(),
+      ast::Expression::Identifier {
+        name: String::from(name),
+      },
+    )
+  }
+
   #[test]
   fn test_parse_empty() {
     let parse_ast = parse_program(&String::from("\n"));
@@ -20,19 +65,30 @@ mod tests {
     assert_eq!(
       parse_ast,
       ast::Program {
-        statements: vec![ast::LocatedStatement {
-          location: ast::Location::new(1, 1),
-          node: ast::Statement::Expression {
-            expression: ast::Expression::Call {
-              function: Box::new(ast::Expression::Identifier {
-                name: String::from("print"),
-              }),
-              args: vec![ast::Expression::String {
-                value: String::from("Hello world"),
-              },],
-            },
+        statements: vec![located(
+          1,
+          1,
+          21,
+          ast::Statement::Expression {
+            expression: located(
+              1,
+              1,
+              21,
+              ast::Expression::Call {
+                function: Box::new(name(1, "print")),
+                args: vec![located(
+                  1,
+                  7,
+                  20,
+                  ast::Expression::String {
+                    value: String::from("Hello world"),
+                  },
+                ),],
+                keywords: vec![],
+              },
+            ),
           },
-        },],
+        ),],
       }
     );
   }
@@ -44,25 +100,69 @@ mod tests {
     assert_eq!(
       parse_ast,
       ast::Program {
-        statements: vec![ast::LocatedStatement {
-          location: ast::Location::new(1, 1),
-          node: ast::Statement::Expression {
-            expression: ast::Expression::Call {
-              function: Box::new(ast::Expression::Identifier {
-                name: String::from("print"),
-              }),
-              args: vec![
-                ast::Expression::String {
-                  value: String::from("Hello world"),
-                },
+        statements: vec![located(
+          1,
+          1,
+          24,
+          ast::Statement::Expression {
+            expression: located(
+              1,
+              1,
+              24,
+              ast::Expression::Call {
+                function: Box::new(name(1, "print")),
+                args: vec![
+                  located(
+                    1,
+                    7,
+                    20,
+                    ast::Expression::String {
+                      value: String::from("Hello world"),
+                    },
+                  ),
+                  number(1, 22, 2),
+                ],
+                keywords: vec![],
+              },
+            ),
+          },
+        ),],
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_big_int_shift() {
+    let source = String::from("123456789012345678901234567890 << 2\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        36,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            36,
+            ast::Expression::Binop {
+              a: Box::new(located(
+                1,
+                1,
+                31,
                 ast::Expression::Number {
-                  value: ast::Number::Integer { value: 2 },
+                  value: ast::Number::Integer {
+                    value: "123456789012345678901234567890".parse().unwrap(),
+                  },
                 },
-              ],
+              )),
+              op: ast::Operator::LShift,
+              b: Box::new(number(1, 35, 2)),
             },
-          },
-        },],
-      }
+          ),
+        },
+      )
     );
   }
 
@@ -70,44 +170,31 @@ mod tests {
   fn test_parse_if_elif_else() {
     let source = String::from("if 1: 10\nelif 2: 20\nelse: 30\n");
     let parse_ast = parse_statement(&source).unwrap();
+    let expression = |row, start, value| {
+      located(
+        row,
+        start,
+        start + 2,
+        ast::Statement::Expression {
+          expression: number(row, start, value),
+        },
+      )
+    };
     assert_eq!(
       parse_ast,
       ast::LocatedStatement {
         location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(3, 9),
         node: ast::Statement::If {
-          test: ast::Expression::Number {
-            value: ast::Number::Integer { value: 1 },
-          },
-          body: vec![ast::LocatedStatement {
-            location: ast::Location::new(1, 7),
-            node: ast::Statement::Expression {
-              expression: ast::Expression::Number {
-                value: ast::Number::Integer { value: 10 },
-              }
-            },
-          },],
+          test: number(1, 4, 1),
+          body: vec![expression(1, 7, 10)],
           orelse: Some(vec![ast::LocatedStatement {
             location: ast::Location::new(2, 1),
+            end_location: ast::Location::new(3, 9),
             node: ast::Statement::If {
-              test: ast::Expression::Number {
-                value: ast::Number::Integer { value: 2 },
-              },
-              body: vec![ast::LocatedStatement {
-                location: ast::Location::new(2, 9),
-                node: ast::Statement::Expression {
-                  expression: ast::Expression::Number {
-                    value: ast::Number::Integer { value: 20 },
-                  },
-                },
-              },],
-              orelse: Some(vec![ast::LocatedStatement {
-                location: ast::Location::new(3, 7),
-                node: ast::Statement::Expression {
-                  expression: ast::Expression::Number {
-                    value: ast::Number::Integer { value: 30 },
-                  },
-                },
-              },]),
+              test: number(2, 6, 2),
+              body: vec![expression(2, 9, 20)],
+              orelse: Some(vec![expression(3, 7, 30)]),
             }
           },]),
         }
@@ -121,23 +208,34 @@ mod tests {
     let parse_ast = parse_statement(&source);
     assert_eq!(
       parse_ast,
-      Ok(ast::LocatedStatement {
-        location: ast::Location::new(1, 1),
-        node: ast::Statement::Expression {
-          expression: ast::Expression::Lambda {
-            args: vec![String::from("x"), String::from("y")],
-            body: Box::new(ast::Expression::Binop {
-              a: Box::new(ast::Expression::Identifier {
-                name: String::from("x"),
-              }),
-              op: ast::Operator::Mult,
-              b: Box::new(ast::Expression::Identifier {
-                name: String::from("y"),
-              })
-            })
-          }
+      Ok(located(
+        1,
+        1,
+        19,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            19,
+            ast::Expression::Lambda {
+              args: ast::Parameters {
+                args: vec![String::from("x"), String::from("y")],
+                ..Default::default()
+              },
+              body: Box::new(located(
+                1,
+                14,
+                19,
+                ast::Expression::Binop {
+                  a: Box::new(name(14, "x")),
+                  op: ast::Operator::Mult,
+                  b: Box::new(name(18, "y")),
+                }
+              ))
+            }
+          )
         }
-      })
+      ))
     )
   }
 
@@ -148,22 +246,700 @@ mod tests {
       parse_statement(&source),
       Ok(ast::LocatedStatement {
         location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(3, 7),
         node: ast::Statement::ClassDef {
           name: String::from("Foo"),
           args: vec![String::from("A"), String::from("B")],
           body: vec![ast::LocatedStatement {
             location: ast::Location::new(2, 2),
+            end_location: ast::Location::new(3, 7),
             node: ast::Statement::FunctionDef {
               name: String::from("__init__"),
-              args: vec![String::from("self")],
-              body: vec![ast::LocatedStatement {
-                location: ast::Location::new(3, 3),
-                node: ast::Statement::Pass,
-              }],
+              args: ast::Parameters {
+                args: vec![String::from("self")],
+                ..Default::default()
+              },
+              body: vec![located(3, 3, 7, ast::Statement::Pass)],
             }
           }],
         }
       })
     )
   }
+
+  #[test]
+  fn test_parse_function_parameters() {
+    let source =
+      String::from("def f(a, b=1, *args, c, d=2, **kwargs):\n pass\n");
+    assert_eq!(
+      parse_statement(&source),
+      Ok(ast::LocatedStatement {
+        location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(2, 6),
+        node: ast::Statement::FunctionDef {
+          name: String::from("f"),
+          args: ast::Parameters {
+            args: vec![String::from("a"), String::from("b")],
+            vararg: Some(String::from("args")),
+            kwonlyargs: vec![String::from("c"), String::from("d")],
+            kwarg: Some(String::from("kwargs")),
+            defaults: vec![number(1, 12, 1)],
+            kw_defaults: vec![None, Some(number(1, 27, 2))],
+          },
+          body: vec![located(2, 2, 6, ast::Statement::Pass)],
+        }
+      })
+    )
+  }
+
+  #[test]
+  fn test_parse_call_with_keywords() {
+    let source = String::from("f(x, *xs, k=y, **kw)\n");
+    assert_eq!(
+      parse_statement(&source),
+      Ok(located(
+        1,
+        1,
+        21,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            21,
+            ast::Expression::Call {
+              function: Box::new(name(1, "f")),
+              args: vec![
+                name(3, "x"),
+                located(
+                  1,
+                  6,
+                  9,
+                  ast::Expression::Starred {
+                    value: Box::new(name(7, "xs")),
+                  },
+                ),
+              ],
+              keywords: vec![
+                ast::Keyword {
+                  name: Some(String::from("k")),
+                  value: name(13, "y"),
+                },
+                ast::Keyword {
+                  name: None,
+                  value: name(18, "kw"),
+                },
+              ],
+            },
+          ),
+        }
+      ))
+    )
+  }
+
+  #[test]
+  fn test_parse_global_and_nonlocal() {
+    let source = String::from("global a, b\nnonlocal c\n");
+    assert_eq!(
+      parse_program(&source),
+      Ok(ast::Program {
+        statements: vec![
+          located(
+            1,
+            1,
+            12,
+            ast::Statement::Global {
+              names: vec![String::from("a"), String::from("b")],
+            },
+          ),
+          located(
+            2,
+            1,
+            11,
+            ast::Statement::Nonlocal {
+              names: vec![String::from("c")],
+            },
+          ),
+        ],
+      })
+    )
+  }
+
+  #[test]
+  fn test_parse_yield() {
+    let source = String::from("x = yield 1, 2\nyield from y\n");
+    assert_eq!(
+      parse_program(&source),
+      Ok(ast::Program {
+        statements: vec![
+          located(
+            1,
+            1,
+            15,
+            ast::Statement::Assign {
+              targets: vec![name(1, "x")],
+              value: located(
+                1,
+                5,
+                15,
+                ast::Expression::Yield {
+                  value: Some(Box::new(located(
+                    1,
+                    11,
+                    15,
+                    ast::Expression::Tuple {
+                      elements: vec![number(1, 11, 1), number(1, 14, 2)],
+                    },
+                  ))),
+                },
+              ),
+            },
+          ),
+          located(
+            2,
+            1,
+            13,
+            ast::Statement::Expression {
+              expression: located(
+                2,
+                1,
+                13,
+                ast::Expression::YieldFrom {
+                  value: Box::new(located(
+                    2,
+                    12,
+                    13,
+                    ast::Expression::Identifier {
+                      name: String::from("y"),
+                    },
+                  )),
+                },
+              ),
+            },
+          ),
+        ],
+      })
+    )
+  }
+
+  #[test]
+  fn test_parse_list_comprehension() {
+    let source = String::from("[x for x, y in z if y for w in x]\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        34,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            34,
+            ast::Expression::Comprehension {
+              kind: Box::new(ast::ComprehensionKind::List {
+                element: name(2, "x"),
+              }),
+              generators: vec![
+                ast::Comprehension {
+                  target: located(
+                    1,
+                    8,
+                    12,
+                    ast::Expression::Tuple {
+                      elements: vec![name(8, "x"), name(11, "y")],
+                    },
+                  ),
+                  iter: name(16, "z"),
+                  ifs: vec![name(21, "y")],
+                },
+                ast::Comprehension {
+                  target: name(27, "w"),
+                  iter: name(32, "x"),
+                  ifs: vec![],
+                },
+              ],
+            },
+          ),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_generator_expression_argument() {
+    let source = String::from("f(x for x in y)\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        16,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            16,
+            ast::Expression::Call {
+              function: Box::new(name(1, "f")),
+              args: vec![located(
+                1,
+                3,
+                15,
+                ast::Expression::Comprehension {
+                  kind: Box::new(ast::ComprehensionKind::GeneratorExpression {
+                    element: name(3, "x"),
+                  }),
+                  generators: vec![ast::Comprehension {
+                    target: name(9, "x"),
+                    iter: name(14, "y"),
+                    ifs: vec![],
+                  }],
+                },
+              )],
+              keywords: vec![],
+            },
+          ),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_with_items() {
+    let source = String::from("with a as b, c:\n  pass\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      ast::LocatedStatement {
+        location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(2, 7),
+        node: ast::Statement::With {
+          items: vec![
+            ast::WithItem {
+              context_expr: name(6, "a"),
+              optional_vars: Some(name(11, "b")),
+            },
+            ast::WithItem {
+              context_expr: name(14, "c"),
+              optional_vars: None,
+            },
+          ],
+          body: vec![located(2, 3, 7, ast::Statement::Pass)],
+        },
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_raise_from() {
+    let source = String::from("raise a from b\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        15,
+        ast::Statement::Raise {
+          expression: Some(name(7, "a")),
+          cause: Some(name(14, "b")),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_try_finally_without_handlers() {
+    let source = String::from("try:\n  pass\nfinally:\n  pass\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    let pass = |row| located(row, 3, 7, ast::Statement::Pass);
+    assert_eq!(
+      parse_ast,
+      ast::LocatedStatement {
+        location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(4, 7),
+        node: ast::Statement::Try {
+          body: vec![pass(2)],
+          handlers: vec![],
+          orelse: None,
+          finalbody: Some(vec![pass(4)]),
+        },
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_chained_unpacking_assignment() {
+    let source = String::from("a, *b = c = d, e\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        17,
+        ast::Statement::Assign {
+          targets: vec![
+            located(
+              1,
+              1,
+              6,
+              ast::Expression::Tuple {
+                elements: vec![
+                  name(1, "a"),
+                  located(
+                    1,
+                    4,
+                    6,
+                    ast::Expression::Starred {
+                      value: Box::new(name(5, "b")),
+                    },
+                  ),
+                ],
+              },
+            ),
+            name(9, "c"),
+          ],
+          value: located(
+            1,
+            13,
+            17,
+            ast::Expression::Tuple {
+              elements: vec![name(13, "d"), name(16, "e")],
+            },
+          ),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_del_targets() {
+    let source = String::from("del a, b[c]\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        12,
+        ast::Statement::Delete {
+          targets: vec![
+            name(5, "a"),
+            located(
+              1,
+              8,
+              12,
+              ast::Expression::Subscript {
+                a: Box::new(name(8, "b")),
+                b: Box::new(name(10, "c")),
+              },
+            ),
+          ],
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_set_display_and_comprehension() {
+    let source = String::from("{a, b}, {a for a in b}\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        23,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            23,
+            ast::Expression::Tuple {
+              elements: vec![
+                located(
+                  1,
+                  1,
+                  7,
+                  ast::Expression::Set {
+                    elements: vec![name(2, "a"), name(5, "b")],
+                  },
+                ),
+                located(
+                  1,
+                  9,
+                  23,
+                  ast::Expression::Comprehension {
+                    kind: Box::new(ast::ComprehensionKind::Set {
+                      element: name(10, "a"),
+                    }),
+                    generators: vec![ast::Comprehension {
+                      target: name(16, "a"),
+                      iter: name(21, "b"),
+                      ifs: vec![],
+                    }],
+                  },
+                ),
+              ],
+            },
+          ),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_fstring() {
+    let source = String::from("'a' f'{x!r:>{w}} {{}}'\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    // The parts of the f-string are located at the whole string, and the
+    // expressions in its fields from the start of the f'' token on:
+    let string = |s: &str| {
+      located(
+        1,
+        1,
+        23,
+        ast::Expression::String {
+          value: String::from(s),
+        },
+      )
+    };
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        23,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            23,
+            ast::Expression::JoinedStr {
+              values: vec![
+                string("a"),
+                located(
+                  1,
+                  1,
+                  23,
+                  ast::Expression::FormattedValue {
+                    value: Box::new(name(6, "x")),
+                    conversion: Some(ast::ConversionFlag::Repr),
+                    spec: Some(Box::new(located(
+                      1,
+                      1,
+                      23,
+                      ast::Expression::JoinedStr {
+                        values: vec![
+                          string(">"),
+                          located(
+                            1,
+                            1,
+                            23,
+                            ast::Expression::FormattedValue {
+                              value: Box::new(name(12, "w")),
+                              conversion: None,
+                              spec: None,
+                            },
+                          ),
+                        ],
+                      },
+                    ))),
+                  },
+                ),
+                string(" {}"),
+              ],
+            },
+          ),
+        },
+      )
+    );
+  }
+
+  #[test]
+  fn test_parse_fstring_errors() {
+    for source in &["f'{'\n", "f'}'\n", "f'{}'\n", "f'{x!z}'\n"] {
+      assert!(parse_statement(&source.to_string()).is_err());
+    }
+  }
+
+  #[test]
+  fn test_parse_bytes() {
+    let source = String::from("b'a\\x00' b\"c\"\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      located(
+        1,
+        1,
+        14,
+        ast::Statement::Expression {
+          expression: located(
+            1,
+            1,
+            14,
+            ast::Expression::Bytes {
+              value: vec![b'a', 0, b'c'],
+            },
+          ),
+        },
+      )
+    );
+    assert!(parse_statement(&String::from("b'\u{e9}'\n")).is_err());
+  }
+
+  #[test]
+  fn test_parse_implicit_string_concatenation() {
+    let source = String::from("'a' r'\\d' \"\"\"\nc\"\"\"\n");
+    let parse_ast = parse_statement(&source).unwrap();
+    assert_eq!(
+      parse_ast,
+      ast::LocatedStatement {
+        location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(2, 5),
+        node: ast::Statement::Expression {
+          expression: ast::LocatedExpression {
+            location: ast::Location::new(1, 1),
+            end_location: ast::Location::new(2, 5),
+            node: ast::Expression::String {
+              value: String::from("a\\d\nc"),
+            },
+          },
+        },
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_expression_locations() {
+    let source = String::from("x = (a +
+     b[1:])
+");
+    let parse_ast = parse_statement(&source).unwrap();
+    let row_2 = |start, end, node| located(2, start, end, node);
+    assert_eq!(
+      parse_ast,
+      ast::LocatedStatement {
+        location: ast::Location::new(1, 1),
+        end_location: ast::Location::new(2, 12),
+        node: ast::Statement::Assign {
+          targets: vec![name(1, "x")],
+          value: ast::LocatedExpression {
+            location: ast::Location::new(1, 6),
+            end_location: ast::Location::new(2, 11),
+            node: ast::Expression::Binop {
+              a: Box::new(name(6, "a")),
+              op: ast::Operator::Add,
+              b: Box::new(row_2(
+                6,
+                11,
+                ast::Expression::Subscript {
+                  a: Box::new(row_2(
+                    6,
+                    7,
+                    ast::Expression::Identifier {
+                      name: String::from("b"),
+                    },
+                  )),
+                  // The bounds which are left out are located at the slice:
+                  b: Box::new(row_2(
+                    8,
+                    10,
+                    ast::Expression::Slice {
+                      elements: vec![
+                        number(2, 8, 1),
+                        row_2(8, 10, ast::Expression::PyNone),
+                        row_2(8, 10, ast::Expression::PyNone),
+                      ],
+                    },
+                  )),
+                },
+              )),
+            },
+          },
+        },
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    let error = parse_program(&String::from("if x:\n")).unwrap_err();
+    assert!(error.is_incomplete());
+    assert_eq!(error.location, ast::Location::new(1, 7));
+    assert_eq!(
+      error.kind,
+      ParseErrorKind::Incomplete {
+        expected: vec![String::from("indent")],
+      }
+    );
+
+    let error = parse_program(&String::from("x = 1 +\n")).unwrap_err();
+    assert!(!error.is_incomplete());
+    assert_eq!(error.location, ast::Location::new(1, 8));
+    assert_eq!(error.to_string(), "invalid syntax");
+    match error.kind {
+      ParseErrorKind::UnrecognizedToken { token, expected } => {
+        assert_eq!(token, Tok::Newline);
+        assert!(expected.contains(&String::from("name")));
+      }
+      kind => panic!("unexpected error kind {:?}", kind),
+    }
+
+    let error = parse_program(&String::from("x = 1\ny = 'a\n")).unwrap_err();
+    assert_eq!(error.location, ast::Location::new(2, 5));
+    assert_eq!(
+      error.kind,
+      ParseErrorKind::Lexical(LexicalError::UnterminatedString {
+        location: ast::Location::new(2, 5),
+      })
+    );
+    assert_eq!(error.to_string(), "unterminated string literal");
+
+    let error = parse_program(&String::from("x = f'{!r}'\n")).unwrap_err();
+    assert_eq!(error.location, ast::Location::new(1, 5));
+    assert_eq!(error.to_string(), "f-string: empty expression not allowed");
+  }
+
+  #[test]
+  fn test_argument_order_errors() {
+    let error_text = |source: &str| {
+      parse_program(&String::from(source)).unwrap_err().to_string()
+    };
+    let error = parse_program(&String::from("def f(a=1, b):\n  pass\n"))
+      .unwrap_err();
+    assert_eq!(
+      error.kind,
+      ParseErrorKind::Argument(ArgumentError::NonDefaultAfterDefault)
+    );
+    assert_eq!(error.location, ast::Location::new(1, 12));
+    assert_eq!(
+      error.to_string(),
+      "non-default argument follows default argument"
+    );
+    let error = parse_program(&String::from("lambda a=1, b: 1\n")).unwrap_err();
+    assert_eq!(error.location, ast::Location::new(1, 13));
+    assert_eq!(
+      error_text("def f(a, *b, **a):\n  pass\n"),
+      "duplicate argument 'a' in function definition"
+    );
+    assert_eq!(
+      error_text("lambda x, x: 0\n"),
+      "duplicate argument 'x' in function definition"
+    );
+    let error = parse_program(&String::from("f(a=1, 2)\n")).unwrap_err();
+    assert_eq!(error.location, ast::Location::new(1, 8));
+    assert_eq!(
+      error.to_string(),
+      "positional argument follows keyword argument"
+    );
+    assert_eq!(
+      error_text("f(**y, *x)\n"),
+      "iterable argument unpacking follows keyword argument unpacking"
+    );
+    assert!(parse_program(&String::from("f(1, a=2, *x, b=3, **y)\n")).is_ok());
+  }
 }
@@ -1,31 +1,33 @@
 // use std::ops::Deref;
 use std::char;
-use std::collections::HashMap;
 use std::io::{self, Write};
 
+use num_traits::ToPrimitive;
+
 use super::compile;
 use super::objbool;
+use super::objdict::{self, DictElements};
+use super::objgenerator;
+use super::objiter;
+use super::objnumber::{self, Number};
+use super::objstr;
 use super::objtype;
-use super::pyobject::DictProtocol;
 use super::pyobject::{
   AttributeProtocol, IdProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
-  PyObjectRef, PyResult, Scope,
+  PyObjectRef, PyResult, Scope, TypeProtocol,
 };
 use super::vm::VirtualMachine;
 
 fn get_locals(vm: &mut VirtualMachine) -> PyObjectRef {
-  let d = vm.new_dict();
-  // TODO: implement dict_iter_items?
   let locals = vm.get_locals();
-  match locals.borrow().kind {
-    PyObjectKind::Dict { ref elements } => {
-      for l in elements {
-        d.set_item(l.0, l.1.clone());
-      }
-    }
-    _ => {}
+  let elements = match locals.borrow().kind {
+    PyObjectKind::Dict { ref elements } => elements.clone(),
+    _ => DictElements::new(),
   };
-  d
+  PyObject::new(
+    PyObjectKind::Dict { elements: elements },
+    vm.context().dict_type.clone(),
+  )
 }
 
 fn dir_locals(vm: &mut VirtualMachine) -> PyObjectRef {
@@ -42,14 +44,57 @@ fn dir_object(vm: &mut VirtualMachine, _obj: PyObjectRef) -> PyObjectRef {
   d
 }
 
-// builtin_abs
+fn builtin_abs(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 1 {
+    return Err(
+      vm.new_type_error("abs() takes exactly one argument".to_string()),
+    );
+  }
+  match objnumber::to_number(&args.args[0].borrow().kind) {
+    Some(Number::Integer(value)) => Ok(vm.context().new_int(value.abs())),
+    Some(Number::Float(value)) => Ok(vm.context().new_float(value.abs())),
+    // The absolute value of a complex number is its magnitude:
+    Some(Number::Complex(value)) => Ok(vm.context().new_float(value.norm())),
+    None => Err(vm.new_type_error(format!(
+      "bad operand type for abs(): '{}'",
+      objtype::get_type_name(&args.args[0].typ())
+    ))),
+  }
+}
+
+fn get_iterable_arg(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+) -> PyResult {
+  if args.args.len() != 1 {
+    return Err(vm.new_type_error(format!(
+      "{}() takes exactly one argument ({} given)",
+      name,
+      args.args.len()
+    )));
+  }
+  objiter::get_iter(vm, &args.args[0])
+}
 
 fn builtin_all(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
-  Ok(vm.new_bool(args.args.into_iter().all(|e| objbool::boolval(e))))
+  let iterator = get_iterable_arg(vm, &args, "all")?;
+  while let Some(item) = objiter::get_next_object(vm, &iterator)? {
+    if !objbool::boolval(vm, item)? {
+      return Ok(vm.new_bool(false));
+    }
+  }
+  Ok(vm.new_bool(true))
 }
 
 fn builtin_any(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
-  Ok(vm.new_bool(args.args.into_iter().any(|e| objbool::boolval(e))))
+  let iterator = get_iterable_arg(vm, &args, "any")?;
+  while let Some(item) = objiter::get_next_object(vm, &iterator)? {
+    if objbool::boolval(vm, item)? {
+      return Ok(vm.new_bool(true));
+    }
+  }
+  Ok(vm.new_bool(false))
 }
 
 // builtin_ascii
@@ -62,19 +107,25 @@ fn builtin_any(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 
 fn builtin_chr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() != 1 {
-    return Err(vm.new_exception("Expected one arguments".to_string()));
+    return Err(vm.new_type_error("Expected one arguments".to_string()));
   }
 
   let code_point_obj = args.args[0].borrow();
 
-  let code_point = *match code_point_obj.kind {
-    PyObjectKind::Integer { ref value } => value,
-    ref kind => unimplemented!("{:?} not implemented for chr", kind),
-  } as u32;
+  let code_point = match code_point_obj.kind {
+    PyObjectKind::Integer { ref value } => value.to_u32(),
+    _ => {
+      return Err(vm.new_type_error("an integer is required".to_string()));
+    }
+  };
 
-  let txt = match char::from_u32(code_point) {
+  let txt = match code_point.and_then(char::from_u32) {
     Some(value) => value.to_string(),
-    None => '_'.to_string(),
+    None => {
+      return Err(
+        vm.new_value_error("chr() arg not in range(0x110000)".to_string()),
+      );
+    }
   };
 
   Ok(vm.new_str(txt))
@@ -84,15 +135,15 @@ fn builtin_chr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 
 fn builtin_compile(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() < 1 {
-    return Err(vm.new_exception("Expected more arguments".to_string()));
+    return Err(vm.new_type_error("Expected more arguments".to_string()));
   }
   // TODO:
   let mode = compile::Mode::Eval;
   let source = args.args[0].borrow().str();
 
-  match compile::compile(vm, &source, mode) {
+  match compile::compile(vm, &source, mode, None) {
     Ok(value) => Ok(value),
-    Err(msg) => Err(vm.new_exception(msg)),
+    Err(error) => Err(vm.new_syntax_error(&error)),
   }
 }
 
@@ -108,19 +159,30 @@ fn builtin_dir(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   }
 }
 
-// builtin_divmod
+fn builtin_divmod(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 2 {
+    return Err(vm.new_type_error("divmod expected 2 arguments".to_string()));
+  }
+  let a = args.args[0].clone();
+  let b = args.args[1].clone();
+  let div = vm._floordiv(a.clone(), b.clone())?;
+  let modulo = vm._modulo(a, b)?;
+  Ok(vm.context().new_tuple(vec![div, modulo]))
+}
 // builtin_enumerate
 
 fn builtin_eval(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   let args = args.args;
   if args.len() > 3 {
     return Err(
-      vm.new_exception("Expected at maximum of 3 arguments".to_string()),
+      vm.new_type_error("Expected at maximum of 3 arguments".to_string()),
     );
   } else if args.len() > 2 {
     // TODO: handle optional global and locals
   } else {
-    return Err(vm.new_exception("Expected at least one argument".to_string()));
+    return Err(
+      vm.new_type_error("Expected at least one argument".to_string()),
+    );
   }
   let source = args[0].clone();
   let _globals = args[1].clone();
@@ -146,7 +208,28 @@ fn builtin_eval(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 // builtin_exec
 // builtin_filter
 // builtin_float
-// builtin_format
+fn builtin_format(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.is_empty() || args.args.len() > 2 {
+    return Err(vm.new_type_error(format!(
+      "format expected 1 or 2 arguments, got {}",
+      args.args.len()
+    )));
+  }
+  let spec = match args.args.get(1) {
+    Some(spec) => match objstr::get_value(spec) {
+      Some(spec) => spec,
+      None => {
+        return Err(
+          vm.new_type_error("format() argument 2 must be str".to_string()),
+        )
+      }
+    },
+    None => String::new(),
+  };
+  let value = objstr::format_value(vm, &args.args[0], &spec)?;
+  Ok(vm.new_str(value))
+}
+
 // builtin_frozenset
 
 fn builtin_getattr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
@@ -157,10 +240,10 @@ fn builtin_getattr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
     if let PyObjectKind::String { ref value } = attr.kind {
       vm.get_attribute(obj, value)
     } else {
-      Err(vm.new_exception("Attr can only be str for now".to_string()))
+      Err(vm.new_type_error("Attr can only be str for now".to_string()))
     }
   } else {
-    Err(vm.new_exception("Expected 2 arguments".to_string()))
+    Err(vm.new_type_error("Expected 2 arguments".to_string()))
   }
 }
 
@@ -178,58 +261,116 @@ fn builtin_hasattr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
       };
       Ok(vm.context().new_bool(has_attr))
     } else {
-      Err(vm.new_exception("Attr can only be str for now".to_string()))
+      Err(vm.new_type_error("Attr can only be str for now".to_string()))
     }
   } else {
-    Err(vm.new_exception("Expected 2 arguments".to_string()))
+    Err(vm.new_type_error("Expected 2 arguments".to_string()))
+  }
+}
+
+fn builtin_hash(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 1 {
+    return Err(vm.new_type_error("Expected only one argument".to_string()));
   }
+  let hash = objdict::hash(vm, &args.args[0])?;
+  Ok(vm.context().new_int(hash))
 }
 
-// builtin_hash
 // builtin_help
 // builtin_hex
 
 fn builtin_id(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() != 1 {
-    return Err(vm.new_exception("Expected only one argument".to_string()));
+    return Err(vm.new_type_error("Expected only one argument".to_string()));
   }
 
-  Ok(vm.context().new_int(args.args[0].get_id() as i32))
+  Ok(vm.context().new_int(args.args[0].get_id()))
 }
 
 // builtin_input
 // builtin_int
-// builtin_isinstance
-// builtin_issubclass
-// builtin_iter
+
+fn builtin_isinstance(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 2 {
+    return Err(vm.new_type_error("Expected 2 arguments".to_string()));
+  }
+
+  let classes = match objtype::get_classes(&args.args[1]) {
+    Some(classes) => classes,
+    None => {
+      return Err(vm.new_type_error(
+        "isinstance() arg 2 must be a type or tuple of types".to_string(),
+      ))
+    }
+  };
+  let isinstance = classes
+    .iter()
+    .any(|cls| objtype::isinstance(&args.args[0], cls));
+  Ok(vm.context().new_bool(isinstance))
+}
+
+fn builtin_issubclass(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 2 {
+    return Err(vm.new_type_error("Expected 2 arguments".to_string()));
+  }
+
+  let is_class = match args.args[0].borrow().kind {
+    PyObjectKind::Class { .. } => true,
+    _ => false,
+  };
+  if !is_class {
+    return Err(
+      vm.new_type_error("issubclass() arg 1 must be a class".to_string()),
+    );
+  }
+  let classes = match objtype::get_classes(&args.args[1]) {
+    Some(classes) => classes,
+    None => {
+      return Err(vm.new_type_error(
+        "issubclass() arg 2 must be a class or tuple of classes".to_string(),
+      ))
+    }
+  };
+  let issubclass = classes
+    .iter()
+    .any(|cls| objtype::issubclass(&args.args[0], cls));
+  Ok(vm.context().new_bool(issubclass))
+}
+
+fn builtin_iter(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 1 {
+    return Err(vm.new_type_error(format!(
+      "iter expected 1 argument, got {}",
+      args.args.len()
+    )));
+  }
+  objiter::get_iter(vm, &args.args[0])
+}
 
 fn builtin_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() != 1 {
-    panic!("len(s) expects exactly one parameter");
+    return Err(
+      vm.new_type_error("len(s) expects exactly one parameter".to_string()),
+    );
   }
   match args.args[0].borrow().kind {
     PyObjectKind::Dict { ref elements } => {
-      Ok(vm.context().new_int(elements.len() as i32))
+      Ok(vm.context().new_int(elements.len()))
     }
     PyObjectKind::Tuple { ref elements } => {
-      Ok(vm.context().new_int(elements.len() as i32))
+      Ok(vm.context().new_int(elements.len()))
     }
     PyObjectKind::String { ref value } => {
-      Ok(vm.context().new_int(value.len() as i32))
+      Ok(vm.context().new_int(value.chars().count()))
     }
     _ => {
       let len_method_name = "__len__".to_string();
       match vm.get_attribute(args.args[0].clone(), &len_method_name) {
         Ok(value) => vm.invoke(value, PyFuncArgs::default()),
-        Err(..) => Err(
-          vm.context().new_str(
-            format!(
-              "TypeError: object of this {:?} type has no method {:?}",
-              args.args[0], len_method_name
-            )
-            .to_string(),
-          ),
-        ),
+        Err(..) => Err(vm.new_type_error(format!(
+          "object of type '{}' has no len()",
+          objtype::get_type_name(&args.args[0].typ())
+        ))),
       }
     }
   }
@@ -239,17 +380,28 @@ fn builtin_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 
 fn builtin_locals(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() != 0 {
-    panic!("locals() doesn't take any arguments");
+    return Err(
+      vm.new_type_error("locals() doesn't take any arguments".to_string()),
+    );
   }
   Ok(vm.get_locals())
 }
 
 pub fn builtin_print(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   trace!("print called with {:?}", args);
-  for a in args.args {
-    print!("{} ", a.borrow().str());
+  let sep = match args.get_kwarg("sep") {
+    Some(sep) => sep.borrow().str(),
+    None => " ".to_string(),
+  };
+  let end = match args.get_kwarg("end") {
+    Some(end) => end.borrow().str(),
+    None => "\n".to_string(),
+  };
+  let mut strings = vec![];
+  for arg in &args.args {
+    strings.push(vm.to_str(arg.clone())?);
   }
-  println!();
+  print!("{}{}", strings.join(&sep), end);
   io::stdout().flush().unwrap();
   Ok(vm.get_none())
 }
@@ -258,30 +410,65 @@ pub fn builtin_print(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 // builtin_max
 // builtin_memoryview
 // builtin_min
-// builtin_next
+// next(iterator[, default]) returns the default instead of raising
+// StopIteration when the iterator is exhausted:
+fn builtin_next(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.is_empty() || args.args.len() > 2 {
+    return Err(vm.new_type_error(format!(
+      "next expected 1 or 2 arguments, got {}",
+      args.args.len()
+    )));
+  }
+  match objiter::call_next(vm, &args.args[0]) {
+    Err(ref exc)
+      if args.args.len() == 2 && objgenerator::is_stop_iteration(vm, exc) =>
+    {
+      Ok(args.args[1].clone())
+    }
+    result => result,
+  }
+}
+
 // builtin_object
 // builtin_oct
 // builtin_open
-// builtin_ord
+
+fn builtin_ord(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() != 1 {
+    return Err(vm.new_type_error("Expected one arguments".to_string()));
+  }
+  let value = match objstr::get_value(&args.args[0]) {
+    Some(value) => value,
+    None => {
+      return Err(vm.new_type_error(format!(
+        "ord() expected string of length 1, but {} found",
+        objtype::get_type_name(&args.args[0].typ())
+      )))
+    }
+  };
+  let mut chars = value.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) => Ok(vm.context().new_int(c as u32)),
+    _ => Err(vm.new_type_error(format!(
+      "ord() expected a character, but string of length {} found",
+      value.chars().count()
+    ))),
+  }
+}
+
 // builtin_pow
 // builtin_print
 // builtin_property
+// builtin_range
 
-fn builtin_range(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+fn builtin_repr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   if args.args.len() != 1 {
-    unimplemented!("only the single-parameter range is implemented");
-  }
-  match args.args[0].borrow().kind {
-    PyObjectKind::Integer { ref value } => {
-      let range_elements: Vec<PyObjectRef> =
-        (0..*value).map(|num| vm.context().new_int(num)).collect();
-      Ok(vm.context().new_list(range_elements))
-    }
-    _ => panic!("first argument to range must be an integer"),
+    return Err(vm.new_type_error("Expected only one argument".to_string()));
   }
+  let value = vm.to_repr(args.args[0].clone())?;
+  Ok(vm.new_str(value))
 }
 
-// builtin_repr
 // builtin_reversed
 // builtin_round
 // builtin_set
@@ -296,10 +483,10 @@ fn builtin_setattr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
       obj.set_attr(name, value);
       Ok(vm.get_none())
     } else {
-      Err(vm.new_exception("Attr can only be str for now".to_string()))
+      Err(vm.new_type_error("Attr can only be str for now".to_string()))
     }
   } else {
-    Err(vm.new_exception("Expected 3 arguments".to_string()))
+    Err(vm.new_type_error("Expected 3 arguments".to_string()))
   }
 }
 
@@ -307,25 +494,6 @@ fn builtin_setattr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 // builtin_sorted
 // builtin_staticmethod
 
-// TODO: should with following format
-// class str(object='')
-// class str(object=b'', encoding='utf-8', errors='strict')
-fn builtin_str(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
-  if args.args.len() != 1 {
-    panic!("str expects exactly one parameter");
-  };
-  let s = match args.args[0].borrow().kind {
-    PyObjectKind::Integer { value: _ } | PyObjectKind::String { value: _ } => {
-      args.args[0].borrow().str()
-    }
-    _ => {
-      return Err(vm.context().new_str(
-        "TypeError: object of this type cannot be converted to str".to_string(),
-      ))
-    }
-  };
-  Ok(vm.new_str(s))
-}
 // builtin_sum
 // builtin_super
 // builtin_vars
@@ -334,33 +502,59 @@ fn builtin_str(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
 
 pub fn make_module(ctx: &PyContext) -> PyObjectRef {
   // scope[String::from("print")] = print;
-  let mut dict = HashMap::new();
+  let mut dict = DictElements::new();
+  dict.insert(String::from("abs"), ctx.new_rustfunc(builtin_abs));
   dict.insert(String::from("all"), ctx.new_rustfunc(builtin_all));
   dict.insert(String::from("any"), ctx.new_rustfunc(builtin_any));
+  dict.insert(String::from("bool"), ctx.bool_type.clone());
   dict.insert(String::from("chr"), ctx.new_rustfunc(builtin_chr));
   dict.insert(String::from("compile"), ctx.new_rustfunc(builtin_compile));
+  dict.insert(String::from("complex"), ctx.complex_type.clone());
   // TODO: can we just insert dict here?
+  dict.insert(String::from("bytearray"), ctx.bytearray_type.clone());
+  dict.insert(String::from("bytes"), ctx.bytes_type.clone());
   dict.insert(String::from("dict"), ctx.dict_type.clone());
   dict.insert(String::from("dir"), ctx.new_rustfunc(builtin_dir));
+  dict.insert(String::from("divmod"), ctx.new_rustfunc(builtin_divmod));
   dict.insert(String::from("eval"), ctx.new_rustfunc(builtin_eval));
+  dict.insert(String::from("float"), ctx.float_type.clone());
+  dict.insert(String::from("format"), ctx.new_rustfunc(builtin_format));
+  dict.insert(String::from("frozenset"), ctx.frozenset_type.clone());
   dict.insert(String::from("getattr"), ctx.new_rustfunc(builtin_getattr));
   dict.insert(String::from("hasattr"), ctx.new_rustfunc(builtin_hasattr));
+  dict.insert(String::from("hash"), ctx.new_rustfunc(builtin_hash));
   dict.insert(String::from("id"), ctx.new_rustfunc(builtin_id));
   dict.insert(String::from("int"), ctx.int_type.clone());
+  dict.insert(
+    String::from("isinstance"),
+    ctx.new_rustfunc(builtin_isinstance),
+  );
+  dict.insert(
+    String::from("issubclass"),
+    ctx.new_rustfunc(builtin_issubclass),
+  );
+  dict.insert(String::from("iter"), ctx.new_rustfunc(builtin_iter));
   dict.insert(String::from("len"), ctx.new_rustfunc(builtin_len));
   dict.insert(String::from("list"), ctx.list_type.clone());
   dict.insert(String::from("locals"), ctx.new_rustfunc(builtin_locals));
+  dict.insert(String::from("next"), ctx.new_rustfunc(builtin_next));
+  dict.insert(String::from("ord"), ctx.new_rustfunc(builtin_ord));
   dict.insert(String::from("print"), ctx.new_rustfunc(builtin_print));
-  dict.insert(String::from("range"), ctx.new_rustfunc(builtin_range));
+  dict.insert(String::from("range"), ctx.range_type.clone());
+  dict.insert(String::from("repr"), ctx.new_rustfunc(builtin_repr));
+  dict.insert(String::from("set"), ctx.set_type.clone());
   dict.insert(String::from("setattr"), ctx.new_rustfunc(builtin_setattr));
-  dict.insert(String::from("str"), ctx.new_rustfunc(builtin_str));
+  dict.insert(String::from("str"), ctx.str_type.clone());
   dict.insert(String::from("tuple"), ctx.tuple_type.clone());
   dict.insert(String::from("type"), ctx.type_type.clone());
   dict.insert(String::from("object"), ctx.object_type.clone());
-  dict.insert(
-    String::from("BaseException"),
-    ctx.base_exception_type.clone(),
-  );
+  dict.insert(String::from("NotImplemented"), ctx.not_implemented.clone());
+
+  // Exceptions:
+  for exc_type in ctx.exceptions.all() {
+    dict.insert(objtype::get_type_name(&exc_type), exc_type);
+  }
+
   let d2 =
     PyObject::new(PyObjectKind::Dict { elements: dict }, ctx.type_type.clone());
   let scope = PyObject::new(
@@ -388,20 +582,16 @@ pub fn builtin_build_class_(
 ) -> PyResult {
   let function = args.shift();
   let name_arg = args.shift();
-  let name_arg_ref = name_arg.borrow();
-  let name = match name_arg_ref.kind {
-    PyObjectKind::String { ref value } => value,
-    _ => panic!("Class name must by a string!"),
+  let name = match name_arg.borrow().kind {
+    PyObjectKind::String { ref value } => value.clone(),
+    _ => {
+      return Err(vm.new_type_error("Class name must be a string".to_string()))
+    }
   };
   let mut bases = args.args.clone();
   bases.push(vm.context().object_type.clone());
   let metaclass = vm.get_type();
   let namespace = vm.new_dict();
-  &vm.invoke(
-    function,
-    PyFuncArgs {
-      args: vec![namespace.clone()],
-    },
-  );
-  objtype::new(metaclass, name, bases, namespace)
+  vm.invoke(function, PyFuncArgs::new(vec![namespace.clone()], vec![]))?;
+  objtype::new_class(vm, metaclass, &name, bases, namespace)
 }
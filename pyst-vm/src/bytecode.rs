@@ -1,6 +1,8 @@
 extern crate pyst_parser;
 
 use self::pyst_parser::ast;
+use super::objint::IntValue;
+use num_complex::Complex64;
 use std::collections::HashMap;
 use std::fmt;
 
@@ -9,16 +11,39 @@ pub struct CodeObject {
   pub instructions: Vec<Instruction>,
   pub label_map: HashMap<Label, usize>,
   pub locations: Vec<ast::Location>,
-  pub arg_names: Vec<String>,
+  pub arg_names: Vec<String>, // Names of positional arguments
+  pub varargs: Option<String>, // *args
+  pub kwonlyarg_names: Vec<String>,
+  pub varkeywords: Option<String>, // **kwargs
+  pub cell_names: Vec<String>,     // Locals shared with nested functions
+  pub free_names: Vec<String>,     // Cells taken from the enclosing function
+  pub is_generator: bool,
+  pub source_path: Option<String>,
+  pub obj_name: String, // Name of the object that created this code object
 }
 
 impl CodeObject {
-  pub fn new(arg_names: Vec<String>) -> CodeObject {
+  pub fn new(
+    arg_names: Vec<String>,
+    varargs: Option<String>,
+    kwonlyarg_names: Vec<String>,
+    varkeywords: Option<String>,
+    source_path: Option<String>,
+    obj_name: String,
+  ) -> CodeObject {
     CodeObject {
       instructions: Vec::new(),
       label_map: HashMap::new(),
       locations: Vec::new(),
       arg_names: arg_names,
+      varargs: varargs,
+      kwonlyarg_names: kwonlyarg_names,
+      varkeywords: varkeywords,
+      cell_names: Vec::new(),
+      free_names: Vec::new(),
+      is_generator: false,
+      source_path: source_path,
+      obj_name: obj_name,
     }
   }
 }
@@ -33,14 +58,24 @@ pub enum Instruction {
   },
   LoadName {
     name: String,
+    scope: NameScope,
   },
   StoreName {
     name: String,
+    scope: NameScope,
   },
   StoreSubscript,
   StoreAttr {
     name: String,
   },
+  DeleteName {
+    name: String,
+    scope: NameScope,
+  },
+  DeleteSubscript,
+  DeleteAttr {
+    name: String,
+  },
   LoadConst {
     value: Constant,
   },
@@ -49,6 +84,7 @@ pub enum Instruction {
   },
   BinaryOperation {
     op: BinaryOperator,
+    inplace: bool,
   },
   LoadAttr {
     name: String,
@@ -71,12 +107,21 @@ pub enum Instruction {
   JumpIf {
     target: Label,
   },
-  MakeFunction,
+  MakeFunction {
+    has_defaults: bool,
+    has_kw_defaults: bool,
+  },
   CallFunction {
-    count: usize,
+    typ: CallType,
+  },
+  // Push the next item of the iterator on top of the stack, or pop the
+  // iterator and jump to the target when it is exhausted:
+  ForIter {
+    target: Label,
   },
-  ForIter,
   ReturnValue,
+  YieldValue,
+  YieldFrom,
   SetupLoop {
     start: Label,
     end: Label,
@@ -84,33 +129,113 @@ pub enum Instruction {
   SetupExcept {
     handler: Label,
   },
+  // Run the finally clause at the handler label when the try statement is
+  // left, either normally after EnterFinally, or by an exception, return,
+  // break or continue. EndFinally ends the clause and resumes leaving:
+  SetupFinally {
+    handler: Label,
+  },
+  EnterFinally,
+  EndFinally,
+  // End the except clause which handled an exception:
+  PopException,
+  // Enter the context manager on top of the stack, until WithCleanup exits
+  // it. Exceptions which it suppresses continue at the end label:
+  SetupWith {
+    end: Label,
+  },
+  WithCleanup,
   PopBlock,
   Raise {
     argc: usize,
   },
+  // Replace the iterable on top of the stack by its items, the first one on
+  // top:
+  UnpackSequence {
+    size: usize,
+  },
+  // The same, with the items between the first before ones and the last
+  // after ones gathered in a list:
+  UnpackEx {
+    before: usize,
+    after: usize,
+  },
   BuildTuple {
     size: usize,
+    unpack: bool,
   },
   BuildList {
     size: usize,
+    unpack: bool,
+  },
+  BuildSet {
+    size: usize,
+    unpack: bool,
   },
   BuildMap {
     size: usize,
+    unpack: bool,
+    for_call: bool,
   },
   BuildSlice {
     size: usize,
   },
+  // Format the value on top of the stack, converted first by str, repr or
+  // ascii, with the format spec on top of it if there is one:
+  FormatValue {
+    conversion: Option<ast::ConversionFlag>,
+    spec: bool,
+  },
+  // Concatenate the strings on top of the stack:
+  BuildString {
+    size: usize,
+  },
+  // Add the top of the stack to the list or dict which is i items below it,
+  // for each item of a comprehension:
+  ListAppend {
+    i: usize,
+  },
+  SetAdd {
+    i: usize,
+  },
+  MapAdd {
+    i: usize,
+  },
   PrintExpr,
   LoadBuildClass,
   StoreLocals,
 }
 
+// How the arguments of a call are laid out on the stack: only positional
+// arguments, positional and keyword arguments followed by a tuple of the
+// keyword names, or a tuple of positional arguments optionally followed by a
+// dict of keyword arguments:
+#[derive(Debug, Clone)]
+pub enum CallType {
+  Positional(usize),
+  Keyword(usize),
+  Ex(bool),
+}
+
+// Where a name is looked up: in the locals of a function, in the globals, in
+// a cell shared between functions, or in the locals of a module or class body
+// and then in the globals:
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameScope {
+  Local,
+  Global,
+  Cell,
+  Name,
+}
+
 #[derive(Debug, Clone)]
 pub enum Constant {
-  Integer { value: i32 }, // TODO: replace by arbitrary big int math.
+  Integer { value: IntValue },
   Float { value: f64 },
+  Complex { value: Complex64 },
   Boolean { value: bool },
   String { value: String },
+  Bytes { value: Vec<u8> },
   Code { code: CodeObject },
   None,
 }
@@ -127,6 +252,7 @@ pub enum ComparisonOperator {
   NotIn,
   Is,
   IsNot,
+  ExceptionMatch,
 }
 
 #[derive(Debug, Clone)]
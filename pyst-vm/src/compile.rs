@@ -1,61 +1,122 @@
 extern crate pyst_parser;
 
+use self::pyst_parser::error::ParseError;
 use self::pyst_parser::{ast, parser};
-use super::bytecode::{self, CodeObject, Instruction};
+use super::bytecode::{self, CallType, CodeObject, Instruction};
+use super::objint::IntValue;
 use super::pyobject::{PyObject, PyObjectKind, PyObjectRef};
+use super::symboltable::{self, SymbolScope, SymbolTable, SymbolTableError};
 use super::vm::VirtualMachine;
+use num_complex::Complex64;
+use std::fmt;
 
 struct Compiler {
   code_object_stack: Vec<CodeObject>,
+  symbol_table_stack: Vec<SymbolTable>,
   nxt_label: usize,
   current_source_location: ast::Location,
+  source_path: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum CompileError {
+  Parse(ParseError),
+  // The source parses, but breaks a rule which the grammar does not check,
+  // such as 'yield' only appearing in functions, or assigning to a call:
+  Syntax {
+    error: String,
+    location: ast::Location,
+  },
+  // eval() was given a statement rather than an expression:
+  ExpectedExpression {
+    location: ast::Location,
+  },
+}
+
+impl CompileError {
+  pub fn location(&self) -> ast::Location {
+    match self {
+      CompileError::Parse(error) => error.location,
+      CompileError::Syntax { location, .. }
+      | CompileError::ExpectedExpression { location } => *location,
+    }
+  }
+}
+
+impl fmt::Display for CompileError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CompileError::Parse(error) => write!(f, "{}", error),
+      CompileError::Syntax { error, .. } => write!(f, "{}", error),
+      CompileError::ExpectedExpression { .. } => {
+        write!(f, "Expecting expression, got statement")
+      }
+    }
+  }
+}
+
+impl From<ParseError> for CompileError {
+  fn from(error: ParseError) -> Self {
+    CompileError::Parse(error)
+  }
+}
+
+impl From<SymbolTableError> for CompileError {
+  fn from(error: SymbolTableError) -> Self {
+    CompileError::Syntax {
+      error: error.error,
+      location: error.location,
+    }
+  }
 }
 
 pub fn compile(
   vm: &mut VirtualMachine,
   source: &String,
   mode: Mode,
-) -> Result<PyObjectRef, String> {
-  let mut compiler = Compiler::new();
-  compiler.push_new_code_object();
+  source_path: Option<String>,
+) -> Result<PyObjectRef, CompileError> {
+  let mut compiler = Compiler::new(source_path);
+  compiler.push_new_code_object("<module>".to_string());
   match mode {
-    Mode::Exec => match parser::parse_program(source) {
-      Ok(ast) => {
-        compiler.compile_program(&ast);
+    Mode::Exec => {
+      let ast = parser::parse_program(source)?;
+      let symbol_table = symboltable::make_symbol_table(&ast.statements)?;
+      compiler.symbol_table_stack.push(symbol_table);
+      compiler.compile_program(&ast)?;
+    }
+    Mode::Eval => {
+      let statement = parser::parse_statement(source)?;
+      let symbol_table =
+        symboltable::make_symbol_table(std::slice::from_ref(&statement))?;
+      compiler.symbol_table_stack.push(symbol_table);
+      if let &ast::Statement::Expression { ref expression } = &statement.node {
+        compiler.compile_expression(expression)?;
+        compiler.emit(Instruction::ReturnValue);
+      } else {
+        return Err(CompileError::ExpectedExpression {
+          location: statement.location,
+        });
       }
-      Err(msg) => return Err(msg),
-    },
-    Mode::Eval => match parser::parse_statement(source) {
-      Ok(statement) => {
+    }
+    Mode::Single => {
+      let ast = parser::parse_program(source)?;
+      let symbol_table = symboltable::make_symbol_table(&ast.statements)?;
+      compiler.symbol_table_stack.push(symbol_table);
+      for statement in ast.statements {
         if let &ast::Statement::Expression { ref expression } = &statement.node
         {
-          compiler.compile_expression(expression);
-          compiler.emit(Instruction::ReturnValue);
+          compiler.compile_expression(expression)?;
+          compiler.emit(Instruction::PrintExpr);
         } else {
-          return Err("Expecting expression, got statement".to_string());
-        }
-      }
-      Err(msg) => return Err(msg),
-    },
-    Mode::Single => match parser::parse_program(source) {
-      Ok(ast) => {
-        for statement in ast.statements {
-          if let &ast::Statement::Expression { ref expression } =
-            &statement.node
-          {
-            compiler.compile_expression(expression);
-            compiler.emit(Instruction::PrintExpr);
-          } else {
-            compiler.compile_statement(&statement);
-          }
+          compiler.compile_statement(&statement)?;
         }
-        compiler.emit(Instruction::LoadConst {
-          value: bytecode::Constant::None,
-        });
-        compiler.emit(Instruction::ReturnValue);
       }
-      Err(msg) => return Err(msg),
-    },
+      compiler.emit(Instruction::LoadConst {
+        value: bytecode::Constant::None,
+      });
+      compiler.emit(Instruction::ReturnValue);
+    }
   };
 
   let code = compiler.pop_code_object();
@@ -75,25 +136,59 @@ pub enum Mode {
 type Label = usize;
 
 impl Compiler {
-  fn new() -> Self {
+  fn new(source_path: Option<String>) -> Self {
     Compiler {
       code_object_stack: Vec::new(),
+      symbol_table_stack: Vec::new(),
       nxt_label: 0,
       current_source_location: ast::Location::default(),
+      source_path: source_path,
     }
   }
 
-  fn push_new_code_object(&mut self) {
-    self.code_object_stack.push(CodeObject::new(Vec::new()));
+  fn push_new_code_object(&mut self, obj_name: String) {
+    let source_path = self.source_path.clone();
+    self.code_object_stack.push(CodeObject::new(
+      Vec::new(),
+      None,
+      Vec::new(),
+      None,
+      source_path,
+      obj_name,
+    ));
   }
 
   fn pop_code_object(&mut self) -> CodeObject {
     self.code_object_stack.pop().unwrap()
   }
 
-  fn compile_program(&mut self, program: &ast::Program) {
+  // Start compiling the code of a function or class body, in the scope which
+  // the symbol table has for it:
+  fn enter_scope(&mut self, mut code: CodeObject) {
+    let table = self
+      .symbol_table_stack
+      .last_mut()
+      .unwrap()
+      .sub_tables
+      .remove(0);
+    code.cell_names = table.cell_names.clone();
+    code.free_names = table.free_names.clone();
+    code.is_generator = table.is_generator;
+    self.symbol_table_stack.push(table);
+    self.code_object_stack.push(code);
+  }
+
+  fn exit_scope(&mut self) -> CodeObject {
+    self.symbol_table_stack.pop().unwrap();
+    self.code_object_stack.pop().unwrap()
+  }
+
+  fn compile_program(
+    &mut self,
+    program: &ast::Program,
+  ) -> Result<(), CompileError> {
     let size_before = self.code_object_stack.len();
-    self.compile_statements(&program.statements);
+    self.compile_statements(&program.statements)?;
     assert!(self.code_object_stack.len() == size_before);
 
     // Emit None at end:
@@ -101,15 +196,23 @@ impl Compiler {
       value: bytecode::Constant::None,
     });
     self.emit(Instruction::ReturnValue);
+    Ok(())
   }
 
-  fn compile_statements(&mut self, statements: &Vec<ast::LocatedStatement>) {
+  fn compile_statements(
+    &mut self,
+    statements: &Vec<ast::LocatedStatement>,
+  ) -> Result<(), CompileError> {
     for statement in statements {
-      self.compile_statement(statement)
+      self.compile_statement(statement)?
     }
+    Ok(())
   }
 
-  fn compile_statement(&mut self, statement: &ast::LocatedStatement) {
+  fn compile_statement(
+    &mut self,
+    statement: &ast::LocatedStatement,
+  ) -> Result<(), CompileError> {
     trace!("Compiling {:?}", statement);
     self.set_source_location(&statement.location);
 
@@ -125,19 +228,17 @@ impl Compiler {
             name: module.clone(),
             symbol: symbol.clone().map(|s| s.clone()),
           });
-          self.emit(Instruction::StoreName {
-            name: match alias {
-              Some(alias) => alias.clone(),
-              None => match symbol {
-                Some(symbol) => symbol.clone(),
-                None => module.clone(),
-              },
+          self.store_name(match alias {
+            Some(alias) => alias,
+            None => match symbol {
+              Some(symbol) => symbol,
+              None => module,
             },
           });
         }
       }
       ast::Statement::Expression { expression } => {
-        self.compile_expression(expression);
+        self.compile_expression(expression)?;
 
         // Pop result of stack, since we not use it:
         self.emit(Instruction::Pop);
@@ -147,30 +248,26 @@ impl Compiler {
         match orelse {
           None => {
             // Only if:
-            self.compile_test(test, end_label);
-            self.compile_statements(body);
+            self.compile_test(test, end_label)?;
+            self.compile_statements(body)?;
           }
           Some(statements) => {
             // if - else:
             let else_label = self.new_label();
-            self.compile_test(test, else_label);
-            self.compile_statements(body);
+            self.compile_test(test, else_label)?;
+            self.compile_statements(body)?;
             self.emit(Instruction::Jump { target: end_label });
 
             // else:
             self.set_label(else_label);
-            self.compile_statements(statements);
+            self.compile_statements(statements)?;
           }
         }
         self.set_label(end_label);
       }
-      ast::Statement::While {
-        test,
-        body,
-        orelse: _,
-      } => {
-        // TODO: Handle while-loop else clauses
+      ast::Statement::While { test, body, orelse } => {
         let start_label = self.new_label();
+        let else_label = self.new_label();
         let end_label = self.new_label();
         self.emit(Instruction::SetupLoop {
           start: start_label,
@@ -179,68 +276,109 @@ impl Compiler {
 
         self.set_label(start_label);
 
-        self.compile_test(test, end_label);
-        self.compile_statements(body);
+        self.compile_test(test, else_label)?;
+        self.compile_statements(body)?;
         self.emit(Instruction::Jump {
           target: start_label,
         });
+
+        // The else clause runs when the loop ends without a break:
+        self.set_label(else_label);
+        self.emit(Instruction::PopBlock);
+        if let Some(orelse) = orelse {
+          self.compile_statements(orelse)?;
+        }
         self.set_label(end_label);
       }
-      ast::Statement::With { items: _, body: _ } => {
-        // TODO
+      ast::Statement::With { items, body } => {
+        // Several items enter their context managers in turn, as nested with
+        // statements would:
+        let mut end_labels = vec![];
+        for item in items {
+          let end_label = self.new_label();
+          self.compile_expression(&item.context_expr)?;
+          self.emit(Instruction::SetupWith { end: end_label });
+          match item.optional_vars {
+            Some(ref target) => self.compile_store(target)?,
+            None => self.emit(Instruction::Pop),
+          }
+          end_labels.push(end_label);
+        }
+        self.compile_statements(body)?;
+        for end_label in end_labels.into_iter().rev() {
+          self.emit(Instruction::WithCleanup);
+          self.set_label(end_label);
+        }
       }
       ast::Statement::For {
         target,
         iter,
         body,
-        orelse: _,
+        orelse,
       } => {
-        // TODO: Handle for loop else clauses
-        // The thing iterated:
-        for i in iter {
-          self.compile_expression(i);
-        }
-
-        // Retrieve iterator
-        self.emit(Instruction::GetIter);
-
         // Start loop
         let start_label = self.new_label();
+        let else_label = self.new_label();
         let end_label = self.new_label();
         self.emit(Instruction::SetupLoop {
           start: start_label,
           end: end_label,
         });
+
+        // The thing iterated, which is a tuple when there are several:
+        if iter.len() == 1 {
+          self.compile_expression(&iter[0])?;
+        } else {
+          let unpack = self.compile_elements(iter)?;
+          self.emit(Instruction::BuildTuple {
+            size: iter.len(),
+            unpack: unpack,
+          });
+        }
+
+        // Retrieve iterator
+        self.emit(Instruction::GetIter);
+
         self.set_label(start_label);
-        self.emit(Instruction::ForIter);
-
-        // Start of loop iteration, set targets:
-        for t in target {
-          match t {
-            ast::Expression::Identifier { name } => {
-              self.emit(Instruction::StoreName {
-                name: name.to_string(),
-              });
-            }
-            _ => panic!("Not impl"),
-          }
+        self.emit(Instruction::ForIter { target: else_label });
+
+        // Start of loop iteration, set targets. Several targets are unpacked
+        // from the item:
+        if target.len() == 1 {
+          self.compile_store(&target[0])?;
+        } else {
+          self.compile_unpack(target)?;
         }
 
         // Body of loop:
-        self.compile_statements(body);
+        self.compile_statements(body)?;
         self.emit(Instruction::Jump {
           target: start_label,
         });
-        self.set_label(end_label);
+
+        // The else clause runs when the loop ends without a break:
+        self.set_label(else_label);
         self.emit(Instruction::PopBlock);
+        if let Some(orelse) = orelse {
+          self.compile_statements(orelse)?;
+        }
+        self.set_label(end_label);
       }
-      ast::Statement::Raise { expression } => match expression {
+      ast::Statement::Raise { expression, cause } => match expression {
         Some(value) => {
-          self.compile_expression(value);
-          self.emit(Instruction::Raise { argc: 1 });
+          self.compile_expression(value)?;
+          match cause {
+            Some(cause) => {
+              self.compile_expression(cause)?;
+              self.emit(Instruction::Raise { argc: 2 });
+            }
+            None => {
+              self.emit(Instruction::Raise { argc: 1 });
+            }
+          }
         }
         None => {
-          unimplemented!();
+          self.emit(Instruction::Raise { argc: 0 });
         }
       },
       ast::Statement::Try {
@@ -249,65 +387,30 @@ impl Compiler {
         orelse,
         finalbody,
       } => {
-        let mut handler_label = self.new_label();
         let finally_label = self.new_label();
-        let else_label = self.new_label();
-        // try:
-        self.emit(Instruction::SetupExcept {
-          handler: handler_label,
-        });
-        self.compile_statements(body);
-        self.emit(Instruction::PopBlock);
-        self.emit(Instruction::Jump { target: else_label });
-
-        // except handlers:
-        self.set_label(handler_label);
-        handler_label = self.new_label();
-        for handler in handlers {
-          // Check if this handler can handle the exception:
-
-          // TODO: self.emit(isinstance()) start of hack
-          self.emit(Instruction::LoadConst {
-            value: bytecode::Constant::None,
-          });
-          // End of hack
-          self.emit(Instruction::JumpIf {
-            target: handler_label,
-          });
-
-          // We have a match
-          self.compile_statements(&handler.body);
-          self.emit(Instruction::Jump {
-            target: finally_label,
+        if finalbody.is_some() {
+          self.emit(Instruction::SetupFinally {
+            handler: finally_label,
           });
-
-          // Emit a new label for the next handler
-          self.set_label(handler_label);
-          handler_label = self.new_label();
         }
-        self.emit(Instruction::Jump {
-          target: handler_label,
-        });
-
-        // We successfully ran the try block:
-        // else:
-        self.set_label(else_label);
-        if let Some(statements) = orelse {
-          self.compile_statements(statements);
+        if handlers.is_empty() {
+          self.compile_statements(body)?;
+        } else {
+          self.compile_try_except(body, handlers, orelse)?;
         }
 
         // finally:
-        self.set_label(finally_label);
         if let Some(statements) = finalbody {
-          self.compile_statements(statements);
+          self.emit(Instruction::PopBlock);
+          self.emit(Instruction::EnterFinally);
+          self.set_label(finally_label);
+          self.compile_statements(statements)?;
+          self.emit(Instruction::EndFinally);
         }
-
-        // unimplemented!();
       }
       ast::Statement::FunctionDef { name, args, body } => {
-        // Create bytecode for this function:
-        self.code_object_stack.push(CodeObject::new(args.to_vec()));
-        self.compile_statements(body);
+        let flags = self.enter_function(name, args)?;
+        self.compile_statements(body)?;
 
         // Emit None at end:
         self.emit(Instruction::LoadConst {
@@ -315,7 +418,7 @@ impl Compiler {
         });
         self.emit(Instruction::ReturnValue);
 
-        let code = self.code_object_stack.pop().unwrap();
+        let code = self.exit_scope();
         self.emit(Instruction::LoadConst {
           value: bytecode::Constant::Code { code: code },
         });
@@ -326,27 +429,32 @@ impl Compiler {
         });
 
         // Turn code object into function object:
-        self.emit(Instruction::MakeFunction);
-        self.emit(Instruction::StoreName {
-          name: name.to_string(),
+        self.emit(Instruction::MakeFunction {
+          has_defaults: flags.0,
+          has_kw_defaults: flags.1,
         });
+        self.store_name(name);
       }
       ast::Statement::ClassDef { name, body, args } => {
         self.emit(Instruction::LoadBuildClass);
-        self
-          .code_object_stack
-          .push(CodeObject::new(vec![String::from("__locals__")]));
-        self.emit(Instruction::LoadName {
-          name: String::from("__locals__"),
-        });
+        let code = CodeObject::new(
+          vec![String::from("__locals__")],
+          None,
+          Vec::new(),
+          None,
+          self.source_path.clone(),
+          name.clone(),
+        );
+        self.enter_scope(code);
+        self.load_name("__locals__");
         self.emit(Instruction::StoreLocals);
-        self.compile_statements(body);
+        self.compile_statements(body)?;
         self.emit(Instruction::LoadConst {
           value: bytecode::Constant::None,
         });
         self.emit(Instruction::ReturnValue);
 
-        let code = self.code_object_stack.pop().unwrap();
+        let code = self.exit_scope();
         self.emit(Instruction::LoadConst {
           value: bytecode::Constant::Code { code: code },
         });
@@ -356,7 +464,10 @@ impl Compiler {
           },
         });
         // Turn code object into function object:
-        self.emit(Instruction::MakeFunction);
+        self.emit(Instruction::MakeFunction {
+          has_defaults: false,
+          has_kw_defaults: false,
+        });
 
         self.emit(Instruction::LoadConst {
           value: bytecode::Constant::String {
@@ -365,37 +476,38 @@ impl Compiler {
         });
 
         for base in args {
-          self.emit(Instruction::LoadName { name: base.clone() });
+          self.load_name(base);
         }
         self.emit(Instruction::CallFunction {
-          count: 2 + args.len(),
+          typ: CallType::Positional(2 + args.len()),
         });
 
-        self.emit(Instruction::StoreName {
-          name: name.to_string(),
-        });
+        self.store_name(name);
       }
       ast::Statement::Assert { test, msg } => {
         // TODO: if some flag, ignore all assert statements!
 
-        self.compile_expression(test);
+        self.compile_expression(test)?;
 
         // if true, jump over raise:
         let end_label = self.new_label();
         self.emit(Instruction::JumpIf { target: end_label });
 
-        self.emit(Instruction::LoadName {
-          name: String::from("AssertionError"),
-        });
+        self.load_name("AssertionError");
         match msg {
           Some(e) => {
-            self.compile_expression(e);
-            self.emit(Instruction::CallFunction { count: 1 });
+            self.compile_expression(e)?;
+            self.emit(Instruction::CallFunction {
+              typ: CallType::Positional(1),
+            });
           }
           None => {
-            self.emit(Instruction::CallFunction { count: 0 });
+            self.emit(Instruction::CallFunction {
+              typ: CallType::Positional(0),
+            });
           }
         }
+        self.emit(Instruction::Raise { argc: 1 });
         self.set_label(end_label);
       }
       ast::Statement::Break => {
@@ -407,73 +519,333 @@ impl Compiler {
       ast::Statement::Return { value } => {
         match value {
           Some(e) => {
-            let size = e.len();
-            for v in e {
-              self.compile_expression(v);
-            }
-
             // If we have more than 1 return value, make it a tuple:
-            if size > 1 {
-              self.emit(Instruction::BuildTuple { size });
+            if e.len() > 1 {
+              let unpack = self.compile_elements(e)?;
+              self.emit(Instruction::BuildTuple {
+                size: e.len(),
+                unpack: unpack,
+              });
+            } else {
+              self.compile_expression(&e[0])?;
             }
           }
           None => {
-            // TODO: Put none on stack
+            self.emit(Instruction::LoadConst {
+              value: bytecode::Constant::None,
+            });
           }
         }
 
         self.emit(Instruction::ReturnValue);
       }
       ast::Statement::Assign { targets, value } => {
-        self.compile_expression(value);
+        self.compile_expression(value)?;
 
-        for target in targets {
-          self.compile_store(target);
+        // Every target but the last one stores a copy of the value:
+        for (i, target) in targets.iter().enumerate() {
+          if i + 1 < targets.len() {
+            self.emit(Instruction::Duplicate);
+          }
+          self.compile_store(target)?;
         }
       }
       ast::Statement::AugAssign { target, op, value } => {
-        self.compile_expression(target);
-        self.compile_expression(value);
+        match target.node {
+          ast::Expression::Identifier { .. }
+          | ast::Expression::Attribute { .. }
+          | ast::Expression::Subscript { .. } => {}
+          _ => {
+            return Err(CompileError::Syntax {
+              error: "illegal expression for augmented assignment".to_string(),
+              location: target.location,
+            })
+          }
+        }
+        self.compile_expression(target)?;
+        self.compile_expression(value)?;
 
         // Perform operation:
-        self.compile_op(op);
-        self.compile_store(target);
+        self.compile_op(op, true);
+        self.compile_store(target)?;
       }
-      ast::Statement::Delete { targets: _ } => {
-        // TODO: Remove the given names from the scope
-        // self.emit(Instruction::DeleteName);
+      ast::Statement::Delete { targets } => {
+        for target in targets {
+          self.compile_delete(target)?;
+        }
       }
       ast::Statement::Pass => {
         self.emit(Instruction::Pass);
       }
+      ast::Statement::Global { .. } | ast::Statement::Nonlocal { .. } => {
+        // Only affects the symbol table
+      }
     }
+    Ok(())
   }
 
-  fn compile_store(&mut self, target: &ast::Expression) {
-    match target {
-      ast::Expression::Identifier { name } => {
-        self.emit(Instruction::StoreName {
-          name: name.to_string(),
+  // The body, except clauses and else clause of a try statement:
+  fn compile_try_except(
+    &mut self,
+    body: &Vec<ast::LocatedStatement>,
+    handlers: &Vec<ast::ExceptHandler>,
+    orelse: &Option<Vec<ast::LocatedStatement>>,
+  ) -> Result<(), CompileError> {
+    let mut handler_label = self.new_label();
+    let else_label = self.new_label();
+    let end_label = self.new_label();
+    // try:
+    self.emit(Instruction::SetupExcept {
+      handler: handler_label,
+    });
+    self.compile_statements(body)?;
+    self.emit(Instruction::PopBlock);
+    self.emit(Instruction::Jump { target: else_label });
+
+    // except handlers:
+    self.set_label(handler_label);
+    handler_label = self.new_label();
+    for handler in handlers {
+      // If we gave a typ,
+      // check if this handler can handle the exception:
+      if let Some(exc_type) = &handler.typ {
+        // Duplicate exception for test:
+        self.emit(Instruction::Duplicate);
+
+        // Check exception type:
+        self.compile_expression(exc_type)?;
+        self.emit(Instruction::CompareOperation {
+          op: bytecode::ComparisonOperator::ExceptionMatch,
+        });
+
+        // We cannot handle this exception type:
+        self.emit(Instruction::UnaryOperation {
+          op: bytecode::UnaryOperator::Not,
+        });
+        self.emit(Instruction::JumpIf {
+          target: handler_label,
         });
+
+        // We have a match, store in name (except x as y)
+        match &handler.name {
+          Some(alias) => {
+            self.store_name(alias);
+          }
+          None => {
+            // Drop exception from top of stack:
+            self.emit(Instruction::Pop);
+          }
+        }
+      } else {
+        // Catch all!
+        // Drop exception from top of stack:
+        self.emit(Instruction::Pop);
+      }
+
+      // Handler code:
+      match &handler.name {
+        Some(alias) => {
+          self.compile_handler_body_unbinding(&handler.body, alias)?
+        }
+        None => self.compile_statements(&handler.body)?,
+      }
+      self.emit(Instruction::PopException);
+      self.emit(Instruction::Jump { target: end_label });
+
+      // Emit a new label for the next handler
+      self.set_label(handler_label);
+      handler_label = self.new_label();
+    }
+
+    // If code flows here, we have an unhandled exception,
+    // re-raise the exception which is being handled:
+    self.emit(Instruction::Raise { argc: 0 });
+
+    // We successfully ran the try block:
+    // else:
+    self.set_label(else_label);
+    if let Some(statements) = orelse {
+      self.compile_statements(statements)?;
+    }
+    self.set_label(end_label);
+    Ok(())
+  }
+
+  // The body of an except clause which names the exception. However the
+  // body exits, the name is unbound afterwards, as if the body were
+  // try: ... finally: alias = None; del alias
+  fn compile_handler_body_unbinding(
+    &mut self,
+    body: &Vec<ast::LocatedStatement>,
+    alias: &str,
+  ) -> Result<(), CompileError> {
+    let cleanup_label = self.new_label();
+    self.emit(Instruction::SetupFinally {
+      handler: cleanup_label,
+    });
+    self.compile_statements(body)?;
+    self.emit(Instruction::PopBlock);
+    self.emit(Instruction::EnterFinally);
+    self.set_label(cleanup_label);
+    self.emit(Instruction::LoadConst {
+      value: bytecode::Constant::None,
+    });
+    self.store_name(alias);
+    let scope = self.name_scope(alias);
+    self.emit(Instruction::DeleteName {
+      name: alias.to_string(),
+      scope: scope,
+    });
+    self.emit(Instruction::EndFinally);
+    Ok(())
+  }
+
+  fn compile_store(
+    &mut self,
+    target: &ast::LocatedExpression,
+  ) -> Result<(), CompileError> {
+    match &target.node {
+      ast::Expression::Identifier { name } => {
+        self.store_name(name);
       }
       ast::Expression::Subscript { a, b } => {
-        self.compile_expression(a);
-        self.compile_expression(b);
+        self.compile_expression(a)?;
+        self.compile_expression(b)?;
         self.emit(Instruction::StoreSubscript);
       }
       ast::Expression::Attribute { value, name } => {
-        self.compile_expression(value);
+        self.compile_expression(value)?;
         self.emit(Instruction::StoreAttr {
           name: name.to_string(),
         });
       }
+      ast::Expression::Tuple { elements }
+      | ast::Expression::List { elements } => {
+        self.compile_unpack(elements)?;
+      }
+      ast::Expression::Starred { .. } => {
+        return Err(CompileError::Syntax {
+          error: "starred assignment target must be in a list or tuple"
+            .to_string(),
+          location: target.location,
+        })
+      }
       _ => {
-        panic!("WTF: {:?}", target);
+        return Err(CompileError::Syntax {
+          error: format!("can't assign to {}", describe(&target.node)),
+          location: target.location,
+        })
       }
     }
+    Ok(())
   }
 
-  fn compile_op(&mut self, op: &ast::Operator) {
+  fn compile_delete(
+    &mut self,
+    target: &ast::LocatedExpression,
+  ) -> Result<(), CompileError> {
+    match &target.node {
+      ast::Expression::Identifier { name } => {
+        let scope = self.name_scope(name);
+        self.emit(Instruction::DeleteName {
+          name: name.to_string(),
+          scope: scope,
+        });
+      }
+      ast::Expression::Subscript { a, b } => {
+        self.compile_expression(a)?;
+        self.compile_expression(b)?;
+        self.emit(Instruction::DeleteSubscript);
+      }
+      ast::Expression::Attribute { value, name } => {
+        self.compile_expression(value)?;
+        self.emit(Instruction::DeleteAttr {
+          name: name.to_string(),
+        });
+      }
+      ast::Expression::Tuple { elements }
+      | ast::Expression::List { elements } => {
+        for element in elements {
+          self.compile_delete(element)?;
+        }
+      }
+      ast::Expression::Starred { .. } => {
+        return Err(CompileError::Syntax {
+          error: "can't use starred expression here".to_string(),
+          location: target.location,
+        })
+      }
+      _ => {
+        return Err(CompileError::Syntax {
+          error: format!("can't delete {}", describe(&target.node)),
+          location: target.location,
+        })
+      }
+    }
+    Ok(())
+  }
+
+  // Store the items of the value on top of the stack in the targets. A
+  // starred target takes a list of the items which the others leave:
+  fn compile_unpack(
+    &mut self,
+    targets: &Vec<ast::LocatedExpression>,
+  ) -> Result<(), CompileError> {
+    let starred = targets.iter().position(|target| match target.node {
+      ast::Expression::Starred { .. } => true,
+      _ => false,
+    });
+    match starred {
+      Some(before) => {
+        self.emit(Instruction::UnpackEx {
+          before: before,
+          after: targets.len() - before - 1,
+        });
+      }
+      None => {
+        self.emit(Instruction::UnpackSequence {
+          size: targets.len(),
+        });
+      }
+    }
+    for target in targets {
+      match &target.node {
+        ast::Expression::Starred { value } => self.compile_store(value)?,
+        _ => self.compile_store(target)?,
+      }
+    }
+    Ok(())
+  }
+
+  // Push the elements of a tuple or list, and return whether they need to be
+  // unpacked. They do when some are starred, in which case every element is
+  // pushed as an iterable whose items are flattened into the result:
+  fn compile_elements(
+    &mut self,
+    elements: &[ast::LocatedExpression],
+  ) -> Result<bool, CompileError> {
+    let unpack = elements.iter().any(|element| match element.node {
+      ast::Expression::Starred { .. } => true,
+      _ => false,
+    });
+    for element in elements {
+      match &element.node {
+        ast::Expression::Starred { value } => self.compile_expression(value)?,
+        _ => {
+          self.compile_expression(element)?;
+          if unpack {
+            self.emit(Instruction::BuildTuple {
+              size: 1,
+              unpack: false,
+            });
+          }
+        }
+      }
+    }
+    Ok(unpack)
+  }
+
+  fn compile_op(&mut self, op: &ast::Operator, inplace: bool) {
     let i = match op {
       ast::Operator::Add => bytecode::BinaryOperator::Add,
       ast::Operator::Sub => bytecode::BinaryOperator::Subtract,
@@ -489,98 +861,110 @@ impl Compiler {
       ast::Operator::BitXor => bytecode::BinaryOperator::Xor,
       ast::Operator::BitAnd => bytecode::BinaryOperator::And,
     };
-    self.emit(Instruction::BinaryOperation { op: i });
+    self.emit(Instruction::BinaryOperation {
+      op: i,
+      inplace: inplace,
+    });
   }
 
-  fn compile_test(&mut self, expression: &ast::Expression, not_label: Label) {
+  fn compile_test(
+    &mut self,
+    expression: &ast::LocatedExpression,
+    not_label: Label,
+  ) -> Result<(), CompileError> {
     // Compile expression for test, and jump to label if false
-    match expression {
-      ast::Expression::BoolOp { a, op, b } => match op {
-        ast::BooleanOperator::And => {
-          self.compile_test(a, not_label);
-          self.compile_test(b, not_label);
-        }
-        ast::BooleanOperator::Or => {
-          // TODO: Implement boolean or
-          // TODO: implement short circuit code by fiddeling with the labels
-          self.new_label();
-          self.compile_test(a, not_label);
-          self.compile_test(b, not_label);
-          panic!("Not impl");
-        }
-      },
+    match &expression.node {
+      ast::Expression::BoolOp {
+        a,
+        op: ast::BooleanOperator::And,
+        b,
+      } => {
+        self.compile_test(a, not_label)?;
+        self.compile_test(b, not_label)?;
+      }
       _ => {
         // If all else fail, fall back to simple checking of boolean value:
-        self.compile_expression(expression);
+        self.compile_expression(expression)?;
         self.emit(Instruction::UnaryOperation {
           op: bytecode::UnaryOperator::Not,
         });
         self.emit(Instruction::JumpIf { target: not_label });
       }
     }
+    Ok(())
   }
 
-  fn compile_expression(&mut self, expression: &ast::Expression) {
+  fn compile_expression(
+    &mut self,
+    expression: &ast::LocatedExpression,
+  ) -> Result<(), CompileError> {
     trace!("Compiling {:?}", expression);
-    match expression {
-      ast::Expression::Call { function, args } => {
-        self.compile_expression(&*function);
-        let count = args.len();
-        for arg in args {
-          self.compile_expression(arg)
-        }
-        self.emit(Instruction::CallFunction { count: count });
+    // The instructions of an expression are located at it, and those which
+    // come after it at the expression or statement around it again:
+    let location = self.current_source_location;
+    self.set_source_location(&expression.location);
+    match &expression.node {
+      ast::Expression::Call {
+        function,
+        args,
+        keywords,
+      } => {
+        self.compile_expression(&*function)?;
+        self.compile_call(args, keywords)?;
       }
-      ast::Expression::BoolOp { a: _, op: _, b: _ } => {
-        let not_label = self.new_label();
+      ast::Expression::BoolOp { a, op, b } => {
+        // The result is the first operand which decides the outcome, so keep
+        // a copy of it on the stack while testing it:
         let end_label = self.new_label();
-        self.compile_test(expression, not_label);
-        // Load const True
-        self.emit(Instruction::LoadConst {
-          value: bytecode::Constant::Boolean { value: true },
-        });
-        self.emit(Instruction::Jump { target: end_label });
-
-        self.set_label(not_label);
-        // Load const False
-        self.emit(Instruction::LoadConst {
-          value: bytecode::Constant::Boolean { value: false },
-        });
+        self.compile_expression(a)?;
+        self.emit(Instruction::Duplicate);
+        if let ast::BooleanOperator::And = op {
+          self.emit(Instruction::UnaryOperation {
+            op: bytecode::UnaryOperator::Not,
+          });
+        }
+        self.emit(Instruction::JumpIf { target: end_label });
+        self.emit(Instruction::Pop);
+        self.compile_expression(b)?;
         self.set_label(end_label);
       }
       ast::Expression::Binop { a, op, b } => {
-        self.compile_expression(&*a);
-        self.compile_expression(&*b);
+        self.compile_expression(&*a)?;
+        self.compile_expression(&*b)?;
 
         // Perform operation:
-        self.compile_op(op);
+        self.compile_op(op, false);
       }
       ast::Expression::Subscript { a, b } => {
-        self.compile_expression(&*a);
-        self.compile_expression(&*b);
+        self.compile_expression(&*a)?;
+        self.compile_expression(&*b)?;
         self.emit(Instruction::BinaryOperation {
           op: bytecode::BinaryOperator::Subscript,
+          inplace: false,
         });
       }
       ast::Expression::Unop { op, a } => {
-        self.compile_expression(&*a);
+        self.compile_expression(&*a)?;
 
         // Perform operation:
         let i = match op {
+          ast::UnaryOperator::Pos => bytecode::UnaryOperator::Plus,
           ast::UnaryOperator::Neg => bytecode::UnaryOperator::Minus,
+          ast::UnaryOperator::Not => bytecode::UnaryOperator::Not,
+          ast::UnaryOperator::Inv => bytecode::UnaryOperator::Invert,
         };
         let i = Instruction::UnaryOperation { op: i };
         self.emit(i);
       }
       ast::Expression::Attribute { value, name } => {
-        self.compile_expression(&*value);
+        self.compile_expression(&*value)?;
         self.emit(Instruction::LoadAttr {
           name: name.to_string(),
         });
       }
       ast::Expression::Compare { a, op, b } => {
-        self.compile_expression(&*a);
-        self.compile_expression(&*b);
+        self.compile_expression(&*a)?;
+        self.compile_expression(&*b)?;
 
         let i = match op {
           ast::Comparison::Equal => bytecode::ComparisonOperator::Equal,
@@ -603,41 +987,58 @@ impl Compiler {
       }
       ast::Expression::Number { value } => {
         let const_value = match value {
-          ast::Number::Integer { value } => {
-            bytecode::Constant::Integer { value: *value }
-          }
+          ast::Number::Integer { value } => bytecode::Constant::Integer {
+            value: IntValue::from(value.clone()),
+          },
           ast::Number::Float { value } => {
             bytecode::Constant::Float { value: *value }
           }
+          ast::Number::Complex { real, imag } => bytecode::Constant::Complex {
+            value: Complex64::new(*real, *imag),
+          },
         };
         self.emit(Instruction::LoadConst { value: const_value });
       }
       ast::Expression::List { elements } => {
-        let size = elements.len();
-        for element in elements {
-          self.compile_expression(element);
-        }
-        self.emit(Instruction::BuildList { size: size });
+        let unpack = self.compile_elements(elements)?;
+        self.emit(Instruction::BuildList {
+          size: elements.len(),
+          unpack: unpack,
+        });
       }
       ast::Expression::Tuple { elements } => {
-        let size = elements.len();
-        for element in elements {
-          self.compile_expression(element);
-        }
-        self.emit(Instruction::BuildTuple { size: size });
+        let unpack = self.compile_elements(elements)?;
+        self.emit(Instruction::BuildTuple {
+          size: elements.len(),
+          unpack: unpack,
+        });
+      }
+      ast::Expression::Set { elements } => {
+        let unpack = self.compile_elements(elements)?;
+        self.emit(Instruction::BuildSet {
+          size: elements.len(),
+          unpack: unpack,
+        });
       }
       ast::Expression::Dict { elements } => {
         let size = elements.len();
         for (key, value) in elements {
-          self.compile_expression(key);
-          self.compile_expression(value);
+          self.compile_expression(key)?;
+          self.compile_expression(value)?;
         }
-        self.emit(Instruction::BuildMap { size: size });
+        self.emit(Instruction::BuildMap {
+          size: size,
+          unpack: false,
+          for_call: false,
+        });
+      }
+      ast::Expression::Comprehension { kind, generators } => {
+        self.compile_comprehension(kind, generators)?;
       }
       ast::Expression::Slice { elements } => {
         let size = elements.len();
         for element in elements {
-          self.compile_expression(element);
+          self.compile_expression(element)?;
         }
         self.emit(Instruction::BuildSlice { size: size });
       }
@@ -663,16 +1064,42 @@ impl Compiler {
           },
         });
       }
-      ast::Expression::Identifier { name } => {
-        self.emit(Instruction::LoadName {
-          name: name.to_string(),
+      ast::Expression::Bytes { value } => {
+        self.emit(Instruction::LoadConst {
+          value: bytecode::Constant::Bytes {
+            value: value.clone(),
+          },
         });
       }
+      ast::Expression::JoinedStr { values } => {
+        for value in values {
+          self.compile_expression(value)?;
+        }
+        self.emit(Instruction::BuildString { size: values.len() });
+      }
+      ast::Expression::FormattedValue {
+        value,
+        conversion,
+        spec,
+      } => {
+        self.compile_expression(value)?;
+        if let Some(spec) = spec {
+          self.compile_expression(spec)?;
+        }
+        self.emit(Instruction::FormatValue {
+          conversion: *conversion,
+          spec: spec.is_some(),
+        });
+      }
+      ast::Expression::Identifier { name } => {
+        self.load_name(name);
+      }
       ast::Expression::Lambda { args, body } => {
-        self.code_object_stack.push(CodeObject::new(args.to_vec()));
-        self.compile_expression(body);
+        let name = String::from("<lambda>");
+        let flags = self.enter_function(&name, args)?;
+        self.compile_expression(body)?;
         self.emit(Instruction::ReturnValue);
-        let code = self.code_object_stack.pop().unwrap();
+        let code = self.exit_scope();
         self.emit(Instruction::LoadConst {
           value: bytecode::Constant::Code { code: code },
         });
@@ -682,15 +1109,335 @@ impl Compiler {
           },
         });
         // Turn code object into function object:
-        self.emit(Instruction::MakeFunction);
+        self.emit(Instruction::MakeFunction {
+          has_defaults: flags.0,
+          has_kw_defaults: flags.1,
+        });
+      }
+      ast::Expression::Yield { value } => {
+        match value {
+          Some(expression) => self.compile_expression(expression)?,
+          None => self.emit(Instruction::LoadConst {
+            value: bytecode::Constant::None,
+          }),
+        };
+        self.emit(Instruction::YieldValue);
+      }
+      ast::Expression::YieldFrom { value } => {
+        // Delegate to the iterator until it is exhausted, starting by
+        // sending None to it:
+        self.compile_expression(value)?;
+        self.emit(Instruction::GetIter);
+        self.emit(Instruction::LoadConst {
+          value: bytecode::Constant::None,
+        });
+        self.emit(Instruction::YieldFrom);
+      }
+      ast::Expression::Starred { .. } => {
+        return Err(CompileError::Syntax {
+          error: "can't use starred expression here".to_string(),
+          location: expression.location,
+        })
       }
     }
+    self.set_source_location(&location);
+    Ok(())
+  }
+
+  // A comprehension is a function which is called right away with an
+  // iterator over the iterable of its outermost loop. It loops over each of
+  // its generators in turn, and adds each item to the list, set or dict it
+  // returns, or yields it:
+  fn compile_comprehension(
+    &mut self,
+    kind: &ast::ComprehensionKind,
+    generators: &[ast::Comprehension],
+  ) -> Result<(), CompileError> {
+    let name = match kind {
+      ast::ComprehensionKind::GeneratorExpression { .. } => "<genexpr>",
+      ast::ComprehensionKind::List { .. } => "<listcomp>",
+      ast::ComprehensionKind::Set { .. } => "<setcomp>",
+      ast::ComprehensionKind::Dict { .. } => "<dictcomp>",
+    };
+    let code = CodeObject::new(
+      vec![String::from(".0")],
+      None,
+      vec![],
+      None,
+      self.source_path.clone(),
+      name.to_string(),
+    );
+    self.enter_scope(code);
+
+    match kind {
+      ast::ComprehensionKind::GeneratorExpression { .. } => {}
+      ast::ComprehensionKind::List { .. } => {
+        self.emit(Instruction::BuildList {
+          size: 0,
+          unpack: false,
+        });
+      }
+      ast::ComprehensionKind::Set { .. } => {
+        self.emit(Instruction::BuildSet {
+          size: 0,
+          unpack: false,
+        });
+      }
+      ast::ComprehensionKind::Dict { .. } => {
+        self.emit(Instruction::BuildMap {
+          size: 0,
+          unpack: false,
+          for_call: false,
+        });
+      }
+    }
+
+    let mut loop_labels = vec![];
+    for (i, generator) in generators.iter().enumerate() {
+      if i == 0 {
+        self.load_name(".0");
+      } else {
+        self.compile_expression(&generator.iter)?;
+        self.emit(Instruction::GetIter);
+      }
+      let start_label = self.new_label();
+      let end_label = self.new_label();
+      self.emit(Instruction::SetupLoop {
+        start: start_label,
+        end: end_label,
+      });
+      self.set_label(start_label);
+      self.emit(Instruction::ForIter { target: end_label });
+      self.compile_store(&generator.target)?;
+      // Skip to the next item when a condition fails:
+      for condition in &generator.ifs {
+        self.compile_test(condition, start_label)?;
+      }
+      loop_labels.push((start_label, end_label));
+    }
+
+    match kind {
+      ast::ComprehensionKind::GeneratorExpression { element } => {
+        self.compile_expression(element)?;
+        self.emit(Instruction::YieldValue);
+        self.emit(Instruction::Pop);
+      }
+      ast::ComprehensionKind::List { element } => {
+        self.compile_expression(element)?;
+        self.emit(Instruction::ListAppend {
+          i: generators.len(),
+        });
+      }
+      ast::ComprehensionKind::Set { element } => {
+        self.compile_expression(element)?;
+        self.emit(Instruction::SetAdd {
+          i: generators.len(),
+        });
+      }
+      ast::ComprehensionKind::Dict { key, value } => {
+        self.compile_expression(key)?;
+        self.compile_expression(value)?;
+        self.emit(Instruction::MapAdd {
+          i: generators.len(),
+        });
+      }
+    }
+
+    for (start_label, end_label) in loop_labels.into_iter().rev() {
+      self.emit(Instruction::Jump {
+        target: start_label,
+      });
+      self.set_label(end_label);
+      self.emit(Instruction::PopBlock);
+    }
+    if let ast::ComprehensionKind::GeneratorExpression { .. } = kind {
+      self.emit(Instruction::LoadConst {
+        value: bytecode::Constant::None,
+      });
+    }
+    self.emit(Instruction::ReturnValue);
+    let code = self.exit_scope();
+
+    self.emit(Instruction::LoadConst {
+      value: bytecode::Constant::Code { code: code },
+    });
+    self.emit(Instruction::LoadConst {
+      value: bytecode::Constant::String {
+        value: name.to_string(),
+      },
+    });
+    self.emit(Instruction::MakeFunction {
+      has_defaults: false,
+      has_kw_defaults: false,
+    });
+    self.compile_expression(&generators[0].iter)?;
+    self.emit(Instruction::GetIter);
+    self.emit(Instruction::CallFunction {
+      typ: CallType::Positional(1),
+    });
+    Ok(())
+  }
+
+  // Evaluate the default values of a function's parameters in the enclosing
+  // scope, then start the code object of the function. Returns whether
+  // defaults for positional and keyword-only parameters were pushed:
+  fn enter_function(
+    &mut self,
+    name: &String,
+    args: &ast::Parameters,
+  ) -> Result<(bool, bool), CompileError> {
+    let have_defaults = !args.defaults.is_empty();
+    if have_defaults {
+      for default in &args.defaults {
+        self.compile_expression(default)?;
+      }
+      self.emit(Instruction::BuildTuple {
+        size: args.defaults.len(),
+        unpack: false,
+      });
+    }
+
+    let mut kw_default_count = 0;
+    for (arg, default) in args.kwonlyargs.iter().zip(&args.kw_defaults) {
+      if let Some(default) = default {
+        self.emit(Instruction::LoadConst {
+          value: bytecode::Constant::String { value: arg.clone() },
+        });
+        self.compile_expression(default)?;
+        kw_default_count += 1;
+      }
+    }
+    let have_kw_defaults = kw_default_count > 0;
+    if have_kw_defaults {
+      self.emit(Instruction::BuildMap {
+        size: kw_default_count,
+        unpack: false,
+        for_call: false,
+      });
+    }
+
+    let code = CodeObject::new(
+      args.args.clone(),
+      args.vararg.clone(),
+      args.kwonlyargs.clone(),
+      args.kwarg.clone(),
+      self.source_path.clone(),
+      name.clone(),
+    );
+    self.enter_scope(code);
+    Ok((have_defaults, have_kw_defaults))
+  }
+
+  // Push the arguments of a call, with the function already on the stack, and
+  // emit the call itself:
+  fn compile_call(
+    &mut self,
+    args: &[ast::LocatedExpression],
+    keywords: &[ast::Keyword],
+  ) -> Result<(), CompileError> {
+    let must_unpack = keywords.iter().any(|k| k.name.is_none())
+      || args.iter().any(|arg| match arg.node {
+        ast::Expression::Starred { .. } => true,
+        _ => false,
+      });
+
+    if must_unpack {
+      // Collect all positional arguments into a single tuple:
+      let unpack = self.compile_elements(args)?;
+      self.emit(Instruction::BuildTuple {
+        size: args.len(),
+        unpack: unpack,
+      });
+
+      // And all keyword arguments into a single dict:
+      for keyword in keywords {
+        match keyword.name {
+          Some(ref name) => {
+            self.emit(Instruction::LoadConst {
+              value: bytecode::Constant::String {
+                value: name.clone(),
+              },
+            });
+            self.compile_expression(&keyword.value)?;
+            self.emit(Instruction::BuildMap {
+              size: 1,
+              unpack: false,
+              for_call: false,
+            });
+          }
+          None => self.compile_expression(&keyword.value)?,
+        }
+      }
+      let has_kwargs = !keywords.is_empty();
+      if has_kwargs {
+        self.emit(Instruction::BuildMap {
+          size: keywords.len(),
+          unpack: true,
+          for_call: true,
+        });
+      }
+      self.emit(Instruction::CallFunction {
+        typ: CallType::Ex(has_kwargs),
+      });
+    } else {
+      for arg in args {
+        self.compile_expression(arg)?;
+      }
+      if keywords.is_empty() {
+        self.emit(Instruction::CallFunction {
+          typ: CallType::Positional(args.len()),
+        });
+      } else {
+        for keyword in keywords {
+          self.compile_expression(&keyword.value)?;
+        }
+        for keyword in keywords {
+          self.emit(Instruction::LoadConst {
+            value: bytecode::Constant::String {
+              value: keyword.name.clone().unwrap(),
+            },
+          });
+        }
+        self.emit(Instruction::BuildTuple {
+          size: keywords.len(),
+          unpack: false,
+        });
+        self.emit(Instruction::CallFunction {
+          typ: CallType::Keyword(args.len() + keywords.len()),
+        });
+      }
+    }
+    Ok(())
+  }
+
+  fn name_scope(&self, name: &str) -> bytecode::NameScope {
+    match self.symbol_table_stack.last().unwrap().lookup(name) {
+      SymbolScope::Local => bytecode::NameScope::Local,
+      SymbolScope::Global => bytecode::NameScope::Global,
+      SymbolScope::Free | SymbolScope::Cell => bytecode::NameScope::Cell,
+      SymbolScope::Name => bytecode::NameScope::Name,
+    }
+  }
+
+  fn load_name(&mut self, name: &str) {
+    let scope = self.name_scope(name);
+    self.emit(Instruction::LoadName {
+      name: name.to_string(),
+      scope: scope,
+    });
+  }
+
+  fn store_name(&mut self, name: &str) {
+    let scope = self.name_scope(name);
+    self.emit(Instruction::StoreName {
+      name: name.to_string(),
+      scope: scope,
+    });
   }
 
   // Low level helper functions:
   fn emit(&mut self, instruction: Instruction) {
     self.current_code_object().instructions.push(instruction);
-    // TODO: insert source filename
     let location = self.current_source_location.clone();
     self.current_code_object().locations.push(location);
   }
@@ -717,3 +1464,33 @@ impl Compiler {
     self.current_source_location = location.clone();
   }
 }
+
+// What an expression which cannot be assigned to or deleted is called in the
+// error saying so:
+fn describe(expression: &ast::Expression) -> &'static str {
+  match expression {
+    ast::Expression::Call { .. } => "function call",
+    ast::Expression::BoolOp { .. }
+    | ast::Expression::Binop { .. }
+    | ast::Expression::Unop { .. } => "operator",
+    ast::Expression::Compare { .. } => "comparison",
+    ast::Expression::Lambda { .. } => "lambda",
+    ast::Expression::Yield { .. } | ast::Expression::YieldFrom { .. } => {
+      "yield expression"
+    }
+    ast::Expression::Comprehension { kind, .. } => match **kind {
+      ast::ComprehensionKind::GeneratorExpression { .. } => {
+        "generator expression"
+      }
+      ast::ComprehensionKind::List { .. } => "list comprehension",
+      ast::ComprehensionKind::Set { .. } => "set comprehension",
+      ast::ComprehensionKind::Dict { .. } => "dict comprehension",
+    },
+    ast::Expression::JoinedStr { .. }
+    | ast::Expression::FormattedValue { .. } => "f-string expression",
+    ast::Expression::True
+    | ast::Expression::False
+    | ast::Expression::PyNone => "keyword",
+    _ => "literal",
+  }
+}
@@ -5,14 +5,12 @@ use super::pyobject::{PyObjectRef, PyResult};
 use super::vm::VirtualMachine;
 
 pub fn eval(vm: &mut VirtualMachine, source: &String, scope: PyObjectRef) -> PyResult {
-    match compile::compile(vm, source, compile::Mode::Eval) {
+    match compile::compile(vm, source, compile::Mode::Eval, None) {
         Ok(bytecode) => {
             debug!("Code object: {:?}", bytecode);
             vm.run_code_obj(bytecode, scope)
         }
-        Err(msg) => {
-            panic!("Parsing went horribly wrong: {}", msg);
-        }
+        Err(error) => Err(vm.new_syntax_error(&error)),
     }
 }
 
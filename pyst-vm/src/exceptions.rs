@@ -1,33 +1,472 @@
-use super::pyobject::{PyFuncArgs, PyObject, PyObjectKind, PyObjectRef, PyResult};
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, IdProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
 use super::vm::VirtualMachine;
-use std::collections::HashMap;
+use std::fs;
 
-fn init(vm: &mut VirtualMachine, _args: PyFuncArgs) -> PyResult {
-  Ok(vm.new_str("todo".to_string()))
+fn exception_init(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let zelf = args.args[0].clone();
+  let exc_args = vm.context().new_tuple(args.args[1..].to_vec());
+  let msg = if args.args.len() > 1 {
+    args.args[1].clone()
+  } else {
+    vm.new_str(String::new())
+  };
+  zelf.set_attr("args", exc_args);
+  zelf.set_attr("msg", msg);
+  zelf.set_attr("__traceback__", vm.context().new_list(vec![]));
+  zelf.set_attr("__cause__", vm.get_none());
+  zelf.set_attr("__context__", vm.get_none());
+  zelf.set_attr("__suppress_context__", vm.new_bool(false));
+  Ok(vm.get_none())
 }
 
-pub fn create_base_exception_type(
-  type_type: PyObjectRef,
-  object_type: PyObjectRef,
+// The arguments the exception was created with:
+fn get_exception_args(exc: &PyObjectRef) -> Vec<PyObjectRef> {
+  match get_exception_attr(exc, "args") {
+    Some(exc_args) => match exc_args.borrow().kind {
+      PyObjectKind::Tuple { ref elements } => elements.clone(),
+      _ => vec![],
+    },
+    None => vec![],
+  }
+}
+
+// str(exc) is empty without arguments, the str() of a single argument, and
+// the repr() of the tuple of arguments otherwise:
+fn exception_str(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let exc_args = get_exception_args(&args.args[0]);
+  let value = match exc_args.len() {
+    0 => String::new(),
+    1 => vm.to_str(exc_args[0].clone())?,
+    _ => {
+      let exc_args = vm.context().new_tuple(exc_args);
+      vm.to_repr(exc_args)?
+    }
+  };
+  Ok(vm.new_str(value))
+}
+
+// repr(exc) looks like the call which created it, as in ValueError('x'):
+fn exception_repr(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let exc = args.args[0].clone();
+  let mut reprs = vec![];
+  for exc_arg in get_exception_args(&exc) {
+    reprs.push(vm.to_repr(exc_arg)?);
+  }
+  let exc_name = objtype::get_type_name(&exc.typ());
+  Ok(vm.new_str(format!("{}({})", exc_name, reprs.join(", "))))
+}
+
+// Line number lineno (counting from 1) of the given source file:
+fn get_source_line(source_path: &str, lineno: usize) -> Option<String> {
+  let source = fs::read_to_string(source_path).ok()?;
+  let line = source.lines().nth(lineno.checked_sub(1)?)?;
+  Some(line.trim().to_string())
+}
+
+// An exception attribute which is set to something other than None:
+fn get_exception_attr(exc: &PyObjectRef, name: &str) -> Option<PyObjectRef> {
+  let name = String::from(name);
+  if !exc.has_attr(&name) {
+    return None;
+  }
+  let value = exc.get_attr(&name);
+  let is_none = match value.borrow().kind {
+    PyObjectKind::PyNone => true,
+    _ => false,
+  };
+  if is_none {
+    None
+  } else {
+    Some(value)
+  }
+}
+
+// Record the exception which was being handled when exc was raised, unless
+// exc already has one:
+pub fn set_exception_context(exc: &PyObjectRef, handled: &PyObjectRef) {
+  if exc.get_id() == handled.get_id()
+    || get_exception_attr(exc, "__context__").is_some()
+  {
+    return;
+  }
+  exc.set_attr("__context__", handled.clone());
+}
+
+// The exception which exc is chained to, and the message which separates
+// their tracebacks. An explicit __cause__ wins over the __context__, which
+// raise ... from ... suppresses:
+fn get_chained_exception(
+  exc: &PyObjectRef,
+) -> Option<(PyObjectRef, &'static str)> {
+  if let Some(cause) = get_exception_attr(exc, "__cause__") {
+    return Some((
+      cause,
+      "The above exception was the direct cause of the following exception:",
+    ));
+  }
+  let suppress_context = match get_exception_attr(exc, "__suppress_context__") {
+    Some(value) => match value.borrow().kind {
+      PyObjectKind::Boolean { value } => value,
+      _ => false,
+    },
+    None => false,
+  };
+  if suppress_context {
+    return None;
+  }
+  get_exception_attr(exc, "__context__").map(|context| {
+    (
+      context,
+      "During handling of the above exception, another exception occurred:",
+    )
+  })
+}
+
+// Print an exception the way CPython does, after the exceptions it is
+// chained to, with a traceback of the frames it passed through (outermost
+// first) followed by its type and message:
+pub fn print_exception(exc: &PyObjectRef) {
+  let mut chain = vec![(exc.clone(), None)];
+  while let Some((chained, message)) = get_chained_exception(&chain[0].0) {
+    if chain
+      .iter()
+      .any(|(seen, _)| seen.get_id() == chained.get_id())
+    {
+      break;
+    }
+    chain.insert(0, (chained, Some(message)));
+  }
+  for (exc, message) in chain {
+    print_single_exception(&exc);
+    if let Some(message) = message {
+      eprintln!("\n{}\n", message);
+    }
+  }
+}
+
+// Print an exception with the traceback of the frames it passed through:
+fn print_single_exception(exc: &PyObjectRef) {
+  let traceback_name = String::from("__traceback__");
+  if exc.has_attr(&traceback_name) {
+    let traceback = exc.get_attr(&traceback_name);
+    if let PyObjectKind::List { ref elements } = traceback.borrow().kind {
+      if !elements.is_empty() {
+        eprintln!("Traceback (most recent call last):");
+      }
+      for element in elements {
+        if let PyObjectKind::Tuple { ref elements } = element.borrow().kind {
+          let source_path = elements[0].borrow().str();
+          let lineno = elements[1].borrow().str();
+          let obj_name = elements[2].borrow().str();
+          eprintln!(
+            "  File \"{}\", line {}, in {}",
+            source_path, lineno, obj_name
+          );
+          let line = lineno
+            .parse()
+            .ok()
+            .and_then(|lineno| get_source_line(&source_path, lineno));
+          if let Some(line) = line {
+            eprintln!("    {}", line);
+          }
+        };
+      }
+    };
+  }
+
+  let exc_name = objtype::get_type_name(&exc.typ());
+  let msg_name = String::from("msg");
+  let msg = if exc.has_attr(&msg_name) {
+    exc.get_attr(&msg_name).borrow().str()
+  } else {
+    String::new()
+  };
+  if msg.is_empty() {
+    eprintln!("{}", exc_name);
+  } else {
+    eprintln!("{}: {}", exc_name, msg);
+  }
+}
+
+fn create_type(
+  name: &str,
+  type_type: &PyObjectRef,
+  base: &PyObjectRef,
+  dict_type: &PyObjectRef,
 ) -> PyObjectRef {
-  let mut dict = HashMap::new();
-  dict.insert(
-    "__init__".to_string(),
-    PyObject::new(
-      PyObjectKind::RustFunction { function: init },
-      type_type.clone(),
-    ),
-  );
-  let typ = PyObject::new(
-    PyObjectKind::Class {
-      name: "BaseException".to_string(),
-      dict: PyObject::new(
-        PyObjectKind::Dict { elements: dict },
-        type_type.clone(),
-      ),
-      mro: vec![object_type],
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
     },
-    type_type.clone(),
+    dict_type.clone(),
   );
-  typ
+  objtype::new(type_type.clone(), name, vec![base.clone()], dict).unwrap()
+}
+
+#[derive(Debug)]
+pub struct ExceptionZoo {
+  pub base_exception_type: PyObjectRef,
+  pub exception_type: PyObjectRef,
+  pub arithmetic_error: PyObjectRef,
+  pub assertion_error: PyObjectRef,
+  pub attribute_error: PyObjectRef,
+  pub generator_exit: PyObjectRef,
+  pub import_error: PyObjectRef,
+  pub index_error: PyObjectRef,
+  pub key_error: PyObjectRef,
+  pub lookup_error: PyObjectRef,
+  pub module_not_found_error: PyObjectRef,
+  pub name_error: PyObjectRef,
+  pub not_implemented_error: PyObjectRef,
+  pub overflow_error: PyObjectRef,
+  pub recursion_error: PyObjectRef,
+  pub runtime_error: PyObjectRef,
+  pub stop_iteration: PyObjectRef,
+  pub syntax_error: PyObjectRef,
+  pub system_error: PyObjectRef,
+  pub type_error: PyObjectRef,
+  pub unbound_local_error: PyObjectRef,
+  pub unicode_error: PyObjectRef,
+  pub unicode_decode_error: PyObjectRef,
+  pub unicode_encode_error: PyObjectRef,
+  pub value_error: PyObjectRef,
+  pub zero_division_error: PyObjectRef,
+}
+
+impl ExceptionZoo {
+  pub fn new(
+    type_type: &PyObjectRef,
+    object_type: &PyObjectRef,
+    dict_type: &PyObjectRef,
+  ) -> Self {
+    // Sorted By Hierarchy then alphabetized.
+    let base_exception_type =
+      create_type("BaseException", type_type, object_type, dict_type);
+
+    let exception_type =
+      create_type("Exception", type_type, &base_exception_type, dict_type);
+    let generator_exit =
+      create_type("GeneratorExit", type_type, &base_exception_type, dict_type);
+
+    let arithmetic_error =
+      create_type("ArithmeticError", type_type, &exception_type, dict_type);
+    let assertion_error =
+      create_type("AssertionError", type_type, &exception_type, dict_type);
+    let attribute_error =
+      create_type("AttributeError", type_type, &exception_type, dict_type);
+    let import_error =
+      create_type("ImportError", type_type, &exception_type, dict_type);
+    let lookup_error =
+      create_type("LookupError", type_type, &exception_type, dict_type);
+    let name_error =
+      create_type("NameError", type_type, &exception_type, dict_type);
+    let runtime_error =
+      create_type("RuntimeError", type_type, &exception_type, dict_type);
+    let stop_iteration =
+      create_type("StopIteration", type_type, &exception_type, dict_type);
+    let syntax_error =
+      create_type("SyntaxError", type_type, &exception_type, dict_type);
+    let system_error =
+      create_type("SystemError", type_type, &exception_type, dict_type);
+    let type_error =
+      create_type("TypeError", type_type, &exception_type, dict_type);
+    let value_error =
+      create_type("ValueError", type_type, &exception_type, dict_type);
+
+    let overflow_error =
+      create_type("OverflowError", type_type, &arithmetic_error, dict_type);
+    let zero_division_error =
+      create_type("ZeroDivisionError", type_type, &arithmetic_error, dict_type);
+
+    let module_not_found_error =
+      create_type("ModuleNotFoundError", type_type, &import_error, dict_type);
+
+    let index_error =
+      create_type("IndexError", type_type, &lookup_error, dict_type);
+    let key_error =
+      create_type("KeyError", type_type, &lookup_error, dict_type);
+
+    let unbound_local_error =
+      create_type("UnboundLocalError", type_type, &name_error, dict_type);
+
+    let not_implemented_error =
+      create_type("NotImplementedError", type_type, &runtime_error, dict_type);
+    let recursion_error =
+      create_type("RecursionError", type_type, &runtime_error, dict_type);
+
+    let unicode_error =
+      create_type("UnicodeError", type_type, &value_error, dict_type);
+    let unicode_decode_error =
+      create_type("UnicodeDecodeError", type_type, &unicode_error, dict_type);
+    let unicode_encode_error =
+      create_type("UnicodeEncodeError", type_type, &unicode_error, dict_type);
+
+    ExceptionZoo {
+      base_exception_type: base_exception_type,
+      exception_type: exception_type,
+      arithmetic_error: arithmetic_error,
+      assertion_error: assertion_error,
+      attribute_error: attribute_error,
+      generator_exit: generator_exit,
+      import_error: import_error,
+      index_error: index_error,
+      key_error: key_error,
+      lookup_error: lookup_error,
+      module_not_found_error: module_not_found_error,
+      name_error: name_error,
+      not_implemented_error: not_implemented_error,
+      overflow_error: overflow_error,
+      recursion_error: recursion_error,
+      runtime_error: runtime_error,
+      stop_iteration: stop_iteration,
+      syntax_error: syntax_error,
+      system_error: system_error,
+      type_error: type_error,
+      unbound_local_error: unbound_local_error,
+      unicode_error: unicode_error,
+      unicode_decode_error: unicode_decode_error,
+      unicode_encode_error: unicode_encode_error,
+      value_error: value_error,
+      zero_division_error: zero_division_error,
+    }
+  }
+
+  // All exception types, as they should appear in the builtins module:
+  pub fn all(&self) -> Vec<PyObjectRef> {
+    vec![
+      self.base_exception_type.clone(),
+      self.exception_type.clone(),
+      self.arithmetic_error.clone(),
+      self.assertion_error.clone(),
+      self.attribute_error.clone(),
+      self.generator_exit.clone(),
+      self.import_error.clone(),
+      self.index_error.clone(),
+      self.key_error.clone(),
+      self.lookup_error.clone(),
+      self.module_not_found_error.clone(),
+      self.name_error.clone(),
+      self.not_implemented_error.clone(),
+      self.overflow_error.clone(),
+      self.recursion_error.clone(),
+      self.runtime_error.clone(),
+      self.stop_iteration.clone(),
+      self.syntax_error.clone(),
+      self.system_error.clone(),
+      self.type_error.clone(),
+      self.unbound_local_error.clone(),
+      self.unicode_error.clone(),
+      self.unicode_decode_error.clone(),
+      self.unicode_encode_error.clone(),
+      self.value_error.clone(),
+      self.zero_division_error.clone(),
+    ]
+  }
+}
+
+pub fn init(context: &PyContext) {
+  let ref base_exception_type = context.exceptions.base_exception_type;
+  base_exception_type
+    .set_attr("__init__", context.new_rustfunc(exception_init));
+  base_exception_type.set_attr("__str__", context.new_rustfunc(exception_str));
+  base_exception_type
+    .set_attr("__repr__", context.new_rustfunc(exception_repr));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::compile;
+  use super::super::objtype;
+  use super::super::pyobject::{
+    AttributeProtocol, DictProtocol, PyObjectKind, PyObjectRef, PyResult,
+  };
+  use super::VirtualMachine;
+
+  // Run source as test.py, returning the value it leaves in x:
+  fn run_source(vm: &mut VirtualMachine, source: &str) -> PyResult {
+    let code = compile::compile(
+      vm,
+      &String::from(source),
+      compile::Mode::Exec,
+      Some(String::from("test.py")),
+    )
+    .unwrap();
+    let builtins = vm.get_builtin_scope();
+    let scope = vm.context().new_scope(Some(builtins));
+    vm.run_code_obj(code, scope.clone())?;
+    Ok(scope.get_item(&String::from("x")))
+  }
+
+  fn traceback_entries(exc: &PyObjectRef) -> Vec<String> {
+    let traceback = exc.get_attr(&String::from("__traceback__"));
+    let entries = match traceback.borrow().kind {
+      PyObjectKind::List { ref elements } => {
+        elements.iter().map(|entry| entry.borrow().str()).collect()
+      }
+      _ => panic!("__traceback__ should be a list"),
+    };
+    entries
+  }
+
+  #[test]
+  fn test_except_matches_on_type() {
+    let mut vm = VirtualMachine::new();
+    let source = "try:\n  raise KeyError('k')\nexcept TypeError:\n  x = 1\nexcept LookupError as e:\n  x = e\n";
+    let x = run_source(&mut vm, source).unwrap();
+    let key_error = vm.context().exceptions.key_error.clone();
+    assert!(objtype::isinstance(&x, &key_error));
+  }
+
+  #[test]
+  fn test_unhandled_exception_propagates() {
+    let mut vm = VirtualMachine::new();
+    let source = "try:\n  undefined\nexcept ValueError:\n  pass\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let name_error = vm.context().exceptions.name_error.clone();
+    assert!(objtype::isinstance(&exc, &name_error));
+    match exc.get_attr(&String::from("msg")).borrow().kind {
+      PyObjectKind::String { ref value } => {
+        assert_eq!(value, "name 'undefined' is not defined")
+      }
+      _ => panic!("msg should be a string"),
+    }
+  }
+
+  #[test]
+  fn test_traceback_records_frames_outermost_first() {
+    let mut vm = VirtualMachine::new();
+    let source = "def f():\n  raise ValueError()\n\nf()\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    assert_eq!(
+      traceback_entries(&exc),
+      vec!["('test.py', 4, '<module>')", "('test.py', 2, 'f')"]
+    );
+  }
+
+  #[test]
+  fn test_traceback_points_at_the_line_of_the_subexpression() {
+    let mut vm = VirtualMachine::new();
+    let source = "def f():\n  raise ValueError()\n\nx = [1,\n     f()]\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    assert_eq!(
+      traceback_entries(&exc),
+      vec!["('test.py', 5, '<module>')", "('test.py', 2, 'f')"]
+    );
+  }
+
+  #[test]
+  fn test_str_and_repr_are_built_from_args() {
+    let mut vm = VirtualMachine::new();
+    let source = "e = ValueError('bad', 1)\nx = (str(ValueError('bad')), repr(ValueError('bad')), str(e), repr(e), str(KeyError()))\n";
+    let x = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      x.borrow().str(),
+      "('bad', \"ValueError('bad')\", \"('bad', 1)\", \"ValueError('bad', 1)\", '')"
+    );
+  }
 }
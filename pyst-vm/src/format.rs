@@ -0,0 +1,485 @@
+/*
+ * The format specification mini-language, as used by format() and
+ * str.format. Printf-style formatting with % maps its conversions onto
+ * these specifications as well.
+ */
+
+use super::objfloat;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use std::cmp;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FormatAlign {
+  Left,
+  Right,
+  Center,
+  // Padding goes between the sign and the digits:
+  AfterSign,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FormatSign {
+  // Only negative numbers get a sign:
+  Minus,
+  Plus,
+  Space,
+}
+
+// [[fill]align][sign][#][0][width][grouping][.precision][type]
+#[derive(Debug, PartialEq, Clone)]
+pub struct FormatSpec {
+  pub fill: char,
+  pub align: Option<FormatAlign>,
+  pub sign: Option<FormatSign>,
+  pub alternate: bool,
+  pub width: Option<usize>,
+  pub grouping: Option<char>,
+  pub precision: Option<usize>,
+  pub kind: Option<char>,
+}
+
+fn parse_align(c: char) -> Option<FormatAlign> {
+  match c {
+    '<' => Some(FormatAlign::Left),
+    '>' => Some(FormatAlign::Right),
+    '^' => Some(FormatAlign::Center),
+    '=' => Some(FormatAlign::AfterSign),
+    _ => None,
+  }
+}
+
+// Take the decimal number at the start of chars, if there is one:
+fn parse_number(
+  chars: &[char],
+  pos: &mut usize,
+) -> Result<Option<usize>, String> {
+  let start = *pos;
+  while *pos < chars.len() && chars[*pos].is_ascii_digit() {
+    *pos += 1;
+  }
+  if *pos == start {
+    return Ok(None);
+  }
+  let digits: String = chars[start..*pos].iter().collect();
+  match digits.parse::<usize>() {
+    Ok(value) => Ok(Some(value)),
+    Err(_) => Err("Too many decimal digits in format string".to_string()),
+  }
+}
+
+impl FormatSpec {
+  pub fn parse(text: &str) -> Result<FormatSpec, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pos = 0;
+    let mut spec = FormatSpec {
+      fill: ' ',
+      align: None,
+      sign: None,
+      alternate: false,
+      width: None,
+      grouping: None,
+      precision: None,
+      kind: None,
+    };
+    if chars.len() >= 2 && parse_align(chars[1]).is_some() {
+      spec.fill = chars[0];
+      spec.align = parse_align(chars[1]);
+      pos = 2;
+    } else if !chars.is_empty() && parse_align(chars[0]).is_some() {
+      spec.align = parse_align(chars[0]);
+      pos = 1;
+    }
+    if pos < chars.len() {
+      spec.sign = match chars[pos] {
+        '-' => Some(FormatSign::Minus),
+        '+' => Some(FormatSign::Plus),
+        ' ' => Some(FormatSign::Space),
+        _ => None,
+      };
+      if spec.sign.is_some() {
+        pos += 1;
+      }
+    }
+    if pos < chars.len() && chars[pos] == '#' {
+      spec.alternate = true;
+      pos += 1;
+    }
+    // A leading zero on the width pads numbers with zeros after the sign:
+    if pos < chars.len() && chars[pos] == '0' {
+      if spec.align.is_none() {
+        spec.fill = '0';
+        spec.align = Some(FormatAlign::AfterSign);
+      }
+      pos += 1;
+    }
+    spec.width = parse_number(&chars, &mut pos)?;
+    if pos < chars.len() && (chars[pos] == ',' || chars[pos] == '_') {
+      spec.grouping = Some(chars[pos]);
+      pos += 1;
+    }
+    if pos < chars.len() && chars[pos] == '.' {
+      pos += 1;
+      spec.precision = parse_number(&chars, &mut pos)?;
+      if spec.precision.is_none() {
+        return Err("Format specifier missing precision".to_string());
+      }
+    }
+    if pos < chars.len() {
+      spec.kind = Some(chars[pos]);
+      pos += 1;
+    }
+    if pos < chars.len() {
+      return Err("Invalid format specifier".to_string());
+    }
+    Ok(spec)
+  }
+
+  fn unknown_code(&self, type_name: &str) -> String {
+    format!(
+      "Unknown format code '{}' for object of type '{}'",
+      self.kind.unwrap(),
+      type_name
+    )
+  }
+
+  fn sign_for(&self, negative: bool) -> &'static str {
+    if negative {
+      "-"
+    } else {
+      match self.sign {
+        Some(FormatSign::Plus) => "+",
+        Some(FormatSign::Space) => " ",
+        _ => "",
+      }
+    }
+  }
+
+  // Pad text to the width of the specification. Numbers have a sign and
+  // prefix, which stay in front of any padding after the sign:
+  fn pad(&self, prefix: &str, body: &str, default: FormatAlign) -> String {
+    let len = prefix.chars().count() + body.chars().count();
+    let padding = match self.width {
+      Some(width) if width > len => width - len,
+      _ => return format!("{}{}", prefix, body),
+    };
+    let fill = |count: usize| -> String {
+      ::std::iter::repeat(self.fill).take(count).collect()
+    };
+    match self.align.unwrap_or(default) {
+      FormatAlign::Left => format!("{}{}{}", prefix, body, fill(padding)),
+      FormatAlign::Right => format!("{}{}{}", fill(padding), prefix, body),
+      FormatAlign::Center => format!(
+        "{}{}{}{}",
+        fill(padding / 2),
+        prefix,
+        body,
+        fill(padding - padding / 2)
+      ),
+      FormatAlign::AfterSign => format!("{}{}{}", prefix, fill(padding), body),
+    }
+  }
+
+  pub fn format_string(&self, value: &str) -> Result<String, String> {
+    match self.kind {
+      None | Some('s') => {}
+      _ => return Err(self.unknown_code("str")),
+    }
+    if self.sign.is_some() {
+      return Err("Sign not allowed in string format specifier".to_string());
+    }
+    if self.alternate {
+      return Err(
+        "Alternate form (#) not allowed in string format specifier".to_string(),
+      );
+    }
+    if self.align == Some(FormatAlign::AfterSign) {
+      return Err(
+        "'=' alignment not allowed in string format specifier".to_string(),
+      );
+    }
+    let value: String = match self.precision {
+      Some(precision) => value.chars().take(precision).collect(),
+      None => value.to_string(),
+    };
+    Ok(self.pad("", &value, FormatAlign::Left))
+  }
+
+  pub fn format_int(&self, value: &BigInt) -> Result<String, String> {
+    let (radix, prefix) = match self.kind {
+      None | Some('d') | Some('n') => (10, ""),
+      Some('b') => (2, "0b"),
+      Some('o') => (8, "0o"),
+      Some('x') => (16, "0x"),
+      Some('X') => (16, "0X"),
+      Some('c') => {
+        if self.sign.is_some() {
+          return Err(
+            "Sign not allowed with integer format specifier 'c'".to_string(),
+          );
+        }
+        let c = value.to_u32().and_then(::std::char::from_u32);
+        return match c {
+          Some(c) => Ok(self.pad("", &c.to_string(), FormatAlign::Left)),
+          None => Err("%c arg not in range(0x110000)".to_string()),
+        };
+      }
+      Some('e') | Some('E') | Some('f') | Some('F') | Some('g') | Some('G')
+      | Some('%') => {
+        return match value.to_f64() {
+          Some(value) if value.is_finite() => self.format_float(value),
+          _ => Err("int too large to convert to float".to_string()),
+        };
+      }
+      _ => return Err(self.unknown_code("int")),
+    };
+    if self.precision.is_some() {
+      return Err(
+        "Precision not allowed in integer format specifier".to_string(),
+      );
+    }
+    let mut digits = value.abs().to_str_radix(radix);
+    if self.kind == Some('X') {
+      digits = digits.to_uppercase();
+    }
+    if let Some(separator) = self.grouping {
+      let size = if radix == 10 { 3 } else { 4 };
+      digits = group_digits(&digits, separator, size);
+    }
+    let prefix = format!(
+      "{}{}",
+      self.sign_for(value.is_negative()),
+      if self.alternate { prefix } else { "" }
+    );
+    Ok(self.pad(&prefix, &digits, FormatAlign::Right))
+  }
+
+  pub fn format_float(&self, value: f64) -> Result<String, String> {
+    let upper = match self.kind {
+      Some('E') | Some('F') | Some('G') => true,
+      _ => false,
+    };
+    if let Some(precision) = self.precision {
+      if precision > i32::max_value() as usize {
+        return Err("precision too big".to_string());
+      }
+    }
+    let magnitude = value.abs();
+    let body = if magnitude.is_nan() {
+      "nan".to_string()
+    } else if magnitude.is_infinite() {
+      "inf".to_string()
+    } else {
+      match self.kind {
+        None => match self.precision {
+          Some(precision) => {
+            let text = format_general(magnitude, precision, self.alternate);
+            // Without a type there is at least one digit after the point:
+            if text.contains('.') || text.contains('e') {
+              text
+            } else {
+              format!("{}.0", text)
+            }
+          }
+          None => objfloat::to_string(magnitude),
+        },
+        Some('f') | Some('F') => {
+          let text = format_fixed(magnitude, self.precision.unwrap_or(6));
+          if self.alternate && !text.contains('.') {
+            format!("{}.", text)
+          } else {
+            text
+          }
+        }
+        Some('e') | Some('E') => format_exponent(
+          magnitude,
+          self.precision.unwrap_or(6),
+          self.alternate,
+        ),
+        Some('g') | Some('G') | Some('n') => {
+          format_general(magnitude, self.precision.unwrap_or(6), self.alternate)
+        }
+        Some('%') => {
+          format!(
+            "{}%",
+            format_fixed(magnitude * 100.0, self.precision.unwrap_or(6))
+          )
+        }
+        _ => return Err(self.unknown_code("float")),
+      }
+    };
+    let body = if upper { body.to_uppercase() } else { body };
+    let body = match self.grouping {
+      Some(separator) => {
+        // Only the digits before the point are grouped:
+        let end = body
+          .find(|c: char| !c.is_ascii_digit())
+          .unwrap_or_else(|| body.len());
+        format!(
+          "{}{}",
+          group_digits(&body[..end], separator, 3),
+          &body[end..]
+        )
+      }
+      None => body,
+    };
+    let prefix = self.sign_for(value.is_sign_negative() && !value.is_nan());
+    Ok(self.pad(prefix, &body, FormatAlign::Right))
+  }
+}
+
+fn group_digits(digits: &str, separator: char, size: usize) -> String {
+  let len = digits.len();
+  let mut result = String::new();
+  for (i, c) in digits.chars().enumerate() {
+    if i > 0 && (len - i) % size == 0 {
+      result.push(separator);
+    }
+    result.push(c);
+  }
+  result
+}
+
+// No float has more than 1074 digits after the point, so those beyond are
+// zeros. They are added by hand, as format! cannot take a precision that is
+// much larger:
+const MAX_FLOAT_DIGITS: usize = 1074;
+
+fn format_fixed(value: f64, precision: usize) -> String {
+  let digits = cmp::min(precision, MAX_FLOAT_DIGITS);
+  format!("{:.*}{}", digits, value, "0".repeat(precision - digits))
+}
+
+fn format_scientific(value: f64, precision: usize) -> String {
+  let digits = cmp::min(precision, MAX_FLOAT_DIGITS);
+  let text = format!("{:.*e}", digits, value);
+  let (mantissa, exponent) = text.split_at(text.find('e').unwrap());
+  format!("{}{}{}", mantissa, "0".repeat(precision - digits), exponent)
+}
+
+// Scientific notation with precision digits after the point, and an exponent
+// of at least two digits, as in 1.500000e+03:
+fn format_exponent(value: f64, precision: usize, alternate: bool) -> String {
+  let text = format_scientific(value, precision);
+  let (mantissa, exponent) = text.split_at(text.find('e').unwrap());
+  let exponent: i32 = exponent[1..].parse().unwrap();
+  let point = if alternate && precision == 0 { "." } else { "" };
+  format!(
+    "{}{}e{}{:02}",
+    mantissa,
+    point,
+    if exponent < 0 { '-' } else { '+' },
+    exponent.abs()
+  )
+}
+
+// The general format uses fixed point notation for numbers of a moderate
+// size, and scientific notation otherwise. Trailing zeros are removed unless
+// the alternate form is asked for:
+fn format_general(value: f64, precision: usize, alternate: bool) -> String {
+  let precision = if precision == 0 { 1 } else { precision };
+  let exponent = if value.is_zero() {
+    0
+  } else {
+    let text = format_scientific(value, precision - 1);
+    text[text.find('e').unwrap() + 1..].parse::<i32>().unwrap()
+  };
+  let text = if exponent >= -4 && exponent < precision as i32 {
+    let decimals = (precision as i32 - 1 - exponent) as usize;
+    format_fixed(value, decimals)
+  } else {
+    format_exponent(value, precision - 1, alternate)
+  };
+  if alternate {
+    if text.contains('.') {
+      text
+    } else {
+      format!("{}.", text)
+    }
+  } else {
+    remove_trailing_zeros(&text)
+  }
+}
+
+fn remove_trailing_zeros(text: &str) -> String {
+  let (mantissa, exponent) = match text.find('e') {
+    Some(pos) => text.split_at(pos),
+    None => (text, ""),
+  };
+  let mantissa = if mantissa.contains('.') {
+    mantissa.trim_end_matches('0').trim_end_matches('.')
+  } else {
+    mantissa
+  };
+  format!("{}{}", mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{FormatAlign, FormatSpec};
+  use num_bigint::BigInt;
+
+  fn format_int(spec: &str, value: i64) -> Result<String, String> {
+    FormatSpec::parse(spec)?.format_int(&BigInt::from(value))
+  }
+
+  fn format_float(spec: &str, value: f64) -> Result<String, String> {
+    FormatSpec::parse(spec)?.format_float(value)
+  }
+
+  #[test]
+  fn test_parse_format_spec() {
+    let spec = FormatSpec::parse("*^+#012,.3f").unwrap();
+    assert_eq!(spec.fill, '*');
+    assert_eq!(spec.align, Some(FormatAlign::Center));
+    assert!(spec.alternate);
+    assert_eq!(spec.width, Some(12));
+    assert_eq!(spec.grouping, Some(','));
+    assert_eq!(spec.precision, Some(3));
+    assert_eq!(spec.kind, Some('f'));
+    assert!(FormatSpec::parse("10.f").is_err());
+  }
+
+  #[test]
+  fn test_format_int() {
+    assert_eq!(format_int("", -42), Ok("-42".to_string()));
+    assert_eq!(format_int("+05", 42), Ok("+0042".to_string()));
+    assert_eq!(format_int("#x", 255), Ok("0xff".to_string()));
+    assert_eq!(format_int("_b", 10), Ok("1010".to_string()));
+    assert_eq!(format_int(",", 1234567), Ok("1,234,567".to_string()));
+    assert_eq!(format_int("*<6", 7), Ok("7*****".to_string()));
+    assert_eq!(format_int("c", 65), Ok("A".to_string()));
+    assert!(format_int(".2d", 1).is_err());
+  }
+
+  #[test]
+  fn test_format_float() {
+    assert_eq!(format_float(".2f", 3.14159), Ok("3.14".to_string()));
+    assert_eq!(format_float("e", 1500.0), Ok("1.500000e+03".to_string()));
+    assert_eq!(format_float("g", 0.00001), Ok("1e-05".to_string()));
+    assert_eq!(
+      format_float("g", 123456789.0),
+      Ok("1.23457e+08".to_string())
+    );
+    assert_eq!(format_float(".3", 2.0), Ok("2.0".to_string()));
+    assert_eq!(format_float("", 0.1), Ok("0.1".to_string()));
+    assert_eq!(format_float(".1%", 0.25), Ok("25.0%".to_string()));
+    assert_eq!(format_float("08.2f", -1.5), Ok("-0001.50".to_string()));
+    assert_eq!(
+      format_float("F", ::std::f64::INFINITY),
+      Ok("INF".to_string())
+    );
+  }
+
+  #[test]
+  fn test_format_float_with_large_precision() {
+    let text = format_float(".70000f", 1.5).unwrap();
+    assert_eq!(text.len(), 70002);
+    assert!(text.starts_with("1.5000") && text.ends_with("0000"));
+    let text = format_float(".70000e", 1.5).unwrap();
+    assert_eq!(text.len(), 70006);
+    assert!(text.ends_with("00e+00"));
+    assert_eq!(format_float(".70000", 1.5), Ok("1.5".to_string()));
+    assert!(format_float(".3000000000f", 1.5).is_err());
+  }
+}
@@ -1,18 +1,62 @@
+extern crate pyst_parser;
+
+use std::collections::HashMap;
 use std::fmt;
 
+use self::pyst_parser::ast;
 use super::bytecode;
-use super::pyobject::{PyObjectKind, PyObjectRef};
+use super::pyobject::{DictProtocol, PyContext, PyObjectKind, PyObjectRef};
 
 #[derive(Clone, Debug)]
 pub enum Block {
+  // A loop, and the height of the value stack before its iterator, which
+  // break drops:
   Loop {
     start: bytecode::Label,
     end: bytecode::Label,
+    level: usize,
+  },
+  // The handlers of a try statement, and the height of the value stack when
+  // it started:
+  TryExcept {
+    handler: bytecode::Label,
+    level: usize,
+  },
+  Finally {
+    handler: bytecode::Label,
+    level: usize,
+  },
+  // The bound __exit__ method of a context manager, and the height of the
+  // value stack when its with statement started:
+  With {
+    end: bytecode::Label,
+    exit: PyObjectRef,
+    level: usize,
+  },
+  // The exception which an except clause is handling:
+  ExceptHandler {
+    exc: PyObjectRef,
+  },
+  // Why a finally clause is running. None means the try statement ended
+  // normally:
+  FinallyHandler {
+    reason: Option<UnwindReason>,
   },
-  // TODO: Remove this allow once TryExcept is in use
-  #[allow(dead_code)]
-  // TODO: Implement try/except blocks
-  TryExcept,
+}
+
+// Why the blocks of a frame are being left:
+#[derive(Clone, Debug)]
+pub enum UnwindReason {
+  Returning { value: PyObjectRef },
+  Raising { exception: PyObjectRef },
+  Break,
+  Continue,
+}
+
+// How a frame stopped running, when it did not raise an exception:
+pub enum ExecutionResult {
+  Return(PyObjectRef),
+  Yield(PyObjectRef),
 }
 
 pub struct Frame {
@@ -22,7 +66,11 @@ pub struct Frame {
   stack: Vec<PyObjectRef>, // The main data frame of the stack machine
   blocks: Vec<Block>,      // Block frames, for controling loops and exceptions
   pub locals: PyObjectRef, // Variables
+  pub globals: PyObjectRef, // Variables of the module
+  pub cells: HashMap<String, PyObjectRef>, // Variables shared with closures
   pub lasti: usize,        // index of last instruction ran
+  pub yielded: bool,       // Whether the frame was suspended by a yield
+  pub reraised: bool,      // Whether the exception being raised is re-raised
                            // cmp_op: Vec<&'a Fn(NativeType, NativeType) -> bool>, // TODO: change compare to a function list
 }
 
@@ -36,26 +84,40 @@ pub fn copy_code(code_obj: PyObjectRef) -> bytecode::CodeObject {
 }
 
 impl Frame {
-  pub fn new(code: PyObjectRef, globals: PyObjectRef) -> Frame {
-    //populate the globals and locals
-    //TODO: This is wrong, check https://github.com/nedbat/byterun/blob/31e6c4a8212c35b5157919abff43a7daa0f377c6/byterun/pyvm2.py#L95
-    /*
-    let globals = match globals {
-        Some(g) => g,
-        None => HashMap::new(),
-    };
-    */
-    let locals = globals;
-    // locals.extend(callargs);
+  // The closure holds the cells for the free variables of the code. Cells
+  // for its own cell variables are made here, taking the value of the
+  // argument with the same name, if any:
+  pub fn new(
+    ctx: &PyContext,
+    code: PyObjectRef,
+    locals: PyObjectRef,
+    globals: PyObjectRef,
+    closure: Vec<PyObjectRef>,
+  ) -> Frame {
+    let code = copy_code(code);
+    let mut cells = HashMap::new();
+    for name in &code.cell_names {
+      let contents = if locals.contains_key(name) {
+        Some(locals.get_item(name))
+      } else {
+        None
+      };
+      cells.insert(name.clone(), ctx.new_cell(contents));
+    }
+    for (name, cell) in code.free_names.iter().zip(closure) {
+      cells.insert(name.clone(), cell);
+    }
 
     Frame {
-      code: copy_code(code),
+      code: code,
       stack: vec![],
       blocks: vec![],
-      // save the callargs as locals
-      // globals: locals.clone(),
       locals: locals,
+      globals: globals,
+      cells: cells,
       lasti: 0,
+      yielded: false,
+      reraised: false,
     }
   }
 
@@ -67,6 +129,11 @@ impl Frame {
     ins2
   }
 
+  // Source location of the instruction that ran last:
+  pub fn get_location(&self) -> ast::Location {
+    self.code.locations[self.lasti - 1].clone()
+  }
+
   pub fn push_block(&mut self, block: Block) {
     self.blocks.push(block);
   }
@@ -75,8 +142,25 @@ impl Frame {
     self.blocks.pop()
   }
 
-  pub fn last_block(&mut self) -> &Block {
-    self.blocks.last().unwrap()
+  pub fn last_block(&mut self) -> Option<&Block> {
+    self.blocks.last()
+  }
+
+  // The exception which an except or finally clause of the frame is
+  // handling, if any:
+  pub fn handled_exception(&self) -> Option<PyObjectRef> {
+    self
+      .blocks
+      .iter()
+      .rev()
+      .filter_map(|block| match block {
+        Block::ExceptHandler { exc } => Some(exc.clone()),
+        Block::FinallyHandler {
+          reason: Some(UnwindReason::Raising { exception }),
+        } => Some(exception.clone()),
+        _ => None,
+      })
+      .next()
   }
 
   pub fn push_value(&mut self, obj: PyObjectRef) {
@@ -99,6 +183,20 @@ impl Frame {
   pub fn last_value(&mut self) -> PyObjectRef {
     self.stack.last().unwrap().clone()
   }
+
+  pub fn stack_level(&self) -> usize {
+    self.stack.len()
+  }
+
+  // Drop the values pushed since the stack had the given height:
+  pub fn truncate_stack(&mut self, level: usize) {
+    self.stack.truncate(level);
+  }
+
+  // The value depth items below the top of the stack:
+  pub fn nth_value(&self, depth: usize) -> PyObjectRef {
+    self.stack[self.stack.len() - depth - 1].clone()
+  }
 }
 
 impl fmt::Debug for Frame {
@@ -17,19 +17,27 @@ fn import_module(vm: &mut VirtualMachine, module: &String) -> PyResult {
   }
 
   // Time to search for module in any place:
-  let filepath = find_source(module)
-    .map_err(|e| vm.new_exception(format!("Error: {:?}", e)))?;
-  let source = parser::read_file(filepath.as_path())
-    .map_err(|e| vm.new_exception(format!("Error: {:?}", e)))?;
+  let filepath = find_source(module).map_err(|e| {
+    let module_not_found_error =
+      vm.context().exceptions.module_not_found_error.clone();
+    vm.new_exception(module_not_found_error, format!("{}", e))
+  })?;
+  let source = parser::read_file(filepath.as_path()).map_err(|e| {
+    let import_error = vm.context().exceptions.import_error.clone();
+    vm.new_exception(import_error, e)
+  })?;
 
-  let code_obj = match compile::compile(vm, &source, compile::Mode::Exec) {
+  let code_obj = match compile::compile(
+    vm,
+    &source,
+    compile::Mode::Exec,
+    Some(filepath.to_str().unwrap().to_string()),
+  ) {
     Ok(bytecode) => {
       debug!("Code object: {:?}", bytecode);
       bytecode
     }
-    Err(value) => {
-      panic!("Error: {}", value);
-    }
+    Err(error) => return Err(vm.new_syntax_error(&error)),
   };
 
   let builtins = vm.get_builtin_scope();
@@ -51,7 +59,16 @@ pub fn import(
   // If we're importing a symbol, look it up and use it, otherwise construct a module and return
   // that
   let obj = match symbol {
-    Some(symbol) => scope.get_item(symbol),
+    Some(symbol) => {
+      if !scope.contains_key(symbol) {
+        let import_error = vm.context().exceptions.import_error.clone();
+        return Err(vm.new_exception(
+          import_error,
+          format!("cannot import name '{}' from '{}'", symbol, module),
+        ));
+      }
+      scope.get_item(symbol)
+    }
     None => PyObject::new(
       PyObjectKind::Module {
         name: module.clone(),
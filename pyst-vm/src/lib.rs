@@ -1,5 +1,10 @@
 #[macro_use]
 extern crate log;
+extern crate num_bigint;
+extern crate num_complex;
+extern crate num_integer;
+extern crate num_traits;
+extern crate stacker;
 
 pub mod bytecode;
 pub mod pyobject;
@@ -8,18 +13,31 @@ pub mod eval;
 
 mod builtins;
 mod exceptions;
+mod format;
 mod frame;
 mod import;
 mod objbool;
+mod objbytes;
+mod objcomplex;
 mod objdict;
+mod objfloat;
 mod objfunction;
+mod objgenerator;
 mod objint;
+mod objiter;
 mod objlist;
+mod objnone;
+mod objnumber;
 mod objobject;
+mod objrange;
 mod objsequence;
+mod objset;
 mod objstr;
+mod objtuple;
 mod objtype;
+mod symboltable;
 mod sysmodule;
 mod vm;
 
+pub use self::exceptions::print_exception;
 pub use self::vm::VirtualMachine;
\ No newline at end of file
@@ -1,10 +1,88 @@
-use super::pyobject::{PyObjectKind, PyObjectRef};
-
-pub fn boolval(o: PyObjectRef) -> bool {
-  let obj = o.borrow();
-  match obj.kind {
-    PyObjectKind::Boolean { value } => value,
-    PyObjectKind::Integer { value } => value != 0,
-    ref kind => unimplemented!("converting to boolean unsupported for: {:?}", kind),
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+use num_traits::Zero;
+
+// Truth value testing, which falls back on __bool__ and then __len__ for
+// objects of user defined classes:
+pub fn boolval(
+  vm: &mut VirtualMachine,
+  obj: PyObjectRef,
+) -> Result<bool, PyObjectRef> {
+  let value = match obj.borrow().kind {
+    PyObjectKind::Boolean { value } => Some(value),
+    PyObjectKind::Integer { ref value } => Some(!value.is_zero()),
+    PyObjectKind::Float { value } => Some(value != 0.0),
+    PyObjectKind::Complex { value } => Some(!value.is_zero()),
+    PyObjectKind::String { ref value } => Some(!value.is_empty()),
+    PyObjectKind::Bytes { ref value } | PyObjectKind::ByteArray { ref value } => Some(!value.is_empty()),
+    PyObjectKind::List { ref elements } | PyObjectKind::Tuple { ref elements } => Some(!elements.is_empty()),
+    PyObjectKind::Dict { ref elements }
+    | PyObjectKind::Set { ref elements }
+    | PyObjectKind::FrozenSet { ref elements } => Some(!elements.is_empty()),
+    PyObjectKind::PyNone => Some(false),
+    _ => None,
+  };
+  if let Some(value) = value {
+    return Ok(value);
+  }
+
+  let args = PyFuncArgs::new(vec![obj.clone()], vec![]);
+  if let Some(method) = objtype::get_special_method(&obj, "__bool__") {
+    let result = vm.invoke(method, args)?;
+    let value = match result.borrow().kind {
+      PyObjectKind::Boolean { value } => Ok(value),
+      _ => Err(vm.new_type_error(format!(
+        "__bool__ should return bool, returned {}",
+        objtype::get_type_name(&result.typ())
+      ))),
+    };
+    return value;
   }
+  if let Some(method) = objtype::get_special_method(&obj, "__len__") {
+    let result = vm.invoke(method, args)?;
+    let value = match result.borrow().kind {
+      PyObjectKind::Integer { ref value } => Ok(!value.is_zero()),
+      _ => Err(vm.new_type_error(format!(
+        "'{}' object cannot be interpreted as an integer",
+        objtype::get_type_name(&result.typ())
+      ))),
+    };
+    return value;
+  }
+  // Objects are truthy by default:
+  Ok(true)
+}
+
+fn bool_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let value = match args.args.len() {
+    1 => false,
+    2 => boolval(vm, args.args[1].clone())?,
+    _ => {
+      return Err(
+        vm.new_type_error("bool expected at most 1 argument".to_string()),
+      )
+    }
+  };
+  Ok(vm.context().new_bool(value))
+}
+
+// bool is a subclass of int:
+pub fn create_type(type_type: PyObjectRef, int_type: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "bool", vec![int_type.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref bool_type = context.bool_type;
+  bool_type.set_attr("__new__", context.new_rustfunc(bool_new));
 }
@@ -0,0 +1,1070 @@
+/*
+ * Builtin bytes and bytearray types, which hold binary data. A bytes object
+ * is immutable, while a bytearray can be changed in place.
+ */
+
+use std::str;
+
+use super::objdict::{self, DictElements};
+use super::objiter;
+use super::objsequence::PySliceableSequence;
+use super::objstr;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+use num_traits::ToPrimitive;
+
+pub fn get_kind_value(kind: &PyObjectKind) -> Option<&Vec<u8>> {
+  match kind {
+    PyObjectKind::Bytes { ref value }
+    | PyObjectKind::ByteArray { ref value } => Some(value),
+    _ => None,
+  }
+}
+
+// The data of a bytes or bytearray object:
+pub fn get_value(obj: &PyObjectRef) -> Option<Vec<u8>> {
+  get_kind_value(&obj.borrow().kind).cloned()
+}
+
+fn is_bytearray(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::ByteArray { .. } => true,
+    _ => false,
+  }
+}
+
+// A new object of the same type as obj, bytes or bytearray:
+fn new_like(
+  vm: &VirtualMachine,
+  obj: &PyObjectRef,
+  value: Vec<u8>,
+) -> PyObjectRef {
+  if is_bytearray(obj) {
+    vm.context().new_bytearray(value)
+  } else {
+    vm.context().new_bytes(value)
+  }
+}
+
+pub fn repr_bytes(value: &[u8]) -> String {
+  let quote = if value.contains(&b'\'') && !value.contains(&b'"') {
+    '"'
+  } else {
+    '\''
+  };
+  let mut result = String::from("b");
+  result.push(quote);
+  for &byte in value {
+    match byte {
+      b'\\' => result.push_str("\\\\"),
+      b'\n' => result.push_str("\\n"),
+      b'\r' => result.push_str("\\r"),
+      b'\t' => result.push_str("\\t"),
+      byte if byte as char == quote => {
+        result.push('\\');
+        result.push(quote);
+      }
+      0x20..=0x7e => result.push(byte as char),
+      byte => result.push_str(&format!("\\x{:02x}", byte)),
+    }
+  }
+  result.push(quote);
+  result
+}
+
+enum Encoding {
+  Utf8,
+  Latin1,
+  Ascii,
+}
+
+fn lookup_encoding(
+  vm: &mut VirtualMachine,
+  encoding: &str,
+) -> Result<Encoding, PyObjectRef> {
+  match encoding.to_lowercase().replace('_', "-").as_str() {
+    "utf-8" | "utf8" | "u8" => Ok(Encoding::Utf8),
+    "latin-1" | "latin1" | "iso-8859-1" | "iso8859-1" | "l1" => {
+      Ok(Encoding::Latin1)
+    }
+    "ascii" | "us-ascii" => Ok(Encoding::Ascii),
+    _ => {
+      let lookup_error = vm.context().exceptions.lookup_error.clone();
+      Err(
+        vm.new_exception(
+          lookup_error,
+          format!("unknown encoding: {}", encoding),
+        ),
+      )
+    }
+  }
+}
+
+// Characters which cannot be encoded or decoded raise an error, unless the
+// errors argument says to ignore or replace them:
+fn check_errors(
+  vm: &mut VirtualMachine,
+  errors: &str,
+) -> Result<(), PyObjectRef> {
+  match errors {
+    "strict" | "ignore" | "replace" => Ok(()),
+    _ => {
+      let lookup_error = vm.context().exceptions.lookup_error.clone();
+      Err(vm.new_exception(
+        lookup_error,
+        format!("unknown error handler name '{}'", errors),
+      ))
+    }
+  }
+}
+
+pub fn encode(
+  vm: &mut VirtualMachine,
+  value: &str,
+  encoding: &str,
+  errors: &str,
+) -> Result<Vec<u8>, PyObjectRef> {
+  let (name, limit) = match lookup_encoding(vm, encoding)? {
+    Encoding::Utf8 => return Ok(value.as_bytes().to_vec()),
+    Encoding::Latin1 => ("latin-1", 0x100),
+    Encoding::Ascii => ("ascii", 0x80),
+  };
+  check_errors(vm, errors)?;
+  let mut result = vec![];
+  for (position, c) in value.chars().enumerate() {
+    if (c as u32) < limit {
+      result.push(c as u8);
+      continue;
+    }
+    match errors {
+      "ignore" => {}
+      "replace" => result.push(b'?'),
+      _ => {
+        let character = objstr::ascii_string(&c.to_string());
+        let unicode_encode_error =
+          vm.context().exceptions.unicode_encode_error.clone();
+        return Err(vm.new_exception(
+          unicode_encode_error,
+          format!(
+            "'{}' codec can't encode character '{}' in position {}: ordinal not in range({})",
+            name, character, position, limit
+          ),
+        ));
+      }
+    }
+  }
+  Ok(result)
+}
+
+pub fn decode(
+  vm: &mut VirtualMachine,
+  value: &[u8],
+  encoding: &str,
+  errors: &str,
+) -> Result<String, PyObjectRef> {
+  let encoding = lookup_encoding(vm, encoding)?;
+  check_errors(vm, errors)?;
+  let mut result = String::new();
+  let mut position = 0;
+  while position < value.len() {
+    // The length of the valid text from position, and of the bytes after
+    // it which cannot be decoded, with the reason why:
+    let (valid, invalid, reason) = match encoding {
+      Encoding::Latin1 => (value.len() - position, 0, ""),
+      Encoding::Ascii => {
+        let valid = value[position..]
+          .iter()
+          .position(|byte| *byte >= 0x80)
+          .unwrap_or(value.len() - position);
+        (valid, 1, "ordinal not in range(128)")
+      }
+      Encoding::Utf8 => match str::from_utf8(&value[position..]) {
+        Ok(text) => (text.len(), 0, ""),
+        Err(err) => {
+          let valid = err.valid_up_to();
+          match err.error_len() {
+            None => (
+              valid,
+              value.len() - position - valid,
+              "unexpected end of data",
+            ),
+            Some(len) if value[position + valid] & 0xc0 == 0x80 => {
+              (valid, len, "invalid start byte")
+            }
+            Some(len) => (valid, len, "invalid continuation byte"),
+          }
+        }
+      },
+    };
+    let text = &value[position..position + valid];
+    match encoding {
+      Encoding::Utf8 => result.push_str(str::from_utf8(text).unwrap()),
+      _ => result.extend(text.iter().map(|byte| *byte as char)),
+    }
+    position += valid;
+    if position >= value.len() {
+      break;
+    }
+    match errors {
+      "ignore" => {}
+      "replace" => result.push('\u{fffd}'),
+      _ => {
+        let name = match encoding {
+          Encoding::Utf8 => "utf-8",
+          _ => "ascii",
+        };
+        let unicode_decode_error =
+          vm.context().exceptions.unicode_decode_error.clone();
+        return Err(vm.new_exception(
+          unicode_decode_error,
+          format!(
+            "'{}' codec can't decode byte {:#04x} in position {}: {}",
+            name, value[position], position, reason
+          ),
+        ));
+      }
+    }
+    position += invalid;
+  }
+  Ok(result)
+}
+
+// The argument at index, or the keyword argument of that name. Passing None
+// is the same as leaving an argument out:
+fn optional_arg(
+  args: &PyFuncArgs,
+  index: usize,
+  name: &str,
+) -> Option<PyObjectRef> {
+  let arg = match args.args.get(index) {
+    Some(arg) => Some(arg.clone()),
+    None => args.get_kwarg(name),
+  };
+  arg.and_then(|arg| match arg.borrow().kind {
+    PyObjectKind::PyNone => None,
+    _ => Some(arg.clone()),
+  })
+}
+
+fn str_arg(
+  vm: &mut VirtualMachine,
+  arg: Option<PyObjectRef>,
+  default: &str,
+) -> Result<String, PyObjectRef> {
+  match arg {
+    Some(arg) => match objstr::get_value(&arg) {
+      Some(value) => Ok(value),
+      None => Err(vm.new_type_error(format!(
+        "expected str, not {}",
+        objtype::get_type_name(&arg.typ())
+      ))),
+    },
+    None => Ok(default.to_string()),
+  }
+}
+
+fn index_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<i32, PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::Integer { ref value } => {
+      return Ok(value.to_i32().unwrap_or(if value.is_negative() {
+        i32::min_value()
+      } else {
+        i32::max_value()
+      }))
+    }
+    PyObjectKind::Boolean { value } => return Ok(value as i32),
+    _ => {}
+  };
+  Err(vm.new_type_error(format!(
+    "'{}' object cannot be interpreted as an integer",
+    objtype::get_type_name(&obj.typ())
+  )))
+}
+
+fn byte_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<u8, PyObjectRef> {
+  match index_arg(vm, obj)? {
+    value @ 0..=255 => Ok(value as u8),
+    _ => Err(vm.new_value_error("bytes must be in range(0, 256)".to_string())),
+  }
+}
+
+fn bytes_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<Vec<u8>, PyObjectRef> {
+  match get_value(obj) {
+    Some(value) => Ok(value),
+    None => Err(vm.new_type_error(format!(
+      "a bytes-like object is required, not '{}'",
+      objtype::get_type_name(&obj.typ())
+    ))),
+  }
+}
+
+// A subsequence to search for is either bytes or a single byte:
+fn sub_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<Vec<u8>, PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::Integer { .. } => {}
+    _ => return bytes_arg(vm, obj),
+  };
+  Ok(vec![byte_arg(vm, obj)?])
+}
+
+// The data of a bytes-like object, or the bytes produced by an iterable of
+// integers:
+fn from_iterable(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<Vec<u8>, PyObjectRef> {
+  if let Some(value) = get_value(obj) {
+    return Ok(value);
+  }
+  if objstr::get_value(obj).is_some() {
+    return Err(
+      vm.new_type_error("cannot convert 'str' object to bytes".to_string()),
+    );
+  }
+  let mut result = vec![];
+  for element in objiter::get_all(vm, obj)? {
+    result.push(byte_arg(vm, &element)?);
+  }
+  Ok(result)
+}
+
+fn find_sub(haystack: &[u8], needle: &[u8], reverse: bool) -> Option<usize> {
+  if needle.len() > haystack.len() {
+    return None;
+  }
+  let mut positions = 0..=haystack.len() - needle.len();
+  let matches =
+    |&position: &usize| &haystack[position..position + needle.len()] == needle;
+  if reverse {
+    positions.rev().find(matches)
+  } else {
+    positions.find(matches)
+  }
+}
+
+fn is_space(byte: u8) -> bool {
+  match byte {
+    b' ' | b'\t' | b'\n' | b'\r' | b'\x0b' | b'\x0c' => true,
+    _ => false,
+  }
+}
+
+// Check the arguments of a bytes or bytearray method, and take out the
+// object itself and its data. Methods which change the data only apply to a
+// bytearray:
+fn method_args(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  min: usize,
+  max: usize,
+  mutable: bool,
+) -> Result<(PyObjectRef, Vec<u8>), PyObjectRef> {
+  match args.args.get(0) {
+    Some(obj)
+      if get_value(obj).is_some() && !(mutable && !is_bytearray(obj)) =>
+    {
+      objdict::check_arg_count(vm, args, name, min, max)?;
+      Ok((obj.clone(), get_value(obj).unwrap()))
+    }
+    _ => Err(vm.new_type_error(format!(
+      "descriptor '{}' requires a {} object",
+      name,
+      if mutable {
+        "'bytearray'"
+      } else {
+        "'bytes' or 'bytearray'"
+      }
+    ))),
+  }
+}
+
+fn set_value(obj: &PyObjectRef, new_value: Vec<u8>) {
+  if let PyObjectKind::ByteArray { ref mut value } = obj.borrow_mut().kind {
+    *value = new_value;
+  }
+}
+
+// The positions of the bytes which a slice selects, in order:
+fn slice_positions(
+  vm: &mut VirtualMachine,
+  len: usize,
+  slice: &PyObjectRef,
+) -> Result<Vec<usize>, PyObjectRef> {
+  let positions: Vec<usize> = (0..len).collect();
+  positions.get_slice_items(vm, slice)
+}
+
+// bytes(), bytes(count), bytes(iterable), bytes(string, encoding[, errors]),
+// and the same for bytearray:
+fn bytes_new(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let typ = args.shift();
+  let bytearray = objtype::issubclass(&typ, &vm.context().bytearray_type);
+  let type_name = if bytearray { "bytearray" } else { "bytes" };
+  if args.args.len() > 3 {
+    return Err(vm.new_type_error(format!(
+      "{}() takes at most 3 arguments ({} given)",
+      type_name,
+      args.args.len()
+    )));
+  }
+  let source = optional_arg(&args, 0, "source");
+  let encoding = optional_arg(&args, 1, "encoding");
+  let errors = optional_arg(&args, 2, "errors");
+  let text = source.as_ref().and_then(objstr::get_value);
+  let value = match (source, text) {
+    (Some(_), Some(text)) => {
+      if encoding.is_none() {
+        return Err(
+          vm.new_type_error("string argument without an encoding".to_string()),
+        );
+      }
+      let encoding = str_arg(vm, encoding, "utf-8")?;
+      let errors = str_arg(vm, errors, "strict")?;
+      encode(vm, &text, &encoding, &errors)?
+    }
+    _ if encoding.is_some() || errors.is_some() => {
+      return Err(
+        vm.new_type_error("encoding without a string argument".to_string()),
+      )
+    }
+    (Some(source), None) => {
+      let count = match source.borrow().kind {
+        PyObjectKind::Integer { ref value } => Some(value.clone()),
+        _ => None,
+      };
+      match count {
+        Some(ref count) if count.is_negative() => {
+          return Err(vm.new_value_error("negative count".to_string()))
+        }
+        Some(count) => match count.to_usize() {
+          Some(count) => vec![0; count],
+          None => {
+            return Err(vm.new_type_error(
+              "cannot fit 'int' into an index-sized integer".to_string(),
+            ))
+          }
+        },
+        None => from_iterable(vm, &source)?,
+      }
+    }
+    (None, _) => vec![],
+  };
+  let kind = if bytearray {
+    PyObjectKind::ByteArray { value: value }
+  } else {
+    PyObjectKind::Bytes { value: value }
+  };
+  Ok(PyObject::new(kind, typ))
+}
+
+fn bytes_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (_, value) = method_args(vm, &args, "__len__", 0, 0, false)?;
+  Ok(vm.context().new_int(value.len()))
+}
+
+fn bytes_getitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "__getitem__", 1, 1, false)?;
+  let index = args.args[1].clone();
+  let is_slice = match index.borrow().kind {
+    PyObjectKind::Slice { .. } => true,
+    _ => false,
+  };
+  if is_slice {
+    let value = value.get_slice_items(vm, &index)?;
+    return Ok(new_like(vm, &obj, value));
+  }
+  let index = index_arg(vm, &index)?;
+  match value.get_pos(index) {
+    Some(position) => Ok(vm.context().new_int(value[position])),
+    None => {
+      let index_error = vm.context().exceptions.index_error.clone();
+      Err(vm.new_exception(index_error, "index out of range".to_string()))
+    }
+  }
+}
+
+fn bytearray_setitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "__setitem__", 2, 2, true)?;
+  let index = args.args[1].clone();
+  let slice = match index.borrow().kind {
+    PyObjectKind::Slice { start, stop, step } => Some((start, stop, step)),
+    _ => None,
+  };
+  match slice {
+    // A simple slice may be replaced by any number of bytes:
+    Some((start, stop, None)) | Some((start, stop, Some(1))) => {
+      let new_value = from_iterable(vm, &args.args[2])?;
+      let start = start.map_or(0, |start| value.get_slice_pos(start));
+      let stop = stop.map_or(value.len(), |stop| value.get_slice_pos(stop));
+      let stop = if stop < start { start } else { stop };
+      value.splice(start..stop, new_value);
+    }
+    Some(_) => {
+      let new_value = from_iterable(vm, &args.args[2])?;
+      let positions = slice_positions(vm, value.len(), &index)?;
+      if positions.len() != new_value.len() {
+        return Err(vm.new_value_error(format!(
+          "attempt to assign bytes of size {} to extended slice of size {}",
+          new_value.len(),
+          positions.len()
+        )));
+      }
+      for (position, byte) in positions.into_iter().zip(new_value) {
+        value[position] = byte;
+      }
+    }
+    None => {
+      let byte = byte_arg(vm, &args.args[2])?;
+      let index = index_arg(vm, &index)?;
+      match value.get_pos(index) {
+        Some(position) => value[position] = byte,
+        None => {
+          let index_error = vm.context().exceptions.index_error.clone();
+          return Err(vm.new_exception(
+            index_error,
+            "bytearray index out of range".to_string(),
+          ));
+        }
+      }
+    }
+  }
+  set_value(&obj, value);
+  Ok(vm.get_none())
+}
+
+fn bytearray_delitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "__delitem__", 1, 1, true)?;
+  let index = args.args[1].clone();
+  let is_slice = match index.borrow().kind {
+    PyObjectKind::Slice { .. } => true,
+    _ => false,
+  };
+  let mut positions = if is_slice {
+    slice_positions(vm, value.len(), &index)?
+  } else {
+    let index = index_arg(vm, &index)?;
+    match value.get_pos(index) {
+      Some(position) => vec![position],
+      None => {
+        let index_error = vm.context().exceptions.index_error.clone();
+        return Err(vm.new_exception(
+          index_error,
+          "bytearray index out of range".to_string(),
+        ));
+      }
+    }
+  };
+  // Remove from the back, so that the other positions stay the same:
+  positions.sort();
+  for position in positions.into_iter().rev() {
+    value.remove(position);
+  }
+  set_value(&obj, value);
+  Ok(vm.get_none())
+}
+
+fn bytes_contains(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (_, value) = method_args(vm, &args, "__contains__", 1, 1, false)?;
+  let sub = sub_arg(vm, &args.args[1])?;
+  Ok(vm.new_bool(find_sub(&value, &sub, false).is_some()))
+}
+
+fn bytes_add(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "__add__", 1, 1, false)?;
+  match get_value(&args.args[1]) {
+    Some(other) => {
+      value.extend(other);
+      Ok(new_like(vm, &obj, value))
+    }
+    None => Ok(vm.context().not_implemented.clone()),
+  }
+}
+
+fn bytes_mul(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "__mul__", 1, 1, false)?;
+  let count = match args.args[1].borrow().kind {
+    PyObjectKind::Integer { ref value } => value.clone(),
+    _ => return Ok(vm.context().not_implemented.clone()),
+  };
+  // Negative counts give empty bytes:
+  let count = if count.is_negative() {
+    Some(0)
+  } else {
+    count.to_usize()
+  };
+  match count {
+    Some(count) => Ok(new_like(vm, &obj, value.repeat(count))),
+    None => Err(vm.new_type_error(
+      "cannot fit 'int' into an index-sized integer".to_string(),
+    )),
+  }
+}
+
+fn bytes_decode(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (_, value) = method_args(vm, &args, "decode", 0, 2, false)?;
+  let encoding = optional_arg(&args, 1, "encoding");
+  let encoding = str_arg(vm, encoding, "utf-8")?;
+  let errors = optional_arg(&args, 2, "errors");
+  let errors = str_arg(vm, errors, "strict")?;
+  let text = decode(vm, &value, &encoding, &errors)?;
+  Ok(vm.new_str(text))
+}
+
+fn bytes_hex(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (_, value) = method_args(vm, &args, "hex", 0, 0, false)?;
+  let text: Vec<String> =
+    value.iter().map(|byte| format!("{:02x}", byte)).collect();
+  Ok(vm.new_str(text.concat()))
+}
+
+// Pairs of hex digits, which may be separated by whitespace:
+fn from_hex(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+) -> Result<Vec<u8>, PyObjectRef> {
+  // The method is called on the type, or on an instance of it:
+  if args.args.is_empty() || args.args.len() > 2 {
+    return Err(vm.new_type_error(format!(
+      "fromhex() takes exactly one argument ({} given)",
+      args.args.len()
+    )));
+  }
+  let arg = args.args.last().unwrap();
+  let text = match objstr::get_value(arg) {
+    Some(text) => text,
+    None => {
+      return Err(vm.new_type_error(format!(
+        "fromhex() argument must be str, not {}",
+        objtype::get_type_name(&arg.typ())
+      )))
+    }
+  };
+  let chars: Vec<char> = text.chars().collect();
+  let mut result = vec![];
+  let mut position = 0;
+  while position < chars.len() {
+    if chars[position].is_whitespace() {
+      position += 1;
+      continue;
+    }
+    let high = chars[position].to_digit(16);
+    let low = chars.get(position + 1).and_then(|c| c.to_digit(16));
+    let error_position = match (high, low) {
+      (Some(high), Some(low)) => {
+        result.push((high * 16 + low) as u8);
+        position += 2;
+        continue;
+      }
+      (None, _) => position,
+      (Some(_), None) => position + 1,
+    };
+    return Err(vm.new_value_error(format!(
+      "non-hexadecimal number found in fromhex() arg at position {}",
+      error_position
+    )));
+  }
+  Ok(result)
+}
+
+fn bytes_fromhex(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let value = from_hex(vm, &args)?;
+  Ok(vm.context().new_bytes(value))
+}
+
+fn bytearray_fromhex(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let value = from_hex(vm, &args)?;
+  Ok(vm.context().new_bytearray(value))
+}
+
+// The start and end positions of the optional start and end arguments:
+fn bounds_args(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  value: &Vec<u8>,
+) -> Result<(usize, usize), PyObjectRef> {
+  let start = match optional_arg(args, 2, "start") {
+    Some(start) => value.get_slice_pos(index_arg(vm, &start)?),
+    None => 0,
+  };
+  let end = match optional_arg(args, 3, "end") {
+    Some(end) => value.get_slice_pos(index_arg(vm, &end)?),
+    None => value.len(),
+  };
+  Ok((start, if end < start { start } else { end }))
+}
+
+fn find(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  reverse: bool,
+) -> Result<Option<usize>, PyObjectRef> {
+  let (_, value) = method_args(vm, &args, name, 1, 3, false)?;
+  let sub = sub_arg(vm, &args.args[1])?;
+  let (start, end) = bounds_args(vm, &args, &value)?;
+  Ok(
+    find_sub(&value[start..end], &sub, reverse)
+      .map(|position| start + position),
+  )
+}
+
+fn bytes_find(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let position = find(vm, args, "find", false)?;
+  Ok(match position {
+    Some(position) => vm.context().new_int(position),
+    None => vm.context().new_int(-1),
+  })
+}
+
+fn bytes_rfind(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let position = find(vm, args, "rfind", true)?;
+  Ok(match position {
+    Some(position) => vm.context().new_int(position),
+    None => vm.context().new_int(-1),
+  })
+}
+
+fn bytes_index(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  match find(vm, args, "index", false)? {
+    Some(position) => Ok(vm.context().new_int(position)),
+    None => Err(vm.new_value_error("subsection not found".to_string())),
+  }
+}
+
+fn bytes_count(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (_, value) = method_args(vm, &args, "count", 1, 3, false)?;
+  let sub = sub_arg(vm, &args.args[1])?;
+  let (start, end) = bounds_args(vm, &args, &value)?;
+  let mut rest = &value[start..end];
+  // The empty subsequence is found between each of the bytes:
+  if sub.is_empty() {
+    return Ok(vm.context().new_int(rest.len() + 1));
+  }
+  let mut count = 0;
+  while let Some(position) = find_sub(rest, &sub, false) {
+    count += 1;
+    rest = &rest[position + sub.len()..];
+  }
+  Ok(vm.context().new_int(count))
+}
+
+fn affix_matches(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  test: fn(&[u8], &[u8]) -> bool,
+) -> PyResult {
+  let (_, value) = method_args(vm, &args, name, 1, 3, false)?;
+  let affix = args.args[1].clone();
+  let affixes = match affix.borrow().kind {
+    PyObjectKind::Tuple { ref elements } => elements.clone(),
+    _ => vec![affix.clone()],
+  };
+  let (start, end) = bounds_args(vm, &args, &value)?;
+  for affix in affixes {
+    let affix = bytes_arg(vm, &affix)?;
+    if test(&value[start..end], &affix) {
+      return Ok(vm.new_bool(true));
+    }
+  }
+  Ok(vm.new_bool(false))
+}
+
+fn bytes_startswith(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  affix_matches(vm, args, "startswith", |value, affix| {
+    value.starts_with(affix)
+  })
+}
+
+fn bytes_endswith(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  affix_matches(vm, args, "endswith", |value, affix| value.ends_with(affix))
+}
+
+fn bytes_replace(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "replace", 2, 3, false)?;
+  let old = bytes_arg(vm, &args.args[1])?;
+  let new = bytes_arg(vm, &args.args[2])?;
+  let mut count = match optional_arg(&args, 3, "count") {
+    Some(count) => index_arg(vm, &count)?,
+    None => -1,
+  };
+  let mut result = vec![];
+  let mut rest = &value[..];
+  while count != 0 {
+    // The empty subsequence is found before each of the bytes, and at the
+    // end:
+    let position = if old.is_empty() {
+      Some(0)
+    } else {
+      find_sub(rest, &old, false)
+    };
+    let position = match position {
+      Some(position) => position,
+      None => break,
+    };
+    result.extend_from_slice(&rest[..position]);
+    result.extend_from_slice(&new);
+    rest = &rest[position + old.len()..];
+    count -= 1;
+    if old.is_empty() {
+      match rest.split_first() {
+        Some((first, tail)) => {
+          result.push(*first);
+          rest = tail;
+        }
+        None => {
+          break;
+        }
+      }
+    }
+  }
+  result.extend_from_slice(rest);
+  Ok(new_like(vm, &obj, result))
+}
+
+fn bytes_split(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "split", 0, 2, false)?;
+  let separator = match optional_arg(&args, 1, "sep") {
+    Some(separator) => {
+      let separator = bytes_arg(vm, &separator)?;
+      if separator.is_empty() {
+        return Err(vm.new_value_error("empty separator".to_string()));
+      }
+      Some(separator)
+    }
+    None => None,
+  };
+  let maxsplit = match optional_arg(&args, 2, "maxsplit") {
+    Some(maxsplit) => index_arg(vm, &maxsplit)?,
+    None => -1,
+  };
+  let mut parts = vec![];
+  let mut rest = &value[..];
+  match separator {
+    Some(separator) => {
+      while maxsplit < 0 || parts.len() < maxsplit as usize {
+        match find_sub(rest, &separator, false) {
+          Some(position) => {
+            parts.push(rest[..position].to_vec());
+            rest = &rest[position + separator.len()..];
+          }
+          None => break,
+        }
+      }
+      parts.push(rest.to_vec());
+    }
+    // Split on runs of whitespace, ignoring whitespace at the ends:
+    None => loop {
+      let start = rest.iter().position(|byte| !is_space(*byte));
+      rest = &rest[start.unwrap_or(rest.len())..];
+      if rest.is_empty() {
+        break;
+      }
+      if maxsplit >= 0 && parts.len() == maxsplit as usize {
+        let end = rest.iter().rposition(|byte| !is_space(*byte)).unwrap();
+        parts.push(rest[..end + 1].to_vec());
+        break;
+      }
+      let end = rest.iter().position(|byte| is_space(*byte));
+      let end = end.unwrap_or(rest.len());
+      parts.push(rest[..end].to_vec());
+      rest = &rest[end..];
+    },
+  }
+  let parts = parts
+    .into_iter()
+    .map(|part| new_like(vm, &obj, part))
+    .collect();
+  Ok(vm.context().new_list(parts))
+}
+
+fn bytes_join(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "join", 1, 1, false)?;
+  let mut result = vec![];
+  for (i, item) in objiter::get_all(vm, &args.args[1])?.iter().enumerate() {
+    let part = match get_value(item) {
+      Some(part) => part,
+      None => {
+        return Err(vm.new_type_error(format!(
+          "sequence item {}: expected a bytes-like object, {} found",
+          i,
+          objtype::get_type_name(&item.typ())
+        )))
+      }
+    };
+    if i > 0 {
+      result.extend_from_slice(&value);
+    }
+    result.extend(part);
+  }
+  Ok(new_like(vm, &obj, result))
+}
+
+fn bytes_strip(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "strip", 0, 1, false)?;
+  let chars = match optional_arg(&args, 1, "bytes") {
+    Some(chars) => Some(bytes_arg(vm, &chars)?),
+    None => None,
+  };
+  let is_stripped = |byte: &u8| match chars {
+    Some(ref chars) => chars.contains(byte),
+    None => is_space(*byte),
+  };
+  let start = value.iter().position(|byte| !is_stripped(byte));
+  let result = match start {
+    Some(start) => {
+      let end = value.iter().rposition(|byte| !is_stripped(byte)).unwrap();
+      value[start..end + 1].to_vec()
+    }
+    None => vec![],
+  };
+  Ok(new_like(vm, &obj, result))
+}
+
+fn bytes_lower(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "lower", 0, 0, false)?;
+  Ok(new_like(vm, &obj, value.to_ascii_lowercase()))
+}
+
+fn bytes_upper(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, value) = method_args(vm, &args, "upper", 0, 0, false)?;
+  Ok(new_like(vm, &obj, value.to_ascii_uppercase()))
+}
+
+fn bytearray_append(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "append", 1, 1, true)?;
+  value.push(byte_arg(vm, &args.args[1])?);
+  set_value(&obj, value);
+  Ok(vm.get_none())
+}
+
+fn bytearray_iadd(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "__iadd__", 1, 1, true)?;
+  match get_value(&args.args[1]) {
+    Some(other) => {
+      value.extend(other);
+      set_value(&obj, value);
+      Ok(obj)
+    }
+    None => Ok(vm.context().not_implemented.clone()),
+  }
+}
+
+fn bytearray_extend(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "extend", 1, 1, true)?;
+  value.extend(from_iterable(vm, &args.args[1])?);
+  set_value(&obj, value);
+  Ok(vm.get_none())
+}
+
+fn bytearray_pop(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, mut value) = method_args(vm, &args, "pop", 0, 1, true)?;
+  let index = match args.args.get(1) {
+    Some(index) => index_arg(vm, index)?,
+    None => -1,
+  };
+  if value.is_empty() {
+    let index_error = vm.context().exceptions.index_error.clone();
+    return Err(
+      vm.new_exception(index_error, "pop from empty bytearray".to_string()),
+    );
+  }
+  match value.get_pos(index) {
+    Some(position) => {
+      let byte = value.remove(position);
+      set_value(&obj, value);
+      Ok(vm.context().new_int(byte))
+    }
+    None => {
+      let index_error = vm.context().exceptions.index_error.clone();
+      Err(vm.new_exception(index_error, "pop index out of range".to_string()))
+    }
+  }
+}
+
+fn bytearray_clear(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (obj, _) = method_args(vm, &args, "clear", 0, 0, true)?;
+  set_value(&obj, vec![]);
+  Ok(vm.get_none())
+}
+
+fn create_bytes_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+  name: &str,
+) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), name, vec![object.clone()], dict).unwrap()
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  create_bytes_type(type_type, object, "bytes")
+}
+
+pub fn create_bytearray_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+) -> PyObjectRef {
+  create_bytes_type(type_type, object, "bytearray")
+}
+
+pub fn init(context: &PyContext) {
+  for typ in &[&context.bytes_type, &context.bytearray_type] {
+    typ.set_attr("__new__", context.new_rustfunc(bytes_new));
+    typ.set_attr("__len__", context.new_rustfunc(bytes_len));
+    typ.set_attr("__getitem__", context.new_rustfunc(bytes_getitem));
+    typ.set_attr("__contains__", context.new_rustfunc(bytes_contains));
+    typ.set_attr("__add__", context.new_rustfunc(bytes_add));
+    typ.set_attr("__mul__", context.new_rustfunc(bytes_mul));
+    typ.set_attr("__rmul__", context.new_rustfunc(bytes_mul));
+    typ.set_attr("count", context.new_rustfunc(bytes_count));
+    typ.set_attr("decode", context.new_rustfunc(bytes_decode));
+    typ.set_attr("endswith", context.new_rustfunc(bytes_endswith));
+    typ.set_attr("find", context.new_rustfunc(bytes_find));
+    typ.set_attr("hex", context.new_rustfunc(bytes_hex));
+    typ.set_attr("index", context.new_rustfunc(bytes_index));
+    typ.set_attr("join", context.new_rustfunc(bytes_join));
+    typ.set_attr("lower", context.new_rustfunc(bytes_lower));
+    typ.set_attr("replace", context.new_rustfunc(bytes_replace));
+    typ.set_attr("rfind", context.new_rustfunc(bytes_rfind));
+    typ.set_attr("split", context.new_rustfunc(bytes_split));
+    typ.set_attr("startswith", context.new_rustfunc(bytes_startswith));
+    typ.set_attr("strip", context.new_rustfunc(bytes_strip));
+    typ.set_attr("upper", context.new_rustfunc(bytes_upper));
+  }
+  let ref bytes_type = context.bytes_type;
+  bytes_type.set_attr("fromhex", context.new_rustfunc(bytes_fromhex));
+  let ref bytearray_type = context.bytearray_type;
+  bytearray_type.set_attr("fromhex", context.new_rustfunc(bytearray_fromhex));
+  bytearray_type
+    .set_attr("__setitem__", context.new_rustfunc(bytearray_setitem));
+  bytearray_type
+    .set_attr("__delitem__", context.new_rustfunc(bytearray_delitem));
+  bytearray_type.set_attr("__iadd__", context.new_rustfunc(bytearray_iadd));
+  bytearray_type.set_attr("append", context.new_rustfunc(bytearray_append));
+  bytearray_type.set_attr("clear", context.new_rustfunc(bytearray_clear));
+  bytearray_type.set_attr("extend", context.new_rustfunc(bytearray_extend));
+  bytearray_type.set_attr("pop", context.new_rustfunc(bytearray_pop));
+}
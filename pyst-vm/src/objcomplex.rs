@@ -0,0 +1,164 @@
+use super::objdict::DictElements;
+use super::objfloat;
+use super::objnumber;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+use num_complex::Complex64;
+use num_traits::{One, Zero};
+
+// The parts of a complex number are printed without a redundant ".0":
+fn part_to_string(value: f64) -> String {
+  let text = objfloat::to_string(value);
+  match text.len().checked_sub(2) {
+    Some(end) if text.ends_with(".0") => text[..end].to_string(),
+    _ => text,
+  }
+}
+
+pub fn to_string(value: Complex64) -> String {
+  if value.re == 0.0 && value.re.is_sign_positive() {
+    format!("{}j", part_to_string(value.im))
+  } else {
+    let sign = if value.im.is_sign_negative() && !value.im.is_nan() {
+      ""
+    } else {
+      "+"
+    };
+    format!(
+      "({}{}{}j)",
+      part_to_string(value.re),
+      sign,
+      part_to_string(value.im)
+    )
+  }
+}
+
+// Divide a by b the way CPython does, scaling by the larger part of the
+// divisor to avoid overflow. Returns None when b is zero.
+pub fn divide(a: Complex64, b: Complex64) -> Option<Complex64> {
+  if b.re.abs() >= b.im.abs() {
+    if b.re == 0.0 {
+      return None;
+    }
+    let ratio = b.im / b.re;
+    let denom = b.re + b.im * ratio;
+    Some(Complex64::new(
+      (a.re + a.im * ratio) / denom,
+      (a.im - a.re * ratio) / denom,
+    ))
+  } else if b.im.abs() >= b.re.abs() {
+    let ratio = b.re / b.im;
+    let denom = b.re * ratio + b.im;
+    Some(Complex64::new(
+      (a.re * ratio + a.im) / denom,
+      (a.im * ratio - a.re) / denom,
+    ))
+  } else {
+    // At least one part of the divisor is NaN:
+    Some(Complex64::new(f64::NAN, f64::NAN))
+  }
+}
+
+// Raise a to a non-negative integral power by repeated squaring:
+fn pow_uint(a: Complex64, n: u32) -> Complex64 {
+  let mut result = Complex64::one();
+  let mut power = a;
+  let mut mask = 1;
+  while mask > 0 && n >= mask {
+    if n & mask != 0 {
+      result = result * power;
+    }
+    mask <<= 1;
+    power = power * power;
+  }
+  result
+}
+
+// Raise a to the power b, or None when zero is raised to a negative or
+// complex power. Small integral exponents use repeated squaring, which
+// keeps results like (1+1j)**2 exact.
+pub fn pow(a: Complex64, b: Complex64) -> Option<Complex64> {
+  if b.im == 0.0 && b.re == b.re.trunc() && b.re.abs() <= 100.0 {
+    let exponent = b.re as i32;
+    if exponent < 0 {
+      return divide(Complex64::one(), pow_uint(a, exponent.unsigned_abs()));
+    }
+    return Some(pow_uint(a, exponent as u32));
+  }
+  if b.is_zero() {
+    Some(Complex64::one())
+  } else if a.is_zero() {
+    if b.im != 0.0 || b.re < 0.0 {
+      None
+    } else {
+      Some(Complex64::zero())
+    }
+  } else {
+    let magnitude = a.re.hypot(a.im);
+    let angle = a.im.atan2(a.re);
+    let mut length = magnitude.powf(b.re);
+    let mut phase = angle * b.re;
+    if b.im != 0.0 {
+      length /= (angle * b.im).exp();
+      phase += b.im * magnitude.ln();
+    }
+    Some(Complex64::new(length * phase.cos(), length * phase.sin()))
+  }
+}
+
+fn to_complex(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<Complex64, PyObjectRef> {
+  let number = objnumber::to_number(&obj.borrow().kind);
+  match number {
+    Some(number) => number.to_complex().ok_or_else(|| {
+      let overflow_error = vm.context().exceptions.overflow_error.clone();
+      vm.new_exception(
+        overflow_error,
+        "int too large to convert to float".to_string(),
+      )
+    }),
+    None => Err(vm.new_type_error(format!(
+      "complex() argument must be a number, not '{}'",
+      objtype::get_type_name(&obj.typ())
+    ))),
+  }
+}
+
+fn complex_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() > 3 {
+    return Err(
+      vm.new_type_error("complex() takes at most 2 arguments".to_string()),
+    );
+  }
+  let real = match args.args.get(1) {
+    Some(real) => to_complex(vm, real)?,
+    None => Complex64::zero(),
+  };
+  let imag = match args.args.get(2) {
+    Some(imag) => to_complex(vm, imag)?,
+    None => Complex64::zero(),
+  };
+  Ok(vm.context().new_complex(real + imag * Complex64::i()))
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "complex", vec![object.clone()], dict)
+    .unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref complex_type = context.complex_type;
+  complex_type.set_attr("__new__", context.new_rustfunc(complex_new));
+}
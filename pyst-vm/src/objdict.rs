@@ -1,30 +1,676 @@
+use super::objbool;
+use super::objint::IntValue;
+use super::objiter;
+use super::objnumber::{self, Number};
+use super::objtype;
 use super::pyobject::{
-  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
-  PyObjectRef, PyResult,
+  AttributeProtocol, IdProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
 };
 use super::vm::VirtualMachine;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive};
+use std::cell::{Ref, RefMut};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
-pub fn _set_item(
+// The key of a dict entry. Keys which are strings are kept as Rust strings,
+// so namespaces can be looked up without a VM at hand:
+#[derive(Clone, Debug)]
+pub enum DictKey {
+  String(String),
+  Object(PyObjectRef),
+}
+
+impl DictKey {
+  pub fn from_object(obj: &PyObjectRef) -> DictKey {
+    match obj.borrow().kind {
+      PyObjectKind::String { ref value } => DictKey::String(value.clone()),
+      _ => DictKey::Object(obj.clone()),
+    }
+  }
+
+  pub fn to_object(&self, ctx: &PyContext) -> PyObjectRef {
+    match self {
+      DictKey::String(value) => ctx.new_str(value.clone()),
+      DictKey::Object(obj) => obj.clone(),
+    }
+  }
+}
+
+impl fmt::Display for DictKey {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DictKey::String(value) => write!(f, "{}", value),
+      DictKey::Object(obj) => write!(f, "{}", obj.borrow().str()),
+    }
+  }
+}
+
+// The entries of a dict, in insertion order. Entries are found through their
+// hash, and keys with equal hashes are told apart by comparing them. A
+// removed entry leaves a hole, so that the positions of the others stay the
+// same, and the holes are squeezed out once they make up half the entries:
+#[derive(Clone, Debug, Default)]
+pub struct DictElements {
+  entries: Vec<Option<(i64, DictKey, PyObjectRef)>>,
+  positions: HashMap<i64, Vec<usize>>,
+  len: usize,
+}
+
+impl DictElements {
+  pub fn new() -> DictElements {
+    Default::default()
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn iter<'a>(
+    &'a self,
+  ) -> impl Iterator<Item = (&'a DictKey, &'a PyObjectRef)> + 'a {
+    self.live_entries().map(|(_, key, value)| (key, value))
+  }
+
+  // The first entry at or after position, along with the position after it,
+  // for iterators which step through the entries:
+  pub fn next_entry(
+    &self,
+    position: usize,
+  ) -> Option<(&DictKey, &PyObjectRef, usize)> {
+    self
+      .entries
+      .iter()
+      .enumerate()
+      .skip(position)
+      .find_map(|(pos, entry)| match entry {
+        Some((_, key, value)) => Some((key, value, pos + 1)),
+        None => None,
+      })
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.find_string(key).is_some()
+  }
+
+  pub fn get(&self, key: &str) -> Option<&PyObjectRef> {
+    self.find_string(key).map(|pos| &self.entry(pos).2)
+  }
+
+  pub fn insert(&mut self, key: String, value: PyObjectRef) {
+    let hash = hash_string(&key);
+    match self.find_string(&key) {
+      Some(pos) => self.entry_mut(pos).2 = value,
+      None => self.push(hash, DictKey::String(key), value),
+    }
+  }
+
+  pub fn remove(&mut self, key: &str) -> Option<PyObjectRef> {
+    self.find_string(key).map(|pos| self.remove_at(pos))
+  }
+
+  // The key of the last entry, which is the cheapest one to remove:
+  pub fn last_key(&self) -> Option<&DictKey> {
+    self.live_entries().next_back().map(|(_, key, _)| key)
+  }
+
+  fn live_entries<'a>(
+    &'a self,
+  ) -> impl DoubleEndedIterator<Item = &'a (i64, DictKey, PyObjectRef)> + 'a {
+    self.entries.iter().filter_map(|entry| entry.as_ref())
+  }
+
+  fn entry(&self, pos: usize) -> &(i64, DictKey, PyObjectRef) {
+    self.entries[pos].as_ref().unwrap()
+  }
+
+  fn entry_mut(&mut self, pos: usize) -> &mut (i64, DictKey, PyObjectRef) {
+    self.entries[pos].as_mut().unwrap()
+  }
+
+  fn has_key_at(&self, pos: usize, key: &DictKey) -> bool {
+    match (self.entries.get(pos), key) {
+      (Some(Some((_, DictKey::String(ref a), _))), DictKey::String(ref b)) => {
+        a == b
+      }
+      (Some(Some((_, DictKey::Object(ref a), _))), DictKey::Object(ref b)) => {
+        a.get_id() == b.get_id()
+      }
+      _ => false,
+    }
+  }
+
+  fn find_string(&self, key: &str) -> Option<usize> {
+    self
+      .candidates(hash_string(key))
+      .iter()
+      .cloned()
+      .find(|pos| match self.entry(*pos).1 {
+        DictKey::String(ref value) => value == key,
+        DictKey::Object(_) => false,
+      })
+  }
+
+  fn candidates(&self, hash: i64) -> &[usize] {
+    match self.positions.get(&hash) {
+      Some(positions) => positions,
+      None => &[],
+    }
+  }
+
+  fn push(&mut self, hash: i64, key: DictKey, value: PyObjectRef) {
+    self
+      .positions
+      .entry(hash)
+      .or_insert_with(Vec::new)
+      .push(self.entries.len());
+    self.entries.push(Some((hash, key, value)));
+    self.len += 1;
+  }
+
+  fn remove_at(&mut self, pos: usize) -> PyObjectRef {
+    let (hash, _, value) = self.entries[pos].take().unwrap();
+    self.len -= 1;
+    let no_candidates_left = {
+      let candidates = self.positions.get_mut(&hash).unwrap();
+      candidates.retain(|candidate| *candidate != pos);
+      candidates.is_empty()
+    };
+    if no_candidates_left {
+      self.positions.remove(&hash);
+    }
+    // Holes at the end need no squeezing out:
+    while let Some(None) = self.entries.last() {
+      self.entries.pop();
+    }
+    if self.len * 2 < self.entries.len() {
+      self.compact();
+    }
+    value
+  }
+
+  fn compact(&mut self) {
+    self.entries.retain(|entry| entry.is_some());
+    self.positions.clear();
+    for (pos, entry) in self.entries.iter().enumerate() {
+      let hash = entry.as_ref().unwrap().0;
+      self
+        .positions
+        .entry(hash)
+        .or_insert_with(Vec::new)
+        .push(pos);
+    }
+  }
+}
+
+fn hash_string(value: &str) -> i64 {
+  let mut hasher = DefaultHasher::new();
+  value.hash(&mut hasher);
+  hasher.finish() as i64
+}
+
+fn hash_bytes(value: &[u8]) -> i64 {
+  let mut hasher = DefaultHasher::new();
+  value.hash(&mut hasher);
+  hasher.finish() as i64
+}
+
+// Integers are hashed modulo the Mersenne prime 2**61 - 1, as CPython does,
+// so that numbers which compare equal have equal hashes. -1 is reserved:
+fn hash_integer(value: &IntValue) -> i64 {
+  let modulus = (1i64 << 61) - 1;
+  let hash = match value {
+    IntValue::Small(value) => (value.unsigned_abs() % modulus as u64) as i64,
+    IntValue::Big(value) => {
+      (value.abs() % BigInt::from(modulus)).to_i64().unwrap()
+    }
+  };
+  let hash = if value.is_negative() { -hash } else { hash };
+  if hash == -1 {
+    -2
+  } else {
+    hash
+  }
+}
+
+fn hash_float(value: f64) -> i64 {
+  if value.is_finite() && value.fract() == 0.0 {
+    hash_integer(&IntValue::from(BigInt::from_f64(value).unwrap()))
+  } else {
+    value.to_bits() as i64
+  }
+}
+
+fn hash_number(number: &Number) -> i64 {
+  match number {
+    Number::Integer(value) => hash_integer(value),
+    Number::Float(value) => hash_float(*value),
+    Number::Complex(value) => hash_float(value.re)
+      .wrapping_add(hash_float(value.im).wrapping_mul(1_000_003)),
+  }
+}
+
+// The hash of a Python object, as hash(obj) computes it:
+pub fn hash(
   vm: &mut VirtualMachine,
-  _d: PyObjectRef,
-  _idx: PyObjectRef,
-  _obj: PyObjectRef,
-) -> PyResult {
-  Ok(vm.get_none())
+  obj: &PyObjectRef,
+) -> Result<i64, PyObjectRef> {
+  if let Some(method) = objtype::get_special_method(obj, "__hash__") {
+    if let PyObjectKind::PyNone = method.borrow().kind {
+      let type_name = objtype::get_type_name(&obj.typ());
+      return Err(
+        vm.new_type_error(format!("unhashable type: '{}'", type_name)),
+      );
+    }
+    let args = PyFuncArgs::new(vec![obj.clone()], vec![]);
+    let result = vm.invoke(method, args)?;
+    return match result.borrow().kind {
+      PyObjectKind::Integer { ref value } => Ok(hash_integer(value)),
+      _ => Err(vm.new_type_error(
+        "__hash__ method should return an integer".to_string(),
+      )),
+    };
+  }
+  let elements = match obj.borrow().kind {
+    PyObjectKind::String { ref value } => return Ok(hash_string(value)),
+    PyObjectKind::Bytes { ref value } => return Ok(hash_bytes(value)),
+    PyObjectKind::Tuple { ref elements } => elements.clone(),
+    // The hash of a frozenset does not depend on the order of its elements:
+    PyObjectKind::FrozenSet { ref elements } => {
+      return Ok(elements.live_entries().fold(0x1dcf_23a6, |result, entry| {
+        let hash = entry.0;
+        result ^ (hash ^ (hash << 16) ^ 89_869_747).wrapping_mul(3_644_798_167)
+      }));
+    }
+    PyObjectKind::List { .. }
+    | PyObjectKind::ByteArray { .. }
+    | PyObjectKind::Dict { .. }
+    | PyObjectKind::Set { .. } => {
+      let type_name = objtype::get_type_name(&obj.typ());
+      return Err(
+        vm.new_type_error(format!("unhashable type: '{}'", type_name)),
+      );
+    }
+    ref kind => {
+      return Ok(match objnumber::to_number(kind) {
+        Some(number) => hash_number(&number),
+        // Other objects are only equal to themselves:
+        None => obj.get_id() as i64,
+      });
+    }
+  };
+  let mut result: i64 = 0x345678;
+  for element in elements {
+    result = (result ^ hash(vm, &element)?).wrapping_mul(1_000_003);
+  }
+  Ok(result)
+}
+
+// Sets keep their elements in the entries of a dict as well, mapping each
+// element to itself:
+pub fn get_elements(obj: &PyObjectRef) -> Ref<'_, DictElements> {
+  Ref::map(obj.borrow(), |obj| match obj.kind {
+    PyObjectKind::Dict { ref elements }
+    | PyObjectKind::Set { ref elements }
+    | PyObjectKind::FrozenSet { ref elements } => elements,
+    _ => panic!("Cannot extract dict elements from non-dict"),
+  })
+}
+
+pub fn get_mut_elements(obj: &PyObjectRef) -> RefMut<'_, DictElements> {
+  RefMut::map(obj.borrow_mut(), |obj| match obj.kind {
+    PyObjectKind::Dict { ref mut elements }
+    | PyObjectKind::Set { ref mut elements }
+    | PyObjectKind::FrozenSet { ref mut elements } => elements,
+    _ => panic!("Cannot extract dict elements from non-dict"),
+  })
+}
+
+// Find the position of the entry for key, along with the hash of key.
+// Comparing keys may run Python code, so the dict is not kept borrowed:
+fn lookup(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  key: &PyObjectRef,
+) -> Result<(i64, Option<usize>), PyObjectRef> {
+  let hash = hash(vm, key)?;
+  if let PyObjectKind::String { ref value } = key.borrow().kind {
+    return Ok((hash, get_elements(dict).find_string(value)));
+  }
+  'lookup: loop {
+    let candidates = {
+      let elements = get_elements(dict);
+      elements
+        .candidates(hash)
+        .iter()
+        .map(|pos| (*pos, elements.entry(*pos).1.clone()))
+        .collect::<Vec<_>>()
+    };
+    for (pos, candidate) in candidates {
+      let candidate_obj = match candidate {
+        DictKey::Object(ref obj) if obj.get_id() == key.get_id() => {
+          return Ok((hash, Some(pos)))
+        }
+        ref candidate => candidate.to_object(vm.context()),
+      };
+      let equal = vm._eq(candidate_obj, key.clone())?;
+      let equal = objbool::boolval(vm, equal)?;
+      // The comparison may have changed the dict, so the positions found
+      // before it can no longer be trusted:
+      if !get_elements(dict).has_key_at(pos, &candidate) {
+        continue 'lookup;
+      }
+      if equal {
+        return Ok((hash, Some(pos)));
+      }
+    }
+    return Ok((hash, None));
+  }
+}
+
+pub fn get_item(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  key: &PyObjectRef,
+) -> Result<Option<PyObjectRef>, PyObjectRef> {
+  let (_, pos) = lookup(vm, dict, key)?;
+  Ok(pos.map(|pos| get_elements(dict).entry(pos).2.clone()))
+}
+
+pub fn contains_key(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  key: &PyObjectRef,
+) -> Result<bool, PyObjectRef> {
+  let (_, pos) = lookup(vm, dict, key)?;
+  Ok(pos.is_some())
+}
+
+pub fn set_item(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  key: &PyObjectRef,
+  value: PyObjectRef,
+) -> Result<(), PyObjectRef> {
+  let (hash, pos) = lookup(vm, dict, key)?;
+  let mut elements = get_mut_elements(dict);
+  match pos {
+    Some(pos) => elements.entry_mut(pos).2 = value,
+    None => elements.push(hash, DictKey::from_object(key), value),
+  }
+  Ok(())
+}
+
+// Remove the entry for key, returning its value if there was one:
+pub fn del_item(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  key: &PyObjectRef,
+) -> Result<Option<PyObjectRef>, PyObjectRef> {
+  let (_, pos) = lookup(vm, dict, key)?;
+  Ok(pos.map(|pos| get_mut_elements(dict).remove_at(pos)))
+}
+
+pub fn get_keys(vm: &VirtualMachine, dict: &PyObjectRef) -> Vec<PyObjectRef> {
+  get_elements(dict)
+    .iter()
+    .map(|(key, _)| key.to_object(vm.context()))
+    .collect()
+}
+
+pub fn get_key_value_pairs(
+  vm: &VirtualMachine,
+  dict: &PyObjectRef,
+) -> Vec<(PyObjectRef, PyObjectRef)> {
+  get_elements(dict)
+    .iter()
+    .map(|(key, value)| (key.to_object(vm.context()), value.clone()))
+    .collect()
+}
+
+// Dicts are equal when they map the same keys to equal values:
+pub fn equal(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> Result<bool, PyObjectRef> {
+  if get_elements(a).len() != get_elements(b).len() {
+    return Ok(false);
+  }
+  for (key, value) in get_key_value_pairs(vm, a) {
+    match get_item(vm, b, &key)? {
+      Some(other) => {
+        if !vm.identical_or_equal(&value, &other)? {
+          return Ok(false);
+        }
+      }
+      None => return Ok(false),
+    }
+  }
+  Ok(true)
+}
+
+// The missing key itself is the argument of the KeyError:
+pub fn new_key_error(vm: &mut VirtualMachine, key: PyObjectRef) -> PyObjectRef {
+  let key_error = vm.context().exceptions.key_error.clone();
+  let args = PyFuncArgs::new(vec![key], vec![]);
+  match vm.invoke(key_error, args) {
+    Ok(exception) => exception,
+    Err(err) => err,
+  }
 }
 
 pub fn new(dict_type: PyObjectRef) -> PyObjectRef {
   PyObject::new(
     PyObjectKind::Dict {
-      elements: HashMap::new(),
+      elements: DictElements::new(),
     },
     dict_type.clone(),
   )
 }
 
-fn dict_new(_vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
-  Ok(new(args.args[0].clone()))
+fn is_dict(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::Dict { .. } => true,
+    _ => false,
+  }
+}
+
+// Check the number of arguments a method gets besides self:
+pub fn check_arg_count(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  min: usize,
+  max: usize,
+) -> Result<(), PyObjectRef> {
+  let count = args.args.len() - 1;
+  if count < min || count > max {
+    let expected = if min == max {
+      format!("exactly {}", min)
+    } else if count < min {
+      format!("at least {}", min)
+    } else {
+      format!("at most {}", max)
+    };
+    let plural = if expected.ends_with(" 1") { "" } else { "s" };
+    Err(vm.new_type_error(format!(
+      "{} expected {} argument{}, got {}",
+      name, expected, plural, count
+    )))
+  } else {
+    Ok(())
+  }
+}
+
+// Check the arguments of a dict method, and take out the dict itself:
+fn method_args(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  min: usize,
+  max: usize,
+) -> Result<PyObjectRef, PyObjectRef> {
+  match args.args.get(0) {
+    Some(dict) if is_dict(dict) => {
+      check_arg_count(vm, args, name, min, max)?;
+      Ok(dict.clone())
+    }
+    _ => Err(vm.new_type_error(format!(
+      "descriptor '{}' requires a 'dict' object",
+      name
+    ))),
+  }
+}
+
+// Add the items of a mapping, or of an iterable of pairs, and the keyword
+// arguments, as dict.update does:
+fn update(
+  vm: &mut VirtualMachine,
+  dict: &PyObjectRef,
+  other: Option<PyObjectRef>,
+  kwargs: Vec<(String, PyObjectRef)>,
+) -> Result<(), PyObjectRef> {
+  if let Some(other) = other {
+    let pairs = if is_dict(&other) {
+      get_key_value_pairs(vm, &other)
+    } else {
+      let mut pairs = vec![];
+      for (index, item) in objiter::get_all(vm, &other)?.iter().enumerate() {
+        let item = objiter::get_all(vm, item)?;
+        if item.len() != 2 {
+          return Err(vm.new_value_error(format!(
+            "dictionary update sequence element #{} has length {}; 2 is required",
+            index,
+            item.len()
+          )));
+        }
+        pairs.push((item[0].clone(), item[1].clone()));
+      }
+      pairs
+    };
+    for (key, value) in pairs {
+      set_item(vm, dict, &key, value)?;
+    }
+  }
+  for (name, value) in kwargs {
+    get_mut_elements(dict).insert(name, value);
+  }
+  Ok(())
+}
+
+fn dict_new(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let dict = new(args.shift());
+  if args.args.len() > 1 {
+    return Err(vm.new_type_error(format!(
+      "dict expected at most 1 arguments, got {}",
+      args.args.len()
+    )));
+  }
+  update(vm, &dict, args.args.pop(), args.kwargs)?;
+  Ok(dict)
+}
+
+fn dict_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "__len__", 0, 0)?;
+  let len = get_elements(&dict).len();
+  Ok(vm.context().new_int(len))
+}
+
+fn dict_contains(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "__contains__", 1, 1)?;
+  let result = contains_key(vm, &dict, &args.args[1])?;
+  Ok(vm.new_bool(result))
+}
+
+fn dict_getitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "__getitem__", 1, 1)?;
+  match get_item(vm, &dict, &args.args[1])? {
+    Some(value) => Ok(value),
+    None => Err(new_key_error(vm, args.args[1].clone())),
+  }
+}
+
+fn dict_setitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "__setitem__", 2, 2)?;
+  set_item(vm, &dict, &args.args[1], args.args[2].clone())?;
+  Ok(vm.get_none())
+}
+
+fn dict_delitem(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "__delitem__", 1, 1)?;
+  match del_item(vm, &dict, &args.args[1])? {
+    Some(_) => Ok(vm.get_none()),
+    None => Err(new_key_error(vm, args.args[1].clone())),
+  }
+}
+
+fn dict_get(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "get", 1, 2)?;
+  match get_item(vm, &dict, &args.args[1])? {
+    Some(value) => Ok(value),
+    None => Ok(args.args.get(2).cloned().unwrap_or_else(|| vm.get_none())),
+  }
+}
+
+// keys, values and items return lists rather than views:
+fn dict_keys(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "keys", 0, 0)?;
+  let keys = get_keys(vm, &dict);
+  Ok(vm.context().new_list(keys))
+}
+
+fn dict_values(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "values", 0, 0)?;
+  let values = get_elements(&dict)
+    .iter()
+    .map(|(_, value)| value.clone())
+    .collect();
+  Ok(vm.context().new_list(values))
+}
+
+fn dict_items(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "items", 0, 0)?;
+  let items = get_key_value_pairs(vm, &dict)
+    .into_iter()
+    .map(|(key, value)| vm.context().new_tuple(vec![key, value]))
+    .collect();
+  Ok(vm.context().new_list(items))
+}
+
+fn dict_pop(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "pop", 1, 2)?;
+  match del_item(vm, &dict, &args.args[1])? {
+    Some(value) => Ok(value),
+    None => match args.args.get(2) {
+      Some(default) => Ok(default.clone()),
+      None => Err(new_key_error(vm, args.args[1].clone())),
+    },
+  }
+}
+
+fn dict_setdefault(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "setdefault", 1, 2)?;
+  if let Some(value) = get_item(vm, &dict, &args.args[1])? {
+    return Ok(value);
+  }
+  let default = args.args.get(2).cloned().unwrap_or_else(|| vm.get_none());
+  set_item(vm, &dict, &args.args[1], default.clone())?;
+  Ok(default)
+}
+
+fn dict_update(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let dict = method_args(vm, &args, "update", 0, 1)?;
+  let other = args.args.get(1).cloned();
+  update(vm, &dict, other, args.kwargs.split_off(0))?;
+  Ok(vm.get_none())
 }
 
 pub fn create_type(
@@ -43,4 +689,16 @@ pub fn create_type(
 pub fn init(context: &PyContext) {
   let ref dict_type = context.dict_type;
   dict_type.set_attr("__new__", context.new_rustfunc(dict_new));
+  dict_type.set_attr("__len__", context.new_rustfunc(dict_len));
+  dict_type.set_attr("__contains__", context.new_rustfunc(dict_contains));
+  dict_type.set_attr("__getitem__", context.new_rustfunc(dict_getitem));
+  dict_type.set_attr("__setitem__", context.new_rustfunc(dict_setitem));
+  dict_type.set_attr("__delitem__", context.new_rustfunc(dict_delitem));
+  dict_type.set_attr("get", context.new_rustfunc(dict_get));
+  dict_type.set_attr("keys", context.new_rustfunc(dict_keys));
+  dict_type.set_attr("values", context.new_rustfunc(dict_values));
+  dict_type.set_attr("items", context.new_rustfunc(dict_items));
+  dict_type.set_attr("pop", context.new_rustfunc(dict_pop));
+  dict_type.set_attr("setdefault", context.new_rustfunc(dict_setdefault));
+  dict_type.set_attr("update", context.new_rustfunc(dict_update));
 }
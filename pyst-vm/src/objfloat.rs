@@ -0,0 +1,135 @@
+use super::objdict::DictElements;
+use super::objnumber::{self, Number};
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+use std::str::FromStr;
+
+// Format a float the way Python's repr does: the shortest digits which
+// round trip, switching to exponent notation for very large or small values.
+pub fn to_string(value: f64) -> String {
+  if value.is_nan() {
+    return String::from("nan");
+  }
+  if value.is_infinite() {
+    return String::from(if value > 0.0 { "inf" } else { "-inf" });
+  }
+  let scientific = format!("{:e}", value);
+  let mut parts = scientific.splitn(2, 'e');
+  let mantissa = parts.next().unwrap();
+  let exponent = i32::from_str(parts.next().unwrap()).unwrap();
+  if exponent < -4 || exponent >= 16 {
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+  } else {
+    let positional = format!("{}", value);
+    if positional.contains('.') {
+      positional
+    } else {
+      format!("{}.0", positional)
+    }
+  }
+}
+
+// Floor division and modulo of floats, with the sign of the result following
+// the divisor as in Python. The divisor must not be zero.
+pub fn divmod(a: f64, b: f64) -> (f64, f64) {
+  let mut modulo = a % b;
+  let mut div = (a - modulo) / b;
+  if modulo != 0.0 {
+    if (b < 0.0) != (modulo < 0.0) {
+      modulo += b;
+      div -= 1.0;
+    }
+  } else {
+    modulo = 0.0_f64.copysign(b);
+  }
+  let floordiv = if div != 0.0 {
+    let floordiv = div.floor();
+    if div - floordiv > 0.5 {
+      floordiv + 1.0
+    } else {
+      floordiv
+    }
+  } else {
+    0.0_f64.copysign(a / b)
+  };
+  (floordiv, modulo)
+}
+
+fn float_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  if args.args.len() > 2 {
+    return Err(
+      vm.new_type_error("float expected at most 1 argument".to_string()),
+    );
+  }
+  if args.args.len() == 1 {
+    return Ok(vm.context().new_float(0.0));
+  }
+  let arg = args.args[1].clone();
+  let value = match arg.borrow().kind {
+    PyObjectKind::String { ref value } => {
+      match f64::from_str(value.trim()).ok() {
+        Some(value) => value,
+        None => {
+          return Err(vm.new_value_error(format!(
+            "could not convert string to float: '{}'",
+            value
+          )))
+        }
+      }
+    }
+    ref kind => match objnumber::to_number(kind) {
+      Some(Number::Complex(..)) | None => {
+        return Err(vm.new_type_error(format!(
+          "float() argument must be a string or a number, not '{}'",
+          objtype::get_type_name(&arg.typ())
+        )))
+      }
+      Some(number) => match number.to_float() {
+        Some(value) => value,
+        None => {
+          let overflow_error = vm.context().exceptions.overflow_error.clone();
+          return Err(vm.new_exception(
+            overflow_error,
+            "int too large to convert to float".to_string(),
+          ));
+        }
+      },
+    },
+  };
+  Ok(vm.context().new_float(value))
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "float", vec![object.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref float_type = context.float_type;
+  float_type.set_attr("__new__", context.new_rustfunc(float_new));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::to_string;
+
+  #[test]
+  fn test_to_string_matches_python_repr() {
+    assert_eq!(to_string(1.0), "1.0");
+    assert_eq!(to_string(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(to_string(1e16), "1e+16");
+    assert_eq!(to_string(0.0001), "0.0001");
+    assert_eq!(to_string(0.00001), "1e-05");
+    assert_eq!(to_string(-2.5e-300), "-2.5e-300");
+  }
+}
@@ -1,12 +1,12 @@
+use super::objdict::DictElements;
 use super::objtype;
 use super::pyobject::{
   AttributeProtocol, PyFuncArgs, PyObject, PyObjectKind, PyObjectRef, PyResult,
 };
 use super::vm::VirtualMachine;
-use std::collections::HashMap;
 
 pub fn create_type(type_type: PyObjectRef) -> PyObjectRef {
-  let mut dict = HashMap::new();
+  let mut dict = DictElements::new();
   dict.insert(
     "__get__".to_string(),
     PyObject::new(
@@ -31,7 +31,7 @@ pub fn create_type(type_type: PyObjectRef) -> PyObjectRef {
 }
 
 pub fn create_bound_method_type(type_type: PyObjectRef) -> PyObjectRef {
-  let dict = HashMap::new();
+  let dict = DictElements::new();
   let typ = PyObject::new(
     PyObjectKind::Class {
       name: "method".to_string(),
@@ -54,7 +54,7 @@ pub fn create_member_descriptor_type(
   type_type: PyObjectRef,
   object: PyObjectRef,
 ) -> PyObjectRef {
-  let mut dict = HashMap::new();
+  let mut dict = DictElements::new();
 
   dict.insert(
     String::from("__get__"),
@@ -0,0 +1,338 @@
+/*
+ * Generators hold the frame of a generator function call, which runs up to
+ * its next yield each time the generator is resumed.
+ */
+
+use super::bytecode;
+use super::frame::{ExecutionResult, Frame};
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+
+pub fn new_generator(vm: &VirtualMachine, frame: Frame) -> PyObjectRef {
+  PyObject::new(
+    PyObjectKind::Generator {
+      name: frame.code.obj_name.clone(),
+      frame: Some(frame),
+      running: false,
+    },
+    vm.context().generator_type.clone(),
+  )
+}
+
+pub fn is_generator(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::Generator { .. } => true,
+    _ => false,
+  }
+}
+
+pub fn new_stop_iteration(
+  vm: &mut VirtualMachine,
+  value: PyObjectRef,
+) -> PyObjectRef {
+  let stop_iteration = vm.context().exceptions.stop_iteration.clone();
+  let args = match value.borrow().kind {
+    PyObjectKind::PyNone => vec![],
+    _ => vec![value.clone()],
+  };
+  let exc = vm
+    .invoke(stop_iteration, PyFuncArgs::new(args, vec![]))
+    .unwrap();
+  exc.set_attr("value", value);
+  exc
+}
+
+// The value a StopIteration carries, like the return value of a generator:
+pub fn stop_iteration_value(
+  vm: &mut VirtualMachine,
+  exc: &PyObjectRef,
+) -> PyObjectRef {
+  let value_name = String::from("value");
+  let args_name = String::from("args");
+  if exc.has_attr(&value_name) {
+    exc.get_attr(&value_name)
+  } else if exc.has_attr(&args_name) {
+    match exc.get_attr(&args_name).borrow().kind {
+      PyObjectKind::Tuple { ref elements } if !elements.is_empty() => {
+        elements[0].clone()
+      }
+      _ => vm.get_none(),
+    }
+  } else {
+    vm.get_none()
+  }
+}
+
+pub fn is_stop_iteration(vm: &VirtualMachine, exc: &PyObjectRef) -> bool {
+  objtype::isinstance(exc, &vm.context().exceptions.stop_iteration)
+}
+
+// Take the frame out of a generator to run it. None means the generator has
+// finished:
+fn take_frame(
+  vm: &mut VirtualMachine,
+  gen: &PyObjectRef,
+) -> Result<Option<Frame>, PyObjectRef> {
+  let frame = match gen.borrow_mut().kind {
+    PyObjectKind::Generator {
+      ref mut frame,
+      ref mut running,
+      ..
+    } => {
+      if *running {
+        None
+      } else {
+        let frame = frame.take();
+        *running = frame.is_some();
+        Some(frame)
+      }
+    }
+    _ => panic!("Expected a generator"),
+  };
+  frame.ok_or_else(|| {
+    vm.new_value_error("generator already executing".to_string())
+  })
+}
+
+fn put_frame(gen: &PyObjectRef, new_frame: Option<Frame>) {
+  if let PyObjectKind::Generator {
+    ref mut frame,
+    ref mut running,
+    ..
+  } = gen.borrow_mut().kind
+  {
+    *frame = new_frame;
+    *running = false;
+  }
+}
+
+// Run the frame of a generator up to its next yield, starting by raising the
+// given exception inside it, if any. Returning from the frame finishes the
+// generator with a StopIteration:
+fn resume(
+  vm: &mut VirtualMachine,
+  gen: &PyObjectRef,
+  frame: Frame,
+  exception: Option<PyObjectRef>,
+) -> PyResult {
+  let (frame, result) = vm.resume_frame(frame, exception);
+  match result {
+    Ok(ExecutionResult::Yield(value)) => {
+      put_frame(gen, Some(frame));
+      Ok(value)
+    }
+    Ok(ExecutionResult::Return(value)) => {
+      put_frame(gen, None);
+      Err(new_stop_iteration(vm, value))
+    }
+    Err(exc) => {
+      put_frame(gen, None);
+      if is_stop_iteration(vm, &exc) {
+        let runtime_error = vm.context().exceptions.runtime_error.clone();
+        Err(vm.new_exception(
+          runtime_error,
+          "generator raised StopIteration".to_string(),
+        ))
+      } else {
+        Err(exc)
+      }
+    }
+  }
+}
+
+pub fn send(
+  vm: &mut VirtualMachine,
+  gen: &PyObjectRef,
+  value: PyObjectRef,
+) -> PyResult {
+  let mut frame = match take_frame(vm, gen)? {
+    Some(frame) => frame,
+    None => {
+      let none = vm.get_none();
+      return Err(new_stop_iteration(vm, none));
+    }
+  };
+  if frame.lasti == 0 {
+    if let PyObjectKind::PyNone = value.borrow().kind {
+    } else {
+      put_frame(gen, Some(frame));
+      return Err(vm.new_type_error(
+        "can't send non-None value to a just-started generator".to_string(),
+      ));
+    }
+  } else {
+    // The value becomes the result of the yield expression:
+    frame.push_value(value);
+  }
+  resume(vm, gen, frame, None)
+}
+
+pub fn throw(
+  vm: &mut VirtualMachine,
+  gen: &PyObjectRef,
+  exc: PyObjectRef,
+) -> PyResult {
+  let mut frame = match take_frame(vm, gen)? {
+    Some(frame) => frame,
+    None => return Err(exc),
+  };
+  if frame.lasti == 0 {
+    // A generator which did not start yet finishes right away:
+    put_frame(gen, None);
+    return Err(exc);
+  }
+
+  // A frame suspended in a yield from resumes at the YieldFrom, with the
+  // delegated iterator on top of its stack. The exception is thrown into the
+  // iterator when it is a generator:
+  let is_delegating = match frame.code.instructions.get(frame.lasti) {
+    Some(bytecode::Instruction::YieldFrom) => true,
+    _ => false,
+  };
+  if !is_delegating {
+    return resume(vm, gen, frame, Some(exc));
+  }
+  let iterator = frame.pop_value();
+  frame.lasti += 1;
+  if !is_generator(&iterator) {
+    return resume(vm, gen, frame, Some(exc));
+  }
+  match throw(vm, &iterator, exc) {
+    Ok(value) => {
+      frame.lasti -= 1;
+      frame.push_value(iterator);
+      put_frame(gen, Some(frame));
+      Ok(value)
+    }
+    Err(exc) => {
+      if is_stop_iteration(vm, &exc) {
+        let value = stop_iteration_value(vm, &exc);
+        frame.push_value(value);
+        resume(vm, gen, frame, None)
+      } else {
+        resume(vm, gen, frame, Some(exc))
+      }
+    }
+  }
+}
+
+// Finish a generator by raising GeneratorExit inside it:
+pub fn close(
+  vm: &mut VirtualMachine,
+  gen: &PyObjectRef,
+) -> Result<(), PyObjectRef> {
+  let generator_exit = vm.context().exceptions.generator_exit.clone();
+  let exc = vm.invoke(generator_exit.clone(), PyFuncArgs::default())?;
+  match throw(vm, gen, exc) {
+    Ok(_) => {
+      let runtime_error = vm.context().exceptions.runtime_error.clone();
+      Err(vm.new_exception(
+        runtime_error,
+        "generator ignored GeneratorExit".to_string(),
+      ))
+    }
+    Err(exc) => {
+      if objtype::isinstance(&exc, &generator_exit)
+        || is_stop_iteration(vm, &exc)
+      {
+        Ok(())
+      } else {
+        Err(exc)
+      }
+    }
+  }
+}
+
+fn check_generator(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  arg_count: usize,
+) -> Result<PyObjectRef, PyObjectRef> {
+  if args.args.is_empty() || !is_generator(&args.args[0]) {
+    return Err(vm.new_type_error(format!(
+      "descriptor '{}' requires a 'generator' object",
+      name
+    )));
+  }
+  if args.args.len() != arg_count + 1 {
+    return Err(vm.new_type_error(format!(
+      "{}() takes exactly {} argument{} ({} given)",
+      name,
+      arg_count,
+      if arg_count == 1 { "" } else { "s" },
+      args.args.len() - 1
+    )));
+  }
+  Ok(args.args[0].clone())
+}
+
+fn generator_iter(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  check_generator(vm, &args, "__iter__", 0)
+}
+
+fn generator_next(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let gen = check_generator(vm, &args, "__next__", 0)?;
+  let none = vm.get_none();
+  send(vm, &gen, none)
+}
+
+fn generator_send(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let gen = check_generator(vm, &args, "send", 1)?;
+  send(vm, &gen, args.args[1].clone())
+}
+
+// throw(typ[, val]) raises an instance of the exception type typ, created
+// from val, or the exception instance typ:
+fn generator_throw(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let arg_count = if args.args.len() > 2 { 2 } else { 1 };
+  let gen = check_generator(vm, &args, "throw", arg_count)?;
+  let typ = args.args[1].clone();
+  let base_exception = vm.context().exceptions.base_exception_type.clone();
+  let exc = if objtype::isinstance(&typ, &vm.get_type())
+    && objtype::issubclass(&typ, &base_exception)
+  {
+    let exc_args = args.args[2..].to_vec();
+    vm.invoke(typ, PyFuncArgs::new(exc_args, vec![]))?
+  } else if objtype::isinstance(&typ, &base_exception) {
+    typ
+  } else {
+    return Err(vm.new_type_error(format!(
+      "exceptions must be classes or instances deriving from \
+       BaseException, not {}",
+      objtype::get_type_name(&typ.typ())
+    )));
+  };
+  throw(vm, &gen, exc)
+}
+
+fn generator_close(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let gen = check_generator(vm, &args, "close", 0)?;
+  close(vm, &gen)?;
+  Ok(vm.get_none())
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "generator", vec![object.clone()], dict)
+    .unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref generator_type = context.generator_type;
+  generator_type.set_attr("__iter__", context.new_rustfunc(generator_iter));
+  generator_type.set_attr("__next__", context.new_rustfunc(generator_next));
+  generator_type.set_attr("send", context.new_rustfunc(generator_send));
+  generator_type.set_attr("throw", context.new_rustfunc(generator_throw));
+  generator_type.set_attr("close", context.new_rustfunc(generator_close));
+}
@@ -1,33 +1,402 @@
-use super::pyobject::{PyFuncArgs, PyObject, PyObjectKind, PyObjectRef};
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
 use super::vm::VirtualMachine;
-use std::collections::HashMap;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Num, Pow, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub};
 
-fn str(
+// The value of an int. Values which fit in an i64 are kept in one, so that
+// arithmetic on them needs no allocation, and only the others in a BigInt.
+// Operations on small values which overflow switch to BigInt:
+#[derive(Clone)]
+pub enum IntValue {
+  Small(i64),
+  Big(BigInt),
+}
+
+impl IntValue {
+  pub fn to_bigint(&self) -> BigInt {
+    match self {
+      IntValue::Small(value) => BigInt::from(*value),
+      IntValue::Big(value) => value.clone(),
+    }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    match self {
+      IntValue::Small(value) => *value == 0,
+      IntValue::Big(value) => value.is_zero(),
+    }
+  }
+
+  pub fn is_negative(&self) -> bool {
+    match self {
+      IntValue::Small(value) => *value < 0,
+      IntValue::Big(value) => value.is_negative(),
+    }
+  }
+
+  pub fn abs(&self) -> IntValue {
+    match self {
+      IntValue::Small(value) if *value != i64::min_value() => {
+        IntValue::Small(value.abs())
+      }
+      value => IntValue::from(value.to_bigint().abs()),
+    }
+  }
+
+  pub fn pow(&self, exponent: u32) -> IntValue {
+    if let IntValue::Small(value) = self {
+      if let Some(result) = value.checked_pow(exponent) {
+        return IntValue::Small(result);
+      }
+    }
+    IntValue::from(Pow::pow(self.to_bigint(), exponent))
+  }
+}
+
+// Apply an operation to two ints, on machine words while the result fits in
+// one and on BigInts otherwise:
+fn binop(
+  a: &IntValue,
+  b: &IntValue,
+  small: fn(i64, i64) -> Option<i64>,
+  big: fn(BigInt, BigInt) -> BigInt,
+) -> IntValue {
+  if let (IntValue::Small(v1), IntValue::Small(v2)) = (a, b) {
+    if let Some(value) = small(*v1, *v2) {
+      return IntValue::Small(value);
+    }
+  }
+  IntValue::from(big(a.to_bigint(), b.to_bigint()))
+}
+
+impl From<BigInt> for IntValue {
+  fn from(value: BigInt) -> IntValue {
+    match value.to_i64() {
+      Some(value) => IntValue::Small(value),
+      None => IntValue::Big(value),
+    }
+  }
+}
+
+macro_rules! int_value_from_small {
+  ($($t:ty),*) => {$(
+    impl From<$t> for IntValue {
+      fn from(value: $t) -> IntValue {
+        IntValue::Small(i64::from(value))
+      }
+    }
+  )*};
+}
+
+int_value_from_small!(i8, u8, i16, u16, i32, u32, i64);
+
+macro_rules! int_value_from_large {
+  ($($t:ty),*) => {$(
+    impl From<$t> for IntValue {
+      fn from(value: $t) -> IntValue {
+        match value.to_i64() {
+          Some(value) => IntValue::Small(value),
+          None => IntValue::Big(BigInt::from(value)),
+        }
+      }
+    }
+  )*};
+}
+
+int_value_from_large!(u64, usize, isize);
+
+impl ToPrimitive for IntValue {
+  fn to_i64(&self) -> Option<i64> {
+    match self {
+      IntValue::Small(value) => Some(*value),
+      IntValue::Big(..) => None,
+    }
+  }
+
+  fn to_u64(&self) -> Option<u64> {
+    match self {
+      IntValue::Small(value) => value.to_u64(),
+      IntValue::Big(value) => value.to_u64(),
+    }
+  }
+
+  fn to_f64(&self) -> Option<f64> {
+    match self {
+      IntValue::Small(value) => value.to_f64(),
+      IntValue::Big(value) => value.to_f64(),
+    }
+  }
+}
+
+// Small and big values never overlap, so only two small values can be equal:
+impl PartialEq for IntValue {
+  fn eq(&self, other: &IntValue) -> bool {
+    match (self, other) {
+      (IntValue::Small(v1), IntValue::Small(v2)) => v1 == v2,
+      (IntValue::Big(v1), IntValue::Big(v2)) => v1 == v2,
+      _ => false,
+    }
+  }
+}
+
+impl Eq for IntValue {}
+
+impl PartialOrd for IntValue {
+  fn partial_cmp(&self, other: &IntValue) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for IntValue {
+  fn cmp(&self, other: &IntValue) -> Ordering {
+    match (self, other) {
+      (IntValue::Small(v1), IntValue::Small(v2)) => v1.cmp(v2),
+      _ => self.to_bigint().cmp(&other.to_bigint()),
+    }
+  }
+}
+
+impl Hash for IntValue {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    match self {
+      IntValue::Small(value) => value.hash(state),
+      IntValue::Big(value) => value.hash(state),
+    }
+  }
+}
+
+impl fmt::Display for IntValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      IntValue::Small(value) => write!(f, "{}", value),
+      IntValue::Big(value) => write!(f, "{}", value),
+    }
+  }
+}
+
+impl fmt::Debug for IntValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(self, f)
+  }
+}
+
+impl Add for IntValue {
+  type Output = IntValue;
+  fn add(self, other: IntValue) -> IntValue {
+    binop(&self, &other, i64::checked_add, |a, b| a + b)
+  }
+}
+
+impl Sub for IntValue {
+  type Output = IntValue;
+  fn sub(self, other: IntValue) -> IntValue {
+    binop(&self, &other, i64::checked_sub, |a, b| a - b)
+  }
+}
+
+impl Mul for IntValue {
+  type Output = IntValue;
+  fn mul(self, other: IntValue) -> IntValue {
+    binop(&self, &other, i64::checked_mul, |a, b| a * b)
+  }
+}
+
+impl Neg for IntValue {
+  type Output = IntValue;
+  fn neg(self) -> IntValue {
+    match self {
+      IntValue::Small(value) if value != i64::min_value() => {
+        IntValue::Small(-value)
+      }
+      value => IntValue::from(-value.to_bigint()),
+    }
+  }
+}
+
+// ~x is -x - 1, which never overflows an i64:
+impl Not for IntValue {
+  type Output = IntValue;
+  fn not(self) -> IntValue {
+    match self {
+      IntValue::Small(value) => IntValue::Small(!value),
+      IntValue::Big(value) => IntValue::from(!value),
+    }
+  }
+}
+
+impl<'a> BitAnd for &'a IntValue {
+  type Output = IntValue;
+  fn bitand(self, other: &IntValue) -> IntValue {
+    binop(self, other, |a, b| Some(a & b), |a, b| a & b)
+  }
+}
+
+impl<'a> BitOr for &'a IntValue {
+  type Output = IntValue;
+  fn bitor(self, other: &IntValue) -> IntValue {
+    binop(self, other, |a, b| Some(a | b), |a, b| a | b)
+  }
+}
+
+impl<'a> BitXor for &'a IntValue {
+  type Output = IntValue;
+  fn bitxor(self, other: &IntValue) -> IntValue {
+    binop(self, other, |a, b| Some(a ^ b), |a, b| a ^ b)
+  }
+}
+
+impl Shl<usize> for IntValue {
+  type Output = IntValue;
+  fn shl(self, count: usize) -> IntValue {
+    if let IntValue::Small(value) = self {
+      // The shift fits when no bits other than the sign are shifted out:
+      if count < 64 && (value << count) >> count == value {
+        return IntValue::Small(value << count);
+      }
+    }
+    IntValue::from(self.to_bigint() << count)
+  }
+}
+
+impl Shr<usize> for IntValue {
+  type Output = IntValue;
+  fn shr(self, count: usize) -> IntValue {
+    match self {
+      IntValue::Small(value) => IntValue::Small(value >> count.min(63)),
+      IntValue::Big(value) => IntValue::from(value >> count),
+    }
+  }
+}
+
+// Convert a single argument of int() to an int:
+fn to_int(
   vm: &mut VirtualMachine,
-  _args: PyFuncArgs,
-) -> Result<PyObjectRef, PyObjectRef> {
-  Ok(vm.new_str("todo".to_string()))
-}
-
-pub fn create_type(type_type: PyObjectRef) -> PyObjectRef {
-  let mut dict = HashMap::new();
-  dict.insert(
-    "__str__".to_string(),
-    PyObject::new(
-      PyObjectKind::RustFunction { function: str },
-      type_type.clone(),
-    ),
-  );
-  let typ = PyObject::new(
-    PyObjectKind::Class {
-      name: "int".to_string(),
-      dict: PyObject::new(
-        PyObjectKind::Dict { elements: dict },
-        type_type.clone(),
-      ),
-      mro: vec![],
+  obj: &PyObjectRef,
+  base: u32,
+) -> Result<BigInt, PyObjectRef> {
+  let value = match obj.borrow().kind {
+    PyObjectKind::Integer { ref value } => value.to_bigint(),
+    PyObjectKind::Boolean { value } => BigInt::from(value as u8),
+    PyObjectKind::Float { value } => {
+      if value.is_nan() {
+        return Err(
+          vm.new_value_error("cannot convert float NaN to integer".to_string()),
+        );
+      }
+      match BigInt::from_f64(value.trunc()) {
+        Some(value) => value,
+        None => {
+          let overflow_error = vm.context().exceptions.overflow_error.clone();
+          return Err(vm.new_exception(
+            overflow_error,
+            "cannot convert float infinity to integer".to_string(),
+          ));
+        }
+      }
+    }
+    PyObjectKind::String { ref value } => {
+      match BigInt::from_str_radix(value.trim(), base) {
+        Ok(value) => value,
+        Err(..) => {
+          return Err(vm.new_value_error(format!(
+            "invalid literal for int() with base {}: '{}'",
+            base, value
+          )))
+        }
+      }
+    }
+    _ => {
+      return Err(vm.new_type_error(format!(
+        "int() argument must be a string or a number, not '{}'",
+        objtype::get_type_name(&obj.typ())
+      )))
+    }
+  };
+  Ok(value)
+}
+
+fn int_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let value = match args.args.len() {
+    1 => BigInt::zero(),
+    2 => to_int(vm, &args.args[1], 10)?,
+    3 => {
+      let base = match args.args[2].borrow().kind {
+        PyObjectKind::Integer { ref value } => value.to_u32(),
+        _ => None,
+      };
+      match base {
+        Some(base) if base >= 2 && base <= 36 => {}
+        _ => {
+          return Err(
+            vm.new_value_error("int() base must be >= 2 and <= 36".to_string()),
+          )
+        }
+      };
+      let is_string = match args.args[1].borrow().kind {
+        PyObjectKind::String { .. } => true,
+        _ => false,
+      };
+      if !is_string {
+        return Err(vm.new_type_error(
+          "int() can't convert non-string with explicit base".to_string(),
+        ));
+      }
+      to_int(vm, &args.args[1], base.unwrap())?
+    }
+    _ => {
+      return Err(
+        vm.new_type_error("int() takes at most 2 arguments".to_string()),
+      )
+    }
+  };
+  Ok(vm.context().new_int(value))
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
     },
     type_type.clone(),
   );
-  typ
+  objtype::new(type_type.clone(), "int", vec![object.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref int_type = context.int_type;
+  int_type.set_attr("__new__", context.new_rustfunc(int_new));
+}
+
+// Convert to float, or None when the value is too large to be represented:
+pub fn to_float(value: &IntValue) -> Option<f64> {
+  value.to_f64().filter(|value| value.is_finite())
+}
+
+// Division and modulo round towards negative infinity, like Python does.
+// The caller is responsible for checking b is not zero.
+pub fn floordiv(a: &IntValue, b: &IntValue) -> IntValue {
+  binop(a, b, checked_div_floor, |a, b| a.div_floor(&b))
+}
+
+pub fn modulo(a: &IntValue, b: &IntValue) -> IntValue {
+  binop(a, b, checked_mod_floor, |a, b| a.mod_floor(&b))
+}
+
+// i64::MIN // -1 is the one quotient which does not fit in an i64:
+fn checked_div_floor(a: i64, b: i64) -> Option<i64> {
+  a.checked_div(b).map(|_| Integer::div_floor(&a, &b))
+}
+
+fn checked_mod_floor(a: i64, b: i64) -> Option<i64> {
+  a.checked_rem(b).map(|_| Integer::mod_floor(&a, &b))
 }
@@ -0,0 +1,302 @@
+/*
+ * The iterator protocol: iter() gets an iterator from an iterable, and
+ * next() takes the items out of an iterator until it raises StopIteration.
+ */
+
+use super::objdict::{self, DictElements};
+use super::objgenerator;
+use super::objrange;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+
+fn new_iterator(vm: &VirtualMachine, iterated_obj: PyObjectRef) -> PyObjectRef {
+  let size = match iterated_obj.borrow().kind {
+    PyObjectKind::Dict { ref elements }
+    | PyObjectKind::Set { ref elements }
+    | PyObjectKind::FrozenSet { ref elements } => Some(elements.len()),
+    _ => None,
+  };
+  PyObject::new(
+    PyObjectKind::Iterator {
+      position: 0,
+      iterated_obj: iterated_obj,
+      size: size,
+    },
+    vm.context().iter_type.clone(),
+  )
+}
+
+fn new_not_iterable_error(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> PyObjectRef {
+  let type_name = objtype::get_type_name(&obj.typ());
+  vm.new_type_error(format!("'{}' object is not iterable", type_name))
+}
+
+pub fn is_iterable(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::List { .. }
+    | PyObjectKind::Tuple { .. }
+    | PyObjectKind::String { .. }
+    | PyObjectKind::Bytes { .. }
+    | PyObjectKind::ByteArray { .. }
+    | PyObjectKind::Dict { .. }
+    | PyObjectKind::Set { .. }
+    | PyObjectKind::FrozenSet { .. }
+    | PyObjectKind::Range { .. } => true,
+    _ => {
+      objtype::get_special_method(obj, "__iter__").is_some()
+        || objtype::get_special_method(obj, "__getitem__").is_some()
+    }
+  }
+}
+
+// Get an iterator for obj, as iter(obj) does:
+pub fn get_iter(vm: &mut VirtualMachine, obj: &PyObjectRef) -> PyResult {
+  if let Some(method) = objtype::get_special_method(obj, "__iter__") {
+    let iterator =
+      vm.invoke(method, PyFuncArgs::new(vec![obj.clone()], vec![]))?;
+    if objtype::get_special_method(&iterator, "__next__").is_none() {
+      return Err(vm.new_type_error(format!(
+        "iter() returned non-iterator of type '{}'",
+        objtype::get_type_name(&iterator.typ())
+      )));
+    }
+    return Ok(iterator);
+  }
+  let iterated_obj = match obj.borrow().kind {
+    PyObjectKind::List { .. }
+    | PyObjectKind::Tuple { .. }
+    | PyObjectKind::String { .. }
+    | PyObjectKind::Bytes { .. }
+    | PyObjectKind::ByteArray { .. }
+    | PyObjectKind::Range { .. }
+    | PyObjectKind::Dict { .. }
+    | PyObjectKind::Set { .. }
+    | PyObjectKind::FrozenSet { .. } => obj.clone(),
+    // Other objects are iterable when they can be indexed from zero:
+    _ => {
+      if !is_iterable(obj) {
+        return Err(new_not_iterable_error(vm, obj));
+      }
+      obj.clone()
+    }
+  };
+  Ok(new_iterator(vm, iterated_obj))
+}
+
+fn is_builtin_iterator(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::Iterator { .. } => true,
+    _ => false,
+  }
+}
+
+// Take the next item out of an iterator. None means the iterator is
+// exhausted:
+pub fn get_next_object(
+  vm: &mut VirtualMachine,
+  iterator: &PyObjectRef,
+) -> Result<Option<PyObjectRef>, PyObjectRef> {
+  if !is_builtin_iterator(iterator) {
+    return match call_next(vm, iterator) {
+      Ok(value) => Ok(Some(value)),
+      Err(exc) => {
+        if objgenerator::is_stop_iteration(vm, &exc) {
+          Ok(None)
+        } else {
+          Err(exc)
+        }
+      }
+    };
+  }
+
+  let (position, iterated_obj, size) = match iterator.borrow().kind {
+    PyObjectKind::Iterator {
+      position,
+      ref iterated_obj,
+      size,
+    } => (position, iterated_obj.clone(), size),
+    _ => unreachable!(),
+  };
+  if let Some(size) = size {
+    if objdict::get_elements(&iterated_obj).len() != size {
+      let kind = match iterated_obj.borrow().kind {
+        PyObjectKind::Dict { .. } => "dictionary",
+        _ => "Set",
+      };
+      let runtime_error = vm.context().exceptions.runtime_error.clone();
+      return Err(vm.new_exception(
+        runtime_error,
+        format!("{} changed size during iteration", kind),
+      ));
+    }
+  }
+  // The item and the position of the item after it, or None if the
+  // iterated object is not a builtin sequence:
+  let next = match iterated_obj.borrow().kind {
+    PyObjectKind::List { ref elements }
+    | PyObjectKind::Tuple { ref elements } => Some(
+      elements
+        .get(position)
+        .map(|element| (element.clone(), position + 1)),
+    ),
+    // The position in a string is a byte offset:
+    PyObjectKind::String { ref value } => Some(
+      value[position..]
+        .chars()
+        .next()
+        .map(|c| (vm.new_str(c.to_string()), position + c.len_utf8())),
+    ),
+    PyObjectKind::Bytes { ref value }
+    | PyObjectKind::ByteArray { ref value } => Some(
+      value
+        .get(position)
+        .map(|byte| (vm.context().new_int(*byte), position + 1)),
+    ),
+    PyObjectKind::Range {
+      ref start,
+      ref stop,
+      ref step,
+    } => Some(
+      objrange::get(start, stop, step, position)
+        .map(|value| (vm.context().new_int(value), position + 1)),
+    ),
+    // The position in a dict or set is that of its next entry:
+    PyObjectKind::Dict { ref elements }
+    | PyObjectKind::Set { ref elements }
+    | PyObjectKind::FrozenSet { ref elements } => Some(
+      elements
+        .next_entry(position)
+        .map(|(key, _, next)| (key.to_object(vm.context()), next)),
+    ),
+    _ => None,
+  };
+  let next = match next {
+    Some(next) => next,
+    None => get_next_indexed_item(vm, &iterated_obj, position)?,
+  };
+  match next {
+    Some((value, next_position)) => {
+      if let PyObjectKind::Iterator {
+        ref mut position, ..
+      } = iterator.borrow_mut().kind
+      {
+        *position = next_position;
+      }
+      Ok(Some(value))
+    }
+    None => Ok(None),
+  }
+}
+
+// Objects which are indexed by __getitem__ end with an IndexError:
+fn get_next_indexed_item(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+  position: usize,
+) -> Result<Option<(PyObjectRef, usize)>, PyObjectRef> {
+  let method = match objtype::get_special_method(obj, "__getitem__") {
+    Some(method) => method,
+    None => return Ok(None),
+  };
+  let index = vm.context().new_int(position);
+  match vm.invoke(method, PyFuncArgs::new(vec![obj.clone(), index], vec![])) {
+    Ok(value) => Ok(Some((value, position + 1))),
+    Err(exc) => {
+      let index_error = vm.context().exceptions.index_error.clone();
+      if objtype::isinstance(&exc, &index_error)
+        || objgenerator::is_stop_iteration(vm, &exc)
+      {
+        Ok(None)
+      } else {
+        Err(exc)
+      }
+    }
+  }
+}
+
+// Call next() on an iterator, which raises StopIteration when it is
+// exhausted:
+pub fn call_next(vm: &mut VirtualMachine, iterator: &PyObjectRef) -> PyResult {
+  if is_builtin_iterator(iterator) {
+    return match get_next_object(vm, iterator)? {
+      Some(value) => Ok(value),
+      None => {
+        let none = vm.get_none();
+        Err(objgenerator::new_stop_iteration(vm, none))
+      }
+    };
+  }
+  if objgenerator::is_generator(iterator) {
+    let none = vm.get_none();
+    return objgenerator::send(vm, iterator, none);
+  }
+  match objtype::get_special_method(iterator, "__next__") {
+    Some(method) => {
+      vm.invoke(method, PyFuncArgs::new(vec![iterator.clone()], vec![]))
+    }
+    None => Err(vm.new_type_error(format!(
+      "'{}' object is not an iterator",
+      objtype::get_type_name(&iterator.typ())
+    ))),
+  }
+}
+
+// All the items of an iterable:
+pub fn get_all(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<Vec<PyObjectRef>, PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::List { ref elements }
+    | PyObjectKind::Tuple { ref elements } => return Ok(elements.clone()),
+    _ => {}
+  };
+  let iterator = get_iter(vm, obj)?;
+  let mut elements = vec![];
+  while let Some(element) = get_next_object(vm, &iterator)? {
+    elements.push(element);
+  }
+  Ok(elements)
+}
+
+fn iter_iter(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  match args.args.get(0) {
+    Some(iterator) => Ok(iterator.clone()),
+    None => Err(
+      vm.new_type_error("descriptor '__iter__' needs an argument".to_string()),
+    ),
+  }
+}
+
+fn iter_next(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  match args.args.get(0) {
+    Some(iterator) => call_next(vm, iterator),
+    None => Err(
+      vm.new_type_error("descriptor '__next__' needs an argument".to_string()),
+    ),
+  }
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "iterator", vec![object.clone()], dict)
+    .unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref iter_type = context.iter_type;
+  iter_type.set_attr("__iter__", context.new_rustfunc(iter_iter));
+  iter_type.set_attr("__next__", context.new_rustfunc(iter_next));
+}
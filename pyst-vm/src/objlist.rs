@@ -1,11 +1,13 @@
+use super::objdict::DictElements;
+use super::objiter;
 use super::objsequence::PySliceableSequence;
 use super::objtype;
 use super::pyobject::{
   AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
-  PyObjectRef, PyResult,
+  PyObjectRef, PyResult, TypeProtocol,
 };
 use super::vm::VirtualMachine;
-use std::collections::HashMap;
+use num_traits::ToPrimitive;
 
 // set_item:
 pub fn set_item(
@@ -16,14 +18,63 @@ pub fn set_item(
 ) -> PyResult {
   match &(idx.borrow()).kind {
     PyObjectKind::Integer { value } => {
-      let pos_index = l.get_pos(*value);
-      l[pos_index] = obj;
+      match value.to_i32().and_then(|value| l.get_pos(value)) {
+        Some(pos_index) => {
+          l[pos_index] = obj;
+          Ok(vm.get_none())
+        }
+        None => {
+          let index_error = vm.context().exceptions.index_error.clone();
+          Err(vm.new_exception(
+            index_error,
+            "list assignment index out of range".to_string(),
+          ))
+        }
+      }
+    }
+    _ => Err(vm.new_type_error(format!(
+      "list indices must be integers or slices, not {}",
+      objtype::get_type_name(&idx.typ())
+    ))),
+  }
+}
+
+// del l[idx], where idx is an index or a slice:
+pub fn del_item(
+  vm: &mut VirtualMachine,
+  l: &mut Vec<PyObjectRef>,
+  idx: PyObjectRef,
+) -> PyResult {
+  match &(idx.borrow()).kind {
+    PyObjectKind::Integer { value } => {
+      match value.to_i32().and_then(|value| l.get_pos(value)) {
+        Some(pos_index) => {
+          l.remove(pos_index);
+          Ok(vm.get_none())
+        }
+        None => {
+          let index_error = vm.context().exceptions.index_error.clone();
+          Err(vm.new_exception(
+            index_error,
+            "list assignment index out of range".to_string(),
+          ))
+        }
+      }
+    }
+    PyObjectKind::Slice { .. } => {
+      // Slicing the positions of the items tells which ones to remove:
+      let positions: Vec<usize> = (0..l.len()).collect();
+      let mut removed = positions.get_slice_items(vm, &idx)?;
+      removed.sort();
+      for pos_index in removed.into_iter().rev() {
+        l.remove(pos_index);
+      }
       Ok(vm.get_none())
     }
-    _ => panic!(
-      "TypeError: indexing type {:?} with index {:?} is not supported (yet?)",
-      l, idx
-    ),
+    _ => Err(vm.new_type_error(format!(
+      "list indices must be integers or slices, not {}",
+      objtype::get_type_name(&idx.typ())
+    ))),
   }
 }
 
@@ -37,10 +88,30 @@ fn append(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
       elements.push(o);
       Ok(vm.get_none())
     } else {
-      Err(vm.new_exception("list.append is called with no list".to_string()))
+      Err(vm.new_type_error("list.append is called with no list".to_string()))
+    }
+  } else {
+    Err(vm.new_type_error("list.append requires two arguments".to_string()))
+  }
+}
+
+// l += iterable extends the list itself, so other references to it see the
+// new elements:
+fn iadd(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  trace!("list.__iadd__ called with: {:?}", args);
+  if args.args.len() == 2 {
+    let l = args.args[0].clone();
+    let new_elements = objiter::get_all(vm, &args.args[1])?;
+    if let PyObjectKind::List { ref mut elements } = l.borrow_mut().kind {
+      elements.extend(new_elements);
+    } else {
+      return Err(
+        vm.new_type_error("list.__iadd__ is called with no list".to_string()),
+      );
     }
+    Ok(l)
   } else {
-    Err(vm.new_exception("list.append requires two arguments".to_string()))
+    Err(vm.new_type_error("list.__iadd__ requires two arguments".to_string()))
   }
 }
 
@@ -53,10 +124,10 @@ fn clear(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
       elements.clear();
       Ok(vm.get_none())
     } else {
-      Err(vm.new_exception("list.clear is called with no list".to_string()))
+      Err(vm.new_type_error("list.clear is called with no list".to_string()))
     }
   } else {
-    Err(vm.new_exception("list.clear requires one arguments".to_string()))
+    Err(vm.new_type_error("list.clear requires one arguments".to_string()))
   }
 }
 
@@ -67,12 +138,12 @@ fn len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
     let l = args.args[0].clone();
     let list_obj = l.borrow();
     if let PyObjectKind::List { ref elements } = list_obj.kind {
-      Ok(vm.context().new_int(elements.len() as i32))
+      Ok(vm.context().new_int(elements.len()))
     } else {
-      Err(vm.new_exception("list.len is called with no list".to_string()))
+      Err(vm.new_type_error("list.len is called with no list".to_string()))
     }
   } else {
-    Err(vm.new_exception("list.len requires one arguments".to_string()))
+    Err(vm.new_type_error("list.len requires one arguments".to_string()))
   }
 }
 
@@ -85,17 +156,17 @@ fn reverse(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
       elements.reverse();
       Ok(vm.get_none())
     } else {
-      Err(vm.new_exception("list.reverse is called with no list".to_string()))
+      Err(vm.new_type_error("list.reverse is called with no list".to_string()))
     }
   } else {
-    Err(vm.new_exception("list.reverse requires one arguments".to_string()))
+    Err(vm.new_type_error("list.reverse requires one arguments".to_string()))
   }
 }
 
 pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
   let dict = PyObject::new(
     PyObjectKind::Dict {
-      elements: HashMap::new(),
+      elements: DictElements::new(),
     },
     type_type.clone(),
   );
@@ -104,6 +175,7 @@ pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
 
 pub fn init(context: &PyContext) {
   let ref list_type = context.list_type;
+  list_type.set_attr("__iadd__", context.new_rustfunc(iadd));
   list_type.set_attr("__len__", context.new_rustfunc(len));
   list_type.set_attr("append", context.new_rustfunc(append));
   list_type.set_attr("clear", context.new_rustfunc(clear));
@@ -0,0 +1,68 @@
+/*
+ * The types of the singletons None and NotImplemented. Calling either type
+ * gives back its one instance.
+ */
+
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult,
+};
+use super::vm::VirtualMachine;
+
+fn create_singleton_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+  name: &str,
+) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), name, vec![object.clone()], dict).unwrap()
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  create_singleton_type(type_type, object, "NoneType")
+}
+
+pub fn create_not_implemented_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+) -> PyObjectRef {
+  create_singleton_type(type_type, object, "NotImplementedType")
+}
+
+fn none_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let none = vm.get_none();
+  singleton_new(vm, args, "NoneType", none)
+}
+
+fn not_implemented_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let not_implemented = vm.context().not_implemented.clone();
+  singleton_new(vm, args, "NotImplementedType", not_implemented)
+}
+
+fn singleton_new(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  type_name: &str,
+  singleton: PyObjectRef,
+) -> PyResult {
+  if args.args.len() > 1 || !args.kwargs.is_empty() {
+    return Err(vm.new_type_error(format!("{} takes no arguments", type_name)));
+  }
+  Ok(singleton)
+}
+
+pub fn init(context: &PyContext) {
+  context
+    .none_type
+    .set_attr("__new__", context.new_rustfunc(none_new));
+  context
+    .not_implemented_type
+    .set_attr("__new__", context.new_rustfunc(not_implemented_new));
+}
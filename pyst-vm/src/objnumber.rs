@@ -0,0 +1,109 @@
+use super::objint::{self, IntValue};
+use super::pyobject::PyObjectKind;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_traits::{FromPrimitive, Zero};
+use std::cmp::Ordering;
+
+// A value of any of the built-in numeric types, with bool treated as int:
+#[derive(Debug, Clone)]
+pub enum Number {
+  Integer(IntValue),
+  Float(f64),
+  Complex(Complex64),
+}
+
+// Two numbers converted to their common type:
+#[derive(Debug, Clone)]
+pub enum NumberPair {
+  Integer(IntValue, IntValue),
+  Float(f64, f64),
+  Complex(Complex64, Complex64),
+}
+
+pub fn to_number(kind: &PyObjectKind) -> Option<Number> {
+  match kind {
+    PyObjectKind::Boolean { value } => {
+      Some(Number::Integer(IntValue::from(*value as u8)))
+    }
+    PyObjectKind::Integer { value } => Some(Number::Integer(value.clone())),
+    PyObjectKind::Float { value } => Some(Number::Float(*value)),
+    PyObjectKind::Complex { value } => Some(Number::Complex(*value)),
+    _ => None,
+  }
+}
+
+impl Number {
+  // Returns None when an int is too large to be represented as a float:
+  pub fn to_float(&self) -> Option<f64> {
+    match self {
+      Number::Integer(value) => objint::to_float(value),
+      Number::Float(value) => Some(*value),
+      Number::Complex(..) => None,
+    }
+  }
+
+  pub fn to_complex(&self) -> Option<Complex64> {
+    match self {
+      Number::Complex(value) => Some(*value),
+      _ => self.to_float().map(|value| Complex64::new(value, 0.0)),
+    }
+  }
+}
+
+// Convert both numbers to the wider of their types, following Python's
+// int < float < complex ordering. Returns None when an int is too large to
+// be converted to float:
+pub fn coerce(a: Number, b: Number) -> Option<NumberPair> {
+  match (a, b) {
+    (Number::Integer(v1), Number::Integer(v2)) => {
+      Some(NumberPair::Integer(v1, v2))
+    }
+    (a @ Number::Complex(..), b) | (a, b @ Number::Complex(..)) => {
+      Some(NumberPair::Complex(a.to_complex()?, b.to_complex()?))
+    }
+    (a, b) => Some(NumberPair::Float(a.to_float()?, b.to_float()?)),
+  }
+}
+
+// Compare an int with a float exactly, without rounding the int:
+fn cmp_int_float(a: &IntValue, b: f64) -> Option<Ordering> {
+  if b.is_nan() {
+    return None;
+  }
+  if b.is_infinite() {
+    return Some(if b > 0.0 {
+      Ordering::Less
+    } else {
+      Ordering::Greater
+    });
+  }
+  let floor = b.floor();
+  match a.to_bigint().cmp(&BigInt::from_f64(floor).unwrap()) {
+    Ordering::Equal if floor != b => Some(Ordering::Less),
+    ordering => Some(ordering),
+  }
+}
+
+pub fn eq(a: &Number, b: &Number) -> bool {
+  match (a, b) {
+    (Number::Complex(v1), Number::Complex(v2)) => v1 == v2,
+    (Number::Complex(v1), other) | (other, Number::Complex(v1)) => {
+      v1.im.is_zero() && eq(&Number::Float(v1.re), other)
+    }
+    (a, b) => partial_cmp(a, b) == Some(Ordering::Equal),
+  }
+}
+
+// Complex numbers and NaN are unordered:
+pub fn partial_cmp(a: &Number, b: &Number) -> Option<Ordering> {
+  match (a, b) {
+    (Number::Integer(v1), Number::Integer(v2)) => Some(v1.cmp(v2)),
+    (Number::Float(v1), Number::Float(v2)) => v1.partial_cmp(v2),
+    (Number::Integer(v1), Number::Float(v2)) => cmp_int_float(v1, *v2),
+    (Number::Float(v1), Number::Integer(v2)) => {
+      cmp_int_float(v2, *v1).map(Ordering::reverse)
+    }
+    _ => None,
+  }
+}
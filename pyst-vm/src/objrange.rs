@@ -0,0 +1,162 @@
+/*
+ * A range produces its numbers while it is iterated, instead of holding them
+ * all in a list.
+ */
+
+use super::objdict::DictElements;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
+use super::vm::VirtualMachine;
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+pub fn len(start: &BigInt, stop: &BigInt, step: &BigInt) -> BigInt {
+  let (distance, step) = match step.sign() {
+    Sign::Minus => (start - stop, -step),
+    _ => (stop - start, step.clone()),
+  };
+  if distance.sign() == Sign::Plus {
+    (distance - BigInt::one()).div_floor(&step) + BigInt::one()
+  } else {
+    BigInt::zero()
+  }
+}
+
+// The item at the given index, or None when the range is shorter:
+pub fn get(
+  start: &BigInt,
+  stop: &BigInt,
+  step: &BigInt,
+  index: usize,
+) -> Option<BigInt> {
+  let value = start + step * BigInt::from(index);
+  let in_range = match step.sign() {
+    Sign::Minus => value > *stop,
+    _ => value < *stop,
+  };
+  if in_range {
+    Some(value)
+  } else {
+    None
+  }
+}
+
+pub fn to_string(start: &BigInt, stop: &BigInt, step: &BigInt) -> String {
+  if step.is_one() {
+    format!("range({}, {})", start, stop)
+  } else {
+    format!("range({}, {}, {})", start, stop, step)
+  }
+}
+
+fn to_index(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<BigInt, PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::Integer { ref value } => Ok(value.to_bigint()),
+    PyObjectKind::Boolean { value } => Ok(BigInt::from(value as u8)),
+    _ => Err(vm.new_type_error(format!(
+      "'{}' object cannot be interpreted as an integer",
+      objtype::get_type_name(&obj.typ())
+    ))),
+  }
+}
+
+// range(stop) or range(start, stop[, step]):
+fn range_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let mut bounds = vec![];
+  for arg in args.args.iter().skip(1) {
+    bounds.push(to_index(vm, arg)?);
+  }
+  let (start, stop, step) = match bounds.len() {
+    1 => (BigInt::zero(), bounds[0].clone(), BigInt::one()),
+    2 => (bounds[0].clone(), bounds[1].clone(), BigInt::one()),
+    3 => (bounds[0].clone(), bounds[1].clone(), bounds[2].clone()),
+    0 => {
+      return Err(
+        vm.new_type_error("range expected 1 argument, got 0".to_string()),
+      )
+    }
+    count => {
+      return Err(vm.new_type_error(format!(
+        "range expected at most 3 arguments, got {}",
+        count
+      )))
+    }
+  };
+  if step.is_zero() {
+    return Err(
+      vm.new_value_error("range() arg 3 must not be zero".to_string()),
+    );
+  }
+  Ok(PyObject::new(
+    PyObjectKind::Range {
+      start: start,
+      stop: stop,
+      step: step,
+    },
+    vm.context().range_type.clone(),
+  ))
+}
+
+fn range_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let length = match args.args.get(0) {
+    Some(obj) => match obj.borrow().kind {
+      PyObjectKind::Range {
+        ref start,
+        ref stop,
+        ref step,
+      } => Some(len(start, stop, step)),
+      _ => None,
+    },
+    None => None,
+  };
+  match length {
+    Some(length) => Ok(vm.context().new_int(length)),
+    None => Err(vm.new_type_error(
+      "descriptor '__len__' requires a 'range' object".to_string(),
+    )),
+  }
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "range", vec![object.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref range_type = context.range_type;
+  range_type.set_attr("__new__", context.new_rustfunc(range_new));
+  range_type.set_attr("__len__", context.new_rustfunc(range_len));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::len;
+  use num_bigint::BigInt;
+
+  #[test]
+  fn test_len_counts_partial_steps() {
+    let len_of = |start: i32, stop: i32, step: i32| {
+      len(
+        &BigInt::from(start),
+        &BigInt::from(stop),
+        &BigInt::from(step),
+      )
+    };
+    assert_eq!(len_of(0, 10, 1), BigInt::from(10));
+    assert_eq!(len_of(2, 11, 3), BigInt::from(3));
+    assert_eq!(len_of(10, 0, -3), BigInt::from(4));
+    assert_eq!(len_of(5, 1, 1), BigInt::from(0));
+  }
+}
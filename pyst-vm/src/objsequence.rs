@@ -1,61 +1,147 @@
-use super::pyobject::{PyObject, PyObjectKind, PyObjectRef, PyResult};
+use super::objtype;
+use super::pyobject::{PyObjectKind, PyObjectRef, PyResult, TypeProtocol};
 use super::vm::VirtualMachine;
+use num_traits::ToPrimitive;
 use std::marker::Sized;
 
 pub trait PySliceableSequence {
   fn do_slice(&self, start: usize, stop: usize) -> Self;
   fn do_stepped_slice(&self, start: usize, stop: usize, step: usize) -> Self;
+  // Take every step'th item from the range [start, stop), back to front:
+  fn do_stepped_slice_reverse(
+    &self,
+    start: usize,
+    stop: usize,
+    step: usize,
+  ) -> Self;
   fn len(&self) -> usize;
-  fn get_pos(&self, p: i32) -> usize {
-    if p < 0 {
-      self.len() - ((-p) as usize)
-    } else if p as usize > self.len() {
+
+  // Position of the item at index p, or None when out of range:
+  fn get_pos(&self, p: i32) -> Option<usize> {
+    let len = self.len() as i64;
+    let pos = if p < 0 { len + p as i64 } else { p as i64 };
+    if pos < 0 || pos >= len {
+      None
+    } else {
+      Some(pos as usize)
+    }
+  }
+
+  // Slice positions are clamped to the sequence bounds:
+  fn get_slice_pos(&self, p: i32) -> usize {
+    let len = self.len() as i64;
+    let pos = if p < 0 { len + p as i64 } else { p as i64 };
+    if pos < 0 {
+      0
+    } else if pos > len {
       self.len()
     } else {
-      p as usize
+      pos as usize
     }
   }
-  fn get_slice_items(&self, slice: &PyObjectRef) -> Self
+
+  fn get_slice_items(
+    &self,
+    vm: &mut VirtualMachine,
+    slice: &PyObjectRef,
+  ) -> Result<Self, PyObjectRef>
   where
     Self: Sized,
   {
     match &(slice.borrow()).kind {
-      PyObjectKind::Slice { start, stop, step } => {
-        let start = match start {
-          &Some(start) => self.get_pos(start),
-          &None => 0,
-        };
-        let stop = match stop {
-          &Some(stop) => self.get_pos(stop),
-          &None => self.len() as usize,
-        };
-        match step {
-          &None | &Some(1) => self.do_slice(start, stop),
-          &Some(num) => {
-            if num < 0 {
-              unimplemented!("negative step indexing not yet supported")
-            };
-            self.do_stepped_slice(start, stop, num as usize)
-          }
+      PyObjectKind::Slice { start, stop, step } => match step {
+        &None | &Some(1) => {
+          let start = start.map_or(0, |start| self.get_slice_pos(start));
+          let stop = stop.map_or(self.len(), |stop| self.get_slice_pos(stop));
+          let stop = if stop < start { start } else { stop };
+          Ok(self.do_slice(start, stop))
         }
-      }
+        &Some(0) => {
+          Err(vm.new_value_error("slice step cannot be zero".to_string()))
+        }
+        &Some(num) if num > 0 => {
+          let start = start.map_or(0, |start| self.get_slice_pos(start));
+          let stop = stop.map_or(self.len(), |stop| self.get_slice_pos(stop));
+          let stop = if stop < start { start } else { stop };
+          Ok(self.do_stepped_slice(start, stop, num as usize))
+        }
+        &Some(num) => {
+          // With a negative step, start is the (inclusive) upper bound and
+          // stop the (exclusive) lower bound, so shift both by one to map
+          // "before the first item" onto position zero:
+          let len = self.len() as i64;
+          let shift = |p: i32| {
+            let pos = if p < 0 { len + p as i64 } else { p as i64 };
+            if pos < -1 {
+              0
+            } else if pos >= len {
+              len as usize
+            } else {
+              (pos + 1) as usize
+            }
+          };
+          let upper = start.map_or(self.len(), |start| shift(start));
+          let lower = stop.map_or(0, |stop| shift(stop));
+          let lower = if lower > upper { upper } else { lower };
+          Ok(self.do_stepped_slice_reverse(lower, upper, (-num) as usize))
+        }
+      },
       kind => panic!("get_slice_items called with non-slice: {:?}", kind),
     }
   }
 }
 
-impl PySliceableSequence for Vec<PyObjectRef> {
+impl<T: Clone> PySliceableSequence for Vec<T> {
   fn do_slice(&self, start: usize, stop: usize) -> Self {
     self[start..stop].to_vec()
   }
   fn do_stepped_slice(&self, start: usize, stop: usize, step: usize) -> Self {
     self[start..stop].iter().step_by(step).cloned().collect()
   }
+  fn do_stepped_slice_reverse(
+    &self,
+    start: usize,
+    stop: usize,
+    step: usize,
+  ) -> Self {
+    self[start..stop]
+      .iter()
+      .rev()
+      .step_by(step)
+      .cloned()
+      .collect()
+  }
   fn len(&self) -> usize {
     self.len()
   }
 }
 
+// The elements of a tuple or list:
+pub fn get_elements(obj: &PyObjectRef) -> Vec<PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::Tuple { ref elements }
+    | PyObjectKind::List { ref elements } => elements.clone(),
+    ref kind => panic!("get_elements called for non-sequence: {:?}", kind),
+  }
+}
+
+// Sequences are equal when their elements are, compared in turn with ==:
+pub fn equal(
+  vm: &mut VirtualMachine,
+  a: &[PyObjectRef],
+  b: &[PyObjectRef],
+) -> Result<bool, PyObjectRef> {
+  if a.len() != b.len() {
+    return Ok(false);
+  }
+  for (a, b) in a.iter().zip(b) {
+    if !vm.identical_or_equal(a, b)? {
+      return Ok(false);
+    }
+  }
+  Ok(true)
+}
+
 pub fn get_item(
   vm: &mut VirtualMachine,
   sequence: &PyObjectRef,
@@ -64,35 +150,28 @@ pub fn get_item(
 ) -> PyResult {
   match &(subscript.borrow()).kind {
     PyObjectKind::Integer { value } => {
-      let pos_index = elements.get_pos(*value);
-      if pos_index < elements.len() {
-        let obj = elements[pos_index].clone();
-        Ok(obj)
-      } else {
-        Err(vm.new_exception("Index out of bounds!".to_string()))
+      match value.to_i32().and_then(|value| elements.get_pos(value)) {
+        Some(pos_index) => Ok(elements[pos_index].clone()),
+        None => {
+          let index_error = vm.context().exceptions.index_error.clone();
+          Err(vm.new_exception(index_error, "Index out of bounds!".to_string()))
+        }
       }
     }
-    PyObjectKind::Slice {
-      start: _,
-      stop: _,
-      step: _,
-    } => Ok(PyObject::new(
-      match &(sequence.borrow()).kind {
-        PyObjectKind::Tuple { elements: _ } => PyObjectKind::Tuple {
-          elements: elements.get_slice_items(&subscript),
-        },
-        PyObjectKind::List { elements: _ } => PyObjectKind::List {
-          elements: elements.get_slice_items(&subscript),
-        },
+    PyObjectKind::Slice { .. } => {
+      let elements = elements.get_slice_items(vm, &subscript)?;
+      Ok(match &(sequence.borrow()).kind {
+        PyObjectKind::Tuple { .. } => vm.context().new_tuple(elements),
+        PyObjectKind::List { .. } => vm.context().new_list(elements),
         ref kind => {
           panic!("sequence get_item called for non-sequence: {:?}", kind)
         }
-      },
-      vm.get_type(),
-    )),
-    _ => Err(vm.new_exception(format!(
-      "TypeError: indexing type {:?} with index {:?} is not supported (yet?)",
-      sequence, subscript
+      })
+    }
+    _ => Err(vm.new_type_error(format!(
+      "{} indices must be integers or slices, not {}",
+      objtype::get_type_name(&sequence.typ()),
+      objtype::get_type_name(&subscript.typ())
     ))),
   }
 }
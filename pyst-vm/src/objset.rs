@@ -0,0 +1,590 @@
+/*
+ * Builtin set and frozenset types. Their elements are kept in the entries of
+ * a dict, which map each element to itself.
+ */
+
+use super::objdict::{self, DictElements};
+use super::objiter;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult,
+};
+use super::vm::VirtualMachine;
+
+pub fn to_string(type_name: &str, elements: &DictElements) -> String {
+  if elements.is_empty() {
+    return format!("{}()", type_name);
+  }
+  let elements = format!(
+    "{{{}}}",
+    elements
+      .iter()
+      .map(|(_, element)| element.borrow().repr())
+      .collect::<Vec<_>>()
+      .join(", ")
+  );
+  if type_name == "set" {
+    elements
+  } else {
+    format!("{}({})", type_name, elements)
+  }
+}
+
+fn is_set(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::Set { .. } | PyObjectKind::FrozenSet { .. } => true,
+    _ => false,
+  }
+}
+
+fn is_frozen(obj: &PyObjectRef) -> bool {
+  match obj.borrow().kind {
+    PyObjectKind::FrozenSet { .. } => true,
+    _ => false,
+  }
+}
+
+pub fn new_set(vm: &VirtualMachine, frozen: bool) -> PyObjectRef {
+  let elements = DictElements::new();
+  if frozen {
+    PyObject::new(
+      PyObjectKind::FrozenSet { elements: elements },
+      vm.context().frozenset_type.clone(),
+    )
+  } else {
+    PyObject::new(
+      PyObjectKind::Set { elements: elements },
+      vm.context().set_type.clone(),
+    )
+  }
+}
+
+pub fn add(
+  vm: &mut VirtualMachine,
+  set: &PyObjectRef,
+  element: PyObjectRef,
+) -> Result<(), PyObjectRef> {
+  objdict::set_item(vm, set, &element.clone(), element)
+}
+
+pub fn get_elements(set: &PyObjectRef) -> Vec<PyObjectRef> {
+  objdict::get_elements(set)
+    .iter()
+    .map(|(_, element)| element.clone())
+    .collect()
+}
+
+// The elements of an iterable, in a set of their own unless it is one
+// already:
+fn to_set(
+  vm: &mut VirtualMachine,
+  iterable: &PyObjectRef,
+) -> Result<PyObjectRef, PyObjectRef> {
+  if is_set(iterable) {
+    return Ok(iterable.clone());
+  }
+  let set = new_set(vm, false);
+  for element in objiter::get_all(vm, iterable)? {
+    add(vm, &set, element)?;
+  }
+  Ok(set)
+}
+
+// The elements of a which are, or are not, in b:
+fn filter(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+  keep: bool,
+) -> Result<PyObjectRef, PyObjectRef> {
+  let result = new_set(vm, is_frozen(a));
+  for element in get_elements(a) {
+    if objdict::contains_key(vm, b, &element)? == keep {
+      add(vm, &result, element)?;
+    }
+  }
+  Ok(result)
+}
+
+fn union(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> Result<PyObjectRef, PyObjectRef> {
+  let result = new_set(vm, is_frozen(a));
+  for element in get_elements(a).into_iter().chain(get_elements(b)) {
+    add(vm, &result, element)?;
+  }
+  Ok(result)
+}
+
+fn symmetric_difference(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> Result<PyObjectRef, PyObjectRef> {
+  let result = filter(vm, a, b, false)?;
+  for element in get_elements(b) {
+    if !objdict::contains_key(vm, a, &element)? {
+      add(vm, &result, element)?;
+    }
+  }
+  Ok(result)
+}
+
+fn is_subset(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> Result<bool, PyObjectRef> {
+  for element in get_elements(a) {
+    if !objdict::contains_key(vm, b, &element)? {
+      return Ok(false);
+    }
+  }
+  Ok(true)
+}
+
+// Sets, frozen or not, are equal when they have the same elements:
+pub fn equal(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> Result<bool, PyObjectRef> {
+  Ok(
+    objdict::get_elements(a).len() == objdict::get_elements(b).len()
+      && is_subset(vm, a, b)?,
+  )
+}
+
+// Check the arguments of a set method, and take out the set itself. Methods
+// which change the set do not apply to a frozenset:
+fn method_args(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  min: usize,
+  max: usize,
+  mutable: bool,
+) -> Result<PyObjectRef, PyObjectRef> {
+  match args.args.get(0) {
+    Some(set) if is_set(set) && !(mutable && is_frozen(set)) => {
+      objdict::check_arg_count(vm, args, name, min, max)?;
+      Ok(set.clone())
+    }
+    _ => Err(vm.new_type_error(format!(
+      "descriptor '{}' requires a {} object",
+      name,
+      if mutable {
+        "'set'"
+      } else {
+        "'set' or 'frozenset'"
+      }
+    ))),
+  }
+}
+
+// Apply a binary operation to a set and each of the iterables after it:
+fn fold_args(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  operation: fn(&mut VirtualMachine, &PyObjectRef, &PyObjectRef) -> PyResult,
+) -> PyResult {
+  let set = method_args(vm, &args, name, 0, usize::max_value(), false)?;
+  // The result is a new set, even when there is nothing to combine with:
+  let mut result = union(vm, &set, &new_set(vm, false))?;
+  for other in &args.args[1..] {
+    let other = to_set(vm, other)?;
+    result = operation(vm, &result, &other)?;
+  }
+  Ok(result)
+}
+
+// Operators only combine sets with other sets:
+fn binary_operator(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  operation: fn(&mut VirtualMachine, &PyObjectRef, &PyObjectRef) -> PyResult,
+) -> PyResult {
+  let set = method_args(vm, &args, name, 1, 1, false)?;
+  if !is_set(&args.args[1]) {
+    return Ok(vm.context().not_implemented.clone());
+  }
+  operation(vm, &set, &args.args[1])
+}
+
+// In-place operators replace the elements of the set on the left, and give
+// back that same set:
+fn inplace_operator(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  operation: fn(&mut VirtualMachine, &PyObjectRef, &PyObjectRef) -> PyResult,
+) -> PyResult {
+  let set = method_args(vm, &args, name, 1, 1, true)?;
+  if !is_set(&args.args[1]) {
+    return Ok(vm.context().not_implemented.clone());
+  }
+  let result = operation(vm, &set, &args.args[1])?;
+  let elements = objdict::get_elements(&result).clone();
+  *objdict::get_mut_elements(&set) = elements;
+  Ok(set)
+}
+
+// Replace the elements of a set with the result of an operation on it:
+fn update_with(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  operation: fn(&mut VirtualMachine, &PyObjectRef, &PyObjectRef) -> PyResult,
+) -> PyResult {
+  let set = method_args(vm, &args, name, 0, usize::max_value(), true)?;
+  for other in &args.args[1..] {
+    let other = to_set(vm, other)?;
+    let result = operation(vm, &set, &other)?;
+    let elements = objdict::get_elements(&result).clone();
+    *objdict::get_mut_elements(&set) = elements;
+  }
+  Ok(vm.get_none())
+}
+
+fn set_union(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> PyResult {
+  union(vm, a, b)
+}
+
+fn set_intersection(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> PyResult {
+  filter(vm, a, b, true)
+}
+
+fn set_difference(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> PyResult {
+  filter(vm, a, b, false)
+}
+
+fn set_symmetric_difference(
+  vm: &mut VirtualMachine,
+  a: &PyObjectRef,
+  b: &PyObjectRef,
+) -> PyResult {
+  symmetric_difference(vm, a, b)
+}
+
+fn set_new(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let typ = args.shift();
+  let frozen = objtype::issubclass(&typ, &vm.context().frozenset_type);
+  let type_name = if frozen { "frozenset" } else { "set" };
+  if args.args.len() > 1 {
+    return Err(vm.new_type_error(format!(
+      "{} expected at most 1 argument, got {}",
+      type_name,
+      args.args.len()
+    )));
+  }
+  let set = new_set(vm, frozen);
+  set.borrow_mut().typ = Some(typ);
+  if let Some(iterable) = args.args.get(0) {
+    for element in objiter::get_all(vm, iterable)? {
+      add(vm, &set, element)?;
+    }
+  }
+  Ok(set)
+}
+
+fn set_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "__len__", 0, 0, false)?;
+  let len = objdict::get_elements(&set).len();
+  Ok(vm.context().new_int(len))
+}
+
+fn set_contains(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "__contains__", 1, 1, false)?;
+  let result = objdict::contains_key(vm, &set, &args.args[1])?;
+  Ok(vm.new_bool(result))
+}
+
+fn set_or(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  binary_operator(vm, args, "__or__", set_union)
+}
+
+fn set_and(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  binary_operator(vm, args, "__and__", set_intersection)
+}
+
+fn set_sub(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  binary_operator(vm, args, "__sub__", set_difference)
+}
+
+fn set_xor(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  binary_operator(vm, args, "__xor__", set_symmetric_difference)
+}
+
+fn set_ior(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  inplace_operator(vm, args, "__ior__", set_union)
+}
+
+fn set_iand(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  inplace_operator(vm, args, "__iand__", set_intersection)
+}
+
+fn set_isub(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  inplace_operator(vm, args, "__isub__", set_difference)
+}
+
+fn set_ixor(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  inplace_operator(vm, args, "__ixor__", set_symmetric_difference)
+}
+
+// Sets are ordered by inclusion:
+fn compare(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  proper: bool,
+  reversed: bool,
+) -> PyResult {
+  let set = method_args(vm, &args, name, 1, 1, false)?;
+  let other = args.args[1].clone();
+  if !is_set(&other) {
+    return Ok(vm.context().not_implemented.clone());
+  }
+  let (a, b) = if reversed { (other, set) } else { (set, other) };
+  let result = is_subset(vm, &a, &b)?
+    && !(proper
+      && objdict::get_elements(&a).len() == objdict::get_elements(&b).len());
+  Ok(vm.new_bool(result))
+}
+
+fn set_le(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  compare(vm, args, "__le__", false, false)
+}
+
+fn set_lt(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  compare(vm, args, "__lt__", true, false)
+}
+
+fn set_ge(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  compare(vm, args, "__ge__", false, true)
+}
+
+fn set_gt(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  compare(vm, args, "__gt__", true, true)
+}
+
+fn set_copy(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "copy", 0, 0, false)?;
+  union(vm, &set, &new_set(vm, false))
+}
+
+fn set_union_method(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  fold_args(vm, args, "union", set_union)
+}
+
+fn set_intersection_method(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  fold_args(vm, args, "intersection", set_intersection)
+}
+
+fn set_difference_method(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  fold_args(vm, args, "difference", set_difference)
+}
+
+fn set_symmetric_difference_method(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  let set = method_args(vm, &args, "symmetric_difference", 1, 1, false)?;
+  let other = to_set(vm, &args.args[1])?;
+  symmetric_difference(vm, &set, &other)
+}
+
+fn set_issubset(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "issubset", 1, 1, false)?;
+  let other = to_set(vm, &args.args[1])?;
+  let result = is_subset(vm, &set, &other)?;
+  Ok(vm.new_bool(result))
+}
+
+fn set_issuperset(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "issuperset", 1, 1, false)?;
+  let other = to_set(vm, &args.args[1])?;
+  let result = is_subset(vm, &other, &set)?;
+  Ok(vm.new_bool(result))
+}
+
+fn set_isdisjoint(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "isdisjoint", 1, 1, false)?;
+  let other = to_set(vm, &args.args[1])?;
+  let common = filter(vm, &set, &other, true)?;
+  let result = objdict::get_elements(&common).is_empty();
+  Ok(vm.new_bool(result))
+}
+
+fn set_add(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "add", 1, 1, true)?;
+  add(vm, &set, args.args[1].clone())?;
+  Ok(vm.get_none())
+}
+
+fn set_remove(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "remove", 1, 1, true)?;
+  match objdict::del_item(vm, &set, &args.args[1])? {
+    Some(_) => Ok(vm.get_none()),
+    None => Err(objdict::new_key_error(vm, args.args[1].clone())),
+  }
+}
+
+fn set_discard(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "discard", 1, 1, true)?;
+  objdict::del_item(vm, &set, &args.args[1])?;
+  Ok(vm.get_none())
+}
+
+fn set_pop(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "pop", 0, 0, true)?;
+  let last = objdict::get_elements(&set)
+    .last_key()
+    .map(|key| key.to_object(vm.context()));
+  match last {
+    Some(element) => {
+      objdict::del_item(vm, &set, &element)?;
+      Ok(element)
+    }
+    None => {
+      let key_error = vm.context().exceptions.key_error.clone();
+      Err(vm.new_exception(key_error, "pop from an empty set".to_string()))
+    }
+  }
+}
+
+fn set_clear(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let set = method_args(vm, &args, "clear", 0, 0, true)?;
+  *objdict::get_mut_elements(&set) = DictElements::new();
+  Ok(vm.get_none())
+}
+
+fn set_update(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  update_with(vm, args, "update", set_union)
+}
+
+fn set_intersection_update(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  update_with(vm, args, "intersection_update", set_intersection)
+}
+
+fn set_difference_update(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  update_with(vm, args, "difference_update", set_difference)
+}
+
+fn set_symmetric_difference_update(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+) -> PyResult {
+  update_with(
+    vm,
+    args,
+    "symmetric_difference_update",
+    set_symmetric_difference,
+  )
+}
+
+fn create_set_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+  name: &str,
+) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), name, vec![object.clone()], dict).unwrap()
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  create_set_type(type_type, object, "set")
+}
+
+pub fn create_frozenset_type(
+  type_type: PyObjectRef,
+  object: PyObjectRef,
+) -> PyObjectRef {
+  create_set_type(type_type, object, "frozenset")
+}
+
+pub fn init(context: &PyContext) {
+  for typ in &[&context.set_type, &context.frozenset_type] {
+    typ.set_attr("__new__", context.new_rustfunc(set_new));
+    typ.set_attr("__len__", context.new_rustfunc(set_len));
+    typ.set_attr("__contains__", context.new_rustfunc(set_contains));
+    typ.set_attr("__or__", context.new_rustfunc(set_or));
+    typ.set_attr("__and__", context.new_rustfunc(set_and));
+    typ.set_attr("__sub__", context.new_rustfunc(set_sub));
+    typ.set_attr("__xor__", context.new_rustfunc(set_xor));
+    typ.set_attr("__le__", context.new_rustfunc(set_le));
+    typ.set_attr("__lt__", context.new_rustfunc(set_lt));
+    typ.set_attr("__ge__", context.new_rustfunc(set_ge));
+    typ.set_attr("__gt__", context.new_rustfunc(set_gt));
+    typ.set_attr("copy", context.new_rustfunc(set_copy));
+    typ.set_attr("union", context.new_rustfunc(set_union_method));
+    typ.set_attr(
+      "intersection",
+      context.new_rustfunc(set_intersection_method),
+    );
+    typ.set_attr("difference", context.new_rustfunc(set_difference_method));
+    typ.set_attr(
+      "symmetric_difference",
+      context.new_rustfunc(set_symmetric_difference_method),
+    );
+    typ.set_attr("issubset", context.new_rustfunc(set_issubset));
+    typ.set_attr("issuperset", context.new_rustfunc(set_issuperset));
+    typ.set_attr("isdisjoint", context.new_rustfunc(set_isdisjoint));
+  }
+  let ref set_type = context.set_type;
+  set_type.set_attr("__ior__", context.new_rustfunc(set_ior));
+  set_type.set_attr("__iand__", context.new_rustfunc(set_iand));
+  set_type.set_attr("__isub__", context.new_rustfunc(set_isub));
+  set_type.set_attr("__ixor__", context.new_rustfunc(set_ixor));
+  set_type.set_attr("add", context.new_rustfunc(set_add));
+  set_type.set_attr("remove", context.new_rustfunc(set_remove));
+  set_type.set_attr("discard", context.new_rustfunc(set_discard));
+  set_type.set_attr("pop", context.new_rustfunc(set_pop));
+  set_type.set_attr("clear", context.new_rustfunc(set_clear));
+  set_type.set_attr("update", context.new_rustfunc(set_update));
+  set_type.set_attr(
+    "intersection_update",
+    context.new_rustfunc(set_intersection_update),
+  );
+  set_type.set_attr(
+    "difference_update",
+    context.new_rustfunc(set_difference_update),
+  );
+  set_type.set_attr(
+    "symmetric_difference_update",
+    context.new_rustfunc(set_symmetric_difference_update),
+  );
+}
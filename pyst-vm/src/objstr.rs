@@ -1,16 +1,46 @@
+/*
+ * The builtin str type. Strings are indexed by characters rather than by
+ * bytes, and can be formatted with % or with str.format.
+ */
+
+use super::format::{FormatAlign, FormatSign, FormatSpec};
+use super::objbytes;
+use super::objdict::{self, DictElements};
+use super::objiter;
 use super::objsequence::PySliceableSequence;
-use super::pyobject::{PyObjectKind, PyObjectRef, PyResult};
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult, TypeProtocol,
+};
 use super::vm::VirtualMachine;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
 
 impl PySliceableSequence for String {
   fn do_slice(&self, start: usize, stop: usize) -> Self {
-    self[start..stop].to_string()
+    self.chars().skip(start).take(stop - start).collect()
   }
   fn do_stepped_slice(&self, start: usize, stop: usize, step: usize) -> Self {
-    self[start..stop].chars().step_by(step).collect()
+    self
+      .chars()
+      .skip(start)
+      .take(stop - start)
+      .step_by(step)
+      .collect()
+  }
+  fn do_stepped_slice_reverse(
+    &self,
+    start: usize,
+    stop: usize,
+    step: usize,
+  ) -> Self {
+    let chars: Vec<char> =
+      self.chars().skip(start).take(stop - start).collect();
+    chars.into_iter().rev().step_by(step).collect()
   }
   fn len(&self) -> usize {
-    self.len()
+    self.chars().count()
   }
 }
 
@@ -19,20 +49,1447 @@ pub fn subscript(
   value: &String,
   b: PyObjectRef,
 ) -> PyResult {
-  // let value = a
   match &(*b.borrow()).kind {
     &PyObjectKind::Integer { value: ref pos } => {
-      let idx = value.get_pos(*pos);
-      Ok(vm.new_str(value[idx..idx + 1].to_string()))
+      match pos.to_i32().and_then(|pos| value.get_pos(pos)) {
+        Some(idx) => {
+          Ok(vm.new_str(value.chars().nth(idx).unwrap().to_string()))
+        }
+        None => {
+          let index_error = vm.context().exceptions.index_error.clone();
+          Err(vm.new_exception(
+            index_error,
+            "string index out of range".to_string(),
+          ))
+        }
+      }
     }
     &PyObjectKind::Slice {
       start: _,
       stop: _,
       step: _,
-    } => Ok(vm.new_str(value.get_slice_items(&b))),
-    _ => panic!(
-      "TypeError: indexing type {:?} with index {:?} is not supported (yet?)",
-      value, b
+    } => {
+      let value = value.get_slice_items(vm, &b)?;
+      Ok(vm.new_str(value))
+    }
+    _ => Err(vm.new_type_error(format!(
+      "string indices must be integers, not {}",
+      objtype::get_type_name(&b.typ())
+    ))),
+  }
+}
+
+// Quote a string the way repr does. Single quotes are preferred, unless
+// the string contains single quotes but no double quotes:
+pub fn repr_string(value: &str) -> String {
+  let quote = if value.contains('\'') && !value.contains('"') {
+    '"'
+  } else {
+    '\''
+  };
+  let mut result = String::new();
+  result.push(quote);
+  for c in value.chars() {
+    match c {
+      '\\' => result.push_str("\\\\"),
+      '\n' => result.push_str("\\n"),
+      '\r' => result.push_str("\\r"),
+      '\t' => result.push_str("\\t"),
+      c if c == quote => {
+        result.push('\\');
+        result.push(c);
+      }
+      c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+        result.push_str(&format!("\\x{:02x}", c as u32))
+      }
+      c if c.is_control() => {
+        if (c as u32) < 0x100 {
+          result.push_str(&format!("\\x{:02x}", c as u32))
+        } else if (c as u32) < 0x10000 {
+          result.push_str(&format!("\\u{:04x}", c as u32))
+        } else {
+          result.push_str(&format!("\\U{:08x}", c as u32))
+        }
+      }
+      c => result.push(c),
+    }
+  }
+  result.push(quote);
+  result
+}
+
+// Escape the characters of a repr which are not ASCII, as ascii() does:
+pub fn ascii_string(value: &str) -> String {
+  let mut result = String::new();
+  for c in value.chars() {
+    match c as u32 {
+      code if code < 0x80 => result.push(c),
+      code if code < 0x100 => result.push_str(&format!("\\x{:02x}", code)),
+      code if code < 0x10000 => result.push_str(&format!("\\u{:04x}", code)),
+      code => result.push_str(&format!("\\U{:08x}", code)),
+    }
+  }
+  result
+}
+
+pub fn get_value(obj: &PyObjectRef) -> Option<String> {
+  match obj.borrow().kind {
+    PyObjectKind::String { ref value } => Some(value.clone()),
+    _ => None,
+  }
+}
+
+fn str_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<String, PyObjectRef> {
+  match get_value(obj) {
+    Some(value) => Ok(value),
+    None => Err(vm.new_type_error(format!(
+      "must be str, not {}",
+      objtype::get_type_name(&obj.typ())
+    ))),
+  }
+}
+
+fn int_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<BigInt, PyObjectRef> {
+  match obj.borrow().kind {
+    PyObjectKind::Integer { ref value } => return Ok(value.to_bigint()),
+    PyObjectKind::Boolean { value } => return Ok(BigInt::from(value as u8)),
+    _ => {}
+  };
+  Err(vm.new_type_error(format!(
+    "'{}' object cannot be interpreted as an integer",
+    objtype::get_type_name(&obj.typ())
+  )))
+}
+
+// Sizes beyond the range of usize are far beyond any string:
+fn size_arg(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+) -> Result<usize, PyObjectRef> {
+  let value = int_arg(vm, obj)?;
+  Ok(if value.is_negative() {
+    0
+  } else {
+    value.to_usize().unwrap_or(usize::max_value())
+  })
+}
+
+// Bind the arguments of a str method to its parameters, after self. Only
+// the first `required` parameters must be given, and passing None is the
+// same as leaving an argument out:
+fn bind_args(
+  vm: &mut VirtualMachine,
+  args: &PyFuncArgs,
+  name: &str,
+  params: &[&str],
+  required: usize,
+) -> Result<(String, Vec<Option<PyObjectRef>>), PyObjectRef> {
+  let value = match args.args.get(0).and_then(get_value) {
+    Some(value) => value,
+    None => {
+      return Err(vm.new_type_error(format!(
+        "descriptor '{}' requires a 'str' object",
+        name
+      )))
+    }
+  };
+  objdict::check_arg_count(vm, args, name, 0, params.len())?;
+  let mut bound: Vec<Option<PyObjectRef>> = params
+    .iter()
+    .enumerate()
+    .map(|(i, _)| args.args.get(i + 1).cloned())
+    .collect();
+  for (keyword, value) in &args.kwargs {
+    match params.iter().position(|param| param == keyword) {
+      Some(i) => bound[i] = Some(value.clone()),
+      None => {
+        return Err(vm.new_type_error(format!(
+          "'{}' is an invalid keyword argument for {}()",
+          keyword, name
+        )))
+      }
+    }
+  }
+  for (i, param) in params.iter().enumerate().take(required) {
+    if bound[i].is_none() {
+      return Err(vm.new_type_error(format!(
+        "{}() missing required argument '{}'",
+        name, param
+      )));
+    }
+  }
+  let bound = bound
+    .into_iter()
+    .map(|arg| {
+      arg.and_then(|arg| match arg.borrow().kind {
+        PyObjectKind::PyNone => None,
+        _ => Some(arg.clone()),
+      })
+    })
+    .collect();
+  Ok((value, bound))
+}
+
+// The part of a string between the optional start and end arguments, and
+// the character position it starts at:
+fn substring(
+  vm: &mut VirtualMachine,
+  value: &String,
+  start: &Option<PyObjectRef>,
+  end: &Option<PyObjectRef>,
+) -> Result<(String, usize), PyObjectRef> {
+  let mut position =
+    |arg: &Option<PyObjectRef>, default: usize| -> Result<usize, PyObjectRef> {
+      match arg {
+        Some(arg) => {
+          let index = int_arg(vm, arg)?;
+          let index = index.to_i32().unwrap_or(if index.is_negative() {
+            i32::min_value()
+          } else {
+            i32::max_value()
+          });
+          Ok(value.get_slice_pos(index))
+        }
+        None => Ok(default),
+      }
+    };
+  let start = position(start, 0)?;
+  let end = position(end, PySliceableSequence::len(value))?;
+  if end < start {
+    return Ok((String::new(), start));
+  }
+  Ok((value.do_slice(start, end), start))
+}
+
+fn char_position(value: &str, byte_position: usize) -> usize {
+  value[..byte_position].chars().count()
+}
+
+fn str_new(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let typ = args.shift();
+  if args.args.len() > 1 {
+    return Err(vm.new_type_error(format!(
+      "str() takes at most 1 argument ({} given)",
+      args.args.len()
+    )));
+  }
+  let value = match args.args.get(0) {
+    Some(obj) => vm.to_str(obj.clone())?,
+    None => String::new(),
+  };
+  Ok(PyObject::new(PyObjectKind::String { value: value }, typ))
+}
+
+fn str_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "__len__", &[], 0)?;
+  Ok(vm.context().new_int(value.chars().count()))
+}
+
+fn str_lower(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "lower", &[], 0)?;
+  Ok(vm.new_str(value.to_lowercase()))
+}
+
+fn str_casefold(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "casefold", &[], 0)?;
+  Ok(vm.new_str(value.to_lowercase()))
+}
+
+fn str_upper(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "upper", &[], 0)?;
+  Ok(vm.new_str(value.to_uppercase()))
+}
+
+fn str_swapcase(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "swapcase", &[], 0)?;
+  let mut result = String::new();
+  for c in value.chars() {
+    if c.is_lowercase() {
+      result.extend(c.to_uppercase());
+    } else if c.is_uppercase() {
+      result.extend(c.to_lowercase());
+    } else {
+      result.push(c);
+    }
+  }
+  Ok(vm.new_str(result))
+}
+
+fn str_capitalize(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "capitalize", &[], 0)?;
+  let mut chars = value.chars();
+  let result = match chars.next() {
+    Some(first) => first
+      .to_uppercase()
+      .chain(chars.flat_map(|c| c.to_lowercase()))
+      .collect(),
+    None => String::new(),
+  };
+  Ok(vm.new_str(result))
+}
+
+// Words start after characters which are not letters:
+fn str_title(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, _) = bind_args(vm, &args, "title", &[], 0)?;
+  let mut result = String::new();
+  let mut in_word = false;
+  for c in value.chars() {
+    if in_word {
+      result.extend(c.to_lowercase());
+    } else {
+      result.extend(c.to_uppercase());
+    }
+    in_word = c.is_alphabetic();
+  }
+  Ok(vm.new_str(result))
+}
+
+fn predicate(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  test: fn(&str) -> bool,
+) -> PyResult {
+  let (value, _) = bind_args(vm, &args, name, &[], 0)?;
+  Ok(vm.new_bool(test(&value)))
+}
+
+fn str_isalnum(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isalnum", |value| {
+    !value.is_empty() && value.chars().all(char::is_alphanumeric)
+  })
+}
+
+fn str_isalpha(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isalpha", |value| {
+    !value.is_empty() && value.chars().all(char::is_alphabetic)
+  })
+}
+
+fn str_isdecimal(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isdecimal", |value| {
+    !value.is_empty() && value.chars().all(|c| c.to_digit(10).is_some())
+  })
+}
+
+fn str_isdigit(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isdigit", |value| {
+    !value.is_empty() && value.chars().all(|c| c.to_digit(10).is_some())
+  })
+}
+
+fn str_isnumeric(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isnumeric", |value| {
+    !value.is_empty() && value.chars().all(char::is_numeric)
+  })
+}
+
+fn str_isspace(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isspace", |value| {
+    !value.is_empty() && value.chars().all(char::is_whitespace)
+  })
+}
+
+fn str_isprintable(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isprintable", |value| {
+    value.chars().all(|c| !c.is_control())
+  })
+}
+
+fn str_isidentifier(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isidentifier", |value| {
+    let mut chars = value.chars();
+    match chars.next() {
+      Some(first) if first.is_alphabetic() || first == '_' => {
+        chars.all(|c| c.is_alphanumeric() || c == '_')
+      }
+      _ => false,
+    }
+  })
+}
+
+// A string is lowercase when it has cased characters, and none of them are
+// uppercase, and the other way around:
+fn str_islower(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "islower", |value| {
+    value.chars().any(char::is_lowercase)
+      && !value.chars().any(char::is_uppercase)
+  })
+}
+
+fn str_isupper(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "isupper", |value| {
+    value.chars().any(char::is_uppercase)
+      && !value.chars().any(char::is_lowercase)
+  })
+}
+
+// In a titlecased string, uppercase characters only follow uncased ones, and
+// lowercase characters only follow cased ones:
+fn str_istitle(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  predicate(vm, args, "istitle", |value| {
+    let mut cased = false;
+    let mut previous_cased = false;
+    for c in value.chars() {
+      if c.is_uppercase() {
+        if previous_cased {
+          return false;
+        }
+        previous_cased = true;
+        cased = true;
+      } else if c.is_lowercase() {
+        if !previous_cased {
+          return false;
+        }
+        previous_cased = true;
+        cased = true;
+      } else {
+        previous_cased = false;
+      }
+    }
+    cased
+  })
+}
+
+fn strip(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  left: bool,
+  right: bool,
+) -> PyResult {
+  let (value, args) = bind_args(vm, &args, name, &["chars"], 0)?;
+  let chars = match args[0] {
+    Some(ref chars) => Some(str_arg(vm, chars)?),
+    None => None,
+  };
+  let is_stripped = |c: char| match chars {
+    Some(ref chars) => chars.contains(c),
+    None => c.is_whitespace(),
+  };
+  let mut result: &str = &value;
+  if left {
+    result = result.trim_start_matches(is_stripped);
+  }
+  if right {
+    result = result.trim_end_matches(is_stripped);
+  }
+  Ok(vm.new_str(result.to_string()))
+}
+
+fn str_strip(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  strip(vm, args, "strip", true, true)
+}
+
+fn str_lstrip(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  strip(vm, args, "lstrip", true, false)
+}
+
+fn str_rstrip(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  strip(vm, args, "rstrip", false, true)
+}
+
+fn fill_char_arg(
+  vm: &mut VirtualMachine,
+  arg: &Option<PyObjectRef>,
+) -> Result<char, PyObjectRef> {
+  let fill = match arg {
+    Some(arg) => str_arg(vm, arg)?,
+    None => return Ok(' '),
+  };
+  let mut chars = fill.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) => Ok(c),
+    _ => Err(vm.new_type_error(
+      "The fill character must be exactly one character long".to_string(),
+    )),
+  }
+}
+
+fn justify(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  align: FormatAlign,
+) -> PyResult {
+  let (value, args) = bind_args(vm, &args, name, &["width", "fillchar"], 1)?;
+  let width = size_arg(vm, args[0].as_ref().unwrap())?;
+  let fill = fill_char_arg(vm, &args[1])?;
+  let len = value.chars().count();
+  if width <= len {
+    return Ok(vm.new_str(value));
+  }
+  let padding = width - len;
+  let left = match align {
+    FormatAlign::Left => 0,
+    FormatAlign::Right => padding,
+    // An odd padding has the extra character on the left for an odd width:
+    _ => padding / 2 + (padding & width & 1),
+  };
+  let fill = |count: usize| -> String {
+    ::std::iter::repeat(fill).take(count).collect()
+  };
+  let result = format!("{}{}{}", fill(left), value, fill(padding - left));
+  Ok(vm.new_str(result))
+}
+
+fn str_center(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  justify(vm, args, "center", FormatAlign::Center)
+}
+
+fn str_ljust(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  justify(vm, args, "ljust", FormatAlign::Left)
+}
+
+fn str_rjust(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  justify(vm, args, "rjust", FormatAlign::Right)
+}
+
+// Pad with zeros after the sign:
+fn str_zfill(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) = bind_args(vm, &args, "zfill", &["width"], 1)?;
+  let width = size_arg(vm, args[0].as_ref().unwrap())?;
+  let len = value.chars().count();
+  if width <= len {
+    return Ok(vm.new_str(value));
+  }
+  let zeros = "0".repeat(width - len);
+  let result = if value.starts_with('+') || value.starts_with('-') {
+    format!("{}{}{}", &value[..1], zeros, &value[1..])
+  } else {
+    format!("{}{}", zeros, value)
+  };
+  Ok(vm.new_str(result))
+}
+
+fn str_encode(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) =
+    bind_args(vm, &args, "encode", &["encoding", "errors"], 0)?;
+  let encoding = match args[0] {
+    Some(ref arg) => str_arg(vm, arg)?,
+    None => "utf-8".to_string(),
+  };
+  let errors = match args[1] {
+    Some(ref arg) => str_arg(vm, arg)?,
+    None => "strict".to_string(),
+  };
+  let value = objbytes::encode(vm, &value, &encoding, &errors)?;
+  Ok(vm.context().new_bytes(value))
+}
+
+fn str_expandtabs(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) = bind_args(vm, &args, "expandtabs", &["tabsize"], 0)?;
+  let tab_size = match args[0] {
+    Some(ref arg) => size_arg(vm, arg)?,
+    None => 8,
+  };
+  let mut result = String::new();
+  let mut column = 0;
+  for c in value.chars() {
+    match c {
+      '\t' => {
+        if tab_size > 0 {
+          let spaces = tab_size - column % tab_size;
+          result.push_str(&" ".repeat(spaces));
+          column += spaces;
+        }
+      }
+      '\n' | '\r' => {
+        result.push(c);
+        column = 0;
+      }
+      c => {
+        result.push(c);
+        column += 1;
+      }
+    }
+  }
+  Ok(vm.new_str(result))
+}
+
+fn find(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  reverse: bool,
+) -> Result<Option<usize>, PyObjectRef> {
+  let (value, args) = bind_args(vm, &args, name, &["sub", "start", "end"], 1)?;
+  let sub = str_arg(vm, args[0].as_ref().unwrap())?;
+  let (text, start) = substring(vm, &value, &args[1], &args[2])?;
+  let found = if reverse {
+    text.rfind(&sub as &str)
+  } else {
+    text.find(&sub as &str)
+  };
+  Ok(found.map(|position| start + char_position(&text, position)))
+}
+
+fn str_find(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let position = find(vm, args, "find", false)?;
+  Ok(match position {
+    Some(position) => vm.context().new_int(position),
+    None => vm.context().new_int(-1),
+  })
+}
+
+fn str_rfind(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let position = find(vm, args, "rfind", true)?;
+  Ok(match position {
+    Some(position) => vm.context().new_int(position),
+    None => vm.context().new_int(-1),
+  })
+}
+
+fn str_index(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  match find(vm, args, "index", false)? {
+    Some(position) => Ok(vm.context().new_int(position)),
+    None => Err(vm.new_value_error("substring not found".to_string())),
+  }
+}
+
+fn str_rindex(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  match find(vm, args, "rindex", true)? {
+    Some(position) => Ok(vm.context().new_int(position)),
+    None => Err(vm.new_value_error("substring not found".to_string())),
+  }
+}
+
+fn str_count(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) =
+    bind_args(vm, &args, "count", &["sub", "start", "end"], 1)?;
+  let sub = str_arg(vm, args[0].as_ref().unwrap())?;
+  let (text, _) = substring(vm, &value, &args[1], &args[2])?;
+  // The empty string is found between each of the characters:
+  let count = if sub.is_empty() {
+    text.chars().count() + 1
+  } else {
+    text.matches(&sub as &str).count()
+  };
+  Ok(vm.context().new_int(count))
+}
+
+fn affix_matches(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  test: fn(&str, &str) -> bool,
+) -> PyResult {
+  let (value, args) =
+    bind_args(vm, &args, name, &["affix", "start", "end"], 1)?;
+  let affix = args[0].as_ref().unwrap();
+  let affixes = match affix.borrow().kind {
+    PyObjectKind::Tuple { ref elements } => elements.clone(),
+    _ => vec![affix.clone()],
+  };
+  let (text, _) = substring(vm, &value, &args[1], &args[2])?;
+  for affix in affixes {
+    match get_value(&affix) {
+      Some(ref affix) if test(&text, affix) => return Ok(vm.new_bool(true)),
+      Some(_) => {}
+      None => {
+        return Err(vm.new_type_error(format!(
+          "{} first arg must be str or a tuple of str, not {}",
+          name,
+          objtype::get_type_name(&affix.typ())
+        )))
+      }
+    }
+  }
+  Ok(vm.new_bool(false))
+}
+
+fn str_startswith(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  affix_matches(vm, args, "startswith", |text, affix| {
+    text.starts_with(affix)
+  })
+}
+
+fn str_endswith(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  affix_matches(vm, args, "endswith", |text, affix| text.ends_with(affix))
+}
+
+fn str_replace(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) =
+    bind_args(vm, &args, "replace", &["old", "new", "count"], 2)?;
+  let old = str_arg(vm, args[0].as_ref().unwrap())?;
+  let new = str_arg(vm, args[1].as_ref().unwrap())?;
+  let count = match args[2] {
+    Some(ref count) => int_arg(vm, count)?,
+    None => BigInt::from(-1),
+  };
+  let result = if count.is_negative() {
+    value.replace(&old as &str, &new)
+  } else {
+    let count = count.to_usize().unwrap_or(usize::max_value());
+    value.replacen(&old as &str, &new, count)
+  };
+  Ok(vm.new_str(result))
+}
+
+fn separator_arg(
+  vm: &mut VirtualMachine,
+  arg: &Option<PyObjectRef>,
+) -> Result<Option<String>, PyObjectRef> {
+  match arg {
+    Some(arg) => {
+      let separator = str_arg(vm, arg)?;
+      if separator.is_empty() {
+        Err(vm.new_value_error("empty separator".to_string()))
+      } else {
+        Ok(Some(separator))
+      }
+    }
+    None => Ok(None),
+  }
+}
+
+// Split on runs of whitespace, ignoring whitespace at the start. The rest
+// of the string is the last item once maxsplit items have been split off:
+fn split_whitespace(value: &str, maxsplit: Option<usize>) -> Vec<String> {
+  let mut result = vec![];
+  let mut rest = value.trim_start();
+  while !rest.is_empty() {
+    if Some(result.len()) == maxsplit {
+      result.push(rest.to_string());
+      break;
+    }
+    let end = rest.find(char::is_whitespace).unwrap_or_else(|| rest.len());
+    result.push(rest[..end].to_string());
+    rest = rest[end..].trim_start();
+  }
+  result
+}
+
+fn split(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  reverse: bool,
+) -> PyResult {
+  let (value, args) = bind_args(vm, &args, name, &["sep", "maxsplit"], 0)?;
+  let separator = separator_arg(vm, &args[0])?;
+  let maxsplit = match args[1] {
+    Some(ref maxsplit) => {
+      let maxsplit = int_arg(vm, maxsplit)?;
+      if maxsplit.is_negative() {
+        None
+      } else {
+        maxsplit.to_usize()
+      }
+    }
+    None => None,
+  };
+  let parts: Vec<String> = match (separator, reverse) {
+    (None, false) => split_whitespace(&value, maxsplit),
+    (None, true) => {
+      // Split the reversed string, and reverse the parts back:
+      let reversed: String = value.chars().rev().collect();
+      split_whitespace(&reversed, maxsplit)
+        .into_iter()
+        .rev()
+        .map(|part| part.chars().rev().collect())
+        .collect()
+    }
+    (Some(separator), false) => match maxsplit {
+      Some(maxsplit) => value
+        .splitn(maxsplit + 1, &separator as &str)
+        .map(String::from)
+        .collect(),
+      None => value.split(&separator as &str).map(String::from).collect(),
+    },
+    (Some(separator), true) => {
+      let mut parts: Vec<String> = match maxsplit {
+        Some(maxsplit) => value
+          .rsplitn(maxsplit + 1, &separator as &str)
+          .map(String::from)
+          .collect(),
+        None => value.rsplit(&separator as &str).map(String::from).collect(),
+      };
+      parts.reverse();
+      parts
+    }
+  };
+  let parts = parts.into_iter().map(|part| vm.new_str(part)).collect();
+  Ok(vm.context().new_list(parts))
+}
+
+fn str_split(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  split(vm, args, "split", false)
+}
+
+fn str_rsplit(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  split(vm, args, "rsplit", true)
+}
+
+fn is_line_break(c: char) -> bool {
+  match c {
+    '\n' | '\r' | '\x0b' | '\x0c' | '\x1c' | '\x1d' | '\x1e' | '\u{85}'
+    | '\u{2028}' | '\u{2029}' => true,
+    _ => false,
+  }
+}
+
+fn str_splitlines(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) = bind_args(vm, &args, "splitlines", &["keepends"], 0)?;
+  let keep_ends = match args[0] {
+    Some(ref keep_ends) => !int_arg(vm, keep_ends)?.is_zero(),
+    None => false,
+  };
+  let mut lines = vec![];
+  let mut line = String::new();
+  let mut chars = value.chars().peekable();
+  while let Some(c) = chars.next() {
+    if !is_line_break(c) {
+      line.push(c);
+      continue;
+    }
+    let mut end = c.to_string();
+    if c == '\r' && chars.peek() == Some(&'\n') {
+      end.push(chars.next().unwrap());
+    }
+    if keep_ends {
+      line.push_str(&end);
+    }
+    lines.push(vm.new_str(line));
+    line = String::new();
+  }
+  if !line.is_empty() {
+    lines.push(vm.new_str(line));
+  }
+  Ok(vm.context().new_list(lines))
+}
+
+fn partition(
+  vm: &mut VirtualMachine,
+  args: PyFuncArgs,
+  name: &str,
+  reverse: bool,
+) -> PyResult {
+  let (value, args) = bind_args(vm, &args, name, &["sep"], 1)?;
+  let separator = separator_arg(vm, &args[0])?.unwrap();
+  let found = if reverse {
+    value.rfind(&separator as &str)
+  } else {
+    value.find(&separator as &str)
+  };
+  let parts = match found {
+    Some(position) => vec![
+      value[..position].to_string(),
+      separator.clone(),
+      value[position + separator.len()..].to_string(),
+    ],
+    None if reverse => vec![String::new(), String::new(), value],
+    None => vec![value, String::new(), String::new()],
+  };
+  let parts = parts.into_iter().map(|part| vm.new_str(part)).collect();
+  Ok(vm.context().new_tuple(parts))
+}
+
+fn str_partition(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  partition(vm, args, "partition", false)
+}
+
+fn str_rpartition(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  partition(vm, args, "rpartition", true)
+}
+
+fn str_join(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let (value, args) = bind_args(vm, &args, "join", &["iterable"], 1)?;
+  let mut parts = vec![];
+  for (i, item) in objiter::get_all(vm, args[0].as_ref().unwrap())?
+    .iter()
+    .enumerate()
+  {
+    match get_value(item) {
+      Some(part) => parts.push(part),
+      None => {
+        return Err(vm.new_type_error(format!(
+          "sequence item {}: expected str instance, {} found",
+          i,
+          objtype::get_type_name(&item.typ())
+        )))
+      }
+    }
+  }
+  Ok(vm.new_str(parts.join(&value)))
+}
+
+fn str_format(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let value = match args.args.get(0).and_then(get_value) {
+    Some(value) => value,
+    None => {
+      return Err(vm.new_type_error(
+        "descriptor 'format' requires a 'str' object".to_string(),
+      ))
+    }
+  };
+  let mut numbering = FieldNumbering::Unknown;
+  let result = format_fields(
+    vm,
+    &value,
+    &args.args[1..],
+    &args.kwargs,
+    &mut numbering,
+    2,
+  )?;
+  Ok(vm.new_str(result))
+}
+
+// Format obj according to a format specification, as format(obj, spec)
+// does:
+pub fn format_value(
+  vm: &mut VirtualMachine,
+  obj: &PyObjectRef,
+  spec: &str,
+) -> Result<String, PyObjectRef> {
+  let spec_obj = vm.new_str(spec.to_string());
+  if let Some(method) = objtype::get_special_method(obj, "__format__") {
+    let args = PyFuncArgs::new(vec![obj.clone(), spec_obj], vec![]);
+    let result = vm.invoke(method, args)?;
+    return match get_value(&result) {
+      Some(value) => Ok(value),
+      None => Err(vm.new_type_error(format!(
+        "__format__ must return a str, not {}",
+        objtype::get_type_name(&result.typ())
+      ))),
+    };
+  }
+  let result = match obj.borrow().kind {
+    PyObjectKind::Boolean { value } if !spec.is_empty() => {
+      FormatSpec::parse(spec)
+        .and_then(|spec| spec.format_int(&BigInt::from(value as u8)))
+    }
+    PyObjectKind::Integer { ref value } => FormatSpec::parse(spec)
+      .and_then(|spec| spec.format_int(&value.to_bigint())),
+    PyObjectKind::Float { value } => {
+      FormatSpec::parse(spec).and_then(|spec| spec.format_float(value))
+    }
+    PyObjectKind::String { ref value } => {
+      FormatSpec::parse(spec).and_then(|spec| spec.format_string(value))
+    }
+    _ if spec.is_empty() => return vm.to_str(obj.clone()),
+    _ => {
+      return Err(vm.new_type_error(format!(
+        "unsupported format string passed to {}.__format__",
+        objtype::get_type_name(&obj.typ())
+      )))
+    }
+  };
+  result.map_err(|message| vm.new_value_error(message))
+}
+
+// Fields are either all numbered automatically, or all by hand:
+#[derive(PartialEq)]
+enum FieldNumbering {
+  Unknown,
+  Automatic(usize),
+  Manual,
+}
+
+// Take the text of a replacement field, which starts after a '{', up to its
+// matching '}':
+fn take_field(chars: &[char], pos: &mut usize) -> Option<String> {
+  let start = *pos;
+  let mut depth = 1;
+  while *pos < chars.len() {
+    match chars[*pos] {
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+        if depth == 0 {
+          let field = chars[start..*pos].iter().collect();
+          *pos += 1;
+          return Some(field);
+        }
+      }
+      _ => {}
+    }
+    *pos += 1;
+  }
+  None
+}
+
+fn format_fields(
+  vm: &mut VirtualMachine,
+  format: &str,
+  args: &[PyObjectRef],
+  kwargs: &[(String, PyObjectRef)],
+  numbering: &mut FieldNumbering,
+  recursion: usize,
+) -> Result<String, PyObjectRef> {
+  if recursion == 0 {
+    return Err(
+      vm.new_value_error("Max string recursion exceeded".to_string()),
+    );
+  }
+  let chars: Vec<char> = format.chars().collect();
+  let mut result = String::new();
+  let mut pos = 0;
+  while pos < chars.len() {
+    let c = chars[pos];
+    pos += 1;
+    match c {
+      '{' if chars.get(pos) == Some(&'{') => {
+        result.push('{');
+        pos += 1;
+      }
+      '}' if chars.get(pos) == Some(&'}') => {
+        result.push('}');
+        pos += 1;
+      }
+      '}' => {
+        return Err(vm.new_value_error(
+          "Single '}' encountered in format string".to_string(),
+        ))
+      }
+      '{' => {
+        let field = match take_field(&chars, &mut pos) {
+          Some(field) => field,
+          None => {
+            return Err(vm.new_value_error(
+              "expected '}' before end of string".to_string(),
+            ))
+          }
+        };
+        let text =
+          format_field(vm, &field, args, kwargs, numbering, recursion)?;
+        result.push_str(&text);
+      }
+      c => result.push(c),
+    }
+  }
+  Ok(result)
+}
+
+// A replacement field is "name!conversion:spec", where the name is an
+// argument followed by attributes and indexes, as in "0.real" or "a[1]":
+fn format_field(
+  vm: &mut VirtualMachine,
+  field: &str,
+  args: &[PyObjectRef],
+  kwargs: &[(String, PyObjectRef)],
+  numbering: &mut FieldNumbering,
+  recursion: usize,
+) -> Result<String, PyObjectRef> {
+  let (name, spec) = match field.find(':') {
+    Some(position) => (&field[..position], &field[position + 1..]),
+    None => (field, ""),
+  };
+  let (name, conversion) = match name.find('!') {
+    Some(position) => (&name[..position], Some(&name[position + 1..])),
+    None => (name, None),
+  };
+  let first_end = name.find(|c| c == '.' || c == '[').unwrap_or(name.len());
+  let (first, mut rest) = name.split_at(first_end);
+
+  let mut obj = if first.is_empty() || first.chars().all(|c| c.is_ascii_digit())
+  {
+    let index = if first.is_empty() {
+      match *numbering {
+        FieldNumbering::Unknown => 0,
+        FieldNumbering::Automatic(index) => index,
+        FieldNumbering::Manual => {
+          return Err(vm.new_value_error(
+            "cannot switch from manual field specification to automatic field numbering".to_string(),
+          ))
+        }
+      }
+    } else {
+      if let FieldNumbering::Automatic(_) = *numbering {
+        return Err(vm.new_value_error(
+          "cannot switch from automatic field numbering to manual field specification".to_string(),
+        ));
+      }
+      first.parse::<usize>().unwrap_or(usize::max_value())
+    };
+    *numbering = if first.is_empty() {
+      FieldNumbering::Automatic(index + 1)
+    } else {
+      FieldNumbering::Manual
+    };
+    match args.get(index) {
+      Some(arg) => arg.clone(),
+      None => {
+        let index_error = vm.context().exceptions.index_error.clone();
+        return Err(vm.new_exception(
+          index_error,
+          format!(
+            "Replacement index {} out of range for positional args tuple",
+            index
+          ),
+        ));
+      }
+    }
+  } else {
+    match kwargs.iter().find(|(name, _)| name == first) {
+      Some((_, value)) => value.clone(),
+      None => {
+        let key = vm.new_str(first.to_string());
+        return Err(objdict::new_key_error(vm, key));
+      }
+    }
+  };
+
+  while !rest.is_empty() {
+    if rest.starts_with('.') {
+      let end = rest[1..]
+        .find(|c| c == '.' || c == '[')
+        .map_or(rest.len(), |end| end + 1);
+      let attr = rest[1..end].to_string();
+      obj = vm.get_attribute(obj, &attr)?;
+      rest = &rest[end..];
+    } else {
+      let end = match rest.find(']') {
+        Some(end) => end,
+        None => {
+          return Err(
+            vm.new_value_error("Missing ']' in format string".to_string()),
+          )
+        }
+      };
+      let key = &rest[1..end];
+      let key = match key.parse::<usize>() {
+        Ok(index) => vm.context().new_int(index),
+        Err(_) => vm.new_str(key.to_string()),
+      };
+      obj = vm.subscript(obj, key)?;
+      rest = &rest[end + 1..];
+    }
+  }
+
+  let obj = match conversion {
+    None => obj,
+    Some("s") => {
+      let value = vm.to_str(obj)?;
+      vm.new_str(value)
+    }
+    Some("r") => {
+      let value = vm.to_repr(obj)?;
+      vm.new_str(value)
+    }
+    Some("a") => {
+      let value = vm.to_ascii(obj)?;
+      vm.new_str(value)
+    }
+    Some(conversion) => {
+      return Err(vm.new_value_error(format!(
+        "Unknown conversion specifier {}",
+        conversion
+      )))
+    }
+  };
+  // The specification may have replacement fields of its own:
+  if spec.contains('{') {
+    let spec = format_fields(vm, spec, args, kwargs, numbering, recursion - 1)?;
+    format_value(vm, &obj, &spec)
+  } else {
+    format_value(vm, &obj, spec)
+  }
+}
+
+// Printf-style formatting, as in "%s: %5.2f" % (name, value). A mapping
+// supplies the values of "%(key)s" conversions:
+pub fn format_percent(
+  vm: &mut VirtualMachine,
+  format: &str,
+  values: PyObjectRef,
+) -> Result<String, PyObjectRef> {
+  let (args, mapping) = match values.borrow().kind {
+    PyObjectKind::Tuple { ref elements } => (elements.clone(), None),
+    PyObjectKind::Dict { .. } => (vec![values.clone()], Some(values.clone())),
+    _ => (vec![values.clone()], None),
+  };
+  let mut next_arg = 0;
+  let mut take_arg = |vm: &mut VirtualMachine| match args.get(next_arg) {
+    Some(arg) => {
+      next_arg += 1;
+      Ok(arg.clone())
+    }
+    None => Err(
+      vm.new_type_error("not enough arguments for format string".to_string()),
     ),
+  };
+
+  let chars: Vec<char> = format.chars().collect();
+  let mut result = String::new();
+  let mut pos = 0;
+  while pos < chars.len() {
+    if chars[pos] != '%' {
+      result.push(chars[pos]);
+      pos += 1;
+      continue;
+    }
+    let start = pos;
+    pos += 1;
+    let mut key_value = None;
+    if chars.get(pos) == Some(&'(') {
+      let end = match chars[pos..].iter().position(|c| *c == ')') {
+        Some(end) => pos + end,
+        None => {
+          return Err(vm.new_value_error("incomplete format key".to_string()))
+        }
+      };
+      let key: String = chars[pos + 1..end].iter().collect();
+      pos = end + 1;
+      let mapping = match mapping {
+        Some(ref mapping) => mapping.clone(),
+        None => {
+          return Err(
+            vm.new_type_error("format requires a mapping".to_string()),
+          )
+        }
+      };
+      let key = vm.new_str(key);
+      key_value = Some(vm.subscript(mapping, key)?);
+    }
+
+    let mut spec = FormatSpec {
+      fill: ' ',
+      align: Some(FormatAlign::Right),
+      sign: None,
+      alternate: false,
+      width: None,
+      grouping: None,
+      precision: None,
+      kind: None,
+    };
+    let mut zero = false;
+    while pos < chars.len() {
+      match chars[pos] {
+        '-' => spec.align = Some(FormatAlign::Left),
+        '+' => spec.sign = Some(FormatSign::Plus),
+        ' ' if spec.sign.is_none() => spec.sign = Some(FormatSign::Space),
+        ' ' => {}
+        '#' => spec.alternate = true,
+        '0' => zero = true,
+        _ => break,
+      }
+      pos += 1;
+    }
+    // A width or precision must fit in a C int, as in CPython:
+    let mut number = |vm: &mut VirtualMachine,
+                      pos: &mut usize,
+                      name: &str|
+     -> Result<Option<usize>, PyObjectRef> {
+      let value = if chars.get(*pos) == Some(&'*') {
+        *pos += 1;
+        let arg = take_arg(vm)?;
+        Some(size_arg(vm, &arg)?)
+      } else {
+        let start = *pos;
+        while *pos < chars.len() && chars[*pos].is_ascii_digit() {
+          *pos += 1;
+        }
+        let digits: String = chars[start..*pos].iter().collect();
+        if digits.is_empty() {
+          None
+        } else {
+          Some(digits.parse::<usize>().unwrap_or(usize::max_value()))
+        }
+      };
+      match value {
+        Some(value) if value > i32::max_value() as usize => {
+          Err(vm.new_value_error(format!("{} too big", name)))
+        }
+        value => Ok(value),
+      }
+    };
+    spec.width = number(vm, &mut pos, "width")?;
+    if chars.get(pos) == Some(&'.') {
+      pos += 1;
+      spec.precision = Some(number(vm, &mut pos, "precision")?.unwrap_or(0));
+    }
+    while pos < chars.len() && "hlL".contains(chars[pos]) {
+      pos += 1;
+    }
+    let conversion = match chars.get(pos) {
+      Some(conversion) => *conversion,
+      None => return Err(vm.new_value_error("incomplete format".to_string())),
+    };
+    pos += 1;
+    if conversion == '%' {
+      result.push('%');
+      continue;
+    }
+    let value = match key_value {
+      Some(value) => value,
+      None => take_arg(vm)?,
+    };
+    let numeric = "diuoxXeEfFgGc".contains(conversion);
+    if zero && numeric && spec.align != Some(FormatAlign::Left) {
+      spec.fill = '0';
+      spec.align = Some(FormatAlign::AfterSign);
+    }
+
+    let type_name = objtype::get_type_name(&value.typ());
+    let text = match conversion {
+      's' | 'r' | 'a' => {
+        let text = match conversion {
+          's' => vm.to_str(value)?,
+          'r' => vm.to_repr(value)?,
+          _ => vm.to_ascii(value)?,
+        };
+        spec.sign = None;
+        spec.alternate = false;
+        spec.format_string(&text)
+      }
+      'c' => {
+        let text = match value.borrow().kind {
+          PyObjectKind::Integer { ref value } => value
+            .to_u32()
+            .and_then(::std::char::from_u32)
+            .map(|c| c.to_string()),
+          PyObjectKind::String { ref value } if value.chars().count() == 1 => {
+            Some(value.clone())
+          }
+          _ => None,
+        };
+        match text {
+          Some(text) => {
+            spec.fill = ' ';
+            spec.sign = None;
+            spec.precision = None;
+            spec.format_string(&text)
+          }
+          None => {
+            return Err(
+              vm.new_type_error("%c requires int or char".to_string()),
+            )
+          }
+        }
+      }
+      'd' | 'i' | 'u' | 'o' | 'x' | 'X' => {
+        let integer = match value.borrow().kind {
+          PyObjectKind::Integer { ref value } => Some(value.to_bigint()),
+          PyObjectKind::Boolean { value } => Some(BigInt::from(value as u8)),
+          PyObjectKind::Float { value } if "diu".contains(conversion) => {
+            BigInt::from_f64(value.trunc())
+          }
+          _ => None,
+        };
+        let integer = match integer {
+          Some(integer) => integer,
+          None if "diu".contains(conversion) => {
+            return Err(vm.new_type_error(format!(
+              "%{} format: a real number is required, not {}",
+              conversion, type_name
+            )))
+          }
+          None => {
+            return Err(vm.new_type_error(format!(
+              "%{} format: an integer is required, not {}",
+              conversion, type_name
+            )))
+          }
+        };
+        spec.kind = match conversion {
+          'o' | 'x' | 'X' => Some(conversion),
+          _ => Some('d'),
+        };
+        spec.precision = None;
+        spec.format_int(&integer)
+      }
+      'e' | 'E' | 'f' | 'F' | 'g' | 'G' => {
+        let float = match value.borrow().kind {
+          PyObjectKind::Float { value } => Some(value),
+          PyObjectKind::Integer { ref value } => value.to_f64(),
+          PyObjectKind::Boolean { value } => Some(value as u8 as f64),
+          _ => None,
+        };
+        let float = match float {
+          Some(float) => float,
+          None => {
+            return Err(vm.new_type_error(format!(
+              "must be real number, not {}",
+              type_name
+            )))
+          }
+        };
+        spec.kind = Some(conversion);
+        spec.precision = Some(spec.precision.unwrap_or(6));
+        spec.format_float(float)
+      }
+      _ => {
+        return Err(vm.new_value_error(format!(
+          "unsupported format character '{}' ({:#x}) at index {}",
+          conversion,
+          conversion as u32,
+          pos - 1 - start + chars[..start].len()
+        )))
+      }
+    };
+    match text {
+      Ok(text) => result.push_str(&text),
+      Err(message) => return Err(vm.new_value_error(message)),
+    }
   }
+  if mapping.is_none() && next_arg < args.len() {
+    return Err(vm.new_type_error(
+      "not all arguments converted during string formatting".to_string(),
+    ));
+  }
+  Ok(result)
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "str", vec![object.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref str_type = context.str_type;
+  str_type.set_attr("__new__", context.new_rustfunc(str_new));
+  str_type.set_attr("__len__", context.new_rustfunc(str_len));
+  str_type.set_attr("capitalize", context.new_rustfunc(str_capitalize));
+  str_type.set_attr("casefold", context.new_rustfunc(str_casefold));
+  str_type.set_attr("center", context.new_rustfunc(str_center));
+  str_type.set_attr("count", context.new_rustfunc(str_count));
+  str_type.set_attr("encode", context.new_rustfunc(str_encode));
+  str_type.set_attr("endswith", context.new_rustfunc(str_endswith));
+  str_type.set_attr("expandtabs", context.new_rustfunc(str_expandtabs));
+  str_type.set_attr("find", context.new_rustfunc(str_find));
+  str_type.set_attr("format", context.new_rustfunc(str_format));
+  str_type.set_attr("index", context.new_rustfunc(str_index));
+  str_type.set_attr("isalnum", context.new_rustfunc(str_isalnum));
+  str_type.set_attr("isalpha", context.new_rustfunc(str_isalpha));
+  str_type.set_attr("isdecimal", context.new_rustfunc(str_isdecimal));
+  str_type.set_attr("isdigit", context.new_rustfunc(str_isdigit));
+  str_type.set_attr("isidentifier", context.new_rustfunc(str_isidentifier));
+  str_type.set_attr("islower", context.new_rustfunc(str_islower));
+  str_type.set_attr("isnumeric", context.new_rustfunc(str_isnumeric));
+  str_type.set_attr("isprintable", context.new_rustfunc(str_isprintable));
+  str_type.set_attr("isspace", context.new_rustfunc(str_isspace));
+  str_type.set_attr("istitle", context.new_rustfunc(str_istitle));
+  str_type.set_attr("isupper", context.new_rustfunc(str_isupper));
+  str_type.set_attr("join", context.new_rustfunc(str_join));
+  str_type.set_attr("ljust", context.new_rustfunc(str_ljust));
+  str_type.set_attr("lower", context.new_rustfunc(str_lower));
+  str_type.set_attr("lstrip", context.new_rustfunc(str_lstrip));
+  str_type.set_attr("partition", context.new_rustfunc(str_partition));
+  str_type.set_attr("replace", context.new_rustfunc(str_replace));
+  str_type.set_attr("rfind", context.new_rustfunc(str_rfind));
+  str_type.set_attr("rindex", context.new_rustfunc(str_rindex));
+  str_type.set_attr("rjust", context.new_rustfunc(str_rjust));
+  str_type.set_attr("rpartition", context.new_rustfunc(str_rpartition));
+  str_type.set_attr("rsplit", context.new_rustfunc(str_rsplit));
+  str_type.set_attr("rstrip", context.new_rustfunc(str_rstrip));
+  str_type.set_attr("split", context.new_rustfunc(str_split));
+  str_type.set_attr("splitlines", context.new_rustfunc(str_splitlines));
+  str_type.set_attr("startswith", context.new_rustfunc(str_startswith));
+  str_type.set_attr("strip", context.new_rustfunc(str_strip));
+  str_type.set_attr("swapcase", context.new_rustfunc(str_swapcase));
+  str_type.set_attr("title", context.new_rustfunc(str_title));
+  str_type.set_attr("upper", context.new_rustfunc(str_upper));
+  str_type.set_attr("zfill", context.new_rustfunc(str_zfill));
 }
@@ -0,0 +1,64 @@
+/*
+ * Builtin tuple type. Most of what tuples do is shared with lists, and is
+ * handled by the VM for both of them.
+ */
+
+use super::objdict::DictElements;
+use super::objiter;
+use super::objtype;
+use super::pyobject::{
+  AttributeProtocol, PyContext, PyFuncArgs, PyObject, PyObjectKind,
+  PyObjectRef, PyResult,
+};
+use super::vm::VirtualMachine;
+
+// tuple() or tuple(iterable):
+fn tuple_new(vm: &mut VirtualMachine, mut args: PyFuncArgs) -> PyResult {
+  let typ = args.shift();
+  if args.args.len() > 1 {
+    return Err(vm.new_type_error(format!(
+      "tuple expected at most 1 argument, got {}",
+      args.args.len()
+    )));
+  }
+  let elements = match args.args.get(0) {
+    Some(iterable) => objiter::get_all(vm, iterable)?,
+    None => vec![],
+  };
+  Ok(PyObject::new(
+    PyObjectKind::Tuple { elements: elements },
+    typ,
+  ))
+}
+
+fn tuple_len(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
+  let length = match args.args.get(0) {
+    Some(obj) => match obj.borrow().kind {
+      PyObjectKind::Tuple { ref elements } => Some(elements.len()),
+      _ => None,
+    },
+    None => None,
+  };
+  match length {
+    Some(length) => Ok(vm.context().new_int(length)),
+    None => Err(vm.new_type_error(
+      "descriptor '__len__' requires a 'tuple' object".to_string(),
+    )),
+  }
+}
+
+pub fn create_type(type_type: PyObjectRef, object: PyObjectRef) -> PyObjectRef {
+  let dict = PyObject::new(
+    PyObjectKind::Dict {
+      elements: DictElements::new(),
+    },
+    type_type.clone(),
+  );
+  objtype::new(type_type.clone(), "tuple", vec![object.clone()], dict).unwrap()
+}
+
+pub fn init(context: &PyContext) {
+  let ref tuple_type = context.tuple_type;
+  tuple_type.set_attr("__new__", context.new_rustfunc(tuple_new));
+  tuple_type.set_attr("__len__", context.new_rustfunc(tuple_len));
+}
@@ -30,7 +30,7 @@ pub fn init(context: &PyContext) {
 fn type_mro(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   match _mro(args.args[0].clone()) {
     Some(mro) => Ok(vm.context().new_tuple(mro)),
-    None => Err(vm.new_exception("Only classes have an MRO.".to_string())),
+    None => Err(vm.new_type_error("Only classes have an MRO.".to_string())),
   }
 }
 
@@ -45,11 +45,61 @@ fn _mro(cls: PyObjectRef) -> Option<Vec<PyObjectRef>> {
   }
 }
 
+pub fn isinstance(obj: &PyObjectRef, cls: &PyObjectRef) -> bool {
+  issubclass(&obj.typ(), cls)
+}
+
+pub fn issubclass(typ: &PyObjectRef, cls: &PyObjectRef) -> bool {
+  match _mro(typ.clone()) {
+    Some(mro) => mro.into_iter().any(|c| c.get_id() == cls.get_id()),
+    None => false,
+  }
+}
+
+// The classes named by the second argument of isinstance() and issubclass(),
+// or by an except clause, which is a class or a (nested) tuple of classes.
+// None when it is something else:
+pub fn get_classes(cls: &PyObjectRef) -> Option<Vec<PyObjectRef>> {
+  match cls.borrow().kind {
+    PyObjectKind::Class { .. } => Some(vec![cls.clone()]),
+    PyObjectKind::Tuple { ref elements } => {
+      let mut classes = vec![];
+      for element in elements {
+        classes.extend(get_classes(element)?);
+      }
+      Some(classes)
+    }
+    _ => None,
+  }
+}
+
+// Special methods are looked up on the type of an object rather than on the
+// object itself, so that instance attributes cannot override operators:
+pub fn get_special_method(
+  obj: &PyObjectRef,
+  name: &str,
+) -> Option<PyObjectRef> {
+  let cls = obj.typ();
+  let name = String::from(name);
+  if cls.has_attr(&name) {
+    Some(cls.get_attr(&name))
+  } else {
+    None
+  }
+}
+
+pub fn get_type_name(typ: &PyObjectRef) -> String {
+  match typ.borrow().kind {
+    PyObjectKind::Class { ref name, .. } => name.clone(),
+    _ => panic!("Cannot get type_name of non-type type {:?}", typ),
+  }
+}
+
 fn type_dict(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   match args.args[0].borrow().kind {
     PyObjectKind::Class { ref dict, .. } => Ok(dict.clone()),
     _ => {
-      Err(vm.new_exception("type_dict must be called on a class.".to_string()))
+      Err(vm.new_type_error("type_dict must be called on a class.".to_string()))
     }
   }
 }
@@ -61,12 +111,19 @@ pub fn type_new(vm: &mut VirtualMachine, args: PyFuncArgs) -> PyResult {
   } else if args.args.len() == 4 {
     let typ = args.args[0].clone();
     let name = args.args[1].to_str().unwrap();
-    let mut bases = args.args[2].to_vec().unwrap();
+    let mut bases = match args.args[2].to_vec() {
+      Some(bases) => bases,
+      None => {
+        return Err(vm.new_type_error(
+          "type.__new__() argument 2 must be tuple".to_string(),
+        ))
+      }
+    };
     bases.push(vm.context().object_type.clone());
     let dict = args.args[3].clone();
-    new(typ, &name, bases, dict)
+    new_class(vm, typ, &name, bases, dict)
   } else {
-    Err(vm.new_exception(format!("TypeError: type_new: {:?}", args)))
+    Err(vm.new_type_error("type() takes 1 or 3 arguments".to_string()))
   }
 }
 
@@ -96,9 +153,7 @@ pub fn get_attribute(
     if attr_class.has_attr(&String::from("__get__")) {
       return vm.invoke(
         attr_class.get_attr(&String::from("__get__")),
-        PyFuncArgs {
-          args: vec![attr, obj, cls],
-        },
+        PyFuncArgs::new(vec![attr, obj, cls], vec![]),
       );
     }
   }
@@ -108,10 +163,15 @@ pub fn get_attribute(
   } else if cls.has_attr(name) {
     Ok(cls.get_attr(name))
   } else {
-    Err(vm.new_exception(format!(
-      "AttributeError: {:?} object has no attribute {}",
-      cls, name
-    )))
+    let attribute_error = vm.context().exceptions.attribute_error.clone();
+    Err(vm.new_exception(
+      attribute_error,
+      format!(
+        "'{}' object has no attribute '{}'",
+        get_type_name(&cls),
+        name
+      ),
+    ))
   }
 }
 
@@ -180,6 +240,25 @@ pub fn new(
   ))
 }
 
+// A class defined in Python. Instances which compare equal must hash the
+// same, so a class which defines __eq__ but not __hash__ makes them
+// unhashable:
+pub fn new_class(
+  vm: &mut VirtualMachine,
+  typ: PyObjectRef,
+  name: &str,
+  bases: Vec<PyObjectRef>,
+  dict: PyObjectRef,
+) -> PyResult {
+  {
+    let mut elements = objdict::get_mut_elements(&dict);
+    if elements.contains_key("__eq__") && !elements.contains_key("__hash__") {
+      elements.insert(String::from("__hash__"), vm.get_none());
+    }
+  }
+  new(typ, name, bases, dict)
+}
+
 pub fn call(
   vm: &mut VirtualMachine,
   typ: PyObjectRef,
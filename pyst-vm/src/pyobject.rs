@@ -1,17 +1,30 @@
 use super::bytecode;
 use super::exceptions;
+use super::frame::Frame;
+use super::objbool;
+use super::objbytes;
+use super::objcomplex;
 use super::objdict;
+use super::objfloat;
 use super::objfunction;
+use super::objgenerator;
 use super::objint;
+use super::objiter;
 use super::objlist;
+use super::objnone;
+use super::objnumber;
 use super::objobject;
+use super::objrange;
+use super::objset;
+use super::objstr;
+use super::objtuple;
 use super::objtype;
 use super::vm::VirtualMachine;
+use num_bigint::BigInt;
+use num_complex::Complex64;
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
 use std::fmt;
-use std::ops::{Add, Div, Mul, Rem, Sub};
 use std::rc::Rc;
 
 pub type PyRef<T> = Rc<RefCell<T>>;
@@ -21,16 +34,30 @@ pub type PyResult = Result<PyObjectRef, PyObjectRef>;
 #[derive(Debug)]
 pub struct PyContext {
   pub type_type: PyObjectRef,
+  pub none: PyObjectRef,
   pub none_type: PyObjectRef,
+  pub not_implemented: PyObjectRef,
+  pub not_implemented_type: PyObjectRef,
   pub dict_type: PyObjectRef,
   pub int_type: PyObjectRef,
+  pub bool_type: PyObjectRef,
+  pub float_type: PyObjectRef,
+  pub complex_type: PyObjectRef,
   pub list_type: PyObjectRef,
   pub tuple_type: PyObjectRef,
+  pub str_type: PyObjectRef,
+  pub bytes_type: PyObjectRef,
+  pub bytearray_type: PyObjectRef,
+  pub set_type: PyObjectRef,
+  pub frozenset_type: PyObjectRef,
+  pub range_type: PyObjectRef,
+  pub iter_type: PyObjectRef,
   pub function_type: PyObjectRef,
   pub bound_method_type: PyObjectRef,
+  pub generator_type: PyObjectRef,
   pub member_descriptor_type: PyObjectRef,
   pub object_type: PyObjectRef,
-  pub base_exception_type: PyObjectRef,
+  pub exceptions: exceptions::ExceptionZoo,
 }
 
 fn _nothing() -> PyObjectRef {
@@ -70,46 +97,126 @@ impl PyContext {
       type_type.clone(),
       object_type.clone(),
     );
+    let exceptions =
+      exceptions::ExceptionZoo::new(&type_type, &object_type, &dict_type);
+
+    let int_type = objint::create_type(type_type.clone(), object_type.clone());
+    let bool_type = objbool::create_type(type_type.clone(), int_type.clone());
+    let float_type =
+      objfloat::create_type(type_type.clone(), object_type.clone());
+    let complex_type =
+      objcomplex::create_type(type_type.clone(), object_type.clone());
+    let range_type =
+      objrange::create_type(type_type.clone(), object_type.clone());
+    let iter_type =
+      objiter::create_type(type_type.clone(), object_type.clone());
+    let generator_type =
+      objgenerator::create_type(type_type.clone(), object_type.clone());
+    let str_type = objstr::create_type(type_type.clone(), object_type.clone());
+    let bytes_type =
+      objbytes::create_type(type_type.clone(), object_type.clone());
+    let bytearray_type =
+      objbytes::create_bytearray_type(type_type.clone(), object_type.clone());
+    let set_type = objset::create_type(type_type.clone(), object_type.clone());
+    let frozenset_type =
+      objset::create_frozenset_type(type_type.clone(), object_type.clone());
+    let tuple_type =
+      objtuple::create_type(type_type.clone(), object_type.clone());
+    let none_type =
+      objnone::create_type(type_type.clone(), object_type.clone());
+    let not_implemented_type = objnone::create_not_implemented_type(
+      type_type.clone(),
+      object_type.clone(),
+    );
 
     let context = PyContext {
-      int_type: objint::create_type(type_type.clone()),
+      int_type: int_type,
+      bool_type: bool_type,
+      float_type: float_type,
+      complex_type: complex_type,
       list_type: objlist::create_type(type_type.clone(), object_type.clone()),
-      tuple_type: type_type.clone(),
+      tuple_type: tuple_type,
+      str_type: str_type,
+      bytes_type: bytes_type,
+      bytearray_type: bytearray_type,
+      set_type: set_type,
+      frozenset_type: frozenset_type,
+      range_type: range_type,
+      iter_type: iter_type,
       dict_type: dict_type.clone(),
-      none_type: PyObject::new(PyObjectKind::PyNone, type_type.clone()),
+      none: PyObject::new(PyObjectKind::PyNone, none_type.clone()),
+      none_type: none_type,
+      not_implemented: PyObject::new(
+        PyObjectKind::NotImplemented,
+        not_implemented_type.clone(),
+      ),
+      not_implemented_type: not_implemented_type,
       object_type: object_type.clone(),
       function_type: function_type,
       bound_method_type: bound_method_type,
+      generator_type: generator_type,
       member_descriptor_type: member_descriptor_type,
       type_type: type_type.clone(),
-      base_exception_type: exceptions::create_base_exception_type(
-        type_type.clone(),
-        object_type.clone(),
-      ),
+      exceptions: exceptions,
     };
     objtype::init(&context);
+    objint::init(&context);
+    objbool::init(&context);
+    objfloat::init(&context);
+    objcomplex::init(&context);
     objlist::init(&context);
+    objtuple::init(&context);
+    objnone::init(&context);
+    objrange::init(&context);
+    objiter::init(&context);
+    objgenerator::init(&context);
     objobject::init(&context);
     objdict::init(&context);
-    // TODO: create exception hierarchy here?
-    // exceptions::create_zoo(&context);
+    objset::init(&context);
+    objstr::init(&context);
+    objbytes::init(&context);
+    exceptions::init(&context);
     context
   }
 
-  pub fn new_int(&self, i: i32) -> PyObjectRef {
-    PyObject::new(PyObjectKind::Integer { value: i }, self.int_type.clone())
+  pub fn new_int<T: Into<objint::IntValue>>(&self, i: T) -> PyObjectRef {
+    PyObject::new(
+      PyObjectKind::Integer { value: i.into() },
+      self.int_type.clone(),
+    )
   }
 
   pub fn new_float(&self, i: f64) -> PyObjectRef {
-    PyObject::new(PyObjectKind::Float { value: i }, self.type_type.clone())
+    PyObject::new(PyObjectKind::Float { value: i }, self.float_type.clone())
+  }
+
+  pub fn new_complex(&self, i: Complex64) -> PyObjectRef {
+    PyObject::new(
+      PyObjectKind::Complex { value: i },
+      self.complex_type.clone(),
+    )
   }
 
   pub fn new_str(&self, s: String) -> PyObjectRef {
-    PyObject::new(PyObjectKind::String { value: s }, self.type_type.clone())
+    PyObject::new(PyObjectKind::String { value: s }, self.str_type.clone())
+  }
+
+  pub fn new_bytes(&self, value: Vec<u8>) -> PyObjectRef {
+    PyObject::new(
+      PyObjectKind::Bytes { value: value },
+      self.bytes_type.clone(),
+    )
+  }
+
+  pub fn new_bytearray(&self, value: Vec<u8>) -> PyObjectRef {
+    PyObject::new(
+      PyObjectKind::ByteArray { value: value },
+      self.bytearray_type.clone(),
+    )
   }
 
   pub fn new_bool(&self, b: bool) -> PyObjectRef {
-    PyObject::new(PyObjectKind::Boolean { value: b }, self.type_type.clone())
+    PyObject::new(PyObjectKind::Boolean { value: b }, self.bool_type.clone())
   }
 
   pub fn new_tuple(&self, elements: Vec<PyObjectRef>) -> PyObjectRef {
@@ -129,7 +236,7 @@ impl PyContext {
   pub fn new_dict(&self) -> PyObjectRef {
     PyObject::new(
       PyObjectKind::Dict {
-        elements: HashMap::new(),
+        elements: objdict::DictElements::new(),
       },
       self.dict_type.clone(),
     )
@@ -148,6 +255,16 @@ impl PyContext {
     .into_ref()
   }
 
+  // A cell holds a variable shared between a function and the closures of
+  // its nested functions. It is empty while the variable is unbound:
+  pub fn new_cell(&self, contents: Option<PyObjectRef>) -> PyObjectRef {
+    PyObject {
+      kind: PyObjectKind::Cell { contents: contents },
+      typ: None,
+    }
+    .into_ref()
+  }
+
   pub fn new_module(&self, name: &String, scope: PyObjectRef) -> PyObjectRef {
     PyObject::new(
       PyObjectKind::Module {
@@ -168,12 +285,18 @@ impl PyContext {
   pub fn new_function(
     &self,
     code_obj: PyObjectRef,
-    scope: PyObjectRef,
+    globals: PyObjectRef,
+    closure: Vec<PyObjectRef>,
+    defaults: PyObjectRef,
+    kw_defaults: PyObjectRef,
   ) -> PyObjectRef {
     PyObject::new(
       PyObjectKind::Function {
         code: code_obj,
-        scope: scope,
+        globals: globals,
+        closure: closure,
+        defaults: defaults,
+        kw_defaults: kw_defaults,
       },
       self.function_type.clone(),
     )
@@ -214,6 +337,11 @@ pub struct Scope {
   pub parent: Option<PyObjectRef>, // Parent scope
 }
 
+thread_local! {
+  // The containers whose text is being built:
+  static TEXT_IN_PROGRESS: RefCell<Vec<usize>> = RefCell::new(vec![]);
+}
+
 pub struct PyObject {
   pub kind: PyObjectKind,
   pub typ: Option<PyObjectRef>,
@@ -229,42 +357,98 @@ impl PyObject {
   }
 
   pub fn str(&self) -> String {
+    self.to_text(false)
+  }
+
+  // The representation of strings is quoted. Containers always show their
+  // elements this way:
+  pub fn repr(&self) -> String {
+    self.to_text(true)
+  }
+
+  // A container which contains itself shows as [...], (...) or {...} inside
+  // itself, rather than recursing forever:
+  fn to_text(&self, repr: bool) -> String {
+    let placeholder = match self.kind {
+      PyObjectKind::List { .. } => "[...]",
+      PyObjectKind::Tuple { .. } => "(...)",
+      PyObjectKind::Dict { .. } => "{...}",
+      _ => return self.to_text_unguarded(repr),
+    };
+    let id = self as *const PyObject as usize;
+    let entered = TEXT_IN_PROGRESS.with(|in_progress| {
+      let mut in_progress = in_progress.borrow_mut();
+      if in_progress.contains(&id) {
+        false
+      } else {
+        in_progress.push(id);
+        true
+      }
+    });
+    if !entered {
+      return placeholder.to_string();
+    }
+    let text = self.to_text_unguarded(repr);
+    TEXT_IN_PROGRESS.with(|in_progress| in_progress.borrow_mut().pop());
+    text
+  }
+
+  fn to_text_unguarded(&self, repr: bool) -> String {
     match self.kind {
+      PyObjectKind::String { ref value } if repr => objstr::repr_string(value),
       PyObjectKind::String { ref value } => value.clone(),
-      PyObjectKind::Integer { ref value } => format!("{:?}", value),
-      PyObjectKind::Float { ref value } => format!("{:?}", value),
-      PyObjectKind::Boolean { ref value } => format!("{:?}", value),
+      PyObjectKind::Bytes { ref value } => objbytes::repr_bytes(value),
+      PyObjectKind::ByteArray { ref value } => {
+        format!("bytearray({})", objbytes::repr_bytes(value))
+      }
+      PyObjectKind::Integer { ref value } => format!("{}", value),
+      PyObjectKind::Float { ref value } => objfloat::to_string(*value),
+      PyObjectKind::Complex { ref value } => objcomplex::to_string(*value),
+      PyObjectKind::Boolean { ref value } => {
+        String::from(if *value { "True" } else { "False" })
+      }
       PyObjectKind::List { ref elements } => format!(
         "[{}]",
         elements
           .iter()
-          .map(|elem| elem.borrow().str())
+          .map(|elem| elem.borrow().repr())
           .collect::<Vec<_>>()
           .join(", ")
       ),
       PyObjectKind::Tuple { ref elements } => {
         if elements.len() == 1 {
-          format!("({},)", elements[0].borrow().str())
+          format!("({},)", elements[0].borrow().repr())
         } else {
           format!(
             "({})",
             elements
               .iter()
-              .map(|elem| elem.borrow().str())
+              .map(|elem| elem.borrow().repr())
               .collect::<Vec<_>>()
               .join(", ")
           )
         }
       }
+      PyObjectKind::Set { ref elements } => objset::to_string("set", elements),
+      PyObjectKind::FrozenSet { ref elements } => {
+        objset::to_string("frozenset", elements)
+      }
       PyObjectKind::Dict { ref elements } => format!(
         "{{ {} }}",
         elements
           .iter()
-          .map(|elem| format!("{}: {}", elem.0, elem.1.borrow().str()))
+          .map(|elem| {
+            let key = match elem.0 {
+              objdict::DictKey::String(ref key) => objstr::repr_string(key),
+              objdict::DictKey::Object(ref key) => key.borrow().repr(),
+            };
+            format!("{}: {}", key, elem.1.borrow().repr())
+          })
           .collect::<Vec<_>>()
           .join(", ")
       ),
       PyObjectKind::PyNone => String::from("None"),
+      PyObjectKind::NotImplemented => String::from("NotImplemented"),
       PyObjectKind::Class {
         ref name,
         dict: ref _dict,
@@ -272,22 +456,31 @@ impl PyObject {
       } => format!("<class '{}'>", name),
       PyObjectKind::Instance { dict: _ } => format!("<instance>"),
       PyObjectKind::Code { code: _ } => format!("<code>"),
-      PyObjectKind::Function { code: _, scope: _ } => format!("<func>"),
+      PyObjectKind::Function { .. } => format!("<func>"),
       PyObjectKind::BoundMethod { .. } => format!("<bound-method>"),
+      PyObjectKind::Generator { ref name, .. } => {
+        format!("<generator object {}>", name)
+      }
       PyObjectKind::RustFunction { function: _ } => format!("<rustfunc>"),
       PyObjectKind::Module { ref name, dict: _ } => {
         format!("<module '{}'>", name)
       }
       PyObjectKind::Scope { ref scope } => format!("<scope '{:?}'>", scope),
-      PyObjectKind::NameError { ref name } => format!("NameError: {:?}", name),
+      PyObjectKind::Cell { .. } => format!("<cell>"),
       PyObjectKind::Slice {
         ref start,
         ref stop,
         ref step,
       } => format!("<slice '{:?}:{:?}:{:?}'>", start, stop, step),
+      PyObjectKind::Range {
+        ref start,
+        ref stop,
+        ref step,
+      } => objrange::to_string(start, stop, step),
       PyObjectKind::Iterator {
         ref position,
         ref iterated_obj,
+        ..
       } => format!(
         "<iter pos {} in {}>",
         position,
@@ -296,35 +489,6 @@ impl PyObject {
     }
   }
 
-  // Implement iterator protocol:
-  pub fn nxt(&mut self) -> Option<PyObjectRef> {
-    match self.kind {
-      PyObjectKind::Iterator {
-        ref mut position,
-        iterated_obj: ref iterated_obj_ref,
-      } => {
-        let iterated_obj = &*iterated_obj_ref.borrow_mut();
-        match iterated_obj.kind {
-          PyObjectKind::List { ref elements } => {
-            if *position < elements.len() {
-              let obj_ref = elements[*position].clone();
-              *position += 1;
-              Some(obj_ref)
-            } else {
-              None
-            }
-          }
-          _ => {
-            panic!("NOT IMPL");
-          }
-        }
-      }
-      _ => {
-        panic!("NOT IMPL");
-      }
-    }
-  }
-
   // Move this object into a reference object, transferring ownership.
   pub fn into_ref(self) -> PyObjectRef {
     Rc::new(RefCell::new(self))
@@ -391,6 +555,8 @@ pub trait AttributeProtocol {
   fn get_attr(&self, attr_name: &String) -> PyObjectRef;
   fn set_attr(&self, attr_name: &str, value: PyObjectRef);
   fn has_attr(&self, attr_name: &String) -> bool;
+  // Remove an attribute, returning whether there was one:
+  fn del_attr(&self, attr_name: &String) -> bool;
 }
 
 fn class_get_item(
@@ -466,12 +632,22 @@ impl AttributeProtocol for PyObjectRef {
       ref kind => unimplemented!("set_attr unimplemented for: {:?}", kind),
     };
   }
+
+  fn del_attr(&self, attr_name: &String) -> bool {
+    match self.borrow().kind {
+      PyObjectKind::Instance { ref dict }
+      | PyObjectKind::Class { ref dict, .. } => dict.del_item(attr_name),
+      _ => false,
+    }
+  }
 }
 
 pub trait DictProtocol {
   fn contains_key(&self, k: &String) -> bool;
   fn get_item(&self, k: &String) -> PyObjectRef;
   fn set_item(&self, k: &String, v: PyObjectRef);
+  // Remove an item, returning whether there was one:
+  fn del_item(&self, k: &String) -> bool;
 }
 
 impl DictProtocol for PyObjectRef {
@@ -486,7 +662,7 @@ impl DictProtocol for PyObjectRef {
 
   fn get_item(&self, k: &String) -> PyObjectRef {
     match self.borrow().kind {
-      PyObjectKind::Dict { ref elements } => elements[k].clone(),
+      PyObjectKind::Dict { ref elements } => elements.get(k).unwrap().clone(),
       PyObjectKind::Module { name: _, ref dict } => dict.get_item(k),
       PyObjectKind::Scope { ref scope } => scope.locals.get_item(k),
       _ => panic!("TODO"),
@@ -510,6 +686,15 @@ impl DictProtocol for PyObjectRef {
       _ => panic!("TODO"),
     };
   }
+
+  fn del_item(&self, k: &String) -> bool {
+    match self.borrow_mut().kind {
+      PyObjectKind::Dict { ref mut elements } => elements.remove(k).is_some(),
+      PyObjectKind::Module { ref dict, .. } => dict.del_item(k),
+      PyObjectKind::Scope { ref scope } => scope.locals.del_item(k),
+      _ => panic!("TODO"),
+    }
+  }
 }
 
 pub trait ToRust {
@@ -534,17 +719,31 @@ impl ToRust for PyObjectRef {
 #[derive(Debug, Default, Clone)]
 pub struct PyFuncArgs {
   pub args: Vec<PyObjectRef>,
-  // TODO: add kwargs here
+  pub kwargs: Vec<(String, PyObjectRef)>,
 }
 
 impl PyFuncArgs {
+  pub fn new(
+    args: Vec<PyObjectRef>,
+    kwargs: Vec<(String, PyObjectRef)>,
+  ) -> PyFuncArgs {
+    PyFuncArgs {
+      args: args,
+      kwargs: kwargs,
+    }
+  }
   pub fn insert(&self, item: PyObjectRef) -> PyFuncArgs {
-    let mut args = PyFuncArgs {
-      args: self.args.clone(),
-    };
+    let mut args = self.clone();
     args.args.insert(0, item);
     return args;
   }
+  pub fn get_kwarg(&self, key: &str) -> Option<PyObjectRef> {
+    self
+      .kwargs
+      .iter()
+      .find(|(name, _)| name == key)
+      .map(|(_, value)| value.clone())
+  }
   pub fn shift(&mut self) -> PyObjectRef {
     self.args.remove(0)
   }
@@ -556,12 +755,21 @@ pub enum PyObjectKind {
   String {
     value: String,
   },
+  Bytes {
+    value: Vec<u8>,
+  },
+  ByteArray {
+    value: Vec<u8>,
+  },
   Integer {
-    value: i32,
+    value: objint::IntValue,
   },
   Float {
     value: f64,
   },
+  Complex {
+    value: Complex64,
+  },
   Boolean {
     value: bool,
   },
@@ -572,40 +780,64 @@ pub enum PyObjectKind {
     elements: Vec<PyObjectRef>,
   },
   Dict {
-    elements: HashMap<String, PyObjectRef>,
+    elements: objdict::DictElements,
+  },
+  Set {
+    elements: objdict::DictElements,
+  },
+  FrozenSet {
+    elements: objdict::DictElements,
   },
+  Range {
+    start: BigInt,
+    stop: BigInt,
+    step: BigInt,
+  },
+  // A dict or set is iterated as it is, and must keep the size it had when
+  // the iteration started:
   Iterator {
     position: usize,
     iterated_obj: PyObjectRef,
+    size: Option<usize>,
   },
   Slice {
     start: Option<i32>,
     stop: Option<i32>,
     step: Option<i32>,
   },
-  NameError {
-    // TODO: improve python object and type system
-    name: String,
-  },
   Code {
     code: bytecode::CodeObject,
   },
   Function {
     code: PyObjectRef,
-    scope: PyObjectRef,
+    globals: PyObjectRef,
+    closure: Vec<PyObjectRef>,
+    defaults: PyObjectRef,
+    kw_defaults: PyObjectRef,
   },
   BoundMethod {
     function: PyObjectRef,
     object: PyObjectRef,
   },
+  // The frame of a generator is only missing while it runs, or after it
+  // finished:
+  Generator {
+    name: String,
+    frame: Option<Frame>,
+    running: bool,
+  },
   Scope {
     scope: Scope,
   },
+  Cell {
+    contents: Option<PyObjectRef>,
+  },
   Module {
     name: String,
     dict: PyObjectRef,
   },
   PyNone,
+  NotImplemented,
   Class {
     name: String,
     dict: PyObjectRef,
@@ -623,31 +855,38 @@ impl fmt::Debug for PyObjectKind {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match self {
       &PyObjectKind::String { ref value } => write!(f, "str \"{}\"", value),
+      &PyObjectKind::Bytes { ref value } => write!(f, "bytes {:?}", value),
+      &PyObjectKind::ByteArray { ref value } => {
+        write!(f, "bytearray {:?}", value)
+      }
       &PyObjectKind::Integer { ref value } => write!(f, "int {}", value),
       &PyObjectKind::Float { ref value } => write!(f, "float {}", value),
+      &PyObjectKind::Complex { ref value } => write!(f, "complex {}", value),
       &PyObjectKind::Boolean { ref value } => write!(f, "boolean {}", value),
       &PyObjectKind::List { elements: _ } => write!(f, "list"),
       &PyObjectKind::Tuple { elements: _ } => write!(f, "tuple"),
       &PyObjectKind::Dict { elements: _ } => write!(f, "dict"),
-      &PyObjectKind::Iterator {
-        position: _,
-        iterated_obj: _,
-      } => write!(f, "iterator"),
+      &PyObjectKind::Set { elements: _ } => write!(f, "set"),
+      &PyObjectKind::FrozenSet { elements: _ } => write!(f, "frozenset"),
+      &PyObjectKind::Range { .. } => write!(f, "range"),
+      &PyObjectKind::Iterator { .. } => write!(f, "iterator"),
       &PyObjectKind::Slice {
         start: _,
         stop: _,
         step: _,
       } => write!(f, "slice"),
-      &PyObjectKind::NameError { name: _ } => write!(f, "NameError"),
       &PyObjectKind::Code { ref code } => write!(f, "code: {:?}", code),
-      &PyObjectKind::Function { code: _, scope: _ } => write!(f, "function"),
+      &PyObjectKind::Function { .. } => write!(f, "function"),
       &PyObjectKind::BoundMethod {
         ref function,
         ref object,
       } => write!(f, "bound-method: {:?} of {:?}", function, object),
+      &PyObjectKind::Generator { .. } => write!(f, "generator"),
       &PyObjectKind::Module { name: _, dict: _ } => write!(f, "module"),
       &PyObjectKind::Scope { scope: _ } => write!(f, "scope"),
+      &PyObjectKind::Cell { .. } => write!(f, "cell"),
       &PyObjectKind::PyNone => write!(f, "None"),
+      &PyObjectKind::NotImplemented => write!(f, "NotImplemented"),
       &PyObjectKind::Class {
         ref name,
         dict: _,
@@ -659,237 +898,63 @@ impl fmt::Debug for PyObjectKind {
   }
 }
 
-impl<'a> Add<&'a PyObject> for &'a PyObject {
-  type Output = PyObjectKind;
-
-  fn add(self, rhs: &'a PyObject) -> Self::Output {
-    match self.kind {
-      PyObjectKind::Integer { value: ref value1 } => match &rhs.kind {
-        PyObjectKind::Integer { value: ref value2 } => PyObjectKind::Integer {
-          value: value1 + value2,
-        },
-        PyObjectKind::Float { value: ref value2 } => PyObjectKind::Float {
-          value: (*value1 as f64) + value2,
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      PyObjectKind::Float { value: ref value1 } => match &rhs.kind {
-        PyObjectKind::Float { value: ref value2 } => PyObjectKind::Float {
-          value: value1 + value2,
-        },
-        PyObjectKind::Integer { value: ref value2 } => PyObjectKind::Float {
-          value: value1 + (*value2 as f64),
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      PyObjectKind::String { value: ref value1 } => match rhs.kind {
-        PyObjectKind::String { value: ref value2 } => PyObjectKind::String {
-          value: format!("{}{}", value1, value2),
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      PyObjectKind::List { elements: ref e1 } => match rhs.kind {
-        PyObjectKind::List { elements: ref e2 } => PyObjectKind::List {
-          elements: e1.iter().chain(e2.iter()).map(|e| e.clone()).collect(),
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      _ => {
-        // TODO: Lookup __add__ method in dictionary?
-        panic!("NOT IMPL");
-      }
-    }
-  }
-}
-
-impl<'a> Sub<&'a PyObject> for &'a PyObject {
-  type Output = PyObjectKind;
-
-  fn sub(self, rhs: &'a PyObject) -> Self::Output {
-    match self.kind {
-      PyObjectKind::Integer { value: value1 } => match rhs.kind {
-        PyObjectKind::Integer { value: value2 } => PyObjectKind::Integer {
-          value: value1 - value2,
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      _ => {
-        panic!("NOT IMPL");
-      }
-    }
-  }
-}
-
-impl<'a> Mul<&'a PyObject> for &'a PyObject {
-  type Output = PyObjectKind;
-
-  fn mul(self, rhs: &'a PyObject) -> Self::Output {
-    match self.kind {
-      PyObjectKind::Integer { value: value1 } => match rhs.kind {
-        PyObjectKind::Integer { value: value2 } => PyObjectKind::Integer {
-          value: value1 * value2,
-        },
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      PyObjectKind::String { value: ref value1 } => match rhs.kind {
-        PyObjectKind::Integer { value: value2 } => {
-          let mut result = String::new();
-          for _x in 0..value2 {
-            result.push_str(value1.as_str());
-          }
-          PyObjectKind::String { value: result }
-        }
-        _ => {
-          panic!("NOT IMPL");
-        }
-      },
-      _ => {
-        panic!("NOT IMPL");
-      }
-    }
-  }
-}
-
-impl<'a> Div<&'a PyObject> for &'a PyObject {
-  type Output = PyObjectKind;
-
-  fn div(self, rhs: &'a PyObject) -> Self::Output {
-    match (&self.kind, &rhs.kind) {
-      (
-        PyObjectKind::Integer { value: value1 },
-        PyObjectKind::Integer { value: value2 },
-      ) => PyObjectKind::Integer {
-        value: value1 / value2,
-      },
-      _ => {
-        panic!("NOT IMPL");
-      }
-    }
-  }
-}
-
-impl<'a> Rem<&'a PyObject> for &'a PyObject {
-  type Output = PyObjectKind;
-
-  fn rem(self, rhs: &'a PyObject) -> Self::Output {
-    match (&self.kind, &rhs.kind) {
-      (
-        PyObjectKind::Integer { value: value1 },
-        PyObjectKind::Integer { value: value2 },
-      ) => PyObjectKind::Integer {
-        value: value1 % value2,
-      },
-      (kind1, kind2) => {
-        unimplemented!("% not implemented for kinds: {:?} {:?}", kind1, kind2);
-      }
-    }
-  }
-}
-
 // impl<'a> PartialEq<&'a PyObject> for &'a PyObject {
 impl PartialEq for PyObject {
   fn eq(&self, other: &PyObject) -> bool {
     match (&self.kind, &other.kind) {
-      (
-        PyObjectKind::Integer { value: ref v1i },
-        PyObjectKind::Integer { value: ref v2i },
-      ) => v2i == v1i,
-      (
-        PyObjectKind::Float { value: ref v1i },
-        PyObjectKind::Float { value: ref v2i },
-      ) => v2i == v1i,
       (
         PyObjectKind::String { value: ref v1i },
         PyObjectKind::String { value: ref v2i },
       ) => *v2i == *v1i,
+      (a, b) if objbytes::get_kind_value(a).is_some() => {
+        objbytes::get_kind_value(a) == objbytes::get_kind_value(b)
+      }
       /*
       (&NativeType::Float(ref v1f), &NativeType::Float(ref v2f)) => {
           curr_frame.stack.push(Rc::new(NativeType::Boolean(v2f == v1f)));
       },
       */
-      (
-        PyObjectKind::List { elements: ref l1 },
-        PyObjectKind::List { elements: ref l2 },
-      )
-      | (
-        PyObjectKind::Tuple { elements: ref l1 },
-        PyObjectKind::Tuple { elements: ref l2 },
-      ) => {
-        if l1.len() == l2.len() {
-          Iterator::zip(l1.iter(), l2.iter()).all(|elem| elem.0 == elem.1)
-        } else {
-          false
+      (PyObjectKind::PyNone, PyObjectKind::PyNone) => true,
+      (a, b) if objnumber::to_number(a).is_some() => {
+        match (objnumber::to_number(a), objnumber::to_number(b)) {
+          (Some(v1), Some(v2)) => objnumber::eq(&v1, &v2),
+          _ => false,
         }
       }
-      _ => panic!(
-        "TypeError in COMPARE_OP: can't compare {:?} with {:?}",
-        self, other
-      ),
+      // Without a more specific notion of equality, objects are only equal
+      // to themselves:
+      _ => self as *const PyObject == other as *const PyObject,
     }
   }
 }
 
 impl Eq for PyObject {}
 
+// Orderable objects, returns None when the two objects cannot be compared:
 impl PartialOrd for PyObject {
   fn partial_cmp(&self, other: &PyObject) -> Option<Ordering> {
-    Some(self.cmp(other))
-  }
-}
-
-impl Ord for PyObject {
-  fn cmp(&self, other: &PyObject) -> Ordering {
     match (&self.kind, &other.kind) {
       (
-        PyObjectKind::Integer { value: v1 },
-        PyObjectKind::Integer { value: ref v2 },
-      ) => v1.cmp(v2),
-      _ => panic!("Not impl"),
+        PyObjectKind::String { value: ref v1 },
+        PyObjectKind::String { value: ref v2 },
+      ) => Some(v1.cmp(v2)),
+      (a, b) if objbytes::get_kind_value(a).is_some() => {
+        match (objbytes::get_kind_value(a), objbytes::get_kind_value(b)) {
+          (Some(v1), Some(v2)) => Some(v1.cmp(v2)),
+          _ => None,
+        }
+      }
+      (a, b) => match (objnumber::to_number(a), objnumber::to_number(b)) {
+        (Some(v1), Some(v2)) => objnumber::partial_cmp(&v1, &v2),
+        _ => None,
+      },
     }
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::{PyContext, PyObjectKind};
-
-  #[test]
-  fn test_add_py_integers() {
-    let ctx = PyContext::new();
-    let a = ctx.new_int(33);
-    let b = ctx.new_int(12);
-    let c = &*a.borrow() + &*b.borrow();
-    match c {
-      PyObjectKind::Integer { value } => assert_eq!(value, 45),
-      _ => assert!(false),
-    }
-  }
-
-  #[test]
-  fn test_multiply_str() {
-    let ctx = PyContext::new();
-    let a = ctx.new_str(String::from("Hello "));
-    let b = ctx.new_int(4);
-    let c = &*a.borrow() * &*b.borrow();
-    match c {
-      PyObjectKind::String { value } => {
-        assert_eq!(value, String::from("Hello Hello Hello Hello "))
-      }
-      _ => assert!(false),
-    }
-  }
+  use super::PyContext;
 
   #[test]
   fn test_type_type() {
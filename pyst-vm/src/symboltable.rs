@@ -0,0 +1,726 @@
+/*
+ * Find out, before compiling a program, in which scope each of its names
+ * lives. A name bound in a function is local to it, unless declared global or
+ * nonlocal. A local of a function which nested functions refer to lives in a
+ * cell, which is shared with the closures of those functions.
+ */
+
+extern crate pyst_parser;
+
+use self::pyst_parser::ast;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolScope {
+  Local,  // Local to a function
+  Global, // In the module namespace or the builtins
+  Free,   // In a cell of an enclosing function
+  Cell,   // Local to a function, in a cell shared with nested functions
+  Name,   // In the locals of a module or class body, else in the globals
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeType {
+  Module,
+  Class,
+  Function,
+}
+
+// How a name is used within a single scope:
+#[derive(Debug, Default)]
+struct SymbolUsage {
+  is_parameter: bool,
+  is_assigned: bool,
+  is_referenced: bool,
+  is_global: bool,
+  is_nonlocal: bool,
+  // Where the name was declared global or nonlocal:
+  declared_at: ast::Location,
+}
+
+// A rule about names which the program breaks, at the statement breaking it:
+#[derive(Debug, PartialEq)]
+pub struct SymbolTableError {
+  pub error: String,
+  pub location: ast::Location,
+}
+
+type SymbolTableResult = Result<(), SymbolTableError>;
+
+#[derive(Debug)]
+pub struct SymbolTable {
+  pub typ: ScopeType,
+  pub symbols: HashMap<String, SymbolScope>,
+  // Locals in cells, and the cells taken from the enclosing function when
+  // creating a function from this scope:
+  pub cell_names: Vec<String>,
+  pub free_names: Vec<String>,
+  // Tables of the functions and classes defined in this scope, in the order
+  // in which they are compiled:
+  pub sub_tables: Vec<SymbolTable>,
+  pub is_generator: bool,
+  usages: HashMap<String, SymbolUsage>,
+}
+
+impl SymbolTable {
+  fn new(typ: ScopeType) -> Self {
+    SymbolTable {
+      typ: typ,
+      symbols: HashMap::new(),
+      cell_names: vec![],
+      free_names: vec![],
+      sub_tables: vec![],
+      is_generator: false,
+      usages: HashMap::new(),
+    }
+  }
+
+  // Names which are unknown to the symbol table, like the ones generated by
+  // the compiler, are looked up at runtime:
+  pub fn lookup(&self, name: &str) -> SymbolScope {
+    match self.symbols.get(name) {
+      Some(scope) => *scope,
+      None => SymbolScope::Name,
+    }
+  }
+}
+
+pub fn make_symbol_table(
+  statements: &[ast::LocatedStatement],
+) -> Result<SymbolTable, SymbolTableError> {
+  let mut builder = SymbolTableBuilder {
+    tables: vec![SymbolTable::new(ScopeType::Module)],
+    location: ast::Location::default(),
+  };
+  builder.scan_statements(statements)?;
+  let mut table = builder.tables.pop().unwrap();
+  analyze(&mut table, &HashSet::new())?;
+  Ok(table)
+}
+
+// Decide on the scope of every name, given the names bound in enclosing
+// functions, and check that nested functions can reach their free names:
+fn analyze(
+  table: &mut SymbolTable,
+  enclosing: &HashSet<String>,
+) -> SymbolTableResult {
+  for (name, usage) in &table.usages {
+    let scope = if usage.is_global {
+      SymbolScope::Global
+    } else if usage.is_nonlocal {
+      if !enclosing.contains(name) {
+        return Err(SymbolTableError {
+          error: format!("no binding for nonlocal '{}' found", name),
+          location: usage.declared_at,
+        });
+      }
+      SymbolScope::Free
+    } else {
+      match table.typ {
+        ScopeType::Module => SymbolScope::Name,
+        ScopeType::Class => {
+          if !usage.is_assigned && enclosing.contains(name) {
+            SymbolScope::Free
+          } else {
+            SymbolScope::Name
+          }
+        }
+        ScopeType::Function => {
+          if usage.is_assigned || usage.is_parameter {
+            SymbolScope::Local
+          } else if enclosing.contains(name) {
+            SymbolScope::Free
+          } else {
+            SymbolScope::Global
+          }
+        }
+      }
+    };
+    table.symbols.insert(name.clone(), scope);
+  }
+
+  // Functions make their own names visible to nested functions, while the
+  // names of a class body are not visible to its methods:
+  let mut sub_enclosing = enclosing.clone();
+  match table.typ {
+    ScopeType::Module => sub_enclosing.clear(),
+    ScopeType::Class => {}
+    ScopeType::Function => {
+      for (name, scope) in &table.symbols {
+        match scope {
+          SymbolScope::Local | SymbolScope::Free => {
+            sub_enclosing.insert(name.clone());
+          }
+          SymbolScope::Global => {
+            sub_enclosing.remove(name);
+          }
+          _ => {}
+        }
+      }
+    }
+  }
+
+  let mut free_names: HashSet<String> = table
+    .symbols
+    .iter()
+    .filter(|(_, scope)| **scope == SymbolScope::Free)
+    .map(|(name, _)| name.clone())
+    .collect();
+  for sub_table in &mut table.sub_tables {
+    analyze(sub_table, &sub_enclosing)?;
+    for name in &sub_table.free_names {
+      match table.symbols.get(name) {
+        Some(SymbolScope::Local) if table.typ == ScopeType::Function => {
+          table.symbols.insert(name.clone(), SymbolScope::Cell);
+        }
+        Some(SymbolScope::Cell) | Some(SymbolScope::Free)
+          if table.typ == ScopeType::Function => {}
+        // The cell is bound further out, so it is passed on through this
+        // scope:
+        _ => {
+          free_names.insert(name.clone());
+        }
+      }
+    }
+  }
+
+  table.free_names = free_names.into_iter().collect();
+  table.free_names.sort();
+  table.cell_names = table
+    .symbols
+    .iter()
+    .filter(|(_, scope)| **scope == SymbolScope::Cell)
+    .map(|(name, _)| name.clone())
+    .collect();
+  table.cell_names.sort();
+  Ok(())
+}
+
+enum Usage {
+  Parameter,
+  Assigned,
+  Referenced,
+  Global,
+  Nonlocal,
+}
+
+struct SymbolTableBuilder {
+  tables: Vec<SymbolTable>,
+  // The statement being scanned, which errors point at:
+  location: ast::Location,
+}
+
+impl SymbolTableBuilder {
+  fn enter_scope(&mut self, typ: ScopeType) {
+    self.tables.push(SymbolTable::new(typ));
+  }
+
+  fn leave_scope(&mut self) {
+    let table = self.tables.pop().unwrap();
+    self.tables.last_mut().unwrap().sub_tables.push(table);
+  }
+
+  fn error(&self, error: String) -> SymbolTableError {
+    SymbolTableError {
+      error: error,
+      location: self.location,
+    }
+  }
+
+  fn scan_statements(
+    &mut self,
+    statements: &[ast::LocatedStatement],
+  ) -> SymbolTableResult {
+    for statement in statements {
+      self.scan_statement(statement)?;
+    }
+    Ok(())
+  }
+
+  fn scan_statement(
+    &mut self,
+    statement: &ast::LocatedStatement,
+  ) -> SymbolTableResult {
+    // After a compound statement, errors point at it rather than at the last
+    // statement of its body:
+    let location = self.location;
+    self.location = statement.location;
+    match &statement.node {
+      ast::Statement::Break
+      | ast::Statement::Continue
+      | ast::Statement::Pass => {}
+      ast::Statement::Return { value } => {
+        if let Some(expressions) = value {
+          self.scan_expressions(expressions)?;
+        }
+      }
+      ast::Statement::Raise { expression, cause } => {
+        if let Some(expression) = expression {
+          self.scan_expression(expression)?;
+        }
+        if let Some(cause) = cause {
+          self.scan_expression(cause)?;
+        }
+      }
+      ast::Statement::Import { import_parts } => {
+        for part in import_parts {
+          let name = match part.alias {
+            Some(ref alias) => alias,
+            None => match part.symbol {
+              Some(ref symbol) => symbol,
+              None => &part.module,
+            },
+          };
+          self.register_name(name, Usage::Assigned)?;
+        }
+      }
+      ast::Statement::Global { names } => {
+        for name in names {
+          self.register_name(name, Usage::Global)?;
+        }
+      }
+      ast::Statement::Nonlocal { names } => {
+        for name in names {
+          self.register_name(name, Usage::Nonlocal)?;
+        }
+      }
+      ast::Statement::Assert { test, msg } => {
+        self.scan_expression(test)?;
+        if let Some(msg) = msg {
+          self.scan_expression(msg)?;
+        }
+      }
+      ast::Statement::Delete { targets } => {
+        for target in targets {
+          self.scan_target(target)?;
+        }
+      }
+      ast::Statement::Assign { targets, value } => {
+        self.scan_expression(value)?;
+        for target in targets {
+          self.scan_target(target)?;
+        }
+      }
+      ast::Statement::AugAssign { target, value, .. } => {
+        self.scan_expression(target)?;
+        self.scan_expression(value)?;
+        self.scan_target(target)?;
+      }
+      ast::Statement::Expression { expression } => {
+        self.scan_expression(expression)?;
+      }
+      ast::Statement::If { test, body, orelse }
+      | ast::Statement::While { test, body, orelse } => {
+        self.scan_expression(test)?;
+        self.scan_statements(body)?;
+        if let Some(orelse) = orelse {
+          self.scan_statements(orelse)?;
+        }
+      }
+      ast::Statement::With { items, body } => {
+        for item in items {
+          self.scan_expression(&item.context_expr)?;
+          if let Some(ref target) = item.optional_vars {
+            self.scan_target(target)?;
+          }
+        }
+        self.scan_statements(body)?;
+      }
+      ast::Statement::For {
+        target,
+        iter,
+        body,
+        orelse,
+      } => {
+        self.scan_expressions(iter)?;
+        if target.len() == 1 {
+          self.scan_target(&target[0])?;
+        } else {
+          self.scan_unpack_targets(target)?;
+        }
+        self.scan_statements(body)?;
+        if let Some(orelse) = orelse {
+          self.scan_statements(orelse)?;
+        }
+      }
+      ast::Statement::Try {
+        body,
+        handlers,
+        orelse,
+        finalbody,
+      } => {
+        self.scan_statements(body)?;
+        for handler in handlers {
+          if let Some(ref typ) = handler.typ {
+            self.scan_expression(typ)?;
+          }
+          if let Some(ref name) = handler.name {
+            self.register_name(name, Usage::Assigned)?;
+          }
+          self.scan_statements(&handler.body)?;
+        }
+        if let Some(orelse) = orelse {
+          self.scan_statements(orelse)?;
+        }
+        if let Some(finalbody) = finalbody {
+          self.scan_statements(finalbody)?;
+        }
+      }
+      ast::Statement::ClassDef { name, body, args } => {
+        self.register_name(name, Usage::Assigned)?;
+        for base in args {
+          self.register_name(base, Usage::Referenced)?;
+        }
+        self.enter_scope(ScopeType::Class);
+        self.scan_statements(body)?;
+        self.leave_scope();
+      }
+      ast::Statement::FunctionDef { name, args, body } => {
+        self.register_name(name, Usage::Assigned)?;
+        self.enter_function(args)?;
+        self.scan_statements(body)?;
+        self.leave_scope();
+      }
+    }
+    self.location = location;
+    Ok(())
+  }
+
+  // Targets which the items of a value are unpacked into, one of which can
+  // be starred:
+  fn scan_unpack_targets(
+    &mut self,
+    targets: &[ast::LocatedExpression],
+  ) -> SymbolTableResult {
+    let mut starred = false;
+    for target in targets {
+      match &target.node {
+        ast::Expression::Starred { value } => {
+          if starred {
+            return Err(self.error(
+              "multiple starred expressions in assignment".to_string(),
+            ));
+          }
+          starred = true;
+          self.scan_target(value)?;
+        }
+        _ => self.scan_target(target)?,
+      }
+    }
+    Ok(())
+  }
+
+  fn scan_target(
+    &mut self,
+    target: &ast::LocatedExpression,
+  ) -> SymbolTableResult {
+    match &target.node {
+      ast::Expression::Identifier { name } => {
+        self.register_name(name, Usage::Assigned)
+      }
+      ast::Expression::Tuple { elements }
+      | ast::Expression::List { elements } => {
+        self.scan_unpack_targets(elements)
+      }
+      ast::Expression::Starred { .. } => Err(self.error(
+        "starred assignment target must be in a list or tuple".to_string(),
+      )),
+      _ => self.scan_expression(target),
+    }
+  }
+
+  fn scan_expressions(
+    &mut self,
+    expressions: &[ast::LocatedExpression],
+  ) -> SymbolTableResult {
+    for expression in expressions {
+      self.scan_expression(expression)?;
+    }
+    Ok(())
+  }
+
+  fn scan_expression(
+    &mut self,
+    expression: &ast::LocatedExpression,
+  ) -> SymbolTableResult {
+    match &expression.node {
+      ast::Expression::BoolOp { a, b, .. }
+      | ast::Expression::Binop { a, b, .. }
+      | ast::Expression::Subscript { a, b }
+      | ast::Expression::Compare { a, b, .. } => {
+        self.scan_expression(a)?;
+        self.scan_expression(b)?;
+      }
+      ast::Expression::Unop { a, .. } => self.scan_expression(a)?,
+      ast::Expression::Attribute { value, .. }
+      | ast::Expression::Starred { value } => self.scan_expression(value)?,
+      ast::Expression::Call {
+        function,
+        args,
+        keywords,
+      } => {
+        self.scan_expression(function)?;
+        self.scan_expressions(args)?;
+        for keyword in keywords {
+          self.scan_expression(&keyword.value)?;
+        }
+      }
+      ast::Expression::List { elements }
+      | ast::Expression::Tuple { elements }
+      | ast::Expression::Set { elements }
+      | ast::Expression::Slice { elements } => {
+        self.scan_expressions(elements)?;
+      }
+      ast::Expression::JoinedStr { values } => {
+        self.scan_expressions(values)?;
+      }
+      ast::Expression::FormattedValue { value, spec, .. } => {
+        self.scan_expression(value)?;
+        if let Some(spec) = spec {
+          self.scan_expression(spec)?;
+        }
+      }
+      ast::Expression::Dict { elements } => {
+        for (key, value) in elements {
+          self.scan_expression(key)?;
+          self.scan_expression(value)?;
+        }
+      }
+      ast::Expression::Identifier { name } => {
+        self.register_name(name, Usage::Referenced)?;
+      }
+      ast::Expression::Yield { value } => {
+        self.mark_generator()?;
+        if let Some(value) = value {
+          self.scan_expression(value)?;
+        }
+      }
+      ast::Expression::YieldFrom { value } => {
+        self.mark_generator()?;
+        self.scan_expression(value)?;
+      }
+      ast::Expression::Lambda { args, body } => {
+        self.enter_function(args)?;
+        self.scan_expression(body)?;
+        self.leave_scope();
+      }
+      ast::Expression::Comprehension { kind, generators } => {
+        self.scan_comprehension(kind, generators)?;
+      }
+      ast::Expression::Number { .. }
+      | ast::Expression::String { .. }
+      | ast::Expression::Bytes { .. }
+      | ast::Expression::True
+      | ast::Expression::False
+      | ast::Expression::PyNone => {}
+    }
+    Ok(())
+  }
+
+  // Defaults are evaluated in the enclosing scope, before the function's own
+  // scope starts:
+  fn enter_function(&mut self, args: &ast::Parameters) -> SymbolTableResult {
+    self.scan_expressions(&args.defaults)?;
+    for default in &args.kw_defaults {
+      if let Some(default) = default {
+        self.scan_expression(default)?;
+      }
+    }
+
+    self.enter_scope(ScopeType::Function);
+    let names = args
+      .args
+      .iter()
+      .chain(&args.vararg)
+      .chain(&args.kwonlyargs)
+      .chain(&args.kwarg);
+    for name in names {
+      self.register_name(name, Usage::Parameter)?;
+    }
+    Ok(())
+  }
+
+  // A comprehension runs in a function scope of its own, which gets the
+  // iterator of its outermost loop as the argument ".0". The iterable of that
+  // loop is evaluated in the enclosing scope, after the function is made:
+  fn scan_comprehension(
+    &mut self,
+    kind: &ast::ComprehensionKind,
+    generators: &[ast::Comprehension],
+  ) -> SymbolTableResult {
+    self.enter_scope(ScopeType::Function);
+    self.register_name(".0", Usage::Parameter)?;
+    if let ast::ComprehensionKind::GeneratorExpression { .. } = kind {
+      self.mark_generator()?;
+    }
+    for (i, generator) in generators.iter().enumerate() {
+      if i > 0 {
+        self.scan_expression(&generator.iter)?;
+      }
+      self.scan_target(&generator.target)?;
+      self.scan_expressions(&generator.ifs)?;
+    }
+    match kind {
+      ast::ComprehensionKind::GeneratorExpression { element }
+      | ast::ComprehensionKind::List { element }
+      | ast::ComprehensionKind::Set { element } => {
+        self.scan_expression(element)?;
+      }
+      ast::ComprehensionKind::Dict { key, value } => {
+        self.scan_expression(key)?;
+        self.scan_expression(value)?;
+      }
+    }
+    self.leave_scope();
+    self.scan_expression(&generators[0].iter)
+  }
+
+  fn mark_generator(&mut self) -> SymbolTableResult {
+    let table = self.tables.last_mut().unwrap();
+    if table.typ != ScopeType::Function {
+      return Err(self.error("'yield' outside function".to_string()));
+    }
+    table.is_generator = true;
+    Ok(())
+  }
+
+  fn register_name(&mut self, name: &str, usage: Usage) -> SymbolTableResult {
+    let location = self.location;
+    let error = |error: String| {
+      Err(SymbolTableError {
+        error: error,
+        location: location,
+      })
+    };
+    let table = self.tables.last_mut().unwrap();
+    let scope_type = table.typ;
+    let symbol = table
+      .usages
+      .entry(name.to_string())
+      .or_insert_with(SymbolUsage::default);
+    let keyword = match usage {
+      Usage::Parameter => {
+        symbol.is_parameter = true;
+        return Ok(());
+      }
+      Usage::Assigned => {
+        symbol.is_assigned = true;
+        return Ok(());
+      }
+      Usage::Referenced => {
+        symbol.is_referenced = true;
+        return Ok(());
+      }
+      Usage::Global => "global",
+      Usage::Nonlocal => "nonlocal",
+    };
+
+    // A declaration must come before any other use of the name:
+    if let Usage::Nonlocal = usage {
+      if scope_type == ScopeType::Module {
+        return error(
+          "nonlocal declaration not allowed at module level".to_string(),
+        );
+      }
+    }
+    if symbol.is_parameter {
+      return error(format!("name '{}' is parameter and {}", name, keyword));
+    }
+    if symbol.is_assigned {
+      return error(format!(
+        "name '{}' is assigned to before {} declaration",
+        name, keyword
+      ));
+    }
+    if symbol.is_referenced {
+      return error(format!(
+        "name '{}' is used prior to {} declaration",
+        name, keyword
+      ));
+    }
+    match usage {
+      Usage::Global => symbol.is_global = true,
+      _ => symbol.is_nonlocal = true,
+    }
+    symbol.declared_at = location;
+    if symbol.is_global && symbol.is_nonlocal {
+      return error(format!("name '{}' is nonlocal and global", name));
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::pyst_parser::{ast, parser};
+  use super::{make_symbol_table, SymbolScope, SymbolTable};
+
+  fn symbol_table(source: &str) -> Result<SymbolTable, String> {
+    let program = parser::parse_program(&String::from(source)).unwrap();
+    make_symbol_table(&program.statements).map_err(|error| error.error)
+  }
+
+  #[test]
+  fn test_closure_variables_are_cells() {
+    let table =
+      symbol_table("def f(a):\n  b = 1\n  def g():\n    return a + c\n")
+        .unwrap();
+    let f = &table.sub_tables[0];
+    assert_eq!(f.cell_names, vec![String::from("a")]);
+    assert_eq!(f.lookup("b"), SymbolScope::Local);
+    let g = &f.sub_tables[0];
+    assert_eq!(g.free_names, vec![String::from("a")]);
+    assert_eq!(g.lookup("c"), SymbolScope::Global);
+  }
+
+  #[test]
+  fn test_nonlocal_without_binding_is_error() {
+    let result = symbol_table("def f():\n  def g():\n    nonlocal x\n");
+    assert_eq!(
+      result.unwrap_err(),
+      String::from("no binding for nonlocal 'x' found")
+    );
+  }
+
+  #[test]
+  fn test_yield_makes_function_a_generator() {
+    let table = symbol_table("def f():\n  yield 1\n").unwrap();
+    assert!(table.sub_tables[0].is_generator);
+    let result = symbol_table("class C:\n  yield 1\n");
+    assert_eq!(
+      result.unwrap_err(),
+      String::from("'yield' outside function")
+    );
+  }
+
+  #[test]
+  fn test_comprehension_has_its_own_scope() {
+    let table =
+      symbol_table("def f(k):\n  return [x + k for x in range(k)]\n").unwrap();
+    let f = &table.sub_tables[0];
+    assert_eq!(f.cell_names, vec![String::from("k")]);
+    assert_eq!(f.lookup("x"), SymbolScope::Name);
+    let listcomp = &f.sub_tables[0];
+    assert_eq!(listcomp.lookup(".0"), SymbolScope::Local);
+    assert_eq!(listcomp.lookup("x"), SymbolScope::Local);
+    assert_eq!(listcomp.free_names, vec![String::from("k")]);
+  }
+
+  #[test]
+  fn test_only_one_starred_target_is_allowed() {
+    let result = symbol_table("a, *b, *c = d\n");
+    assert_eq!(
+      result.unwrap_err(),
+      String::from("multiple starred expressions in assignment")
+    );
+  }
+
+  #[test]
+  fn test_errors_point_at_the_statement() {
+    let source = "def f(a):\n  if a:\n    pass\n  global a\n";
+    let program = parser::parse_program(&String::from(source)).unwrap();
+    let error = make_symbol_table(&program.statements).unwrap_err();
+    assert_eq!(error.error, "name 'a' is parameter and global");
+    assert_eq!(error.location, ast::Location::new(4, 3));
+  }
+}
@@ -1,21 +1,46 @@
-use std::cell::RefMut;
-use std::collections::hash_map::HashMap;
-use std::ops::Deref;
+extern crate pyst_parser;
 
+use std::cmp::Ordering;
+use std::mem;
+
+use num_complex::Complex64;
+use num_traits::ToPrimitive;
+
+use self::pyst_parser::ast;
 use super::builtins;
 use super::bytecode;
-use super::frame::{copy_code, Block, Frame};
+use super::compile;
+use super::exceptions::set_exception_context;
+use super::frame::{copy_code, Block, ExecutionResult, Frame, UnwindReason};
 use super::import::import;
+use super::objbool;
+use super::objcomplex;
+use super::objdict;
+use super::objfloat;
+use super::objgenerator;
+use super::objint::{self, IntValue};
+use super::objiter;
 use super::objlist;
+use super::objnumber::{self, Number, NumberPair};
 use super::objobject;
+use super::objsequence;
+use super::objset;
 use super::objstr;
 use super::objtype;
 use super::pyobject::{
   AttributeProtocol, DictProtocol, IdProtocol, ParentProtocol, PyContext,
-  PyFuncArgs, PyObject, PyObjectKind, PyObjectRef, PyResult,
+  PyFuncArgs, PyObject, PyObjectKind, PyObjectRef, PyResult, TypeProtocol,
 };
 use super::sysmodule;
 
+// How deep frames may nest before calls raise RecursionError:
+const MAX_FRAME_DEPTH: usize = 1000;
+
+// Frames run on a new stack segment of STACK_SEGMENT_SIZE bytes when less
+// than STACK_RED_ZONE bytes of the current one are left:
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
 pub struct VirtualMachine {
   frames: Vec<Frame>,
   builtins: PyObjectRef,
@@ -23,13 +48,27 @@ pub struct VirtualMachine {
   ctx: PyContext,
 }
 
+// Look a name up in a scope, then in the scopes it is nested in:
+fn lookup_in_scopes(scope: PyObjectRef, name: &String) -> Option<PyObjectRef> {
+  let mut scope = scope;
+  loop {
+    if scope.contains_key(name) {
+      return Some(scope.get_item(name));
+    } else if scope.has_parent() {
+      scope = scope.get_parent();
+    } else {
+      return None;
+    }
+  }
+}
+
 impl VirtualMachine {
   pub fn run_code_obj(
     &mut self,
     code: PyObjectRef,
     scope: PyObjectRef,
   ) -> PyResult {
-    let frame = Frame::new(code, scope);
+    let frame = Frame::new(&self.ctx, code, scope.clone(), scope, vec![]);
     self.run_frame(frame)
   }
 
@@ -45,8 +84,51 @@ impl VirtualMachine {
     self.ctx.new_dict()
   }
 
-  pub fn new_exception(&self, msg: String) -> PyObjectRef {
-    self.new_str(msg)
+  pub fn new_exception(
+    &mut self,
+    exc_type: PyObjectRef,
+    msg: String,
+  ) -> PyObjectRef {
+    // TODO: exc_type may be user-defined exception, so we should return PyResult
+    // TODO: maybe there is a clearer way to create an instance:
+    info!("New exception created: {}", msg);
+    let pymsg = self.new_str(msg);
+    let args: Vec<PyObjectRef> = vec![pymsg];
+    let args = PyFuncArgs::new(args, vec![]);
+
+    // Call function:
+    self.invoke(exc_type, args).unwrap()
+  }
+
+  pub fn new_type_error(&mut self, msg: String) -> PyObjectRef {
+    let type_error = self.ctx.exceptions.type_error.clone();
+    self.new_exception(type_error, msg)
+  }
+
+  // A SyntaxError for code which does not compile, with the position in the
+  // source which it points to as its lineno and offset:
+  pub fn new_syntax_error(
+    &mut self,
+    error: &compile::CompileError,
+  ) -> PyObjectRef {
+    let syntax_error = self.ctx.exceptions.syntax_error.clone();
+    let exc = self.new_exception(syntax_error, error.to_string());
+    let location = error.location();
+    let lineno = self.ctx.new_int(location.get_row());
+    let offset = self.ctx.new_int(location.get_column());
+    exc.set_attr("lineno", lineno);
+    exc.set_attr("offset", offset);
+    exc
+  }
+
+  pub fn new_value_error(&mut self, msg: String) -> PyObjectRef {
+    let value_error = self.ctx.exceptions.value_error.clone();
+    self.new_exception(value_error, msg)
+  }
+
+  pub fn new_system_error(&mut self, msg: String) -> PyObjectRef {
+    let system_error = self.ctx.exceptions.system_error.clone();
+    self.new_exception(system_error, msg)
   }
 
   pub fn new_scope(&mut self) -> PyObjectRef {
@@ -55,7 +137,7 @@ impl VirtualMachine {
   }
 
   pub fn get_none(&self) -> PyObjectRef {
-    self.ctx.none_type.clone()
+    self.ctx.none.clone()
   }
 
   pub fn new_bound_method(
@@ -111,9 +193,44 @@ impl VirtualMachine {
     }
   }
 
-  // Container of the virtual machine state:
-  pub fn to_str(&mut self, obj: PyObjectRef) -> String {
-    obj.borrow().str()
+  // str(obj), which falls back on __repr__ when there is no __str__:
+  pub fn to_str(&mut self, obj: PyObjectRef) -> Result<String, PyObjectRef> {
+    for name in &["__str__", "__repr__"] {
+      if let Some(result) = self.call_special_method(&obj, name, vec![]) {
+        return self.string_result(result?, name);
+      }
+    }
+    Ok(obj.borrow().str())
+  }
+
+  // repr(obj):
+  pub fn to_repr(&mut self, obj: PyObjectRef) -> Result<String, PyObjectRef> {
+    if let Some(result) = self.call_special_method(&obj, "__repr__", vec![]) {
+      return self.string_result(result?, "__repr__");
+    }
+    Ok(obj.borrow().repr())
+  }
+
+  // ascii(obj), which is repr(obj) with only ASCII characters:
+  pub fn to_ascii(&mut self, obj: PyObjectRef) -> Result<String, PyObjectRef> {
+    let repr = self.to_repr(obj)?;
+    Ok(objstr::ascii_string(&repr))
+  }
+
+  fn string_result(
+    &mut self,
+    result: PyObjectRef,
+    name: &str,
+  ) -> Result<String, PyObjectRef> {
+    match result.borrow().kind {
+      PyObjectKind::String { ref value } => return Ok(value.clone()),
+      _ => {}
+    };
+    let type_name = objtype::get_type_name(&result.typ());
+    Err(self.new_type_error(format!(
+      "{} returned non-string (type {})",
+      name, type_name
+    )))
   }
 
   fn current_frame(&mut self) -> &mut Frame {
@@ -132,39 +249,191 @@ impl VirtualMachine {
     self.current_frame().pop_block()
   }
 
-  fn last_block(&mut self) -> &Block {
-    self.current_frame().last_block()
-  }
-
-  fn unwind_loop(&mut self) -> Block {
-    loop {
-      let block = self.pop_block();
-      match block {
-        Some(Block::Loop { start: _, end: __ }) => break block.unwrap(),
-        Some(Block::TryExcept {}) => {}
-        None => panic!("No block to break / continue"),
-      }
-    }
+  // Prepend the current position to the exception's __traceback__, so that
+  // the entries run from the outermost frame to the innermost one:
+  fn add_traceback_entry(&mut self, exc: &PyObjectRef) {
+    let (location, source_path, obj_name) = {
+      let frame = self.current_frame();
+      (
+        frame.get_location(),
+        frame.code.source_path.clone(),
+        frame.code.obj_name.clone(),
+      )
+    };
+    let traceback_name = String::from("__traceback__");
+    let traceback = if exc.has_attr(&traceback_name) {
+      exc.get_attr(&traceback_name)
+    } else {
+      let traceback = self.ctx.new_list(vec![]);
+      exc.set_attr(&traceback_name, traceback.clone());
+      traceback
+    };
+    let entry = self.ctx.new_tuple(vec![
+      self
+        .ctx
+        .new_str(source_path.unwrap_or(String::from("<unknown>"))),
+      self.ctx.new_int(location.get_row()),
+      self.ctx.new_str(obj_name),
+    ]);
+    if let PyObjectKind::List { ref mut elements } = traceback.borrow_mut().kind
+    {
+      elements.insert(0, entry);
+    };
   }
 
-  fn unwind_exception(&mut self, exc: PyObjectRef) -> Option<PyObjectRef> {
-    // unwind block stack on exception and find any handlers:
-    loop {
-      let block = self.pop_block();
+  // Leave the blocks of the current frame for the given reason, until one of
+  // them takes over: a loop for break and continue, an except clause for an
+  // exception, or a finally clause for any reason. Returns the result of the
+  // frame when none does:
+  fn unwind_blocks(&mut self, mut reason: UnwindReason) -> Option<PyResult> {
+    while let Some(block) = self.pop_block() {
       match block {
-        Some(Block::TryExcept {}) => {
-          // Exception handled?
-          // TODO: how do we know if the exception is handled?
-          let is_handled = true;
-          if is_handled {
+        Block::Loop { start, end, level } => match reason {
+          UnwindReason::Break => {
+            self.current_frame().truncate_stack(level);
+            self.jump(&end);
+            return None;
+          }
+          UnwindReason::Continue => {
+            self.push_block(Block::Loop {
+              start: start,
+              end: end,
+              level: level,
+            });
+            self.jump(&start);
+            return None;
+          }
+          _ => {}
+        },
+        Block::TryExcept { handler, level } => {
+          if let UnwindReason::Raising { ref exception } = reason {
+            // The handler code checks whether it can handle the exception,
+            // so push the exception for it to inspect:
+            self.current_frame().truncate_stack(level);
+            self.push_block(Block::ExceptHandler {
+              exc: exception.clone(),
+            });
+            self.push_value(exception.clone());
+            self.jump(&handler);
             return None;
           }
         }
-        Some(_) => {}
-        None => break,
+        Block::Finally { handler, level } => {
+          self.current_frame().truncate_stack(level);
+          self.push_block(Block::FinallyHandler {
+            reason: Some(reason),
+          });
+          self.jump(&handler);
+          return None;
+        }
+        // The context manager of a with block can suppress an exception, or
+        // replace the reason by raising an exception of its own:
+        Block::With { end, exit, level } => {
+          self.current_frame().truncate_stack(level);
+          let exc = match reason {
+            UnwindReason::Raising { ref exception } => Some(exception.clone()),
+            _ => None,
+          };
+          match self.call_with_exit(exit, exc.as_ref()) {
+            Ok(true) if exc.is_some() => {
+              self.jump(&end);
+              return None;
+            }
+            Ok(_) => {}
+            Err(exit_exc) => {
+              self.add_traceback_entry(&exit_exc);
+              if let Some(exc) = exc {
+                set_exception_context(&exit_exc, &exc);
+              }
+              reason = UnwindReason::Raising {
+                exception: exit_exc,
+              };
+            }
+          }
+        }
+        // Leaving an except or finally clause drops what it was handling:
+        Block::ExceptHandler { .. } | Block::FinallyHandler { .. } => {}
       }
     }
-    Some(exc)
+    match reason {
+      UnwindReason::Raising { exception } => {
+        self.current_frame().reraised = true;
+        Some(Err(exception))
+      }
+      UnwindReason::Returning { value } => Some(Ok(value)),
+      UnwindReason::Break | UnwindReason::Continue => Some(Err(
+        self.new_system_error("No block to break / continue".to_string()),
+      )),
+    }
+  }
+
+  // The exception which is being handled, in the current frame or the frames
+  // which called it:
+  fn handled_exception(&self) -> Option<PyObjectRef> {
+    self
+      .frames
+      .iter()
+      .rev()
+      .filter_map(|frame| frame.handled_exception())
+      .next()
+  }
+
+  // Call the __exit__ method of a with block with the exception which leaves
+  // the block, if any. Returns whether the exception is suppressed:
+  fn call_with_exit(
+    &mut self,
+    exit: PyObjectRef,
+    exc: Option<&PyObjectRef>,
+  ) -> Result<bool, PyObjectRef> {
+    let args = match exc {
+      Some(exc) => {
+        let traceback_name = String::from("__traceback__");
+        let traceback = if exc.has_attr(&traceback_name) {
+          exc.get_attr(&traceback_name)
+        } else {
+          self.get_none()
+        };
+        vec![exc.typ(), exc.clone(), traceback]
+      }
+      None => vec![self.get_none(), self.get_none(), self.get_none()],
+    };
+    let result = self.invoke(exit, PyFuncArgs::new(args, vec![]))?;
+    objbool::boolval(self, result)
+  }
+
+  // Call __enter__ on the context manager of a with statement, and keep its
+  // __exit__ method in the block for when the statement ends:
+  fn execute_setup_with(
+    &mut self,
+    context_manager: PyObjectRef,
+    end: bytecode::Label,
+  ) -> Option<PyResult> {
+    let enter = objtype::get_special_method(&context_manager, "__enter__");
+    let exit = objtype::get_special_method(&context_manager, "__exit__");
+    let (enter, exit) = match (enter, exit) {
+      (Some(enter), Some(exit)) => (enter, exit),
+      _ => {
+        let type_name = objtype::get_type_name(&context_manager.typ());
+        return Some(Err(self.new_type_error(format!(
+          "'{}' object does not support the context manager protocol",
+          type_name
+        ))));
+      }
+    };
+    let exit = self.new_bound_method(exit, context_manager.clone());
+    let args = PyFuncArgs::new(vec![context_manager], vec![]);
+    let value = match self.invoke(enter, args) {
+      Ok(value) => value,
+      Err(err) => return Some(Err(err)),
+    };
+    let level = self.current_frame().stack_level();
+    self.push_block(Block::With {
+      end: end,
+      exit: exit,
+      level: level,
+    });
+    self.push_value(value);
+    None
   }
 
   fn push_value(&mut self, obj: PyObjectRef) {
@@ -183,67 +452,226 @@ impl VirtualMachine {
     self.current_frame().last_value()
   }
 
-  fn store_name(&mut self, name: &String) -> Option<PyResult> {
+  fn nth_value(&mut self, depth: usize) -> PyObjectRef {
+    self.current_frame().nth_value(depth)
+  }
+
+  fn store_name(
+    &mut self,
+    name: &String,
+    scope: bytecode::NameScope,
+  ) -> Option<PyResult> {
     let obj = self.pop_value();
-    self.current_frame().locals.set_item(name, obj);
+    let frame = self.current_frame();
+    match scope {
+      bytecode::NameScope::Local | bytecode::NameScope::Name => {
+        frame.locals.set_item(name, obj)
+      }
+      bytecode::NameScope::Global => frame.globals.set_item(name, obj),
+      bytecode::NameScope::Cell => {
+        if let PyObjectKind::Cell { ref mut contents } =
+          frame.cells[name].borrow_mut().kind
+        {
+          *contents = Some(obj);
+        }
+      }
+    }
     None
   }
 
-  fn load_name(&mut self, name: &String) -> Option<PyResult> {
+  fn load_name(
+    &mut self,
+    name: &String,
+    scope: bytecode::NameScope,
+  ) -> Option<PyResult> {
     // Lookup name in scope and put it onto the stack!
-    let mut scope = self.current_frame().locals.clone();
-    loop {
-      if scope.contains_key(name) {
-        let obj = scope.get_item(name);
-        self.push_value(obj);
-        break None;
-      } else if scope.has_parent() {
-        scope = scope.get_parent();
-      } else {
-        let name_error = PyObject::new(
-          PyObjectKind::NameError {
-            name: name.to_string(),
-          },
-          self.get_type(),
-        );
-        break Some(Err(name_error));
+    let value = match scope {
+      bytecode::NameScope::Local => {
+        let locals = self.current_frame().locals.clone();
+        if locals.contains_key(name) {
+          Some(locals.get_item(name))
+        } else {
+          return Some(Err(self.new_unbound_local_error(name)));
+        }
+      }
+      bytecode::NameScope::Global => {
+        let globals = self.current_frame().globals.clone();
+        lookup_in_scopes(globals, name)
+      }
+      bytecode::NameScope::Name => {
+        let locals = self.current_frame().locals.clone();
+        lookup_in_scopes(locals, name)
+      }
+      bytecode::NameScope::Cell => {
+        let cell = self.current_frame().cells[name].clone();
+        let contents = match cell.borrow().kind {
+          PyObjectKind::Cell { ref contents } => contents.clone(),
+          _ => panic!("Expected a cell for '{}'", name),
+        };
+        match contents {
+          Some(value) => Some(value),
+          None if self.current_frame().code.cell_names.contains(name) => {
+            return Some(Err(self.new_unbound_local_error(name)));
+          }
+          None => {
+            let name_error_type = self.ctx.exceptions.name_error.clone();
+            let msg = format!(
+              "cannot access free variable '{}' where it is not associated \
+               with a value in enclosing scope",
+              name
+            );
+            return Some(Err(self.new_exception(name_error_type, msg)));
+          }
+        }
+      }
+    };
+    match value {
+      Some(value) => {
+        self.push_value(value);
+        None
+      }
+      None => {
+        let name_error_type = self.ctx.exceptions.name_error.clone();
+        let msg = format!("name '{}' is not defined", name);
+        let name_error = self.new_exception(name_error_type, msg);
+        Some(Err(name_error))
+      }
+    }
+  }
+
+  fn delete_name(
+    &mut self,
+    name: &String,
+    scope: bytecode::NameScope,
+  ) -> Option<PyResult> {
+    let frame = self.current_frame();
+    let deleted = match scope {
+      bytecode::NameScope::Local | bytecode::NameScope::Name => {
+        frame.locals.del_item(name)
+      }
+      bytecode::NameScope::Global => frame.globals.del_item(name),
+      bytecode::NameScope::Cell => match frame.cells[name].borrow_mut().kind {
+        PyObjectKind::Cell { ref mut contents } => contents.take().is_some(),
+        _ => panic!("Expected a cell for '{}'", name),
+      },
+    };
+    if deleted {
+      return None;
+    }
+    match scope {
+      bytecode::NameScope::Local | bytecode::NameScope::Cell => {
+        Some(Err(self.new_unbound_local_error(name)))
+      }
+      bytecode::NameScope::Global | bytecode::NameScope::Name => {
+        let name_error_type = self.ctx.exceptions.name_error.clone();
+        let msg = format!("name '{}' is not defined", name);
+        Some(Err(self.new_exception(name_error_type, msg)))
       }
     }
   }
 
+  fn new_unbound_local_error(&mut self, name: &String) -> PyObjectRef {
+    let unbound_local_error = self.ctx.exceptions.unbound_local_error.clone();
+    let msg = format!(
+      "cannot access local variable '{}' where it is not associated with a \
+       value",
+      name
+    );
+    self.new_exception(unbound_local_error, msg)
+  }
+
   fn run_frame(&mut self, frame: Frame) -> PyResult {
+    match self.resume_frame(frame, None).1 {
+      Ok(ExecutionResult::Return(value)) => Ok(value),
+      Ok(ExecutionResult::Yield(_)) => {
+        panic!("Only the frames of generators can yield")
+      }
+      Err(exception) => Err(exception),
+    }
+  }
+
+  // Run a frame until it returns, yields or raises an exception, and hand
+  // the frame back for generators to resume it later. Running starts by
+  // raising the given exception, if any, where the frame was suspended:
+  pub fn resume_frame(
+    &mut self,
+    frame: Frame,
+    exception: Option<PyObjectRef>,
+  ) -> (Frame, Result<ExecutionResult, PyObjectRef>) {
+    if self.frames.len() >= MAX_FRAME_DEPTH {
+      let recursion_error = self.ctx.exceptions.recursion_error.clone();
+      let exception = self.new_exception(
+        recursion_error,
+        "maximum recursion depth exceeded".to_string(),
+      );
+      return (frame, Err(exception));
+    }
+    // Python calls nest on the Rust stack, and a frame takes up more of it
+    // than small thread stacks can hold a thousand of. When the stack runs
+    // low, carry on in a new segment of it on the heap:
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, || {
+      self.execute_frame(frame, exception)
+    })
+  }
+
+  fn execute_frame(
+    &mut self,
+    frame: Frame,
+    exception: Option<PyObjectRef>,
+  ) -> (Frame, Result<ExecutionResult, PyObjectRef>) {
     self.frames.push(frame);
+    let mut pending = exception.map(Err);
 
     // Execute until return or exception:
     let value = loop {
-      let result = self.execute_instruction();
+      let result = match pending.take() {
+        Some(result) => Some(result),
+        None => self.execute_instruction(),
+      };
       match result {
         None => {}
         Some(Ok(value)) => {
-          break Ok(value);
+          let frame = self.current_frame();
+          if frame.yielded {
+            frame.yielded = false;
+            break Ok(ExecutionResult::Yield(value));
+          }
+          break Ok(ExecutionResult::Return(value));
         }
         Some(Err(exception)) => {
-          // unwind block stack on exception and find any handlers.
-          match self.unwind_exception(exception) {
-            None => {}
-            Some(exception) => {
-              let _traceback = self
-                .get_attribute(exception.clone(), &"__traceback__".to_string());
-              // TODO: append line number to traceback?
-              // traceback.append();
-              break Err(exception);
+          // Record that the exception passed through this frame, unless it
+          // is re-raised, and which exception was being handled when it was
+          // raised. Then unwind block stack on exception and find any
+          // handlers.
+          let reraised =
+            mem::replace(&mut self.current_frame().reraised, false);
+          if !reraised {
+            self.add_traceback_entry(&exception);
+            if let Some(handled) = self.handled_exception() {
+              set_exception_context(&exception, &handled);
             }
           }
+          match self.unwind_blocks(UnwindReason::Raising {
+            exception: exception,
+          }) {
+            None => {}
+            Some(Ok(value)) => break Ok(ExecutionResult::Return(value)),
+            Some(Err(exception)) => break Err(exception),
+          }
         }
       }
     };
 
-    self.pop_frame();
-    value
+    (self.pop_frame(), value)
   }
 
-  fn subscript(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+  pub fn subscript(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
     // Subscript implementation: a[b]
+    if let Some(result) =
+      self.call_special_method(&a, "__getitem__", vec![b.clone()])
+    {
+      return result;
+    }
     let a2 = &*a.borrow();
     match &a2.kind {
       PyObjectKind::String { ref value } => objstr::subscript(self, value, b),
@@ -251,9 +679,9 @@ impl VirtualMachine {
       | PyObjectKind::Tuple { ref elements } => {
         super::objsequence::get_item(self, &a, elements, b)
       }
-      _ => Err(self.new_exception(format!(
-        "TypeError: indexing type {:?} with index {:?} is not supported (yet?)",
-        a, b
+      _ => Err(self.new_type_error(format!(
+        "'{}' object is not subscriptable",
+        objtype::get_type_name(&a.typ())
       ))),
     }
   }
@@ -262,16 +690,27 @@ impl VirtualMachine {
     let idx = self.pop_value();
     let obj = self.pop_value();
     let value = self.pop_value();
+    if let Some(result) = self.call_special_method(
+      &obj,
+      "__setitem__",
+      vec![idx.clone(), value.clone()],
+    ) {
+      return match result {
+        Ok(_) => None,
+        Err(value) => Some(Err(value)),
+      };
+    }
+    let type_name = objtype::get_type_name(&obj.typ());
     let a2 = &mut *obj.borrow_mut();
     let result = match &mut a2.kind {
-          PyObjectKind::List { ref mut elements } => {
-              objlist::set_item(self, elements, idx, value)
-          }
-          _ => Err(self.new_exception(format!(
-              "TypeError: __setitem__ assign type {:?} with index {:?} is not supported (yet?)",
-              obj, idx
-          ))),
-      };
+      PyObjectKind::List { ref mut elements } => {
+        objlist::set_item(self, elements, idx, value)
+      }
+      _ => Err(self.new_type_error(format!(
+        "'{}' object does not support item assignment",
+        type_name
+      ))),
+    };
 
     match result {
       Ok(_) => None,
@@ -279,170 +718,734 @@ impl VirtualMachine {
     }
   }
 
-  fn _sub(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    Ok(PyObject::new(a2 - b2, self.get_type()))
+  fn execute_delete_subscript(&mut self) -> Option<PyResult> {
+    let idx = self.pop_value();
+    let obj = self.pop_value();
+    if let Some(result) =
+      self.call_special_method(&obj, "__delitem__", vec![idx.clone()])
+    {
+      return result.err().map(Err);
+    }
+    let type_name = objtype::get_type_name(&obj.typ());
+    let a2 = &mut *obj.borrow_mut();
+    let result = match &mut a2.kind {
+      PyObjectKind::List { ref mut elements } => {
+        objlist::del_item(self, elements, idx)
+      }
+      _ => Err(self.new_type_error(format!(
+        "'{}' object doesn't support item deletion",
+        type_name
+      ))),
+    };
+    result.err().map(Err)
   }
 
-  fn _add(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    Ok(PyObject::new(a2 + b2, self.get_type()))
+  fn new_binop_type_error(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+    op: &str,
+  ) -> PyObjectRef {
+    let a_type_name = objtype::get_type_name(&a.typ());
+    let b_type_name = objtype::get_type_name(&b.typ());
+    self.new_type_error(format!(
+      "unsupported operand type(s) for {}: '{}' and '{}'",
+      op, a_type_name, b_type_name
+    ))
   }
 
-  fn _mul(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    Ok(PyObject::new(a2 * b2, self.get_type()))
+  fn new_overflow_error(&mut self, msg: &str) -> PyObjectRef {
+    let overflow_error = self.ctx.exceptions.overflow_error.clone();
+    self.new_exception(overflow_error, msg.to_string())
   }
 
-  fn _div(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    Ok(PyObject::new(a2 / b2, self.get_type()))
+  fn new_zero_division_error(&mut self, msg: &str) -> PyObjectRef {
+    let zero_division_error = self.ctx.exceptions.zero_division_error.clone();
+    self.new_exception(zero_division_error, msg.to_string())
   }
 
-  fn _pow(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    match (&a2.kind, &b2.kind) {
-      (
-        &PyObjectKind::Integer { value: ref v1 },
-        &PyObjectKind::Integer { value: ref v2 },
-      ) => Ok(self.ctx.new_int(v1.pow(*v2 as u32))),
+  fn int_to_float(&mut self, value: &IntValue) -> Result<f64, PyObjectRef> {
+    objint::to_float(value).ok_or_else(|| {
+      self.new_overflow_error("int too large to convert to float")
+    })
+  }
+
+  // Operands of arithmetic between numbers, converted to their common type.
+  // Returns None unless both of them are numbers:
+  fn numeric_operands(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+  ) -> Result<Option<NumberPair>, PyObjectRef> {
+    let a = objnumber::to_number(&a.borrow().kind);
+    let b = objnumber::to_number(&b.borrow().kind);
+    match (a, b) {
+      (Some(a), Some(b)) => match objnumber::coerce(a, b) {
+        Some(pair) => Ok(Some(pair)),
+        None => {
+          Err(self.new_overflow_error("int too large to convert to float"))
+        }
+      },
+      _ => Ok(None),
+    }
+  }
+
+  // Operands of bitwise operations, which are only defined for ints (and
+  // bools, which are ints too):
+  fn integer_operands(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+  ) -> Option<(IntValue, IntValue)> {
+    let a = objnumber::to_number(&a.borrow().kind);
+    let b = objnumber::to_number(&b.borrow().kind);
+    match (a, b) {
+      (Some(Number::Integer(v1)), Some(Number::Integer(v2))) => Some((v1, v2)),
+      _ => None,
+    }
+  }
+
+  fn _sub(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(v1, v2)) => Ok(self.ctx.new_int(v1 - v2)),
+      Some(NumberPair::Float(v1, v2)) => Ok(self.ctx.new_float(v1 - v2)),
+      Some(NumberPair::Complex(v1, v2)) => Ok(self.ctx.new_complex(v1 - v2)),
+      None => Err(self.new_binop_type_error(&a, &b, "-")),
+    }
+  }
+
+  fn _add(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match (&a.borrow().kind, &b.borrow().kind) {
       (
-        &PyObjectKind::Float { value: ref v1 },
-        &PyObjectKind::Integer { value: ref v2 },
-      ) => Ok(self.ctx.new_float(v1.powf(*v2 as f64))),
+        PyObjectKind::String { value: v1 },
+        PyObjectKind::String { value: v2 },
+      ) => return Ok(self.new_str(format!("{}{}", v1, v2))),
       (
-        &PyObjectKind::Integer { value: ref v1 },
-        &PyObjectKind::Float { value: ref v2 },
-      ) => Ok(self.ctx.new_float((*v1 as f64).powf(*v2))),
+        PyObjectKind::List { elements: e1 },
+        PyObjectKind::List { elements: e2 },
+      ) => {
+        return Ok(
+          self
+            .ctx
+            .new_list(e1.iter().chain(e2.iter()).cloned().collect()),
+        )
+      }
+      _ => {}
+    };
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(v1, v2)) => Ok(self.ctx.new_int(v1 + v2)),
+      Some(NumberPair::Float(v1, v2)) => Ok(self.ctx.new_float(v1 + v2)),
+      Some(NumberPair::Complex(v1, v2)) => Ok(self.ctx.new_complex(v1 + v2)),
+      None => Err(self.new_binop_type_error(&a, &b, "+")),
+    }
+  }
+
+  fn _mul(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match (&a.borrow().kind, &b.borrow().kind) {
       (
-        &PyObjectKind::Float { value: ref v1 },
-        &PyObjectKind::Float { value: ref v2 },
-      ) => Ok(self.ctx.new_float(v1.powf(*v2))),
-      _ => panic!("Not impl"),
+        PyObjectKind::String { value: s },
+        PyObjectKind::Integer { value: n },
+      )
+      | (
+        PyObjectKind::Integer { value: n },
+        PyObjectKind::String { value: s },
+      ) => {
+        // Negative counts give the empty string:
+        let count = if n.is_negative() {
+          Some(0)
+        } else {
+          n.to_usize()
+        };
+        return match count {
+          Some(count) => Ok(self.new_str(s.repeat(count))),
+          None => Err(self.new_overflow_error(
+            "cannot fit 'int' into an index-sized integer",
+          )),
+        };
+      }
+      _ => {}
+    };
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(v1, v2)) => Ok(self.ctx.new_int(v1 * v2)),
+      Some(NumberPair::Float(v1, v2)) => Ok(self.ctx.new_float(v1 * v2)),
+      Some(NumberPair::Complex(v1, v2)) => Ok(self.ctx.new_complex(v1 * v2)),
+      None => Err(self.new_binop_type_error(&a, &b, "*")),
     }
   }
 
-  fn _modulo(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    Ok(PyObject::new(a2 % b2, self.get_type()))
+  fn _div(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(_, ref v2)) if v2.is_zero() => {
+        Err(self.new_zero_division_error("division by zero"))
+      }
+      Some(NumberPair::Integer(v1, v2)) => {
+        let v1 = self.int_to_float(&v1)?;
+        let v2 = self.int_to_float(&v2)?;
+        Ok(self.ctx.new_float(v1 / v2))
+      }
+      Some(NumberPair::Float(_, v2)) if v2 == 0.0 => {
+        Err(self.new_zero_division_error("float division by zero"))
+      }
+      Some(NumberPair::Float(v1, v2)) => Ok(self.ctx.new_float(v1 / v2)),
+      Some(NumberPair::Complex(v1, v2)) => match objcomplex::divide(v1, v2) {
+        Some(result) => Ok(self.ctx.new_complex(result)),
+        None => Err(self.new_zero_division_error("complex division by zero")),
+      },
+      None => Err(self.new_binop_type_error(&a, &b, "/")),
+    }
   }
 
-  fn execute_binop(
-    &mut self,
-    op: &bytecode::BinaryOperator,
-  ) -> Option<PyResult> {
-    let b_ref = self.pop_value();
-    let a_ref = self.pop_value();
-    // TODO: if the left hand side provides __add__, invoke that function.
-    //
-    let result = match op {
-      &bytecode::BinaryOperator::Subtract => self._sub(a_ref, b_ref),
-      &bytecode::BinaryOperator::Add => self._add(a_ref, b_ref),
-      &bytecode::BinaryOperator::Multiply => self._mul(a_ref, b_ref),
-      &bytecode::BinaryOperator::Power => self._pow(a_ref, b_ref),
-      &bytecode::BinaryOperator::Divide => self._div(a_ref, b_ref),
-      &bytecode::BinaryOperator::Subscript => self.subscript(a_ref, b_ref),
-      &bytecode::BinaryOperator::Modulo => self._modulo(a_ref, b_ref),
-      _ => panic!("NOT IMPL {:?}", op),
-    };
-    match result {
-      Ok(value) => {
-        self.push_value(value);
-        None
+  pub fn _floordiv(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(_, ref v2)) if v2.is_zero() => {
+        Err(self.new_zero_division_error("integer division or modulo by zero"))
       }
-      Err(value) => Some(Err(value)),
+      Some(NumberPair::Integer(v1, v2)) => {
+        Ok(self.ctx.new_int(objint::floordiv(&v1, &v2)))
+      }
+      Some(NumberPair::Float(_, v2)) if v2 == 0.0 => {
+        Err(self.new_zero_division_error("float floor division by zero"))
+      }
+      Some(NumberPair::Float(v1, v2)) => {
+        Ok(self.ctx.new_float(objfloat::divmod(v1, v2).0))
+      }
+      _ => Err(self.new_binop_type_error(&a, &b, "//")),
     }
   }
 
-  fn execute_unop(&mut self, op: &bytecode::UnaryOperator) -> Option<PyResult> {
-    let a_ref = self.pop_value();
-    let a = &*a_ref.borrow();
-    let result = match op {
-      &bytecode::UnaryOperator::Minus => {
-        // TODO:
-        // self.invoke('__neg__'
-        match a.kind {
-          PyObjectKind::Integer { value: ref value1 } => {
-            Ok(self.ctx.new_int(-*value1))
+  fn _pow(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(v1, v2)) => {
+        if v2.is_negative() {
+          // A negative exponent gives a float result:
+          if v1.is_zero() {
+            return Err(self.new_zero_division_error(
+              "0.0 cannot be raised to a negative power",
+            ));
           }
-          _ => panic!("Not impl {:?}", a),
+          let v1 = self.int_to_float(&v1)?;
+          let v2 = self.int_to_float(&v2)?;
+          return Ok(self.ctx.new_float(v1.powf(v2)));
+        }
+        match v2.to_u32() {
+          Some(exponent) => Ok(self.ctx.new_int(v1.pow(exponent))),
+          None => Err(self.new_overflow_error("exponent too large")),
         }
       }
-      &bytecode::UnaryOperator::Not => {
-        // TODO:
-        // self.invoke('__neg__'
-        match a.kind {
-          PyObjectKind::Boolean { value: ref value1 } => {
-            Ok(self.ctx.new_bool(!*value1))
-          }
-          _ => panic!("Not impl {:?}", a),
+      Some(NumberPair::Float(v1, v2)) if v1 == 0.0 && v2 < 0.0 => Err(
+        self
+          .new_zero_division_error("0.0 cannot be raised to a negative power"),
+      ),
+      Some(NumberPair::Float(v1, v2))
+        if v1 < 0.0 && v2.is_finite() && v2 != v2.trunc() =>
+      {
+        // A negative number raised to a fractional power is complex:
+        let result =
+          objcomplex::pow(Complex64::new(v1, 0.0), Complex64::new(v2, 0.0));
+        Ok(self.ctx.new_complex(result.unwrap()))
+      }
+      Some(NumberPair::Float(v1, v2)) => {
+        let result = v1.powf(v2);
+        if result.is_infinite() && v1.is_finite() && v2.is_finite() {
+          Err(self.new_overflow_error("Numerical result out of range"))
+        } else {
+          Ok(self.ctx.new_float(result))
         }
       }
-      _ => panic!("Not impl {:?}", op),
+      Some(NumberPair::Complex(v1, v2)) => match objcomplex::pow(v1, v2) {
+        Some(result) => Ok(self.ctx.new_complex(result)),
+        None => Err(
+          self.new_zero_division_error("0.0 to a negative or complex power"),
+        ),
+      },
+      None => Err(self.new_binop_type_error(&a, &b, "**")),
+    }
+  }
+
+  pub fn _modulo(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    // On a string, % is printf-style formatting:
+    let format = match a.borrow().kind {
+      PyObjectKind::String { ref value } => Some(value.clone()),
+      _ => None,
     };
-    match result {
-      Ok(value) => {
-        self.push_value(value);
-        None
+    if let Some(format) = format {
+      let value = objstr::format_percent(self, &format, b)?;
+      return Ok(self.new_str(value));
+    }
+    match self.numeric_operands(&a, &b)? {
+      Some(NumberPair::Integer(_, ref v2)) if v2.is_zero() => {
+        Err(self.new_zero_division_error("integer division or modulo by zero"))
       }
-      Err(value) => Some(Err(value)),
+      Some(NumberPair::Integer(v1, v2)) => {
+        Ok(self.ctx.new_int(objint::modulo(&v1, &v2)))
+      }
+      Some(NumberPair::Float(_, v2)) if v2 == 0.0 => {
+        Err(self.new_zero_division_error("float modulo"))
+      }
+      Some(NumberPair::Float(v1, v2)) => {
+        Ok(self.ctx.new_float(objfloat::divmod(v1, v2).1))
+      }
+      _ => Err(self.new_binop_type_error(&a, &b, "%")),
     }
   }
 
-  fn _eq(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    let result_bool = a2 == b2;
-    let result = self.ctx.new_bool(result_bool);
-    Ok(result)
+  fn _lshift(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.integer_operands(&a, &b) {
+      Some((_, ref v2)) if v2.is_negative() => {
+        Err(self.new_value_error("negative shift count".to_string()))
+      }
+      Some((v1, v2)) => match v2.to_usize() {
+        Some(count) => Ok(self.ctx.new_int(v1 << count)),
+        None => Err(self.new_overflow_error("too many digits in integer")),
+      },
+      None => Err(self.new_binop_type_error(&a, &b, "<<")),
+    }
   }
 
-  fn _ne(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    let result_bool = a2 != b2;
-    let result = self.ctx.new_bool(result_bool);
-    Ok(result)
+  fn _rshift(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    match self.integer_operands(&a, &b) {
+      Some((_, ref v2)) if v2.is_negative() => {
+        Err(self.new_value_error("negative shift count".to_string()))
+      }
+      Some((v1, v2)) => {
+        // Shifting out every bit leaves 0, or -1 for negative numbers:
+        let result = match v2.to_usize() {
+          Some(count) => v1 >> count,
+          None if v1.is_negative() => IntValue::from(-1),
+          None => IntValue::from(0),
+        };
+        Ok(self.ctx.new_int(result))
+      }
+      None => Err(self.new_binop_type_error(&a, &b, ">>")),
+    }
   }
 
-  fn _lt(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    let result_bool = a2 < b2;
-    let result = self.ctx.new_bool(result_bool);
-    Ok(result)
+  // Bitwise operations between two bools give a bool, otherwise both operands
+  // are treated as ints:
+  fn _bitwise(
+    &mut self,
+    a: PyObjectRef,
+    b: PyObjectRef,
+    op: &str,
+    bool_op: fn(bool, bool) -> bool,
+    int_op: fn(&IntValue, &IntValue) -> IntValue,
+  ) -> PyResult {
+    if let (
+      PyObjectKind::Boolean { value: v1 },
+      PyObjectKind::Boolean { value: v2 },
+    ) = (&a.borrow().kind, &b.borrow().kind)
+    {
+      return Ok(self.ctx.new_bool(bool_op(*v1, *v2)));
+    }
+    match self.integer_operands(&a, &b) {
+      Some((v1, v2)) => Ok(self.ctx.new_int(int_op(&v1, &v2))),
+      None => Err(self.new_binop_type_error(&a, &b, op)),
+    }
   }
 
-  fn _le(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+  fn _and(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._bitwise(a, b, "&", |v1, v2| v1 & v2, |v1, v2| v1 & v2)
+  }
+
+  fn _or(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._bitwise(a, b, "|", |v1, v2| v1 | v2, |v1, v2| v1 | v2)
+  }
+
+  fn _xor(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._bitwise(a, b, "^", |v1, v2| v1 ^ v2, |v1, v2| v1 ^ v2)
+  }
+
+  // Call a special method looked up on the type of obj, passing obj as the
+  // first argument. Returns None when the type does not define the method, or
+  // when it returns NotImplemented:
+  fn call_special_method(
+    &mut self,
+    obj: &PyObjectRef,
+    name: &str,
+    mut args: Vec<PyObjectRef>,
+  ) -> Option<PyResult> {
+    let method = objtype::get_special_method(obj, name)?;
+    args.insert(0, obj.clone());
+    match self.invoke(method, PyFuncArgs::new(args, vec![])) {
+      Ok(ref value) if value.get_id() == self.ctx.not_implemented.get_id() => {
+        None
+      }
+      result => Some(result),
+    }
+  }
+
+  // Try the special method of a binary operation on the left operand, then
+  // the reflected method on the right operand. The right operand goes first
+  // when its type is a subclass of the left operand's type, so subclasses
+  // can override operators of their bases:
+  fn call_binary_special_method(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+    name: &str,
+    reflected_name: &str,
+    reflect_same_type: bool,
+  ) -> Option<PyResult> {
+    let same_type = a.typ().get_id() == b.typ().get_id();
+    let reflect = reflect_same_type || !same_type;
+    if reflect && !same_type && objtype::issubclass(&b.typ(), &a.typ()) {
+      if let Some(result) =
+        self.call_special_method(b, reflected_name, vec![a.clone()])
+      {
+        return Some(result);
+      }
+      return self.call_special_method(a, name, vec![b.clone()]);
+    }
+    if let Some(result) = self.call_special_method(a, name, vec![b.clone()]) {
+      return Some(result);
+    }
+    if reflect {
+      self.call_special_method(b, reflected_name, vec![a.clone()])
+    } else {
+      None
+    }
+  }
+
+  // Binary operators dispatch to the special methods of user defined classes
+  // first, and fall back on the built-in implementation:
+  fn binop(
+    &mut self,
+    a: PyObjectRef,
+    b: PyObjectRef,
+    name: &str,
+    reflected_name: &str,
+    builtin: fn(&mut VirtualMachine, PyObjectRef, PyObjectRef) -> PyResult,
+  ) -> PyResult {
+    match self.call_binary_special_method(&a, &b, name, reflected_name, false) {
+      Some(result) => result,
+      None => builtin(self, a, b),
+    }
+  }
+
+  // Augmented assignments first give the left operand a chance to update
+  // itself through its in-place method, such as __iadd__:
+  fn inplace_binop(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+    op: &bytecode::BinaryOperator,
+  ) -> Option<PyResult> {
+    let name = match op {
+      &bytecode::BinaryOperator::Subtract => "__isub__",
+      &bytecode::BinaryOperator::Add => "__iadd__",
+      &bytecode::BinaryOperator::Multiply => "__imul__",
+      &bytecode::BinaryOperator::Power => "__ipow__",
+      &bytecode::BinaryOperator::Divide => "__itruediv__",
+      &bytecode::BinaryOperator::Modulo => "__imod__",
+      &bytecode::BinaryOperator::MatrixMultiply => "__imatmul__",
+      &bytecode::BinaryOperator::FloorDivide => "__ifloordiv__",
+      &bytecode::BinaryOperator::Lshift => "__ilshift__",
+      &bytecode::BinaryOperator::Rshift => "__irshift__",
+      &bytecode::BinaryOperator::And => "__iand__",
+      &bytecode::BinaryOperator::Xor => "__ixor__",
+      &bytecode::BinaryOperator::Or => "__ior__",
+      &bytecode::BinaryOperator::Subscript => return None,
+    };
+    self.call_special_method(a, name, vec![b.clone()])
+  }
+
+  fn execute_binop(
+    &mut self,
+    op: &bytecode::BinaryOperator,
+    inplace: bool,
+  ) -> Option<PyResult> {
+    let b_ref = self.pop_value();
+    let a_ref = self.pop_value();
+    if inplace {
+      match self.inplace_binop(&a_ref, &b_ref, op) {
+        Some(Ok(value)) => {
+          self.push_value(value);
+          return None;
+        }
+        Some(Err(exc)) => return Some(Err(exc)),
+        None => {}
+      }
+    }
+    let result = match op {
+      &bytecode::BinaryOperator::Subtract => {
+        self.binop(a_ref, b_ref, "__sub__", "__rsub__", VirtualMachine::_sub)
+      }
+      &bytecode::BinaryOperator::Add => {
+        self.binop(a_ref, b_ref, "__add__", "__radd__", VirtualMachine::_add)
+      }
+      &bytecode::BinaryOperator::Multiply => {
+        self.binop(a_ref, b_ref, "__mul__", "__rmul__", VirtualMachine::_mul)
+      }
+      &bytecode::BinaryOperator::Power => {
+        self.binop(a_ref, b_ref, "__pow__", "__rpow__", VirtualMachine::_pow)
+      }
+      &bytecode::BinaryOperator::Divide => self.binop(
+        a_ref,
+        b_ref,
+        "__truediv__",
+        "__rtruediv__",
+        VirtualMachine::_div,
+      ),
+      &bytecode::BinaryOperator::Subscript => self.subscript(a_ref, b_ref),
+      &bytecode::BinaryOperator::Modulo => {
+        self.binop(a_ref, b_ref, "__mod__", "__rmod__", VirtualMachine::_modulo)
+      }
+      &bytecode::BinaryOperator::MatrixMultiply => {
+        self.binop(a_ref, b_ref, "__matmul__", "__rmatmul__", |vm, a, b| {
+          Err(vm.new_binop_type_error(&a, &b, "@"))
+        })
+      }
+      &bytecode::BinaryOperator::FloorDivide => self.binop(
+        a_ref,
+        b_ref,
+        "__floordiv__",
+        "__rfloordiv__",
+        VirtualMachine::_floordiv,
+      ),
+      &bytecode::BinaryOperator::Lshift => self.binop(
+        a_ref,
+        b_ref,
+        "__lshift__",
+        "__rlshift__",
+        VirtualMachine::_lshift,
+      ),
+      &bytecode::BinaryOperator::Rshift => self.binop(
+        a_ref,
+        b_ref,
+        "__rshift__",
+        "__rrshift__",
+        VirtualMachine::_rshift,
+      ),
+      &bytecode::BinaryOperator::And => {
+        self.binop(a_ref, b_ref, "__and__", "__rand__", VirtualMachine::_and)
+      }
+      &bytecode::BinaryOperator::Xor => {
+        self.binop(a_ref, b_ref, "__xor__", "__rxor__", VirtualMachine::_xor)
+      }
+      &bytecode::BinaryOperator::Or => {
+        self.binop(a_ref, b_ref, "__or__", "__ror__", VirtualMachine::_or)
+      }
+    };
+    match result {
+      Ok(value) => {
+        self.push_value(value);
+        None
+      }
+      Err(value) => Some(Err(value)),
+    }
+  }
+
+  fn new_unop_type_error(&mut self, a: &PyObjectRef, op: &str) -> PyObjectRef {
+    let a_type_name = objtype::get_type_name(&a.typ());
+    self.new_type_error(format!(
+      "bad operand type for unary {}: '{}'",
+      op, a_type_name
+    ))
+  }
+
+  fn execute_unop(&mut self, op: &bytecode::UnaryOperator) -> Option<PyResult> {
+    let a = self.pop_value();
+    // Truth testing has its own fallbacks for `not`:
+    let name = match op {
+      &bytecode::UnaryOperator::Minus => Some("__neg__"),
+      &bytecode::UnaryOperator::Plus => Some("__pos__"),
+      &bytecode::UnaryOperator::Invert => Some("__invert__"),
+      &bytecode::UnaryOperator::Not => None,
+    };
+    let method_result =
+      name.and_then(|name| self.call_special_method(&a, name, vec![]));
+    let result = if let Some(result) = method_result {
+      result
+    } else {
+      match op {
+        &bytecode::UnaryOperator::Minus => {
+          match objnumber::to_number(&a.borrow().kind) {
+            Some(Number::Integer(value)) => Ok(self.ctx.new_int(-value)),
+            Some(Number::Float(value)) => Ok(self.ctx.new_float(-value)),
+            Some(Number::Complex(value)) => Ok(self.ctx.new_complex(-value)),
+            None => Err(self.new_unop_type_error(&a, "-")),
+          }
+        }
+        &bytecode::UnaryOperator::Plus => {
+          match objnumber::to_number(&a.borrow().kind) {
+            Some(Number::Integer(value)) => Ok(self.ctx.new_int(value)),
+            Some(Number::Float(value)) => Ok(self.ctx.new_float(value)),
+            Some(Number::Complex(value)) => Ok(self.ctx.new_complex(value)),
+            None => Err(self.new_unop_type_error(&a, "+")),
+          }
+        }
+        &bytecode::UnaryOperator::Invert => {
+          match objnumber::to_number(&a.borrow().kind) {
+            Some(Number::Integer(value)) => Ok(self.ctx.new_int(!value)),
+            _ => Err(self.new_unop_type_error(&a, "~")),
+          }
+        }
+        &bytecode::UnaryOperator::Not => match objbool::boolval(self, a) {
+          Ok(value) => Ok(self.ctx.new_bool(!value)),
+          Err(value) => Err(value),
+        },
+      }
+    };
+    match result {
+      Ok(value) => {
+        self.push_value(value);
+        None
+      }
+      Err(value) => Some(Err(value)),
+    }
+  }
+
+  pub fn _eq(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    if let Some(result) =
+      self.call_binary_special_method(&a, &b, "__eq__", "__eq__", true)
+    {
+      return result;
+    }
+    if let Some(result_bool) = self._container_eq(&a, &b)? {
+      return Ok(self.ctx.new_bool(result_bool));
+    }
     let b2 = &*b.borrow();
     let a2 = &*a.borrow();
-    let result_bool = a2 <= b2;
+    let result_bool = a2 == b2;
     let result = self.ctx.new_bool(result_bool);
     Ok(result)
   }
 
-  fn _gt(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+  fn _ne(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    if let Some(result) =
+      self.call_binary_special_method(&a, &b, "__ne__", "__ne__", true)
+    {
+      return result;
+    }
+    // Without __ne__, the result of __eq__ is inverted:
+    if let Some(result) =
+      self.call_binary_special_method(&a, &b, "__eq__", "__eq__", true)
+    {
+      let value = objbool::boolval(self, result?)?;
+      return Ok(self.ctx.new_bool(!value));
+    }
+    if let Some(result_bool) = self._container_eq(&a, &b)? {
+      return Ok(self.ctx.new_bool(!result_bool));
+    }
     let b2 = &*b.borrow();
     let a2 = &*a.borrow();
-    let result_bool = a2 > b2;
+    let result_bool = a2 != b2;
     let result = self.ctx.new_bool(result_bool);
     Ok(result)
   }
 
-  fn _ge(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
-    let b2 = &*b.borrow();
-    let a2 = &*a.borrow();
-    let result_bool = a2 >= b2;
+  // Lists, tuples, dicts and sets are equal to their own kind when their
+  // elements are:
+  fn _container_eq(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+  ) -> Result<Option<bool>, PyObjectRef> {
+    // The elements are copied out before comparing them, since that runs
+    // Python code which may change the containers:
+    let sequences = match (&a.borrow().kind, &b.borrow().kind) {
+      (
+        PyObjectKind::List { elements: ref l1 },
+        PyObjectKind::List { elements: ref l2 },
+      )
+      | (
+        PyObjectKind::Tuple { elements: ref l1 },
+        PyObjectKind::Tuple { elements: ref l2 },
+      ) => Some((l1.clone(), l2.clone())),
+      _ => None,
+    };
+    if let Some((l1, l2)) = sequences {
+      return objsequence::equal(self, &l1, &l2).map(Some);
+    }
+    let is_set = |kind: &PyObjectKind| match kind {
+      PyObjectKind::Set { .. } | PyObjectKind::FrozenSet { .. } => true,
+      _ => false,
+    };
+    let (is_dicts, is_sets) = match (&a.borrow().kind, &b.borrow().kind) {
+      (PyObjectKind::Dict { .. }, PyObjectKind::Dict { .. }) => (true, false),
+      (a, b) => (false, is_set(a) && is_set(b)),
+    };
+    if is_dicts {
+      objdict::equal(self, a, b).map(Some)
+    } else if is_sets {
+      objset::equal(self, a, b).map(Some)
+    } else {
+      Ok(None)
+    }
+  }
+
+  // Whether a container holds an element: it does if the element is the
+  // object itself, which may not be equal to itself, or is equal to it:
+  pub fn identical_or_equal(
+    &mut self,
+    a: &PyObjectRef,
+    b: &PyObjectRef,
+  ) -> Result<bool, PyObjectRef> {
+    if a.get_id() == b.get_id() {
+      return Ok(true);
+    }
+    let equal = self._eq(a.clone(), b.clone())?;
+    objbool::boolval(self, equal)
+  }
+
+  fn _order(
+    &mut self,
+    a: PyObjectRef,
+    b: PyObjectRef,
+    op: &str,
+    name: &str,
+    reflected_name: &str,
+    test: fn(Ordering) -> bool,
+  ) -> PyResult {
+    if let Some(result) =
+      self.call_binary_special_method(&a, &b, name, reflected_name, true)
+    {
+      return result;
+    }
+    let ordering = a.borrow().partial_cmp(&*b.borrow());
+    let result_bool = match ordering {
+      Some(ordering) => test(ordering),
+      None => {
+        // Complex numbers have no order, so comparing them is an error:
+        let is_number = |obj: &PyObjectRef| match obj.borrow().kind {
+          PyObjectKind::Boolean { .. }
+          | PyObjectKind::Integer { .. }
+          | PyObjectKind::Float { .. } => true,
+          _ => false,
+        };
+        if is_number(&a) && is_number(&b) {
+          // NaN is unordered, but comparing to it is not an error:
+          false
+        } else {
+          let a_type_name = objtype::get_type_name(&a.typ());
+          let b_type_name = objtype::get_type_name(&b.typ());
+          return Err(self.new_type_error(format!(
+            "'{}' not supported between instances of '{}' and '{}'",
+            op, a_type_name, b_type_name
+          )));
+        }
+      }
+    };
     let result = self.ctx.new_bool(result_bool);
     Ok(result)
   }
 
+  fn _lt(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._order(a, b, "<", "__lt__", "__gt__", |o| o == Ordering::Less)
+  }
+
+  fn _le(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._order(a, b, "<=", "__le__", "__ge__", |o| o != Ordering::Greater)
+  }
+
+  fn _gt(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._order(a, b, ">", "__gt__", "__lt__", |o| o == Ordering::Greater)
+  }
+
+  fn _ge(&mut self, a: PyObjectRef, b: PyObjectRef) -> PyResult {
+    self._order(a, b, ">=", "__ge__", "__le__", |o| o != Ordering::Less)
+  }
+
   fn _id(&mut self, a: PyObjectRef) -> usize {
     a.get_id()
   }
@@ -465,6 +1468,92 @@ impl VirtualMachine {
     Ok(result)
   }
 
+  fn _contains(
+    &mut self,
+    needle: PyObjectRef,
+    haystack: PyObjectRef,
+  ) -> Result<bool, PyObjectRef> {
+    if let Some(result) =
+      self.call_special_method(&haystack, "__contains__", vec![needle.clone()])
+    {
+      return objbool::boolval(self, result?);
+    }
+    let builtin_result = match (&needle.borrow().kind, &haystack.borrow().kind)
+    {
+      (
+        PyObjectKind::String { value: ref needle },
+        PyObjectKind::String {
+          value: ref haystack,
+        },
+      ) => Some(Ok(haystack.contains(needle.as_str()))),
+      (_, PyObjectKind::String { .. }) => {
+        let needle_type_name = objtype::get_type_name(&needle.typ());
+        Some(Err(self.new_type_error(format!(
+          "'in <string>' requires string as left operand, not {}",
+          needle_type_name
+        ))))
+      }
+      _ => None,
+    };
+    if let Some(result) = builtin_result {
+      return result;
+    }
+    // Any other iterable contains the items it produces:
+    if !objiter::is_iterable(&haystack) {
+      let haystack_type_name = objtype::get_type_name(&haystack.typ());
+      return Err(self.new_type_error(format!(
+        "argument of type '{}' is not iterable",
+        haystack_type_name
+      )));
+    }
+    let iterator = objiter::get_iter(self, &haystack)?;
+    while let Some(element) = objiter::get_next_object(self, &iterator)? {
+      if self.identical_or_equal(&element, &needle)? {
+        return Ok(true);
+      }
+    }
+    Ok(false)
+  }
+
+  fn _in(&mut self, needle: PyObjectRef, haystack: PyObjectRef) -> PyResult {
+    let result_bool = self._contains(needle, haystack)?;
+    Ok(self.ctx.new_bool(result_bool))
+  }
+
+  fn _not_in(
+    &mut self,
+    needle: PyObjectRef,
+    haystack: PyObjectRef,
+  ) -> PyResult {
+    let result_bool = !self._contains(needle, haystack)?;
+    Ok(self.ctx.new_bool(result_bool))
+  }
+
+  fn _exception_match(
+    &mut self,
+    exc: PyObjectRef,
+    cls: PyObjectRef,
+  ) -> PyResult {
+    // An except clause matches if the exception is an instance of the given
+    // class, or of any of the classes in a given tuple:
+    let base_exception = self.ctx.exceptions.base_exception_type.clone();
+    match objtype::get_classes(&cls) {
+      Some(ref classes)
+        if classes
+          .iter()
+          .all(|class| objtype::issubclass(class, &base_exception)) =>
+      {
+        let result_bool =
+          classes.iter().any(|class| objtype::isinstance(&exc, class));
+        Ok(self.ctx.new_bool(result_bool))
+      }
+      _ => Err(self.new_type_error(
+        "catching classes that do not inherit from BaseException is not allowed"
+          .to_string(),
+      )),
+    }
+  }
+
   fn execute_compare(
     &mut self,
     op: &bytecode::ComparisonOperator,
@@ -480,7 +1569,11 @@ impl VirtualMachine {
       &bytecode::ComparisonOperator::GreaterOrEqual => self._ge(a, b),
       &bytecode::ComparisonOperator::Is => self._is(a, b),
       &bytecode::ComparisonOperator::IsNot => self._is_not(a, b),
-      _ => panic!("NOT IMPL {:?}", op),
+      &bytecode::ComparisonOperator::In => self._in(a, b),
+      &bytecode::ComparisonOperator::NotIn => self._not_in(a, b),
+      &bytecode::ComparisonOperator::ExceptionMatch => {
+        self._exception_match(a, b)
+      }
     };
     match result {
       Ok(value) => {
@@ -501,14 +1594,31 @@ impl VirtualMachine {
       PyObjectKind::RustFunction { function } => function(self, args),
       PyObjectKind::Function {
         ref code,
-        ref scope,
+        ref globals,
+        ref closure,
+        ref defaults,
+        ref kw_defaults,
       } => {
-        let scope = self.ctx.new_scope(Some(scope.clone()));
+        let scope = self.ctx.new_scope(Some(globals.clone()));
         let code_object = copy_code(code.clone());
-        for (name, value) in code_object.arg_names.iter().zip(args.args) {
-          scope.set_item(name, value);
+        self.bind_arguments(
+          &code_object,
+          &scope,
+          defaults,
+          kw_defaults,
+          args,
+        )?;
+        let frame = Frame::new(
+          &self.ctx,
+          code.clone(),
+          scope,
+          globals.clone(),
+          closure.clone(),
+        );
+        // Calling a generator function only creates the generator:
+        if code_object.is_generator {
+          return Ok(objgenerator::new_generator(self, frame));
         }
-        let frame = Frame::new(code.clone(), scope);
         self.run_frame(frame)
       }
       PyObjectKind::Class {
@@ -523,12 +1633,153 @@ impl VirtualMachine {
       PyObjectKind::Instance { .. } => {
         objobject::call(self, args.insert(func_ref.clone()))
       }
-      ref kind => {
-        unimplemented!("invoke unimplemented for: {:?}", kind);
+      _ => {
+        let type_name = objtype::get_type_name(&func_ref.typ());
+        Err(
+          self
+            .new_type_error(format!("'{}' object is not callable", type_name)),
+        )
       }
     }
   }
 
+  // Bind the arguments of a call to the parameters of a Python function, in
+  // its new local scope, raising TypeError like CPython when they don't fit:
+  fn bind_arguments(
+    &mut self,
+    code_object: &bytecode::CodeObject,
+    scope: &PyObjectRef,
+    defaults: &PyObjectRef,
+    kw_defaults: &PyObjectRef,
+    args: PyFuncArgs,
+  ) -> Result<(), PyObjectRef> {
+    let func_name = &code_object.obj_name;
+    let arg_count = code_object.arg_names.len();
+    let given_count = args.args.len();
+
+    // Positional arguments, with any extra ones going to *args:
+    let mut positional = args.args.into_iter();
+    for (name, value) in code_object.arg_names.iter().zip(&mut positional) {
+      scope.set_item(name, value);
+    }
+    let extra: Vec<PyObjectRef> = positional.collect();
+    match code_object.varargs {
+      Some(ref name) => scope.set_item(name, self.ctx.new_tuple(extra)),
+      None if !extra.is_empty() => {
+        let default_count = objsequence::get_elements(defaults).len();
+        let expected = if default_count > 0 {
+          format!("from {} to {}", arg_count - default_count, arg_count)
+        } else {
+          arg_count.to_string()
+        };
+        return Err(self.new_type_error(format!(
+          "{}() takes {} positional argument{} but {} {} given",
+          func_name,
+          expected,
+          if arg_count == 1 { "" } else { "s" },
+          given_count,
+          if given_count == 1 { "was" } else { "were" }
+        )));
+      }
+      None => {}
+    }
+
+    // Keyword arguments, with unknown ones going to **kwargs:
+    let kwargs = match code_object.varkeywords {
+      Some(ref name) => {
+        let kwargs = self.new_dict();
+        scope.set_item(name, kwargs.clone());
+        Some(kwargs)
+      }
+      None => None,
+    };
+    for (name, value) in args.kwargs {
+      if code_object.arg_names.contains(&name)
+        || code_object.kwonlyarg_names.contains(&name)
+      {
+        if scope.contains_key(&name) {
+          return Err(self.new_type_error(format!(
+            "{}() got multiple values for argument '{}'",
+            func_name, name
+          )));
+        }
+        scope.set_item(&name, value);
+      } else if let Some(ref kwargs) = kwargs {
+        kwargs.set_item(&name, value);
+      } else {
+        return Err(self.new_type_error(format!(
+          "{}() got an unexpected keyword argument '{}'",
+          func_name, name
+        )));
+      }
+    }
+
+    // Defaults belong to the last positional parameters:
+    let defaults = objsequence::get_elements(defaults);
+    let first_default = arg_count - defaults.len();
+    let mut missing = vec![];
+    for (i, name) in code_object.arg_names.iter().enumerate() {
+      if !scope.contains_key(name) {
+        if i >= first_default {
+          scope.set_item(name, defaults[i - first_default].clone());
+        } else {
+          missing.push(name);
+        }
+      }
+    }
+    if !missing.is_empty() {
+      return Err(self.new_missing_arguments_error(
+        func_name,
+        "positional",
+        missing,
+      ));
+    }
+
+    let mut missing = vec![];
+    for name in &code_object.kwonlyarg_names {
+      if !scope.contains_key(name) {
+        if kw_defaults.contains_key(name) {
+          scope.set_item(name, kw_defaults.get_item(name));
+        } else {
+          missing.push(name);
+        }
+      }
+    }
+    if !missing.is_empty() {
+      return Err(self.new_missing_arguments_error(
+        func_name,
+        "keyword-only",
+        missing,
+      ));
+    }
+    Ok(())
+  }
+
+  fn new_missing_arguments_error(
+    &mut self,
+    func_name: &str,
+    kind: &str,
+    names: Vec<&String>,
+  ) -> PyObjectRef {
+    let quoted: Vec<String> =
+      names.iter().map(|name| format!("'{}'", name)).collect();
+    // Names are listed as 'a', 'a' and 'b', or 'a', 'b', and 'c':
+    let listed = match quoted.split_last() {
+      Some((last, [])) => last.clone(),
+      Some((last, [first])) => format!("{} and {}", first, last),
+      Some((last, rest)) => format!("{}, and {}", rest.join(", "), last),
+      None => String::new(),
+    };
+    self.new_type_error(format!(
+      "{}() missing {} required {} argument{}: {}",
+      func_name,
+      names.len(),
+      kind,
+      if names.len() == 1 { "" } else { "s" },
+      listed
+    ))
+  }
+
   fn import(
     &mut self,
     module: &String,
@@ -566,10 +1817,304 @@ impl VirtualMachine {
   fn store_attr(&mut self, attr_name: &String) -> Option<PyResult> {
     let parent = self.pop_value();
     let value = self.pop_value();
+    match parent.borrow().kind {
+      PyObjectKind::Instance { .. } | PyObjectKind::Class { .. } => {}
+      _ => {
+        let type_name = objtype::get_type_name(&parent.typ());
+        let attribute_error = self.ctx.exceptions.attribute_error.clone();
+        return Some(Err(self.new_exception(
+          attribute_error,
+          format!("'{}' object has no attribute '{}'", type_name, attr_name),
+        )));
+      }
+    }
     parent.set_attr(attr_name, value);
     None
   }
 
+  fn delete_attr(&mut self, attr_name: &String) -> Option<PyResult> {
+    let parent = self.pop_value();
+    if let Some(method) = objtype::get_special_method(&parent, "__delattr__") {
+      let name = self.new_str(attr_name.clone());
+      let args = PyFuncArgs::new(vec![parent, name], vec![]);
+      return self.invoke(method, args).err().map(Err);
+    }
+    let type_name = objtype::get_type_name(&parent.typ());
+    let msg = match parent.borrow().kind {
+      PyObjectKind::Instance { .. } | PyObjectKind::Class { .. } => {
+        if parent.del_attr(attr_name) {
+          return None;
+        }
+        format!("'{}' object has no attribute '{}'", type_name, attr_name)
+      }
+      _ => format!(
+        "attribute '{}' of '{}' objects is not writable",
+        attr_name, type_name
+      ),
+    };
+    let attribute_error = self.ctx.exceptions.attribute_error.clone();
+    Some(Err(self.new_exception(attribute_error, msg)))
+  }
+
+  // An exception instance to raise, or to chain with the cause of raising
+  // it. Raising a class instantiates it without arguments:
+  fn make_exception(&mut self, exception: PyObjectRef, what: &str) -> PyResult {
+    let base_exception = self.ctx.exceptions.base_exception_type.clone();
+    if objtype::isinstance(&exception, &base_exception) {
+      Ok(exception)
+    } else if objtype::isinstance(&exception, &self.get_type())
+      && objtype::issubclass(&exception, &base_exception)
+    {
+      self.invoke(exception, PyFuncArgs::default())
+    } else {
+      let msg = format!(
+        "{} must derive from BaseException, not {}",
+        what,
+        objtype::get_type_name(&exception.typ())
+      );
+      Err(self.new_type_error(msg))
+    }
+  }
+
+  // raise re-raises the exception being handled, raise exc raises exc and
+  // raise exc from cause also sets its __cause__:
+  fn execute_raise(&mut self, argc: usize) -> Option<PyResult> {
+    let cause = match argc {
+      0 => {
+        return Some(Err(match self.handled_exception() {
+          Some(exception) => {
+            self.current_frame().reraised = true;
+            exception
+          }
+          None => {
+            let runtime_error = self.ctx.exceptions.runtime_error.clone();
+            self.new_exception(
+              runtime_error,
+              "No active exception to reraise".to_string(),
+            )
+          }
+        }))
+      }
+      1 => None,
+      2 => Some(self.pop_value()),
+      _ => {
+        return Some(Err(self.new_system_error(format!(
+          "RAISE_VARARGS with {} arguments is not supported",
+          argc
+        ))))
+      }
+    };
+    let exception = self.pop_value();
+    let exception = match self.make_exception(exception, "exceptions") {
+      Ok(exception) => exception,
+      Err(err) => return Some(Err(err)),
+    };
+    if let Some(cause) = cause {
+      let is_none = match cause.borrow().kind {
+        PyObjectKind::PyNone => true,
+        _ => false,
+      };
+      let cause = if is_none {
+        Ok(cause)
+      } else {
+        self.make_exception(cause, "exception causes")
+      };
+      match cause {
+        Ok(cause) => {
+          exception.set_attr("__cause__", cause);
+          exception.set_attr("__suppress_context__", self.new_bool(true));
+        }
+        Err(err) => return Some(Err(err)),
+      }
+    }
+    info!("Exception raised: {:?}", exception);
+    Some(Err(exception))
+  }
+
+  // Pop the elements of a tuple or list being built. When they are unpacked,
+  // each element is an iterable, whose items are flattened into the result:
+  fn pop_elements(
+    &mut self,
+    size: usize,
+    unpack: bool,
+  ) -> Result<Vec<PyObjectRef>, PyObjectRef> {
+    let elements = self.pop_multiple(size);
+    if !unpack {
+      return Ok(elements);
+    }
+    let mut flattened = vec![];
+    for element in elements {
+      flattened.extend(objiter::get_all(self, &element)?);
+    }
+    Ok(flattened)
+  }
+
+  // Push the items of an iterable, the first one on top, for the targets of
+  // an assignment. With a starred target, the size is the number of targets
+  // before it, and the items between those and the after ones go in a list:
+  fn execute_unpack(
+    &mut self,
+    value: PyObjectRef,
+    size: usize,
+    after: Option<usize>,
+  ) -> Option<PyResult> {
+    if !objiter::is_iterable(&value) {
+      return Some(Err(self.new_type_error(format!(
+        "cannot unpack non-iterable {} object",
+        objtype::get_type_name(&value.typ())
+      ))));
+    }
+    let mut elements = match objiter::get_all(self, &value) {
+      Ok(elements) => elements,
+      Err(err) => return Some(Err(err)),
+    };
+    let msg = match after {
+      None if elements.len() > size => {
+        Some(format!("too many values to unpack (expected {})", size))
+      }
+      None if elements.len() < size => Some(format!(
+        "not enough values to unpack (expected {}, got {})",
+        size,
+        elements.len()
+      )),
+      Some(after) if elements.len() < size + after => Some(format!(
+        "not enough values to unpack (expected at least {}, got {})",
+        size + after,
+        elements.len()
+      )),
+      _ => None,
+    };
+    if let Some(msg) = msg {
+      return Some(Err(self.new_value_error(msg)));
+    }
+    if let Some(after) = after {
+      let rest = elements.split_off(size);
+      let (middle, last) = rest.split_at(rest.len() - after);
+      elements.push(self.ctx.new_list(middle.to_vec()));
+      elements.extend_from_slice(last);
+    }
+    for element in elements.into_iter().rev() {
+      self.push_value(element);
+    }
+    None
+  }
+
+  // Send a value to the iterator delegated to by yield from, below it on the
+  // stack. While the iterator yields values, the frame yields them too and
+  // runs this instruction again when resumed. Once the iterator is
+  // exhausted, the value it returned replaces it:
+  fn execute_yield_from(&mut self) -> Option<PyResult> {
+    let value = self.pop_value();
+    let iterator = self.last_value();
+    let result = if objgenerator::is_generator(&iterator) {
+      objgenerator::send(self, &iterator, value)
+    } else if let PyObjectKind::PyNone = value.borrow().kind {
+      objiter::call_next(self, &iterator)
+    } else {
+      let type_name = objtype::get_type_name(&iterator.typ());
+      let attribute_error = self.ctx.exceptions.attribute_error.clone();
+      Err(self.new_exception(
+        attribute_error,
+        format!("'{}' object has no attribute 'send'", type_name),
+      ))
+    };
+    match result {
+      Ok(value) => {
+        let frame = self.current_frame();
+        frame.lasti -= 1;
+        frame.yielded = true;
+        Some(Ok(value))
+      }
+      Err(exc) => {
+        if !objgenerator::is_stop_iteration(self, &exc) {
+          return Some(Err(exc));
+        }
+        self.pop_value();
+        let value = objgenerator::stop_iteration_value(self, &exc);
+        self.push_value(value);
+        None
+      }
+    }
+  }
+
+  // Convert and format a value of an f-string:
+  fn execute_format_value(
+    &mut self,
+    conversion: Option<ast::ConversionFlag>,
+    spec: bool,
+  ) -> PyResult {
+    let spec = if spec {
+      let spec = self.pop_value();
+      let spec = objstr::get_value(&spec);
+      spec.unwrap_or_default()
+    } else {
+      String::new()
+    };
+    let value = self.pop_value();
+    let value = match conversion {
+      None => value,
+      Some(ast::ConversionFlag::Str) => {
+        let value = self.to_str(value)?;
+        self.new_str(value)
+      }
+      Some(ast::ConversionFlag::Repr) => {
+        let value = self.to_repr(value)?;
+        self.new_str(value)
+      }
+      Some(ast::ConversionFlag::Ascii) => {
+        let value = self.to_ascii(value)?;
+        self.new_str(value)
+      }
+    };
+    let text = objstr::format_value(self, &value, &spec)?;
+    Ok(self.new_str(text))
+  }
+
+  // Merge a number of dicts into one, as in f(**a, **b). Repeated keys are
+  // only an error for keyword arguments:
+  fn execute_build_map_unpack(
+    &mut self,
+    size: usize,
+    for_call: bool,
+  ) -> Option<PyResult> {
+    let map_obj = self.new_dict();
+    for obj in self.pop_multiple(size) {
+      let items = match obj.borrow().kind {
+        PyObjectKind::Dict { .. } => objdict::get_key_value_pairs(self, &obj),
+        _ => {
+          let type_name = objtype::get_type_name(&obj.typ());
+          return Some(Err(self.new_type_error(format!(
+            "argument after ** must be a mapping, not {}",
+            type_name
+          ))));
+        }
+      };
+      for (key, value) in items {
+        if for_call {
+          let name = match key.borrow().kind {
+            PyObjectKind::String { ref value } => value.clone(),
+            _ => {
+              return Some(Err(
+                self.new_type_error("keywords must be strings".to_string()),
+              ))
+            }
+          };
+          if map_obj.contains_key(&name) {
+            return Some(Err(self.new_type_error(format!(
+              "got multiple values for keyword argument '{}'",
+              name
+            ))));
+          }
+        }
+        if let Err(err) = objdict::set_item(self, &map_obj, &key, value) {
+          return Some(Err(err));
+        }
+      }
+    }
+    self.push_value(map_obj);
+    None
+  }
+
   // Execute a single instruction:
   fn execute_instruction(&mut self) -> Option<PyResult> {
     let instruction = self.current_frame().fetch_instruction();
@@ -588,14 +2133,20 @@ impl VirtualMachine {
       bytecode::Instruction::LoadConst { ref value } => {
         let obj = match value {
           &bytecode::Constant::Integer { ref value } => {
-            self.ctx.new_int(*value)
+            self.ctx.new_int(value.clone())
           }
           &bytecode::Constant::Float { ref value } => {
             self.ctx.new_float(*value)
           }
+          &bytecode::Constant::Complex { ref value } => {
+            self.ctx.new_complex(*value)
+          }
           &bytecode::Constant::String { ref value } => {
             self.new_str(value.clone())
           }
+          &bytecode::Constant::Bytes { ref value } => {
+            self.ctx.new_bytes(value.clone())
+          }
           &bytecode::Constant::Boolean { ref value } => {
             self.new_bool(value.clone())
           }
@@ -603,7 +2154,7 @@ impl VirtualMachine {
             PyObjectKind::Code { code: code.clone() },
             self.get_type(),
           ),
-          &bytecode::Constant::None => self.ctx.none_type.clone(),
+          &bytecode::Constant::None => self.ctx.none.clone(),
         };
         self.push_value(obj);
         None
@@ -612,66 +2163,157 @@ impl VirtualMachine {
         ref name,
         ref symbol,
       } => self.import(name, symbol),
-      bytecode::Instruction::LoadName { ref name } => self.load_name(name),
-      bytecode::Instruction::StoreName { ref name } => {
+      bytecode::Instruction::LoadName { ref name, scope } => {
+        self.load_name(name, *scope)
+      }
+      bytecode::Instruction::StoreName { ref name, scope } => {
         // take top of stack and assign in scope:
-        self.store_name(name)
+        self.store_name(name, *scope)
       }
       bytecode::Instruction::StoreSubscript => self.execute_store_subscript(),
+      bytecode::Instruction::DeleteName { ref name, scope } => {
+        self.delete_name(name, *scope)
+      }
+      bytecode::Instruction::DeleteSubscript => self.execute_delete_subscript(),
       bytecode::Instruction::Pop => {
         // Pop value from stack and ignore.
         self.pop_value();
         None
       }
-      bytecode::Instruction::BuildList { size } => {
-        let elements = self.pop_multiple(*size);
+      bytecode::Instruction::Duplicate => {
+        // Duplicate top of stack
+        let value = self.last_value();
+        self.push_value(value);
+        None
+      }
+      bytecode::Instruction::BuildList { size, unpack } => {
+        let elements = match self.pop_elements(*size, *unpack) {
+          Ok(elements) => elements,
+          Err(err) => return Some(Err(err)),
+        };
         let list_obj = self.context().new_list(elements);
         self.push_value(list_obj);
         None
       }
-      bytecode::Instruction::BuildTuple { size } => {
-        let elements = self.pop_multiple(*size);
+      bytecode::Instruction::UnpackSequence { size } => {
+        let value = self.pop_value();
+        self.execute_unpack(value, *size, None)
+      }
+      bytecode::Instruction::UnpackEx { before, after } => {
+        let value = self.pop_value();
+        self.execute_unpack(value, *before, Some(*after))
+      }
+      bytecode::Instruction::BuildTuple { size, unpack } => {
+        let elements = match self.pop_elements(*size, *unpack) {
+          Ok(elements) => elements,
+          Err(err) => return Some(Err(err)),
+        };
         let list_obj = self.context().new_tuple(elements);
         self.push_value(list_obj);
         None
       }
-      bytecode::Instruction::BuildMap { size } => {
-        let mut elements = HashMap::new();
-        for _x in 0..*size {
-          let obj = self.pop_value();
-          // XXX: Currently, we only support String keys, so we have to unwrap the
-          // PyObject (and ensure it is a String).
-          let key_pyobj = self.pop_value();
-          let key = match key_pyobj.borrow().kind {
-            PyObjectKind::String { ref value } => value.clone(),
-            ref kind => unimplemented!(
-              "Only strings can be used as dict keys, we saw: {:?}",
-              kind
-            ),
-          };
-          elements.insert(key, obj);
+      bytecode::Instruction::BuildMap {
+        size,
+        unpack,
+        for_call,
+      } => {
+        if *unpack {
+          return self.execute_build_map_unpack(*size, *for_call);
+        }
+        let map_obj = self.new_dict();
+        let elements = self.pop_multiple(2 * size);
+        for pair in elements.chunks(2) {
+          if let Err(err) =
+            objdict::set_item(self, &map_obj, &pair[0], pair[1].clone())
+          {
+            return Some(Err(err));
+          }
         }
-        let map_obj = PyObject::new(
-          PyObjectKind::Dict { elements: elements },
-          self.get_type(),
-        );
         self.push_value(map_obj);
         None
       }
+      bytecode::Instruction::BuildSet { size, unpack } => {
+        let elements = match self.pop_elements(*size, *unpack) {
+          Ok(elements) => elements,
+          Err(err) => return Some(Err(err)),
+        };
+        let set_obj = objset::new_set(self, false);
+        for element in elements {
+          if let Err(err) = objset::add(self, &set_obj, element) {
+            return Some(Err(err));
+          }
+        }
+        self.push_value(set_obj);
+        None
+      }
+      bytecode::Instruction::ListAppend { i } => {
+        let value = self.pop_value();
+        let list_obj = self.nth_value(*i);
+        if let PyObjectKind::List { ref mut elements } =
+          list_obj.borrow_mut().kind
+        {
+          elements.push(value);
+        }
+        None
+      }
+      bytecode::Instruction::SetAdd { i } => {
+        let value = self.pop_value();
+        let set_obj = self.nth_value(*i);
+        objset::add(self, &set_obj, value).err().map(Err)
+      }
+      bytecode::Instruction::MapAdd { i } => {
+        let value = self.pop_value();
+        let key = self.pop_value();
+        let map_obj = self.nth_value(*i);
+        objdict::set_item(self, &map_obj, &key, value)
+          .err()
+          .map(Err)
+      }
+      bytecode::Instruction::FormatValue { conversion, spec } => {
+        match self.execute_format_value(*conversion, *spec) {
+          Ok(value) => {
+            self.push_value(value);
+            None
+          }
+          Err(err) => Some(Err(err)),
+        }
+      }
+      bytecode::Instruction::BuildString { size } => {
+        let mut text = String::new();
+        for value in self.pop_multiple(*size) {
+          if let Some(value) = objstr::get_value(&value) {
+            text.push_str(&value);
+          }
+        }
+        let str_obj = self.new_str(text);
+        self.push_value(str_obj);
+        None
+      }
       bytecode::Instruction::BuildSlice { size } => {
         assert!(*size == 2 || *size == 3);
         let elements = self.pop_multiple(*size);
 
-        let out: Vec<Option<i32>> = elements
-          .into_iter()
-          .map(|x| match x.borrow().kind {
-            PyObjectKind::Integer { value } => Some(value),
+        let mut out: Vec<Option<i32>> = Vec::new();
+        for x in elements {
+          let index = match x.borrow().kind {
+            // Slice bounds beyond the i32 range are clamped, as they are
+            // clamped to the sequence length anyway:
+            PyObjectKind::Integer { ref value } => {
+              Some(value.to_i32().unwrap_or(if value.is_negative() {
+                i32::min_value()
+              } else {
+                i32::max_value()
+              }))
+            }
             PyObjectKind::PyNone => None,
             _ => {
-              panic!("Expect Int or None as BUILD_SLICE arguments, got {:?}", x)
+              return Some(Err(self.new_type_error(
+                "slice indices must be integers or None".to_string(),
+              )))
             }
-          })
-          .collect();
+          };
+          out.push(index);
+        }
 
         let start = out[0];
         let stop = out[1];
@@ -684,51 +2326,110 @@ impl VirtualMachine {
         self.push_value(obj);
         None
       }
-      bytecode::Instruction::BinaryOperation { ref op } => {
-        self.execute_binop(op)
-      }
+      bytecode::Instruction::BinaryOperation {
+        ref op,
+        ref inplace,
+      } => self.execute_binop(op, *inplace),
       bytecode::Instruction::LoadAttr { ref name } => self.load_attr(name),
       bytecode::Instruction::StoreAttr { ref name } => self.store_attr(name),
+      bytecode::Instruction::DeleteAttr { ref name } => self.delete_attr(name),
       bytecode::Instruction::UnaryOperation { ref op } => self.execute_unop(op),
       bytecode::Instruction::CompareOperation { ref op } => {
         self.execute_compare(op)
       }
       bytecode::Instruction::ReturnValue => {
         let value = self.pop_value();
+        self.unwind_blocks(UnwindReason::Returning { value: value })
+      }
+      bytecode::Instruction::YieldValue => {
+        let value = self.pop_value();
+        self.current_frame().yielded = true;
         Some(Ok(value))
       }
+      bytecode::Instruction::YieldFrom => self.execute_yield_from(),
       bytecode::Instruction::SetupLoop { start, end } => {
+        let level = self.current_frame().stack_level();
         self.push_block(Block::Loop {
           start: *start,
           end: *end,
+          level: level,
+        });
+        None
+      }
+      bytecode::Instruction::SetupExcept { handler } => {
+        let level = self.current_frame().stack_level();
+        self.push_block(Block::TryExcept {
+          handler: *handler,
+          level: level,
+        });
+        None
+      }
+      bytecode::Instruction::SetupFinally { handler } => {
+        let level = self.current_frame().stack_level();
+        self.push_block(Block::Finally {
+          handler: *handler,
+          level: level,
         });
         None
       }
+      bytecode::Instruction::EnterFinally => {
+        self.push_block(Block::FinallyHandler { reason: None });
+        None
+      }
+      bytecode::Instruction::EndFinally => match self.pop_block() {
+        Some(Block::FinallyHandler { reason }) => match reason {
+          None => None,
+          Some(UnwindReason::Raising { exception }) => {
+            self.current_frame().reraised = true;
+            Some(Err(exception))
+          }
+          Some(reason) => self.unwind_blocks(reason),
+        },
+        _ => Some(Err(
+          self.new_system_error("Wrong block type for END_FINALLY".to_string()),
+        )),
+      },
+      bytecode::Instruction::PopException => match self.pop_block() {
+        Some(Block::ExceptHandler { .. }) => None,
+        _ => Some(Err(
+          self.new_system_error("Wrong block type for POP_EXCEPT".to_string()),
+        )),
+      },
+      bytecode::Instruction::SetupWith { end } => {
+        let context_manager = self.pop_value();
+        self.execute_setup_with(context_manager, *end)
+      }
+      bytecode::Instruction::WithCleanup => match self.pop_block() {
+        Some(Block::With { exit, .. }) => match self.call_with_exit(exit, None)
+        {
+          Ok(_) => None,
+          Err(err) => Some(Err(err)),
+        },
+        _ => Some(Err(
+          self
+            .new_system_error("Wrong block type for WITH_CLEANUP".to_string()),
+        )),
+      },
       bytecode::Instruction::PopBlock => {
         self.pop_block();
         None
       }
       bytecode::Instruction::GetIter => {
         let iterated_obj = self.pop_value();
-        let iter_obj = PyObject::new(
-          PyObjectKind::Iterator {
-            position: 0,
-            iterated_obj: iterated_obj,
-          },
-          self.ctx.type_type.clone(),
-        );
-        self.push_value(iter_obj);
-        None
+        match objiter::get_iter(self, &iterated_obj) {
+          Ok(iter_obj) => {
+            self.push_value(iter_obj);
+            None
+          }
+          Err(err) => Some(Err(err)),
+        }
       }
-      bytecode::Instruction::ForIter => {
+      bytecode::Instruction::ForIter { target } => {
         // The top of stack contains the iterator, lets push it forward:
-        let next_obj: Option<PyObjectRef> = {
-          let top_of_stack = self.last_value();
-          let ref_mut: RefMut<PyObject> = top_of_stack.deref().borrow_mut();
-          // We require a mutable pyobject here to update the iterator:
-          let mut iterator = ref_mut; // &mut PyObject = ref_mut.;
-                                      // let () = iterator;
-          iterator.nxt()
+        let top_of_stack = self.last_value();
+        let next_obj = match objiter::get_next_object(self, &top_of_stack) {
+          Ok(next_obj) => next_obj,
+          Err(err) => return Some(Err(err)),
         };
 
         // Check the next object:
@@ -741,31 +2442,84 @@ impl VirtualMachine {
             self.pop_value();
 
             // End of for loop
-            let end_label =
-              if let Block::Loop { start: _, end } = self.last_block() {
-                *end
-              } else {
-                panic!("Wrong block type")
-              };
-            self.jump(&end_label);
+            self.jump(target);
           }
         };
         None
       }
-      bytecode::Instruction::MakeFunction => {
+      bytecode::Instruction::MakeFunction {
+        has_defaults,
+        has_kw_defaults,
+      } => {
         let _qualified_name = self.pop_value();
         let code_obj = self.pop_value();
-        // pop argc arguments
-        // argument: name, args, globals
-        let scope = self.current_frame().locals.clone();
-        let obj = self.ctx.new_function(code_obj, scope);
+        let kw_defaults = if *has_kw_defaults {
+          self.pop_value()
+        } else {
+          self.new_dict()
+        };
+        let defaults = if *has_defaults {
+          self.pop_value()
+        } else {
+          self.ctx.new_tuple(vec![])
+        };
+        // The function shares the cells of its free variables with this
+        // frame:
+        let closure = match code_obj.borrow().kind {
+          PyObjectKind::Code { ref code } => code
+            .free_names
+            .iter()
+            .map(|name| self.current_frame().cells[name].clone())
+            .collect(),
+          _ => panic!("Can only make a function from a code object"),
+        };
+        let globals = self.current_frame().globals.clone();
+        let obj = self.ctx.new_function(
+          code_obj,
+          globals,
+          closure,
+          defaults,
+          kw_defaults,
+        );
         self.push_value(obj);
         None
       }
-      bytecode::Instruction::CallFunction { count } => {
-        let args: Vec<PyObjectRef> = self.pop_multiple(*count);
-        // TODO: kwargs
-        let args = PyFuncArgs { args: args };
+      bytecode::Instruction::CallFunction { typ } => {
+        let args = match typ {
+          bytecode::CallType::Positional(count) => {
+            let args = self.pop_multiple(*count);
+            PyFuncArgs::new(args, vec![])
+          }
+          bytecode::CallType::Keyword(count) => {
+            let kwarg_names = self.pop_value();
+            let kwarg_names = objsequence::get_elements(&kwarg_names);
+            let mut args = self.pop_multiple(*count);
+            let kwarg_values = args.split_off(count - kwarg_names.len());
+            let kwargs = kwarg_names
+              .iter()
+              .map(|name| name.borrow().str())
+              .zip(kwarg_values)
+              .collect();
+            PyFuncArgs::new(args, kwargs)
+          }
+          bytecode::CallType::Ex(has_kwargs) => {
+            let kwargs = if *has_kwargs {
+              let kwargs = self.pop_value();
+              let kwargs = match kwargs.borrow().kind {
+                PyObjectKind::Dict { ref elements } => elements
+                  .iter()
+                  .map(|(name, value)| (name.to_string(), value.clone()))
+                  .collect(),
+                _ => vec![],
+              };
+              kwargs
+            } else {
+              vec![]
+            };
+            let args = self.pop_value();
+            PyFuncArgs::new(objsequence::get_elements(&args), kwargs)
+          }
+        };
         let func_ref = self.pop_value();
 
         // Call function:
@@ -788,54 +2542,25 @@ impl VirtualMachine {
       }
       bytecode::Instruction::JumpIf { target } => {
         let obj = self.pop_value();
-        // TODO: determine if this value is True-ish:
-        //if *v == NativeType::Boolean(true) {
-        //    curr_frame.lasti = curr_frame.labels.get(target).unwrap().clone();
-        //}
-        let x = obj.borrow();
-        let result: bool = match x.kind {
-          PyObjectKind::Boolean { ref value } => *value,
-          _ => {
-            panic!("Not impl {:?}", x);
+        match objbool::boolval(self, obj) {
+          Ok(true) => {
+            self.jump(target);
+            None
           }
-        };
-        if result {
-          self.jump(target);
+          Ok(false) => None,
+          Err(value) => Some(Err(value)),
         }
-        None
       }
 
-      bytecode::Instruction::Raise { argc } => {
-        let exception = match argc {
-          1 => self.pop_value(),
-          0 | 2 | 3 => panic!("Not implemented!"),
-          _ => {
-            panic!("Invalid paramter for RAISE_VARARGS, must be between 0 to 3")
-          }
-        };
-        info!("Exception raised: {:?}", exception);
-        Some(Err(exception))
-      }
+      bytecode::Instruction::Raise { argc } => self.execute_raise(*argc),
 
-      bytecode::Instruction::Break => {
-        let block = self.unwind_loop();
-        if let Block::Loop { start: _, end } = block {
-          self.jump(&end);
-        }
-        None
-      }
+      bytecode::Instruction::Break => self.unwind_blocks(UnwindReason::Break),
       bytecode::Instruction::Pass => {
         // Ah, this is nice, just relax!
         None
       }
       bytecode::Instruction::Continue => {
-        let block = self.unwind_loop();
-        if let Block::Loop { start, end: _ } = block {
-          self.jump(&start);
-        } else {
-          assert!(false);
-        }
-        None
+        self.unwind_blocks(UnwindReason::Continue)
       }
       bytecode::Instruction::PrintExpr => {
         let expr = self.pop_value();
@@ -844,9 +2569,7 @@ impl VirtualMachine {
           _ => {
             builtins::builtin_print(
               self,
-              PyFuncArgs {
-                args: vec![expr.clone()],
-              },
+              PyFuncArgs::new(vec![expr.clone()], vec![]),
             )
             .unwrap();
           }
@@ -874,7 +2597,10 @@ impl VirtualMachine {
         }
         None
       }
-      _ => panic!("NOT IMPL {:?}", instruction),
+      _ => Some(Err(self.new_system_error(format!(
+        "Instruction {:?} is not implemented",
+        instruction
+      )))),
     }
   }
 
@@ -889,3 +2615,736 @@ impl VirtualMachine {
     current_frame.lasti = target_pc;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::super::compile;
+  use super::super::objsequence;
+  use super::super::objtype;
+  use super::super::pyobject::{
+    AttributeProtocol, DictProtocol, PyObjectKind, PyResult,
+  };
+  use super::Complex64;
+  use super::IntValue;
+  use super::VirtualMachine;
+
+  #[test]
+  fn test_add_py_integers() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_int(33);
+    let b = vm.context().new_int(12);
+    let res = vm._add(a, b).unwrap();
+    match res.borrow().kind {
+      PyObjectKind::Integer { ref value } => {
+        assert_eq!(*value, IntValue::from(45))
+      }
+      _ => assert!(false),
+    };
+  }
+
+  #[test]
+  fn test_int_arithmetic_does_not_overflow() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_int(2);
+    let b = vm.context().new_int(100);
+    let res = vm._pow(a, b).unwrap();
+    let c = vm.context().new_int(i64::max_value());
+    let res = vm._mul(res, c).unwrap();
+    match res.borrow().kind {
+      PyObjectKind::Integer { ref value } => assert_eq!(
+        value.to_string(),
+        "11692013098647223344361828061502034755750757138432"
+      ),
+      _ => assert!(false),
+    };
+  }
+
+  #[test]
+  fn test_small_int_overflow_switches_to_big_int() {
+    let mut vm = VirtualMachine::new();
+    let source = "m = -9223372036854775807 - 1\nresult = (m - 1, -m, m * -1, m // -1, m % -1, abs(m), 2 ** 63, 3 << 62, m >> 70, ~m, (m - 1) + 1 == m, hash(m - 1) == hash(-(-m + 1)))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "(-9223372036854775809, 9223372036854775808, 9223372036854775808, \
+       9223372036854775808, 0, 9223372036854775808, 9223372036854775808, \
+       13835058055282163712, -1, 9223372036854775807, True, True)"
+    );
+  }
+
+  #[test]
+  fn test_multiply_str() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.new_str(String::from("Hello "));
+    let b = vm.context().new_int(4);
+    let res = vm._mul(a, b).unwrap();
+    match res.borrow().kind {
+      PyObjectKind::String { ref value } => {
+        assert_eq!(value, &String::from("Hello Hello Hello Hello "))
+      }
+      _ => assert!(false),
+    };
+  }
+
+  #[test]
+  fn test_unsupported_operands_raise_type_error() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.new_str(String::from("Hello "));
+    let b = vm.context().new_int(4);
+    let exc = vm._add(a, b).unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_divide_by_zero_raises_zero_division_error() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_int(1);
+    let b = vm.context().new_int(0);
+    let exc = vm._div(a, b).unwrap_err();
+    let zero_division_error =
+      vm.context().exceptions.zero_division_error.clone();
+    assert!(objtype::isinstance(&exc, &zero_division_error));
+  }
+
+  #[test]
+  fn test_mixed_arithmetic_coerces_to_wider_type() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_bool(true);
+    let b = vm.context().new_float(0.5);
+    let res = vm._add(a, b).unwrap();
+    assert_eq!(res.borrow().str(), "1.5");
+    let c = vm.context().new_complex(Complex64::new(0.0, 1.0));
+    let res = vm._mul(res, c).unwrap();
+    assert_eq!(res.borrow().str(), "1.5j");
+  }
+
+  #[test]
+  fn test_bitwise_and_of_bools_is_bool() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_bool(true);
+    let b = vm.context().new_bool(false);
+    let res = vm._or(a.clone(), b).unwrap();
+    assert_eq!(res.borrow().str(), "True");
+    let c = vm.context().new_int(2);
+    let res = vm._or(a, c).unwrap();
+    assert_eq!(res.borrow().str(), "3");
+  }
+
+  #[test]
+  fn test_negative_float_to_fractional_power_is_complex() {
+    let mut vm = VirtualMachine::new();
+    let a = vm.context().new_float(-4.0);
+    let b = vm.context().new_float(0.5);
+    let res = vm._pow(a, b).unwrap();
+    match res.borrow().kind {
+      PyObjectKind::Complex { ref value } => {
+        assert!((value.im - 2.0).abs() < 1e-12)
+      }
+      _ => assert!(false),
+    };
+  }
+
+  fn run_source(vm: &mut VirtualMachine, source: &str) -> PyResult {
+    let code =
+      compile::compile(vm, &String::from(source), compile::Mode::Exec, None)
+        .unwrap();
+    let builtins = vm.get_builtin_scope();
+    let scope = vm.context().new_scope(Some(builtins));
+    vm.run_code_obj(code, scope.clone())?;
+    Ok(scope.get_item(&String::from("result")))
+  }
+
+  #[test]
+  fn test_binop_falls_back_on_reflected_special_method() {
+    let mut vm = VirtualMachine::new();
+    let source = "class Money:\n  def __init__(self, amount):\n    self.amount = amount\n  def __radd__(self, other):\n    return other + self.amount\nresult = 5 + Money(3)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "8");
+  }
+
+  #[test]
+  fn test_not_implemented_raises_type_error() {
+    let mut vm = VirtualMachine::new();
+    let source = "class Money:\n  def __add__(self, other):\n    return NotImplemented\nresult = Money() + 1\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_truth_value_uses_len() {
+    let mut vm = VirtualMachine::new();
+    let source = "class Empty:\n  def __len__(self):\n    return 0\nresult = not Empty()\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "True");
+  }
+
+  #[test]
+  fn test_call_binds_defaults_and_star_arguments() {
+    let mut vm = VirtualMachine::new();
+    let source = "def f(a, b=2, *rest, c=3, **kw):\n  return (a, b, rest, c, len(kw))\nresult = f(*[1, 5, 6], d=4)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(1, 5, (6,), 3, 1)");
+  }
+
+  #[test]
+  fn test_call_with_missing_argument_raises_type_error() {
+    let mut vm = VirtualMachine::new();
+    let source = "def f(a, b):\n  pass\nresult = f(b=1)\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_nonlocal_updates_shared_cell() {
+    let mut vm = VirtualMachine::new();
+    let source = "def counter():\n  n = 0\n  def inc():\n    nonlocal n\n    n = n + 1\n    return n\n  return inc\nc = counter()\nc()\nresult = c()\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "2");
+  }
+
+  #[test]
+  fn test_local_read_before_assignment_raises() {
+    let mut vm = VirtualMachine::new();
+    let source = "x = 1\ndef f():\n  y = x\n  x = 2\nresult = f()\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let unbound_local_error =
+      vm.context().exceptions.unbound_local_error.clone();
+    assert!(objtype::isinstance(&exc, &unbound_local_error));
+  }
+
+  #[test]
+  fn test_generator_receives_sent_values() {
+    let mut vm = VirtualMachine::new();
+    let source = "def running_total():\n  total = 0\n  while True:\n    total = total + (yield total)\ng = running_total()\ng.send(None)\ng.send(3)\nresult = g.send(4)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "7");
+  }
+
+  #[test]
+  fn test_yield_from_returns_value_of_subgenerator() {
+    let mut vm = VirtualMachine::new();
+    let source = "def inner():\n  yield 1\n  yield 2\n  return 10\ndef outer():\n  total = yield from inner()\n  yield total\nresult = 0\nfor x in outer():\n  result = result + x\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "13");
+  }
+
+  #[test]
+  fn test_for_loop_uses_iter_and_next_methods() {
+    let mut vm = VirtualMachine::new();
+    let source = "class Countdown:\n  def __init__(self, n):\n    self.n = n\n  def __iter__(self):\n    return self\n  def __next__(self):\n    if self.n == 0:\n      raise StopIteration\n    self.n = self.n - 1\n    return self.n\nresult = 0\nfor x in Countdown(4):\n  result = result * 10 + x\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "3210");
+  }
+
+  #[test]
+  fn test_getitem_objects_are_iterable_until_index_error() {
+    let mut vm = VirtualMachine::new();
+    let source = "class Squares:\n  def __getitem__(self, i):\n    if i >= 3:\n      raise IndexError\n    return i * i\nresult = []\nfor x in Squares():\n  result.append(x)\nresult.append(4 in Squares())\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "[0, 1, 4, True]");
+  }
+
+  #[test]
+  fn test_next_returns_default_when_exhausted() {
+    let mut vm = VirtualMachine::new();
+    let source =
+      "it = iter(range(1, 3))\nresult = (next(it), next(it), next(it, 0))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(1, 2, 0)");
+  }
+
+  #[test]
+  fn test_comprehensions_loop_over_nested_generators() {
+    let mut vm = VirtualMachine::new();
+    let source = "pairs = [x * 10 + y for x in range(3) if x for y in range(x)]\nsquares = {str(n): n * n for n in range(3)}\ntotal = 0\nfor v in (p * 2 for p in pairs):\n  total = total + v\nresult = (pairs, len(squares), total)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "([10, 20, 21], 3, 102)");
+  }
+
+  #[test]
+  fn test_with_exits_context_managers_and_suppresses() {
+    let mut vm = VirtualMachine::new();
+    let source = "log = []\nclass CM:\n  def __init__(self, name):\n    self.name = name\n  def __enter__(self):\n    log.append(self.name)\n    return self.name\n  def __exit__(self, typ, value, tb):\n    log.append(typ is None)\n    return self.name == 'b'\nwith CM('a') as x, CM('b') as y:\n  log.append(x + y)\n  raise ValueError\nresult = log\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "['a', 'b', 'ab', False, True]");
+  }
+
+  #[test]
+  fn test_finally_runs_when_leaving_try() {
+    let mut vm = VirtualMachine::new();
+    let source = "log = []\ndef f():\n  try:\n    return 'r'\n  finally:\n    log.append('f')\nlog.append(f())\nfor i in range(3):\n  try:\n    if i == 0:\n      continue\n    break\n  finally:\n    log.append(i)\ntry:\n  try:\n    raise KeyError\n  finally:\n    log.append('e')\nexcept KeyError:\n  log.append('k')\nresult = log\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "['f', 'r', 0, 1, 'e', 'k']");
+  }
+
+  #[test]
+  fn test_raise_chains_exceptions() {
+    let mut vm = VirtualMachine::new();
+    let source = "try:\n  try:\n    raise KeyError\n  except KeyError as e:\n    raise ValueError from e\nexcept ValueError as e:\n  chained = e\ntry:\n  try:\n    raise KeyError\n  except KeyError:\n    raise\nexcept KeyError as e:\n  reraised = e\nresult = (type(chained.__cause__) == KeyError, chained.__context__ is chained.__cause__, chained.__suppress_context__, type(reraised) == KeyError)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(True, True, True, True)");
+  }
+
+  #[test]
+  fn test_bare_raise_without_active_exception() {
+    let mut vm = VirtualMachine::new();
+    let exc = run_source(&mut vm, "raise\n").unwrap_err();
+    let runtime_error = vm.context().exceptions.runtime_error.clone();
+    assert!(objtype::isinstance(&exc, &runtime_error));
+  }
+
+  #[test]
+  fn test_except_alias_is_unbound_after_the_handler() {
+    let mut vm = VirtualMachine::new();
+    let source =
+      "try:\n  raise ValueError\nexcept ValueError as e:\n  pass\nresult = e\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let name_error = vm.context().exceptions.name_error.clone();
+    assert!(objtype::isinstance(&exc, &name_error));
+
+    let source = "try:\n  try:\n    raise ValueError\n  except ValueError as e:\n    raise KeyError\nexcept KeyError:\n  pass\ntry:\n  e\n  result = 'bound'\nexcept NameError:\n  result = 'unbound'\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "unbound");
+  }
+
+  #[test]
+  fn test_loop_else_runs_unless_broken() {
+    let mut vm = VirtualMachine::new();
+    let source = "log = []\nfor i in range(2):\n  pass\nelse:\n  log.append('for')\nwhile True:\n  break\nelse:\n  log.append('while')\nfor i in range(3):\n  if i == 1:\n    break\nelse:\n  log.append('broken')\nresult = (log, i)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(['for'], 1)");
+  }
+
+  #[test]
+  fn test_for_loop_unpacks_nested_targets() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = []\nfor i, (a, b) in [(1, (2, 3)), (4, [5, 6])]:\n  result.append(i * a * b)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "[6, 120]");
+  }
+
+  #[test]
+  fn test_unpacking_wrong_number_of_values_raises_value_error() {
+    let mut vm = VirtualMachine::new();
+    let exc =
+      run_source(&mut vm, "for a, b in [(1, 2, 3)]:\n  pass\n").unwrap_err();
+    let value_error = vm.context().exceptions.value_error.clone();
+    assert!(objtype::isinstance(&exc, &value_error));
+  }
+
+  #[test]
+  fn test_assignment_unpacks_starred_and_nested_targets() {
+    let mut vm = VirtualMachine::new();
+    let source = "a, b = 1, 2\na, b = b, a\nfirst, *middle, (x, y) = range(3), 4, 5, [6, 7]\nresult = (a, b, len(first), middle, x, y)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(2, 1, 3, [4, 5], 6, 7)");
+  }
+
+  #[test]
+  fn test_starred_elements_are_flattened_into_displays() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = ([*range(2), 2], (*'ab', *[3]))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "([0, 1, 2], ('a', 'b', 3))");
+  }
+
+  #[test]
+  fn test_del_removes_names_items_and_attributes() {
+    let mut vm = VirtualMachine::new();
+    let source = "class C:\n  pass\nc = C()\nc.a = 1\nc.b = 2\ndel c.a\nl = [0, 1, 2, 3, 4]\ndel l[0], l[1:3]\nd = {'k': 1}\ndel d['k']\nx = 1\ndel x\nresult = (hasattr(c, 'a'), hasattr(c, 'b'), l, len(d))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(False, True, [1, 4], 0)");
+  }
+
+  #[test]
+  fn test_del_missing_key_raises_key_error() {
+    let mut vm = VirtualMachine::new();
+    let exc = run_source(&mut vm, "d = {}\ndel d['k']\n").unwrap_err();
+    let key_error = vm.context().exceptions.key_error.clone();
+    assert!(objtype::isinstance(&exc, &key_error));
+  }
+
+  #[test]
+  fn test_dict_keys_are_hashable_objects() {
+    let mut vm = VirtualMachine::new();
+    let source = "class K:\n  def __init__(self, v):\n    self.v = v\n  def __hash__(self):\n    return hash(self.v)\n  def __eq__(self, other):\n    return self.v == other.v\nd = {1: 'a', (2, 3): 'b', K(4): 'c'}\nd[1.0] = 'z'\nresult = (d[1], d[(2, 3)], d[K(4)], len(d), K(5) in d)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "('z', 'b', 'c', 3, False)");
+  }
+
+  #[test]
+  fn test_dict_methods_keep_insertion_order() {
+    let mut vm = VirtualMachine::new();
+    let source = "d = dict([(3, 'c')], a=1)\nd.update({2: 'b'})\nd.setdefault(0, 'x')\nd.pop('a')\nresult = (d.items(), d.get(9, 'no'), [k for k in d])\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "([(3, 'c'), (2, 'b'), (0, 'x')], 'no', [3, 2, 0])"
+    );
+  }
+
+  #[test]
+  fn test_unhashable_dict_key_raises_type_error() {
+    let mut vm = VirtualMachine::new();
+    let exc = run_source(&mut vm, "d = {[1]: 2}\n").unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_set_displays_comprehensions_and_operators() {
+    let mut vm = VirtualMachine::new();
+    let source = "a = {1, 2, 3, 2}\nb = {x + 1 for x in a}\nresult = (a | b, a & b, a - b, a ^ b, a <= {1, 2, 3}, a < a, len(a))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "({1, 2, 3, 4}, {2, 3}, {1}, {1, 4}, True, False, 3)"
+    );
+  }
+
+  #[test]
+  fn test_frozenset_can_be_a_dict_key() {
+    let mut vm = VirtualMachine::new();
+    let source = "s = set([3, 4])\ns.add(5)\ns.discard(3)\nd = {frozenset(s): 'x'}\nresult = (d[frozenset([5, 4])], s)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "('x', {4, 5})");
+  }
+
+  #[test]
+  fn test_dict_keeps_insertion_order_across_deletions() {
+    let mut vm = VirtualMachine::new();
+    let source = "d = {}\nfor i in range(100):\n  d[i] = i\nfor i in range(99):\n  del d[i]\nd['a'] = 1\nresult = d\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "{ 99: 99, 'a': 1 }");
+  }
+
+  #[test]
+  fn test_resizing_dict_during_iteration_raises_runtime_error() {
+    let mut vm = VirtualMachine::new();
+    let runtime_error = vm.context().exceptions.runtime_error.clone();
+    let source = "d = {1: 1}\nfor k in d:\n  d[k + 1] = 1\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    assert!(objtype::isinstance(&exc, &runtime_error));
+    let source = "s = {1, 2}\nfor k in s:\n  s.discard(k)\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    assert!(objtype::isinstance(&exc, &runtime_error));
+    let source = "d = {1: 1, 2: 2}\nfor k in d:\n  d[k] = 5\nresult = d\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "{ 1: 5, 2: 5 }");
+  }
+
+  #[test]
+  fn test_key_comparison_which_changes_the_dict_restarts_the_lookup() {
+    let mut vm = VirtualMachine::new();
+    let source = "d = {}\nclass First:\n  def __hash__(self):\n    return 777\nfirst = First()\nclass K:\n  def __hash__(self):\n    return 777\n  def __eq__(self, other):\n    d.pop(first)\n    return False\nd[first] = 1\nd['a'] = 0\nd[K()] = 2\ns = set()\nclass H:\n  def __hash__(self):\n    return 5\n  def __eq__(self, other):\n    s.clear()\n    return False\ns.add(H())\ns.add(H())\nresult = (len(d), len(s))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(2, 1)");
+  }
+
+  #[test]
+  fn test_class_defining_only_eq_is_unhashable() {
+    let mut vm = VirtualMachine::new();
+    let source =
+      "class K:\n  def __eq__(self, other):\n    return True\nhash(K())\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_containers_compare_elements_with_eq() {
+    let mut vm = VirtualMachine::new();
+    let source = "class K:\n  def __eq__(self, other):\n    return True\nresult = ([K()] == [K()], K() in [K()], (K(),) != (K(),), {1: K()} == {1: K()})\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(True, True, False, True)");
+  }
+
+  #[test]
+  fn test_element_comparison_may_change_the_containers() {
+    let mut vm = VirtualMachine::new();
+    let source = "class K:\n  def __eq__(self, other):\n    del a[:]\n    return False\na = [K(), 1, 2]\nresult = (a == [5, 6, 7], a)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(False, [])");
+  }
+
+  #[test]
+  fn test_augmented_assignment_updates_mutable_objects_in_place() {
+    let mut vm = VirtualMachine::new();
+    let source = "l = [1]\nm = l\nl += [2]\ns = {1}\nt = s\ns |= {3}\nb = bytearray(b'a')\nc = b\nb += b'b'\nclass A:\n  def __iadd__(self, other):\n    return other\n  def __add__(self, other):\n    return 'add'\na = A()\na += 5\nn = 1\nn += 2\nresult = (m, t, c, a, n)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "([1, 2], {1, 3}, bytearray(b'ab'), 5, 3)"
+    );
+  }
+
+  #[test]
+  fn test_isinstance_and_issubclass_accept_tuples() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = (isinstance(1, (str, int)), isinstance(1, (str, (float, int))), isinstance(1, ()), issubclass(bool, (str, int)), issubclass(int, (str,)))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(True, True, False, True, False)");
+
+    for source in &[
+      "isinstance(1, 2)\n",
+      "issubclass(int, 2)\n",
+      "issubclass(1, int)\n",
+    ] {
+      let exc = run_source(&mut vm, source).unwrap_err();
+      let type_error = vm.context().exceptions.type_error.clone();
+      assert!(objtype::isinstance(&exc, &type_error));
+    }
+  }
+
+  #[test]
+  fn test_deep_recursion_raises_recursion_error() {
+    // Test threads have small stacks, which must not limit the recursion
+    // depth either:
+    let mut vm = VirtualMachine::new();
+    let exc =
+      run_source(&mut vm, "def f(n):\n  return f(n + 1)\nf(0)\n").unwrap_err();
+    let recursion_error = vm.context().exceptions.recursion_error.clone();
+    assert!(objtype::isinstance(&exc, &recursion_error));
+    let frames = exc.get_attr(&String::from("__traceback__"));
+    assert_eq!(objsequence::get_elements(&frames).len(), 1000);
+  }
+
+  #[test]
+  fn test_containers_containing_themselves_are_shown_with_ellipsis() {
+    let mut vm = VirtualMachine::new();
+    let source =
+      "l = [1]\nl.append(l)\nd = {}\nd[1] = d\nresult = (repr(l), str(d))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "('[1, [...]]', '{ 1: {...} }')");
+  }
+
+  #[test]
+  fn test_type_errors_name_the_types_involved() {
+    let mut vm = VirtualMachine::new();
+    let sources = [
+      ("x = 5[0]\n", "'int' object is not subscriptable"),
+      (
+        "x = [1]['a']\n",
+        "list indices must be integers or slices, not str",
+      ),
+      (
+        "x = 5\nx[0] = 1\n",
+        "'int' object does not support item assignment",
+      ),
+      ("x = len(5)\n", "object of type 'int' has no len()"),
+      ("x = None()\n", "'NoneType' object is not callable"),
+      (
+        "x = (1,) < (1, 2)\n",
+        "'<' not supported between instances of 'tuple' and 'tuple'",
+      ),
+    ];
+    for (source, msg) in sources.iter() {
+      let exc = run_source(&mut vm, source).unwrap_err();
+      assert_eq!(exc.get_attr(&String::from("msg")).borrow().str(), *msg);
+    }
+  }
+
+  #[test]
+  fn test_tuple_and_none_have_their_own_types() {
+    let mut vm = VirtualMachine::new();
+    let source = "class T(tuple):\n  pass\nt = T('ab')\nresult = (tuple([1, 2]), tuple(), type(t) is T, len(t), isinstance(int, tuple), type(None)() is None, type(None) is type)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "((1, 2), (), True, 2, False, True, False)"
+    );
+  }
+
+  #[test]
+  fn test_set_of_unhashable_raises_type_error() {
+    let mut vm = VirtualMachine::new();
+    let exc = run_source(&mut vm, "s = {[1]}\n").unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_string_methods_index_by_character() {
+    let mut vm = VirtualMachine::new();
+    let source = "s = 'héllo wörld'\nresult = (len(s), s[1], s[-3:], s.find('w'), s.upper(), s.split(), '-'.join(['a', 'b']), ' x '.strip(), s.replace('l', 'L', 2), s.startswith(('x', 'hé')))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "(11, 'é', 'rld', 6, 'HÉLLO WÖRLD', ['héllo', 'wörld'], 'a-b', 'x', 'héLLo wörld', True)"
+    );
+  }
+
+  #[test]
+  fn test_percent_formatting() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = '%s=%05.1f %-3d|%x %r %%' % ('k', 2.25, 7, 255, 'q') + '%(a)s' % {'a': 1}\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "k=002.2 7  |ff 'q' %1");
+  }
+
+  #[test]
+  fn test_percent_formatting_with_missing_argument_raises_type_error() {
+    let mut vm = VirtualMachine::new();
+    let exc = run_source(&mut vm, "s = '%s %s' % (1,)\n").unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_percent_formatting_with_huge_precision_raises_value_error() {
+    let mut vm = VirtualMachine::new();
+    let source = "s = '%.99999999999999999999f' % 1.5\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let value_error = vm.context().exceptions.value_error.clone();
+    assert!(objtype::isinstance(&exc, &value_error));
+    let source = "result = len('%.70000e' % 1.5)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "70006");
+  }
+
+  #[test]
+  fn test_str_format_mini_language() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = '{} {1} {x[0]} {:>{w}} {:,.2f} {:#b} {!r}'.format('a', 'b', 1234.5, 5, 'c', x=[9], w=3)\n";
+    let exc = run_source(&mut vm, source).unwrap_err();
+    let value_error = vm.context().exceptions.value_error.clone();
+    assert!(objtype::isinstance(&exc, &value_error));
+
+    let source = "result = '{} {} {x[0]} {:>{w}} {:,.2f} {:#b} {!r} {{}}'.format('a', 'b', 'c', 1234.5, 5, 'd', x=[9], w=3)\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "a b 9   c 1,234.50 0b101 'd' {}");
+  }
+
+  #[test]
+  fn test_fstrings_format_their_values() {
+    let mut vm = VirtualMachine::new();
+    let source = "x = 42\nw = 6\ns = 'é'\nresult = f'{x:>{w}}|{x!r}|{s!a}|{x + 1:#x}|{{}}' 'end'\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "    42|42|'\\xe9'|0x2b|{}end");
+  }
+
+  #[test]
+  fn test_fstring_with_huge_precision_is_padded() {
+    let mut vm = VirtualMachine::new();
+    let source = "p = 70000\nresult = (len(f'{1.5:.70000f}'), f'{2.5:.{p}}')\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(70002, '2.5')");
+  }
+
+  #[test]
+  fn test_bytes_methods() {
+    let mut vm = VirtualMachine::new();
+    let source = "b = b'a,b\\x00,c'\nresult = (len(b), b[0], b[1:3], b.split(b','), b'-'.join([b'x', b'y']), b.find(b','), b.hex(), bytes.fromhex('6869'), b'ab' + b'c', 108 in b'hello')\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "(6, 97, b',b', [b'a', b'b\\x00', b'c'], b'x-y', 1, '612c62002c63', b'hi', b'abc', True)"
+    );
+  }
+
+  #[test]
+  fn test_encode_and_decode() {
+    let mut vm = VirtualMachine::new();
+    let source = "result = ('h\u{e9}'.encode(), 'h\u{e9}'.encode('latin-1'), b'h\\xc3\\xa9'.decode('utf-8'), b'a\\xffb'.decode('ascii', 'replace'))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(
+      result.borrow().str(),
+      "(b'h\\xc3\\xa9', b'h\\xe9', 'h\u{e9}', 'a\u{fffd}b')"
+    );
+
+    let exc = run_source(&mut vm, "s = b'\\xff'.decode()\n").unwrap_err();
+    let unicode_decode_error =
+      vm.context().exceptions.unicode_decode_error.clone();
+    assert!(objtype::isinstance(&exc, &unicode_decode_error));
+    let exc =
+      run_source(&mut vm, "s = '\u{20ac}'.encode('latin-1')\n").unwrap_err();
+    let value_error = vm.context().exceptions.value_error.clone();
+    assert!(objtype::isinstance(&exc, &value_error));
+  }
+
+  #[test]
+  fn test_bytearray_is_mutable() {
+    let mut vm = VirtualMachine::new();
+    let source = "a = bytearray(b'hello')\na[0] = 74\na.append(33)\na[1:3] = b'EY'\ndel a[-2]\nresult = (a, a.pop(), bytes(a))\n";
+    let result = run_source(&mut vm, source).unwrap();
+    assert_eq!(result.borrow().str(), "(bytearray(b'JEYl'), 33, b'JEYl')");
+
+    let exc = run_source(&mut vm, "b = b'x'\nb[0] = 1\n").unwrap_err();
+    let type_error = vm.context().exceptions.type_error.clone();
+    assert!(objtype::isinstance(&exc, &type_error));
+  }
+
+  #[test]
+  fn test_syntax_error_points_at_the_error() {
+    let mut vm = VirtualMachine::new();
+    let source = String::from("x = 1\ny = 0o19\n");
+    let error = compile::compile(&mut vm, &source, compile::Mode::Exec, None)
+      .unwrap_err();
+    assert_eq!(error.to_string(), "invalid digit '9' in octal literal");
+    let exc = vm.new_syntax_error(&error);
+    let syntax_error = vm.context().exceptions.syntax_error.clone();
+    assert!(objtype::isinstance(&exc, &syntax_error));
+    assert_eq!(exc.get_attr(&String::from("lineno")).borrow().str(), "2");
+    assert_eq!(exc.get_attr(&String::from("offset")).borrow().str(), "8");
+
+    let source = String::from("def f():\n  nonlocal x\n");
+    let error = compile::compile(&mut vm, &source, compile::Mode::Exec, None)
+      .unwrap_err();
+    let exc = vm.new_syntax_error(&error);
+    assert_eq!(
+      exc.get_attr(&String::from("msg")).borrow().str(),
+      "no binding for nonlocal 'x' found"
+    );
+    assert_eq!(exc.get_attr(&String::from("lineno")).borrow().str(), "2");
+    assert_eq!(exc.get_attr(&String::from("offset")).borrow().str(), "3");
+  }
+
+  #[test]
+  fn test_invalid_targets_are_syntax_errors() {
+    let mut vm = VirtualMachine::new();
+    let sources = [
+      (
+        "x = 1\nf() = 1\n",
+        "can't assign to function call",
+        "2",
+        "1",
+      ),
+      (
+        "for a + 1 in x:\n  pass\n",
+        "can't assign to operator",
+        "1",
+        "5",
+      ),
+      (
+        "with a as 1:\n  pass\n",
+        "can't assign to literal",
+        "1",
+        "11",
+      ),
+      ("del f()\n", "can't delete function call", "1", "5"),
+      ("x = *a\n", "can't use starred expression here", "1", "5"),
+    ];
+    for (source, msg, lineno, offset) in sources.iter() {
+      let error = compile::compile(
+        &mut vm,
+        &source.to_string(),
+        compile::Mode::Exec,
+        None,
+      )
+      .unwrap_err();
+      let exc = vm.new_syntax_error(&error);
+      assert_eq!(exc.get_attr(&String::from("msg")).borrow().str(), *msg);
+      assert_eq!(
+        exc.get_attr(&String::from("lineno")).borrow().str(),
+        *lineno
+      );
+      assert_eq!(
+        exc.get_attr(&String::from("offset")).borrow().str(),
+        *offset
+      );
+    }
+  }
+}
@@ -1,6 +1,6 @@
-#[warn(unused_imports)]
 extern crate clap;
 extern crate env_logger;
+#[macro_use]
 extern crate log;
 extern crate pyst_parser;
 extern crate pyst_vm;
@@ -8,6 +8,7 @@ extern crate pyst_vm;
 use clap::{Arg, Command};
 use pyst_parser::parser;
 use pyst_vm::compile;
+use pyst_vm::print_exception;
 use pyst_vm::VirtualMachine;
 use std::io;
 use std::io::prelude::*;
@@ -35,44 +36,59 @@ fn main() {
     )
     .get_matches();
 
-  match matches.get_one::<String>("script").unwrap() {
-    command => run_script(&command),
-  }
-
-  match matches.get_one::<String>("shell").unwrap() {
-    _ => run_shell(),
+  if let Some(source) = matches.get_one::<String>("compile") {
+    run_command(&mut source.clone());
+  } else if let Some(script) = matches.get_one::<String>("script") {
+    run_script(script);
+    if matches.get_one::<String>("shell").is_some() {
+      run_shell();
+    }
+  } else {
+    run_shell();
   }
 }
 
-fn _run_string(source: &String) {
+fn _run_string(source: &String, source_path: Option<String>) {
   let mut vm = VirtualMachine::new();
-  let code_obj =
-    compile::compile(&mut vm, &source, compile::Mode::Exec).unwrap();
-  format!("Code object: {:?}", code_obj.borrow());
+  let code_obj = match compile::compile(
+    &mut vm,
+    source,
+    compile::Mode::Exec,
+    source_path.clone(),
+  ) {
+    Ok(code_obj) => code_obj,
+    Err(error) => {
+      let source_path = source_path.unwrap_or_else(|| "<string>".to_string());
+      print_syntax_error(source, &source_path, &error);
+      std::process::exit(1);
+    }
+  };
+  debug!("Code object: {:?}", code_obj.borrow());
   let builtins = vm.get_builtin_scope();
   let vars = vm.context().new_scope(Some(builtins)); // Keep track of local variables
   match vm.run_code_obj(code_obj, vars) {
     Ok(_value) => {}
     Err(exc) => {
-      panic!("Exception: {:?}", exc);
+      print_exception(&exc);
+      std::process::exit(1);
     }
   }
 }
 
 fn run_command(source: &mut String) {
-  format!("Running command {}", source);
+  debug!("Running command {}", source);
 
-  source.push_str("\n");
-  _run_string(source)
+  source.push('\n');
+  _run_string(source, Some("<stdin>".to_string()))
 }
 
 fn run_script(script_file: &String) {
-  format!("Running file {}", script_file);
+  debug!("Running file {}", script_file);
   let filepath = Path::new(script_file);
   match parser::read_file(filepath) {
-    Ok(source) => _run_string(&source),
+    Ok(source) => _run_string(&source, Some(script_file.clone())),
     Err(msg) => {
-      format!("Parsing went horribly wrong: {}", msg);
+      error!("Parsing went horribly wrong: {}", msg);
       std::process::exit(1);
     }
   }
@@ -83,33 +99,58 @@ fn shell_exec(
   source: &String,
   scope: PyObjectRef,
 ) -> bool {
-  match compile::compile(vm, source, compile::Mode::Single) {
+  match compile::compile(
+    vm,
+    source,
+    compile::Mode::Single,
+    Some("<stdin>".to_string()),
+  ) {
     Ok(code) => {
       match vm.run_code_obj(code, scope.clone()) {
         Ok(_value) => {
           // Printed already.
         }
-        Err(msg) => {
-          println!("Error: {:?}", msg);
+        Err(exc) => {
+          print_exception(&exc);
         }
       }
     }
-    Err(msg) => {
-      // Enum rather than special string here.
-      if msg == "Unexpected end of input." {
-        return false;
-      } else {
-        println!("Error: {:?}", msg)
-      }
+    // Read more lines when the input stops in the middle of a statement:
+    Err(compile::CompileError::Parse(ref error)) if error.is_incomplete() => {
+      return false;
     }
+    Err(error) => print_syntax_error(source, "<stdin>", &error),
   };
   true
 }
 
+// Print a compile error the way CPython prints a SyntaxError, pointing at
+// the place in the source where it happened:
+fn print_syntax_error(
+  source: &str,
+  source_path: &str,
+  error: &compile::CompileError,
+) {
+  let location = error.location();
+  eprintln!("  File \"{}\", line {}", source_path, location.get_row());
+  let line = location
+    .get_row()
+    .checked_sub(1)
+    .and_then(|index| source.lines().nth(index));
+  if let Some(line) = line {
+    let text = line.trim_start();
+    let indent = line.chars().count() - text.chars().count();
+    let column = location.get_column().saturating_sub(indent + 1);
+    eprintln!("    {}", text.trim_end());
+    eprintln!("    {}^", " ".repeat(column));
+  }
+  eprintln!("SyntaxError: {}", error);
+}
+
 fn read_until_empty_line(input: &mut String) -> Result<i32, std::io::Error> {
   loop {
     print!("..... ");
-    io::stdout().flush().ok().expect("Could not flush stdout");
+    io::stdout().flush().expect("Could not flush stdout");
     let mut line = String::new();
     match io::stdin().read_line(&mut line) {
       Ok(0) => {
@@ -129,10 +170,7 @@ fn read_until_empty_line(input: &mut String) -> Result<i32, std::io::Error> {
 }
 
 fn run_shell() {
-  println!(
-    "Welcome to the magnificent Rust Python {} interpreter",
-    "0.0.1"
-  );
+  println!("Welcome to the magnificent Rust Python 0.0.1 interpreter");
   let mut vm = VirtualMachine::new();
   let builtins = vm.get_builtin_scope();
   let vars = vm.context().new_scope(Some(builtins)); // Keep track of local variables
@@ -141,13 +179,13 @@ fn run_shell() {
   let mut input = String::new();
   loop {
     print!(">>>>> "); // Use 5 items. pypy has 4, cpython has 3.
-    io::stdout().flush().ok().expect("Could not flush stdout");
+    io::stdout().flush().expect("Could not flush stdout");
     match io::stdin().read_line(&mut input) {
       Ok(0) => {
         break;
       }
       Ok(_) => {
-        format!("You entered {:?}", input);
+        debug!("You entered {:?}", input);
         if shell_exec(&mut vm, &input, vars.clone()) {
           // Line was complete.
           input = String::new();
@@ -158,6 +196,7 @@ fn run_shell() {
             }
             Ok(_) => {
               shell_exec(&mut vm, &input, vars.clone());
+              input = String::new();
             }
             Err(msg) => panic!("Error: {:?}", msg),
           }